@@ -0,0 +1,636 @@
+use crate::{
+    AlgorithmVersion, AnyPP, Beatmap, CalcError, Float, ParseError, ParseResult, PpResult,
+};
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs::File;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Result of a single score of a [`BatchGroup`].
+pub type BatchResult = Result<PpResult, BatchError>;
+
+/// Error of a single score within a batch.
+///
+/// If the map of a group could not be parsed or is unfit for calculations,
+/// each score of the group gets the same error.
+#[derive(Clone, Debug)]
+pub enum BatchError {
+    /// The map could not be parsed.
+    Parse(Arc<ParseError>),
+    /// The map can not be used for calculations, see [`Beatmap::check_calculable`].
+    Calc(CalcError),
+    /// The calculation panicked, contains the panic message.
+    ///
+    /// Only the score that caused the panic fails, the other scores are still calculated.
+    Panic(String),
+}
+
+impl BatchError {
+    fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        let msg = match payload.downcast::<String>() {
+            Ok(msg) => *msg,
+            Err(payload) => match payload.downcast_ref::<&str>() {
+                Some(msg) => msg.to_string(),
+                None => String::from("unknown panic payload"),
+            },
+        };
+
+        Self::Panic(msg)
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "failed to parse map: {}", err),
+            Self::Calc(err) => write!(f, "failed to calculate map: {}", err),
+            Self::Panic(msg) => write!(f, "calculation panicked: {}", msg),
+        }
+    }
+}
+
+impl StdError for BatchError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Parse(err) => Some(err.as_ref()),
+            Self::Calc(err) => Some(err),
+            Self::Panic(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum MapSource {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+    Parsed(Arc<Beatmap>),
+}
+
+/// A map and all scores that should be calculated on it.
+///
+/// The map is parsed only once per group.
+#[derive(Clone, Debug)]
+pub struct BatchGroup {
+    source: MapSource,
+    scores: Vec<BatchScore>,
+}
+
+impl BatchGroup {
+    /// Group whose map will be parsed from the `.osu` file at the given path.
+    #[inline]
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        Self::with_source(MapSource::Path(path.into()))
+    }
+
+    /// Group whose map will be parsed from the content of a `.osu` file.
+    #[inline]
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self::with_source(MapSource::Bytes(bytes.into()))
+    }
+
+    /// Group of an already parsed map.
+    #[inline]
+    pub fn from_map(map: impl Into<Arc<Beatmap>>) -> Self {
+        Self::with_source(MapSource::Parsed(map.into()))
+    }
+
+    #[inline]
+    fn with_source(source: MapSource) -> Self {
+        Self {
+            source,
+            scores: Vec::new(),
+        }
+    }
+
+    /// Add a score to the group.
+    #[inline]
+    pub fn score(mut self, score: BatchScore) -> Self {
+        self.scores.push(score);
+
+        self
+    }
+
+    /// Add multiple scores to the group.
+    #[inline]
+    pub fn scores(mut self, scores: impl IntoIterator<Item = BatchScore>) -> Self {
+        self.scores.extend(scores);
+
+        self
+    }
+
+    fn map(&self) -> ParseResult<Arc<Beatmap>> {
+        let map = match &self.source {
+            MapSource::Path(path) => parse(File::open(path)?)?,
+            MapSource::Bytes(bytes) => parse(bytes.as_slice())?,
+            MapSource::Parsed(map) => return Ok(Arc::clone(map)),
+        };
+
+        Ok(Arc::new(map))
+    }
+
+    /// Parse the map and check that it can be used for calculations.
+    fn calculable_map(&self) -> Result<Arc<Beatmap>, BatchError> {
+        let map = self.map().map_err(|err| BatchError::Parse(Arc::new(err)))?;
+        map.check_calculable(map.mode).map_err(BatchError::Calc)?;
+
+        Ok(map)
+    }
+
+    /// Calculate the scores within the given range.
+    ///
+    /// The map has already been checked so the calculations themselves don't fail
+    /// unless they panic.
+    fn calculate(&self, map: &Beatmap, range: Range<usize>) -> Vec<BatchResult> {
+        // Attributes of full plays only depend on the mods and the version
+        let mut attributes = HashMap::new();
+
+        self.scores[range]
+            .iter()
+            .map(|score| {
+                let calculate = || {
+                    let key = (score.mods, score.version);
+                    let cached = score.passed_objects.is_none();
+                    let mut calculator = score.apply(AnyPP::new(map));
+
+                    if cached {
+                        if let Some(result) = attributes.get(&key) {
+                            calculator = calculator.attributes(PpResult::clone(result));
+                        }
+                    }
+
+                    let result = calculate_sync(&mut calculator);
+
+                    if cached && !attributes.contains_key(&key) {
+                        attributes.insert(key, result.clone());
+                    }
+
+                    result
+                };
+
+                panic::catch_unwind(AssertUnwindSafe(calculate)).map_err(BatchError::from_panic)
+            })
+            .collect()
+    }
+}
+
+/// The input of a single score of a [`BatchGroup`].
+///
+/// Unspecified values are handled the same way as in [`AnyPP`].
+#[derive(Clone, Debug, Default)]
+pub struct BatchScore {
+    mods: u32,
+    version: Option<AlgorithmVersion>,
+    passed_objects: Option<usize>,
+    acc: Option<Float>,
+    combo: Option<usize>,
+    n300: Option<usize>,
+    n100: Option<usize>,
+    n50: Option<usize>,
+    n_katu: Option<usize>,
+    n_misses: Option<usize>,
+    score: Option<u32>,
+    /// Makes the calculation panic to test that panics are contained.
+    #[cfg(test)]
+    panics: bool,
+}
+
+impl BatchScore {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Specify mods through their bit values.
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Specify the version of the algorithm, see [`AnyPP::version`].
+    #[inline]
+    pub fn version(mut self, version: AlgorithmVersion) -> Self {
+        self.version.replace(version);
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    ///
+    /// The difficulty attributes of partial plays are not reused.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects.replace(passed_objects);
+
+        self
+    }
+
+    /// Generate the hit results w.r.t. the given accuracy between `0` and `100`
+    /// after all other values have been applied.
+    #[inline]
+    pub fn accuracy(mut self, acc: Float) -> Self {
+        self.acc.replace(acc);
+
+        self
+    }
+
+    /// Specify the max combo of the play.
+    #[inline]
+    pub fn combo(mut self, combo: usize) -> Self {
+        self.combo.replace(combo);
+
+        self
+    }
+
+    /// Specify the amount of 300s of a play.
+    #[inline]
+    pub fn n300(mut self, n300: usize) -> Self {
+        self.n300.replace(n300);
+
+        self
+    }
+
+    /// Specify the amount of 100s of a play.
+    #[inline]
+    pub fn n100(mut self, n100: usize) -> Self {
+        self.n100.replace(n100);
+
+        self
+    }
+
+    /// Specify the amount of 50s of a play.
+    #[inline]
+    pub fn n50(mut self, n50: usize) -> Self {
+        self.n50.replace(n50);
+
+        self
+    }
+
+    /// Specify the amount of katus of a play, see [`AnyPP::n_katu`].
+    #[inline]
+    pub fn n_katu(mut self, n_katu: usize) -> Self {
+        self.n_katu.replace(n_katu);
+
+        self
+    }
+
+    /// Specify the amount of misses of a play.
+    #[inline]
+    pub fn misses(mut self, n_misses: usize) -> Self {
+        self.n_misses.replace(n_misses);
+
+        self
+    }
+
+    /// Specify the score of a play, see [`AnyPP::score`].
+    #[inline]
+    pub fn score(mut self, score: u32) -> Self {
+        self.score.replace(score);
+
+        self
+    }
+
+    fn apply<'m>(&self, mut calculator: AnyPP<'m>) -> AnyPP<'m> {
+        #[cfg(test)]
+        assert!(!self.panics, "intentional panic");
+
+        calculator = calculator.mods(self.mods);
+
+        if let Some(version) = self.version {
+            calculator = calculator.version(version);
+        }
+
+        if let Some(passed_objects) = self.passed_objects {
+            calculator = calculator.passed_objects(passed_objects);
+        }
+
+        if let Some(combo) = self.combo {
+            calculator = calculator.combo(combo);
+        }
+
+        if let Some(n300) = self.n300 {
+            calculator = calculator.n300(n300);
+        }
+
+        if let Some(n100) = self.n100 {
+            calculator = calculator.n100(n100);
+        }
+
+        if let Some(n50) = self.n50 {
+            calculator = calculator.n50(n50);
+        }
+
+        if let Some(n_katu) = self.n_katu {
+            calculator = calculator.n_katu(n_katu);
+        }
+
+        if let Some(n_misses) = self.n_misses {
+            calculator = calculator.misses(n_misses);
+        }
+
+        if let Some(score) = self.score {
+            calculator = calculator.score(score);
+        }
+
+        if let Some(acc) = self.acc {
+            calculator = calculator.accuracy(acc);
+        }
+
+        calculator
+    }
+}
+
+/// Calculates the pp of many scores on many maps over multiple threads.
+///
+/// The map of each [`BatchGroup`] is parsed only once. The scores of a group are then
+/// split into chunks that are calculated in parallel, each chunk reusing the
+/// difficulty attributes for each mod combination.
+///
+/// Panics of single calculations are caught and returned as [`BatchError::Panic`].
+///
+/// # Example
+///
+/// ```no_run
+/// use peace_performance::{BatchCalculator, BatchGroup, BatchScore};
+///
+/// let groups = vec![
+///     BatchGroup::from_path("./maps/1851299.osu")
+///         .score(BatchScore::new().mods(8).accuracy(99.5))
+///         .score(BatchScore::new().mods(8).combo(1000).misses(2)),
+///     BatchGroup::from_path("./maps/1355822.osu").score(BatchScore::new().mods(64)),
+/// ];
+///
+/// let results = BatchCalculator::new().threads(4).calculate(&groups);
+///
+/// for (group, results) in groups.iter().zip(results) {
+///     for result in results {
+///         match result {
+///             Ok(result) => println!("{}pp", result.pp()),
+///             Err(err) => println!("error: {}", err),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BatchCalculator {
+    threads: usize,
+    chunk_size: usize,
+}
+
+impl Default for BatchCalculator {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchCalculator {
+    /// Amount of scores per chunk unless specified otherwise.
+    pub const DEFAULT_CHUNK_SIZE: usize = 64;
+
+    /// Uses as many threads as the system can run in parallel
+    /// and chunks of [`DEFAULT_CHUNK_SIZE`](Self::DEFAULT_CHUNK_SIZE) scores.
+    #[inline]
+    pub fn new() -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+        Self {
+            threads,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Specify the amount of threads, at least one.
+    #[inline]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+
+        self
+    }
+
+    /// Specify the maximum amount of scores of a group that are calculated
+    /// by a single thread, at least one.
+    ///
+    /// Smaller chunks spread large groups over more threads
+    /// but reuse difficulty attributes less.
+    #[inline]
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+
+        self
+    }
+
+    /// Calculate all scores of all groups.
+    ///
+    /// The results are in the same order as the groups and their scores.
+    pub fn calculate(&self, groups: &[BatchGroup]) -> Vec<Vec<BatchResult>> {
+        let maps = self.run(groups.len(), |idx| {
+            let group = &groups[idx];
+
+            panic::catch_unwind(AssertUnwindSafe(|| group.calculable_map()))
+                .unwrap_or_else(|payload| Err(BatchError::from_panic(payload)))
+        });
+
+        let mut chunks = Vec::new();
+
+        for (idx, group) in groups.iter().enumerate() {
+            for start in (0..group.scores.len()).step_by(self.chunk_size) {
+                let end = (start + self.chunk_size).min(group.scores.len());
+                chunks.push((idx, start..end));
+            }
+        }
+
+        let chunk_results = self.run(chunks.len(), |i| {
+            let (idx, range) = &chunks[i];
+
+            match &maps[*idx] {
+                Ok(map) => groups[*idx].calculate(map, range.clone()),
+                Err(err) => vec![Err(err.clone()); range.len()],
+            }
+        });
+
+        let mut results = vec![Vec::new(); groups.len()];
+
+        for ((idx, _), chunk) in chunks.into_iter().zip(chunk_results) {
+            results[idx].extend(chunk);
+        }
+
+        results
+    }
+
+    /// Run `job` for each index in `0..n_jobs` on the threads and collect the results in order.
+    fn run<T, F>(&self, n_jobs: usize, job: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize) -> T + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..n_jobs).map(|_| None).collect::<Vec<_>>());
+        let n_threads = self.threads.min(n_jobs);
+
+        thread::scope(|scope| {
+            for _ in 0..n_threads {
+                scope.spawn(|| loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+
+                    if idx >= n_jobs {
+                        break;
+                    }
+
+                    let result = job(idx);
+
+                    if let Ok(mut results) = results.lock() {
+                        results[idx] = Some(result);
+                    }
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .into_iter()
+            .map(|result| result.expect("missing batch job result"))
+            .collect()
+    }
+}
+
+#[cfg(not(any(feature = "async_std", feature = "async_tokio")))]
+#[inline]
+fn parse(input: impl std::io::Read) -> ParseResult<Beatmap> {
+    Beatmap::parse(input)
+}
+
+#[cfg(any(feature = "async_std", feature = "async_tokio"))]
+#[inline]
+fn parse(input: impl std::io::Read) -> ParseResult<Beatmap> {
+    Beatmap::parse_sync(input)
+}
+
+/// [`AnyPP::calculate`] is async for the async features.
+fn calculate_sync(calculator: &mut AnyPP) -> PpResult {
+    match calculator {
+        #[cfg(feature = "fruits")]
+        AnyPP::Fruits(f) => f.calculate(),
+        #[cfg(feature = "mania")]
+        AnyPP::Mania(m) => m.calculate(),
+        #[cfg(feature = "osu")]
+        AnyPP::Osu(o) => o.calculate(),
+        #[cfg(feature = "taiko")]
+        AnyPP::Taiko(t) => t.calculate(),
+    }
+}
+
+#[cfg(all(test, feature = "osu"))]
+mod tests {
+    use super::*;
+    use crate::{test_util, GameMode};
+
+    #[test]
+    fn batch_errors_and_order() {
+        let groups = vec![
+            BatchGroup::from_bytes(b"not a map".to_vec())
+                .score(BatchScore::new())
+                .score(BatchScore::new().mods(8)),
+            BatchGroup::from_map(Beatmap::default()).score(BatchScore::new()),
+            BatchGroup::from_path("./this/path/does/not/exist.osu"),
+        ];
+
+        let results = BatchCalculator::new().threads(2).calculate(&groups);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].len(), 2);
+        assert!(matches!(results[0][1], Err(BatchError::Parse(_))));
+        assert!(matches!(
+            results[1][0],
+            Err(BatchError::Calc(CalcError::NoHitObjects))
+        ));
+        assert!(results[2].is_empty());
+    }
+
+    #[test]
+    fn batch_error_display_contains_source() {
+        let groups = [BatchGroup::from_map(Beatmap::default()).score(BatchScore::new())];
+        let results = BatchCalculator::new().calculate(&groups);
+        let err = results[0][0].as_ref().unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "failed to calculate map: map contains no hit objects"
+        );
+    }
+
+    #[test]
+    fn batch_chunks_keep_order() {
+        let map = test_util::map(GameMode::STD);
+
+        let scores = (0..50).map(|i| {
+            BatchScore::new()
+                .mods([0, 8, 16, 64][i % 4])
+                .misses(i % 3)
+                .accuracy(100.0 - i as Float / 10.0)
+        });
+
+        let groups = [
+            BatchGroup::from_map(map.clone()).scores(scores.clone()),
+            BatchGroup::from_map(map),
+            BatchGroup::from_bytes(b"not a map".to_vec()).scores(scores.clone().take(5)),
+        ];
+
+        let whole = BatchCalculator::new().threads(1).calculate(&groups);
+        let chunked = BatchCalculator::new()
+            .threads(4)
+            .chunk_size(3)
+            .calculate(&groups);
+
+        assert_eq!(chunked[0].len(), 50);
+        assert!(chunked[1].is_empty());
+        assert_eq!(chunked[2].len(), 5);
+        assert!(chunked[2]
+            .iter()
+            .all(|result| matches!(result, Err(BatchError::Parse(_)))));
+
+        for (whole, chunked) in whole[0].iter().zip(chunked[0].iter()) {
+            let whole = whole.as_ref().unwrap();
+            let chunked = chunked.as_ref().unwrap();
+
+            assert_eq!(whole.pp(), chunked.pp());
+            assert_eq!(whole.stars(), chunked.stars());
+        }
+    }
+
+    #[test]
+    fn batch_catches_panics() {
+        let map = test_util::map(GameMode::STD);
+
+        let groups = [BatchGroup::from_map(map)
+            .score(BatchScore::new())
+            .score(BatchScore {
+                panics: true,
+                ..Default::default()
+            })
+            .score(BatchScore::new().mods(8))];
+
+        let results = BatchCalculator::new().chunk_size(1).calculate(&groups);
+
+        assert!(results[0][0].is_ok());
+        assert!(results[0][2].is_ok());
+
+        match &results[0][1] {
+            Err(err @ BatchError::Panic(msg)) => {
+                assert_eq!(msg, "intentional panic");
+                assert!(err.to_string().starts_with("calculation panicked: "));
+            }
+            other => panic!("expected panic error, got {:?}", other.as_ref().err()),
+        }
+    }
+}
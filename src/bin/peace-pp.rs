@@ -0,0 +1,668 @@
+//! Command-line calculator for the stars and pp of .osu files.
+//!
+//! Requires the `cli` feature, run `peace-pp --help` for all options.
+
+use peace_performance::{
+    AlgorithmVersion, AnyPP, Beatmap, BeatmapAttributes, DifficultyParams, Float, GameMods,
+    ParseResult, PpResult, StarResult,
+};
+
+use std::fmt::Write;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+const USAGE: &str = "\
+Calculate the stars and pp of osu! beatmaps.
+
+USAGE:
+    peace-pp [OPTIONS] <PATH>...
+
+ARGS:
+    <PATH>...    .osu files or directories containing .osu files
+
+OPTIONS:
+    -m, --mods <MODS>            Mods as acronyms, e.g. HDDT, or as bits, e.g. 72
+    -a, --acc <ACC>              Accuracy between 0 and 100
+    -c, --combo <COMBO>          Max combo of the play
+    -x, --misses <MISSES>        Amount of misses
+        --n300 <N300>            Amount of 300s
+        --n100 <N100>            Amount of 100s
+        --n50 <N50>              Amount of 50s
+        --katu <KATU>            Amount of katus, only relevant for osu!ctb
+        --score <SCORE>          Score of the play, only relevant for osu!mania
+    -p, --passed <OBJECTS>       Amount of passed objects for partial plays
+        --clock-rate <RATE>      Custom clock rate that overrides the one of the mods, e.g. 1.2
+        --algorithm <VERSION>    Version of the difficulty and pp algorithm: 2021, 2022, or 2024
+    -j, --json                   Print JSON instead of a table
+    -h, --help                   Print this message
+
+EXIT CODES:
+    0    all maps were calculated
+    1    at least one map could not be read or parsed
+    2    invalid arguments
+";
+
+/// At least one map could not be read, parsed, or calculated.
+const EXIT_FAILED: i32 = 1;
+/// The arguments are invalid.
+const EXIT_USAGE: i32 = 2;
+
+#[derive(Default)]
+struct Args {
+    paths: Vec<PathBuf>,
+    mods: u32,
+    acc: Option<Float>,
+    combo: Option<usize>,
+    misses: Option<usize>,
+    n300: Option<usize>,
+    n100: Option<usize>,
+    n50: Option<usize>,
+    n_katu: Option<usize>,
+    score: Option<u32>,
+    passed_objects: Option<usize>,
+    clock_rate: Option<Float>,
+    version: Option<AlgorithmVersion>,
+    json: bool,
+}
+
+impl Args {
+    /// Returns `None` if the usage was requested.
+    fn parse(mut input: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut args = Self::default();
+
+        while let Some(arg) = input.next() {
+            if !arg.starts_with('-') {
+                args.paths.push(PathBuf::from(arg));
+
+                continue;
+            }
+
+            let (name, inline_value) = match arg.find('=') {
+                Some(idx) if arg.starts_with("--") => {
+                    (&arg[..idx], Some(arg[idx + 1..].to_owned()))
+                }
+                _ => (arg.as_str(), None),
+            };
+
+            match name {
+                "-h" | "--help" => return Ok(None),
+                "-j" | "--json" => args.json = true,
+                _ => {
+                    let value = inline_value
+                        .or_else(|| input.next())
+                        .ok_or_else(|| format!("missing value for `{}`", name))?;
+
+                    match name {
+                        "-m" | "--mods" => args.mods = parse_mods(&value)?,
+                        "-a" | "--acc" => args.acc = Some(parse_acc(&value)?),
+                        "-c" | "--combo" => args.combo = Some(parse_value(name, &value)?),
+                        "-x" | "--misses" => args.misses = Some(parse_value(name, &value)?),
+                        "--n300" => args.n300 = Some(parse_value(name, &value)?),
+                        "--n100" => args.n100 = Some(parse_value(name, &value)?),
+                        "--n50" => args.n50 = Some(parse_value(name, &value)?),
+                        "--katu" => args.n_katu = Some(parse_value(name, &value)?),
+                        "--score" => args.score = Some(parse_value(name, &value)?),
+                        "-p" | "--passed" => args.passed_objects = Some(parse_value(name, &value)?),
+                        "--clock-rate" => args.clock_rate = Some(parse_clock_rate(&value)?),
+                        "--algorithm" => args.version = Some(parse_version(&value)?),
+                        _ => return Err(format!("unknown option `{}`", name)),
+                    }
+                }
+            }
+        }
+
+        if args.paths.is_empty() {
+            return Err("no .osu files or directories given".to_owned());
+        }
+
+        Ok(Some(args))
+    }
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for `{}`", value, name))
+}
+
+fn parse_mods(value: &str) -> Result<u32, String> {
+    let mods = if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) {
+        let bits = parse_value("--mods", value)?;
+        let mods = GameMods::from_bits(bits);
+
+        mods.validate().map(|_| mods)
+    } else {
+        GameMods::from_str(value)
+    };
+
+    mods.map(GameMods::bits)
+        .map_err(|err| format!("invalid mods `{}`: {}", value, err))
+}
+
+fn parse_acc(value: &str) -> Result<Float, String> {
+    let acc: Float = parse_value("--acc", value)?;
+
+    if (0.0..=100.0).contains(&acc) {
+        Ok(acc)
+    } else {
+        Err(format!("invalid accuracy `{}`, expected 0 to 100", value))
+    }
+}
+
+fn parse_clock_rate(value: &str) -> Result<Float, String> {
+    let clock_rate: Float = parse_value("--clock-rate", value)?;
+
+    if clock_rate.is_finite() && clock_rate > 0.0 {
+        Ok(clock_rate)
+    } else {
+        Err(format!(
+            "invalid clock rate `{}`, expected a positive number",
+            value
+        ))
+    }
+}
+
+fn parse_version(value: &str) -> Result<AlgorithmVersion, String> {
+    match value {
+        "2021" => Ok(AlgorithmVersion::V2021),
+        "2022" => Ok(AlgorithmVersion::V2022),
+        "2024" => Ok(AlgorithmVersion::V2024),
+        _ => Err(format!(
+            "invalid algorithm version `{}`, expected 2021, 2022, or 2024",
+            value
+        )),
+    }
+}
+
+struct Row {
+    path: PathBuf,
+    result: PpResult,
+    attributes: BeatmapAttributes,
+    max_combo: Option<usize>,
+}
+
+fn main() {
+    let code = run(std::env::args().skip(1));
+
+    if code != 0 {
+        process::exit(code);
+    }
+}
+
+/// Calculates and prints all given maps, returns the exit code.
+fn run(input: impl Iterator<Item = String>) -> i32 {
+    let args = match Args::parse(input) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{}", USAGE);
+
+            return 0;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\nRun `peace-pp --help` for all options.", err);
+
+            return EXIT_USAGE;
+        }
+    };
+
+    let mut rows = Vec::new();
+    let mut failed = false;
+
+    for path in collect_paths(&args.paths, &mut failed) {
+        match calculate(&path, &args) {
+            Ok(row) => rows.push(row),
+            Err(err) => {
+                eprintln!("error: {}: {}", path.display(), err);
+                failed = true;
+            }
+        }
+    }
+
+    if args.json {
+        println!("{}", json(&rows));
+    } else if !rows.is_empty() {
+        print!("{}", table(&rows));
+    }
+
+    if failed {
+        EXIT_FAILED
+    } else {
+        0
+    }
+}
+
+/// Expands directories into their .osu files, sorted by name.
+fn collect_paths(paths: &[PathBuf], failed: &mut bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.to_owned());
+
+            continue;
+        }
+
+        let entries = fs::read_dir(path).and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+        });
+
+        match entries {
+            Ok(mut entries) => {
+                entries
+                    .retain(|entry| entry.is_file() && entry.extension() == Some("osu".as_ref()));
+                entries.sort();
+                files.append(&mut entries);
+            }
+            Err(err) => {
+                eprintln!("error: {}: {}", path.display(), err);
+                *failed = true;
+            }
+        }
+    }
+
+    files
+}
+
+#[cfg(not(any(feature = "async_std", feature = "async_tokio")))]
+fn parse(input: impl std::io::Read) -> ParseResult<Beatmap> {
+    Beatmap::parse(input)
+}
+
+#[cfg(any(feature = "async_std", feature = "async_tokio"))]
+fn parse(input: impl std::io::Read) -> ParseResult<Beatmap> {
+    Beatmap::parse_sync(input)
+}
+
+fn calculate(path: &Path, args: &Args) -> Result<Row, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let map = parse(file).map_err(|err| err.to_string())?;
+
+    let mut calculator = AnyPP::try_new(&map)
+        .map_err(|err| err.to_string())?
+        .mods(args.mods);
+
+    if let Some(version) = args.version {
+        calculator = calculator.version(version);
+    }
+
+    let mut params = DifficultyParams::default();
+
+    if let Some(clock_rate) = args.clock_rate {
+        calculator = calculator.clock_rate(clock_rate);
+        params = params.clock_rate(clock_rate);
+    }
+
+    if let Some(passed_objects) = args.passed_objects {
+        calculator = calculator.passed_objects(passed_objects);
+    }
+
+    if let Some(combo) = args.combo {
+        calculator = calculator.combo(combo);
+    }
+
+    if let Some(misses) = args.misses {
+        calculator = calculator.misses(misses);
+    }
+
+    if let Some(n300) = args.n300 {
+        calculator = calculator.n300(n300);
+    }
+
+    if let Some(n100) = args.n100 {
+        calculator = calculator.n100(n100);
+    }
+
+    if let Some(n50) = args.n50 {
+        calculator = calculator.n50(n50);
+    }
+
+    if let Some(n_katu) = args.n_katu {
+        calculator = calculator.n_katu(n_katu);
+    }
+
+    if let Some(score) = args.score {
+        calculator = calculator.score(score);
+    }
+
+    // The accuracy is converted into hit results so it must be set last
+    if let Some(acc) = args.acc {
+        calculator = calculator.accuracy(acc);
+    }
+
+    let result = calculator.try_calculate().map_err(|err| err.to_string())?;
+
+    let max_combo = match &result.attributes {
+        StarResult::Fruits(attributes) => Some(attributes.max_combo),
+        StarResult::Osu(attributes) => Some(attributes.max_combo),
+        StarResult::Taiko(_) => Some(map.n_circles as usize),
+        StarResult::Mania(_) => None,
+    };
+
+    Ok(Row {
+        path: path.to_owned(),
+        attributes: map.attributes().mods_with_params(args.mods, params),
+        result,
+        max_combo,
+    })
+}
+
+fn mode_name(mode: u8) -> &'static str {
+    match mode {
+        0 => "osu",
+        1 => "taiko",
+        2 => "fruits",
+        _ => "mania",
+    }
+}
+
+fn optional(value: Option<Float>) -> String {
+    value.map_or_else(|| "-".to_owned(), |value| format!("{:.2}", value))
+}
+
+fn table(rows: &[Row]) -> String {
+    const HEADER: [&str; 18] = [
+        "map", "mode", "mods", "version", "stars", "pp", "aim", "speed", "strain", "acc", "fl",
+        "grade", "combo", "ar", "od", "cs", "hp", "rate",
+    ];
+
+    let cells: Vec<[String; 18]> = rows
+        .iter()
+        .map(|row| {
+            let Row {
+                path,
+                result,
+                attributes,
+                max_combo,
+            } = row;
+
+            [
+                path.display().to_string(),
+                mode_name(result.mode).to_owned(),
+                GameMods::from(result.mods).to_string(),
+                result.version.to_string(),
+                format!("{:.2}", result.stars()),
+                format!("{:.2}", result.pp()),
+                optional(result.raw.aim),
+                optional(result.raw.spd),
+                optional(result.raw.str),
+                optional(result.raw.acc),
+                optional(result.raw.fl),
+                format!("{:?}", result.grade),
+                max_combo.map_or_else(|| "-".to_owned(), |combo| combo.to_string()),
+                format!("{:.2}", attributes.ar),
+                format!("{:.2}", attributes.od),
+                format!("{:.2}", attributes.cs),
+                format!("{:.2}", attributes.hp),
+                format!("{:.2}", attributes.clock_rate),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADER.map(str::len);
+
+    for row in cells.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut output = String::new();
+    let header = HEADER.map(str::to_owned);
+
+    for row in std::iter::once(&header).chain(cells.iter()) {
+        for (i, (cell, width)) in row.iter().zip(widths.iter()).enumerate() {
+            let _ = match i {
+                0 => write!(output, "{:<1$}", cell, width),
+                _ => write!(output, "  {:>1$}", cell, width),
+            };
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+fn json(rows: &[Row]) -> String {
+    let mut output = String::from("[");
+
+    for (i, row) in rows.iter().enumerate() {
+        let Row {
+            path,
+            result,
+            attributes,
+            max_combo,
+        } = row;
+
+        if i > 0 {
+            output.push(',');
+        }
+
+        let _ = write!(
+            output,
+            "\n  {{\"path\":{},\"mode\":\"{}\",\"mods\":{},\"mods_acronyms\":\"{}\",\
+            \"version\":{},\"stars\":{},\"pp\":{},\"pp_raw\":{{\"aim\":{},\"speed\":{},\
+            \"strain\":{},\"acc\":{},\"fl\":{},\"total\":{}}},\"grade\":\"{:?}\",\
+            \"max_combo\":{},\"attributes\":{{\"ar\":{},\"od\":{},\"cs\":{},\"hp\":{},\
+            \"clock_rate\":{}}}}}",
+            json_string(&path.display().to_string()),
+            mode_name(result.mode),
+            result.mods,
+            GameMods::from(result.mods),
+            result.version,
+            json_float(Some(result.stars())),
+            json_float(Some(result.pp())),
+            json_float(result.raw.aim),
+            json_float(result.raw.spd),
+            json_float(result.raw.str),
+            json_float(result.raw.acc),
+            json_float(result.raw.fl),
+            json_float(Some(result.raw.total)),
+            result.grade,
+            max_combo.map_or_else(|| "null".to_owned(), |combo| combo.to_string()),
+            json_float(Some(attributes.ar)),
+            json_float(Some(attributes.od)),
+            json_float(Some(attributes.cs)),
+            json_float(Some(attributes.hp)),
+            json_float(Some(attributes.clock_rate)),
+        );
+    }
+
+    if !rows.is_empty() {
+        output.push('\n');
+    }
+
+    output.push(']');
+
+    output
+}
+
+/// JSON has no representation for NaN or infinity so those become `null` as well.
+fn json_float(value: Option<Float>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => "null".to_owned(),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(input: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(input.iter().map(|arg| arg.to_string()))
+    }
+
+    fn run_args(input: &[&str]) -> i32 {
+        run(input.iter().map(|arg| arg.to_string()))
+    }
+
+    /// Writes a small .osu file with the given hit objects into the temp dir.
+    fn osu_file(name: &str, hit_objects: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("peace-pp-{}-{}.osu", process::id(), name));
+
+        let content = format!(
+            "osu file format v14\n\n\
+            [Difficulty]\n\
+            HPDrainRate:5\n\
+            CircleSize:4\n\
+            OverallDifficulty:8\n\
+            ApproachRate:9\n\
+            SliderMultiplier:1.4\n\
+            SliderTickRate:1\n\n\
+            [TimingPoints]\n\
+            0,500,4,2,0,100,1,0\n\n\
+            [HitObjects]\n{}",
+            hit_objects
+        );
+
+        fs::write(&path, content).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn parse_options() {
+        let args = parse_args(&[
+            "a.osu",
+            "-m",
+            "HDDT",
+            "--acc=98.5",
+            "-c",
+            "500",
+            "--misses=2",
+            "--n100",
+            "10",
+            "--clock-rate=1.2",
+            "--algorithm",
+            "2022",
+            "-j",
+            "b.osu",
+        ])
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(args.paths, [PathBuf::from("a.osu"), PathBuf::from("b.osu")]);
+        assert_eq!(args.mods, 72);
+        assert_eq!(args.acc, Some(98.5));
+        assert_eq!(args.combo, Some(500));
+        assert_eq!(args.misses, Some(2));
+        assert_eq!(args.n100, Some(10));
+        assert_eq!(args.clock_rate, Some(1.2));
+        assert_eq!(args.version, Some(AlgorithmVersion::V2022));
+        assert!(args.json);
+
+        assert!(parse_args(&["-h"]).unwrap().is_none());
+        assert!(parse_args(&["a.osu", "--help"]).unwrap().is_none());
+
+        assert!(parse_args(&[]).is_err());
+        assert!(parse_args(&["a.osu", "--unknown", "1"]).is_err());
+        assert!(parse_args(&["a.osu", "--combo"]).is_err());
+        assert!(parse_args(&["a.osu", "--combo=-1"]).is_err());
+        assert!(parse_args(&["a.osu", "--passed", "many"]).is_err());
+    }
+
+    #[test]
+    fn parse_mods_and_version() {
+        assert_eq!(parse_mods("HDDT"), Ok(72));
+        assert_eq!(parse_mods("hddt"), Ok(72));
+        assert_eq!(parse_mods("72"), Ok(72));
+        assert_eq!(parse_mods("NC"), parse_mods("576"));
+        assert!(parse_mods("DTHT").is_err());
+        assert!(parse_mods("320").is_err());
+        assert!(parse_mods("XY").is_err());
+        assert!(parse_mods("99999999999").is_err());
+
+        assert_eq!(parse_version("2021"), Ok(AlgorithmVersion::V2021));
+        assert_eq!(parse_version("2024"), Ok(AlgorithmVersion::V2024));
+        assert!(parse_version("2023").is_err());
+        assert!(parse_version("").is_err());
+    }
+
+    #[test]
+    fn parse_validates_values() {
+        assert_eq!(parse_acc("0"), Ok(0.0));
+        assert_eq!(parse_acc("100"), Ok(100.0));
+
+        for acc in ["-1", "100.5", "NaN", "inf", "high"] {
+            assert!(parse_acc(acc).is_err(), "{}", acc);
+        }
+
+        assert_eq!(parse_clock_rate("1.5"), Ok(1.5));
+
+        for clock_rate in ["0", "-1", "NaN", "inf", "fast"] {
+            assert!(parse_clock_rate(clock_rate).is_err(), "{}", clock_rate);
+        }
+    }
+
+    #[test]
+    fn json_values() {
+        assert_eq!(json_float(Some(1.5)), "1.5");
+        assert_eq!(json_float(None), "null");
+        assert_eq!(json_float(Some(Float::NAN)), "null");
+        assert_eq!(json_float(Some(Float::INFINITY)), "null");
+
+        assert_eq!(json_string("map.osu"), "\"map.osu\"");
+        assert_eq!(json_string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(json_string("a\nb\tc\r"), "\"a\\nb\\tc\\r\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+        assert_eq!(json_string("ア"), "\"ア\"");
+    }
+
+    #[test]
+    fn exit_codes() {
+        let map = osu_file("circles", "256,192,1000,1,0\n256,192,1500,1,0\n");
+        let empty = osu_file("empty", "");
+        let missing = std::env::temp_dir().join("peace-pp-missing.osu");
+
+        let map = map.to_str().unwrap();
+        let empty = empty.to_str().unwrap();
+        let missing = missing.to_str().unwrap();
+
+        assert_eq!(run_args(&["--help"]), 0);
+        assert_eq!(run_args(&[map]), 0);
+        assert_eq!(
+            run_args(&[map, "-m", "HR", "--acc", "95", "--clock-rate", "1.2"]),
+            0
+        );
+
+        assert_eq!(run_args(&[]), EXIT_USAGE);
+        assert_eq!(run_args(&[map, "--acc", "101"]), EXIT_USAGE);
+        assert_eq!(run_args(&[map, "--clock-rate=0"]), EXIT_USAGE);
+        assert_eq!(run_args(&[map, "--mods", "DTHT"]), EXIT_USAGE);
+
+        assert_eq!(run_args(&[missing]), EXIT_FAILED);
+        assert_eq!(run_args(&[empty]), EXIT_FAILED);
+        assert_eq!(run_args(&[map, missing]), EXIT_FAILED);
+
+        let _ = fs::remove_file(map);
+        let _ = fs::remove_file(empty);
+    }
+}
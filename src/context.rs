@@ -0,0 +1,340 @@
+/// Buffers of star and strain calculations that are kept between calculations.
+///
+/// The `stars_with_context` and `strains_with_context` functions of each mode
+/// use the buffers of the given context instead of allocating new ones so that
+/// repeated calculations on one thread reach a steady state without allocations.
+///
+/// Creating a context does not allocate, its buffers grow as they are used.
+/// Maps that need to be converted to another mode are still converted on each call,
+/// convert them up front with [`Beatmap::to_taiko`](crate::Beatmap::to_taiko) or
+/// [`Beatmap::to_mania`](crate::Beatmap::to_mania) to avoid that.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "taiko")]
+/// # {
+/// use peace_performance::{taiko, Beatmap, CalcContext, DifficultyParams};
+///
+/// # let maps: Vec<Beatmap> = Vec::new();
+/// let mut ctx = CalcContext::new();
+///
+/// for map in maps.iter() {
+///     let params = DifficultyParams::default();
+///     let stars = taiko::stars_with_context(map, 0, None, params, &mut ctx).stars();
+///     println!("{}", stars);
+/// }
+/// # }
+/// ```
+#[derive(Default)]
+pub struct CalcContext {
+    #[cfg(feature = "all_included")]
+    pub(crate) all_included: crate::osu::all_included::Buffers,
+    #[cfg(feature = "all_included")]
+    pub(crate) v2024: crate::osu::v2024::Buffers,
+    #[cfg(feature = "no_leniency")]
+    pub(crate) no_leniency: crate::osu::no_leniency::Buffers,
+    #[cfg(feature = "no_sliders_no_leniency")]
+    pub(crate) no_sliders_no_leniency: crate::osu::no_sliders_no_leniency::Buffers,
+    #[cfg(feature = "taiko")]
+    pub(crate) taiko: crate::taiko::Buffers,
+    #[cfg(feature = "fruits")]
+    pub(crate) fruits: crate::fruits::Buffers,
+    #[cfg(feature = "mania")]
+    pub(crate) mania: crate::mania::Buffers,
+}
+
+impl CalcContext {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Previously used values whose allocations can be reused.
+#[cfg(feature = "osu")]
+pub(crate) struct Pool<T>(Vec<T>);
+
+#[cfg(feature = "osu")]
+impl<T> Default for Pool<T> {
+    #[inline]
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+#[cfg(feature = "osu")]
+impl<T> Pool<T> {
+    #[inline]
+    pub(crate) fn take(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    /// Moves all values into the pool, leaving `values` empty.
+    #[inline]
+    pub(crate) fn put_all(&mut self, values: &mut Vec<T>) {
+        self.0.append(values);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(feature = "osu", feature = "taiko", feature = "fruits"))]
+    use crate::{test_util, Beatmap, DifficultyParams, GameMode, StarResult, Strains};
+
+    /// The map and a shorter one so that buffers shrink and grow between calculations.
+    #[cfg(any(feature = "osu", feature = "taiko", feature = "fruits"))]
+    fn maps(mode: GameMode) -> [Beatmap; 2] {
+        let map = test_util::map(mode);
+
+        let short = Beatmap {
+            hit_objects: map.hit_objects[..60].to_vec(),
+            ..map.clone()
+        };
+
+        [map, short]
+    }
+
+    #[cfg(any(feature = "osu", feature = "taiko", feature = "fruits"))]
+    fn assert_reuse(
+        maps: &[Beatmap],
+        stars: impl Fn(&Beatmap, u32, Option<usize>, DifficultyParams) -> StarResult,
+        stars_ctx: impl Fn(
+            &Beatmap,
+            u32,
+            Option<usize>,
+            DifficultyParams,
+            &mut CalcContext,
+        ) -> StarResult,
+        strains: impl Fn(&Beatmap, u32, DifficultyParams) -> Strains,
+        strains_ctx: impl Fn(&Beatmap, u32, DifficultyParams, &mut CalcContext) -> Strains,
+    ) {
+        let mut ctx = CalcContext::new();
+
+        for map in maps.iter() {
+            for &mods in &[0, 8, 24, 64, 1026, 1096] {
+                for &passed_objects in &[None, Some(40)] {
+                    let params = DifficultyParams::default().clock_rate(1.1);
+                    let fresh = stars(map, mods, passed_objects, params);
+                    let reused = stars_ctx(map, mods, passed_objects, params, &mut ctx);
+
+                    assert_eq!(format!("{:?}", fresh), format!("{:?}", reused));
+                }
+
+                let fresh = strains(map, mods, DifficultyParams::default());
+                let reused = strains_ctx(map, mods, DifficultyParams::default(), &mut ctx);
+
+                assert_eq!(fresh.strains, reused.strains);
+                assert_eq!(fresh.section_length, reused.section_length);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "all_included")]
+    fn osu_context_reuse_matches_fresh() {
+        use crate::osu::{all_included, v2024};
+
+        let maps = maps(GameMode::STD);
+
+        assert_reuse(
+            &maps,
+            |map, mods, passed, params| all_included::stars_with_params(map, mods, passed, params),
+            |map, mods, passed, params, ctx| {
+                all_included::stars_with_context(map, mods, passed, params, ctx)
+            },
+            |map, mods, params| all_included::strains_with_params(map, mods, params),
+            |map, mods, params, ctx| all_included::strains_with_context(map, mods, params, ctx),
+        );
+
+        assert_reuse(
+            &maps,
+            |map, mods, passed, params| v2024::stars_with_params(map, mods, passed, params),
+            |map, mods, passed, params, ctx| {
+                v2024::stars_with_context(map, mods, passed, params, ctx)
+            },
+            |map, mods, params| v2024::strains_with_params(map, mods, params),
+            |map, mods, params, ctx| v2024::strains_with_context(map, mods, params, ctx),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "no_leniency")]
+    fn osu_no_leniency_context_reuse_matches_fresh() {
+        use crate::osu::no_leniency;
+
+        assert_reuse(
+            &maps(GameMode::STD),
+            |map, mods, passed, params| no_leniency::stars_with_params(map, mods, passed, params),
+            |map, mods, passed, params, ctx| {
+                no_leniency::stars_with_context(map, mods, passed, params, ctx)
+            },
+            |map, mods, params| no_leniency::strains_with_params(map, mods, params),
+            |map, mods, params, ctx| no_leniency::strains_with_context(map, mods, params, ctx),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "no_sliders_no_leniency")]
+    fn osu_no_sliders_no_leniency_context_reuse_matches_fresh() {
+        use crate::osu::no_sliders_no_leniency as version;
+
+        assert_reuse(
+            &maps(GameMode::STD),
+            |map, mods, passed, params| version::stars_with_params(map, mods, passed, params),
+            |map, mods, passed, params, ctx| {
+                version::stars_with_context(map, mods, passed, params, ctx)
+            },
+            |map, mods, params| version::strains_with_params(map, mods, params),
+            |map, mods, params, ctx| version::strains_with_context(map, mods, params, ctx),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "taiko")]
+    fn taiko_context_reuse_matches_fresh() {
+        use crate::taiko;
+
+        #[cfg_attr(not(feature = "osu"), allow(unused_mut))]
+        let mut maps = maps(GameMode::TKO).to_vec();
+
+        #[cfg(feature = "osu")]
+        {
+            let std_map = test_util::map(GameMode::STD);
+            let converted = std_map.to_taiko().into_owned();
+
+            // Converting up front gives the same result as converting on each call
+            let mut ctx = CalcContext::new();
+            let params = DifficultyParams::default();
+            let stars = taiko::stars_with_context(&std_map, 0, None, params, &mut ctx).stars();
+            let converted_stars =
+                taiko::stars_with_context(&converted, 0, None, params, &mut ctx).stars();
+
+            assert_eq!(stars, converted_stars);
+
+            maps.push(std_map);
+        }
+
+        assert_reuse(
+            &maps,
+            |map, mods, passed, params| taiko::stars_with_params(map, mods, passed, params),
+            |map, mods, passed, params, ctx| {
+                taiko::stars_with_context(map, mods, passed, params, ctx)
+            },
+            |map, mods, params| taiko::strains_with_params(map, mods, params),
+            |map, mods, params, ctx| taiko::strains_with_context(map, mods, params, ctx),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "fruits")]
+    fn fruits_context_reuse_matches_fresh() {
+        use crate::fruits;
+
+        #[cfg_attr(not(feature = "osu"), allow(unused_mut))]
+        let mut maps = maps(GameMode::CTB).to_vec();
+
+        #[cfg(feature = "osu")]
+        maps.push(test_util::map(GameMode::STD));
+
+        assert_reuse(
+            &maps,
+            |map, mods, passed, params| fruits::stars_with_params(map, mods, passed, params),
+            |map, mods, passed, params, ctx| {
+                fruits::stars_with_context(map, mods, passed, params, ctx)
+            },
+            |map, mods, params| fruits::strains_with_params(map, mods, params),
+            |map, mods, params, ctx| fruits::strains_with_context(map, mods, params, ctx),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "mania")]
+    fn context_reuse_matches_fresh() {
+        use crate::{
+            mania,
+            parse::{HitObject, HitObjectKind, Pos2},
+            AlgorithmVersion, Beatmap, DifficultyParams, Float, GameMode,
+        };
+
+        let map = |columns: u8, n_objects: usize| {
+            let hit_objects = (0..n_objects)
+                .map(|i| HitObject {
+                    pos: Pos2 {
+                        x: (i * 7 % columns as usize) as Float * 512.0 / columns as Float,
+                        y: 192.0,
+                    },
+                    start_time: (i * 120 + i % 3 * 40) as Float,
+                    kind: if i % 5 == 0 {
+                        HitObjectKind::Hold {
+                            end_time: (i * 120 + 300) as Float,
+                        }
+                    } else {
+                        HitObjectKind::Circle
+                    },
+                    sound: 0,
+                })
+                .collect();
+
+            Beatmap {
+                mode: GameMode::MNA,
+                cs: columns as Float,
+                hit_objects,
+                ..Default::default()
+            }
+        };
+
+        let maps = [map(7, 500), map(4, 120)];
+        let mut ctx = CalcContext::new();
+
+        for map in maps.iter() {
+            for &version in &[AlgorithmVersion::V2022, AlgorithmVersion::V2021] {
+                let params = DifficultyParams::default();
+                let fresh = mania::stars_with_version(map, 64, None, params, version);
+                let reused = mania::stars_with_context(map, 64, None, params, version, &mut ctx);
+
+                assert_eq!(fresh.stars(), reused.stars());
+
+                let fresh = mania::strains_with_version(map, 0, params, version);
+                let reused = mania::strains_with_context(map, 0, params, version, &mut ctx);
+
+                assert_eq!(fresh.strains, reused.strains);
+            }
+        }
+
+        // Chords of hold notes with close releases are only considered since V2022
+        let hold = |x: Float, start_time: Float, len: Float| HitObject {
+            pos: Pos2 { x, y: 192.0 },
+            start_time,
+            kind: HitObjectKind::Hold {
+                end_time: start_time + len,
+            },
+            sound: 0,
+        };
+
+        let chords = Beatmap {
+            mode: GameMode::MNA,
+            cs: 4.0,
+            hit_objects: (0..100)
+                .flat_map(|i| {
+                    let start_time = i as Float * 250.0;
+
+                    vec![
+                        hold(64.0, start_time, 150.0),
+                        hold(192.0, start_time, 160.0),
+                    ]
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let params = DifficultyParams::default();
+        let v2021 = mania::strains_with_version(&chords, 0, params, AlgorithmVersion::V2021);
+        let v2022 = mania::strains_with_version(&chords, 0, params, AlgorithmVersion::V2022);
+
+        assert_eq!(mania::strains(&chords, 0).strains, v2021.strains);
+        assert_ne!(v2021.strains, v2022.strains);
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     parse::{DifficultyPoint, TimingPoint},
-    Beatmap,
+    Beatmap, Float,
 };
 
 use std::slice::Iter;
@@ -17,8 +17,8 @@ pub(crate) struct ControlPointIter<'p> {
     timing_points: Iter<'p, TimingPoint>,
     difficulty_points: Iter<'p, DifficultyPoint>,
 
-    next_timing: Option<(f32, f32)>,
-    next_difficulty: Option<(f32, f32)>,
+    next_timing: Option<(Float, Float)>,
+    next_difficulty: Option<(Float, Float)>,
 }
 
 impl<'p> ControlPointIter<'p> {
@@ -39,20 +39,20 @@ impl<'p> ControlPointIter<'p> {
 
 pub(crate) enum ControlPoint {
     Timing {
-        time: f32,
+        time: Float,
         #[allow(dead_code)]
-        beat_len: f32,
+        beat_len: Float,
     },
     Difficulty {
-        time: f32,
-        speed_mult: f32,
+        time: Float,
+        speed_mult: Float,
     },
 }
 
 impl ControlPoint {
     #[inline]
     #[allow(dead_code)]
-    pub(crate) fn time(&self) -> f32 {
+    pub(crate) fn time(&self) -> Float {
         match self {
             Self::Timing { time, .. } => *time,
             Self::Difficulty { time, .. } => *time,
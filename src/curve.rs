@@ -6,19 +6,20 @@
 use crate::{
     math_util,
     parse::{PathType, Pos2},
+    Float,
 };
 
 const BEZIER_TOLERANCE: f32 = 0.25;
-const CATMULL_DETAIL: f32 = 50.0;
+const CATMULL_DETAIL: Float = 50.0;
 
-pub(crate) enum Points {
+pub(crate) enum Points<'p> {
     Single(Pos2),
-    Multi(Vec<Pos2>),
+    Multi(&'p [Pos2]),
 }
 
-impl Points {
+impl Points<'_> {
     #[inline]
-    fn point_at_distance(&self, dist: f32) -> Pos2 {
+    fn point_at_distance(&self, dist: Float) -> Pos2 {
         match self {
             Points::Multi(points) => math_util::point_at_distance(points, dist),
             Points::Single(point) => *point,
@@ -27,55 +28,61 @@ impl Points {
 }
 
 pub(crate) enum Curve<'p> {
-    Bezier(Points),
-    Catmull(Points),
+    Bezier(Points<'p>),
+    Catmull(Points<'p>),
     Linear(&'p [Pos2]),
     Perfect {
         origin: Pos2,
         center: Pos2,
-        radius: f32,
+        radius: Float,
     },
 }
 
 impl<'p> Curve<'p> {
+    /// The points of bezier and catmull curves are computed into `buf`
+    /// so that its allocation can be reused for the next curve.
     #[inline]
-    pub(crate) fn new(points: &'p [Pos2], kind: PathType) -> Self {
+    pub(crate) fn new(points: &'p [Pos2], kind: PathType, buf: &'p mut Vec<Pos2>) -> Self {
         match kind {
-            PathType::Bezier => Self::bezier(points),
-            PathType::Catmull => Self::catmull(points),
+            PathType::Bezier => Self::bezier(points, buf),
+            PathType::Catmull => Self::catmull(points, buf),
             PathType::Linear => Self::Linear(points),
             PathType::PerfectCurve => Self::perfect(points),
         }
     }
 
-    fn bezier(points: &[Pos2]) -> Self {
+    fn bezier(points: &[Pos2], result: &'p mut Vec<Pos2>) -> Self {
         if points.len() == 1 {
             return Self::Bezier(Points::Single(points[0]));
         }
 
         let mut start = 0;
-        let mut result = Vec::new();
+        result.clear();
 
         for (end, (curr, next)) in (1..).zip(points.iter().zip(points.iter().skip(1))) {
             if end - start > 1 && curr == next {
-                Self::_bezier(&mut result, &points[start..end]);
+                Self::_bezier(result, &points[start..end]);
                 start = end;
             }
         }
 
-        Self::_bezier(&mut result, &points[start..]);
+        Self::_bezier(result, &points[start..]);
 
         Self::Bezier(Points::Multi(result))
     }
 
     fn _bezier(result: &mut Vec<Pos2>, points: &[Pos2]) {
+        // The amount of points depends on the accumulated rounding error
+        // so the steps are always done in single precision
         let step = (BEZIER_TOLERANCE / points.len() as f32).max(0.01);
-        let mut i = 0.0;
+        let mut i: f32 = 0.0;
         let n = points.len() as i32 - 1;
 
         while i < 1.0 + step {
+            let t = i as Float;
+
             let point = (0..).zip(points).fold(Pos2::zero(), |point, (p, curr)| {
-                point + *curr * math_util::cpn(p, n) * (1.0 - i).powi(n - p) * i.powi(p)
+                point + *curr * math_util::cpn(p, n) * (1.0 - t).powi(n - p) * t.powi(p)
             });
 
             result.push(point);
@@ -83,14 +90,15 @@ impl<'p> Curve<'p> {
         }
     }
 
-    fn catmull(points: &[Pos2]) -> Self {
+    fn catmull(points: &[Pos2], result: &'p mut Vec<Pos2>) -> Self {
         let len = points.len();
 
         if len == 1 {
             return Self::Catmull(Points::Single(points[0]));
         }
 
-        let mut result = Vec::with_capacity((len as f32 * CATMULL_DETAIL * 2.0) as usize);
+        result.clear();
+        result.reserve((len as Float * CATMULL_DETAIL * 2.0) as usize);
 
         // Handle first iteration distinctly because of v1
         let v1 = points[0];
@@ -98,14 +106,14 @@ impl<'p> Curve<'p> {
         let v3 = points.get(1).copied().unwrap_or(v2);
         let v4 = points.get(2).copied().unwrap_or_else(|| v3 * 2.0 - v2);
 
-        Self::catmull_points(&mut result, v1, v2, v3, v4);
+        Self::catmull_points(result, v1, v2, v3, v4);
 
         // Remaining iterations
         for (i, (&v1, &v2)) in (2..).zip(points.iter().zip(points.iter().skip(1))) {
             let v3 = points.get(i).copied().unwrap_or_else(|| v2 * 2.0 - v1);
             let v4 = points.get(i + 1).copied().unwrap_or_else(|| v3 * 2.0 - v2);
 
-            Self::catmull_points(&mut result, v1, v2, v3, v4);
+            Self::catmull_points(result, v1, v2, v3, v4);
         }
 
         Self::Catmull(Points::Multi(result))
@@ -155,7 +163,7 @@ impl<'p> Curve<'p> {
     fn perfect(points: &[Pos2]) -> Self {
         let (a, b, c) = (points[0], points[1], points[2]);
         let (center, mut radius) = math_util::get_circum_circle(a, b, c);
-        radius *= ((!math_util::is_left(a, b, c)) as i8 * 2 - 1) as f32;
+        radius *= ((!math_util::is_left(a, b, c)) as i8 * 2 - 1) as Float;
 
         Self::Perfect {
             origin: a,
@@ -164,7 +172,7 @@ impl<'p> Curve<'p> {
         }
     }
 
-    pub(crate) fn point_at_distance(&self, dist: f32) -> Pos2 {
+    pub(crate) fn point_at_distance(&self, dist: Float) -> Pos2 {
         match self {
             Self::Bezier(points) => points.point_at_distance(dist),
             Self::Catmull(points) => points.point_at_distance(dist),
@@ -176,4 +184,22 @@ impl<'p> Curve<'p> {
             } => math_util::rotate(*center, *origin, dist / *radius),
         }
     }
+
+    /// Same as `point_at_distance` but a path whose last segment has no length
+    /// is not extrapolated beyond its end, as is the case in lazer.
+    #[cfg(feature = "all_included")]
+    pub(crate) fn point_at_distance_unextended(&self, dist: Float) -> Pos2 {
+        let pos = self.point_at_distance(dist);
+
+        if pos.is_finite() {
+            return pos;
+        }
+
+        match self {
+            Self::Bezier(Points::Multi(points))
+            | Self::Catmull(Points::Multi(points))
+            | Self::Linear(points) => points.last().copied().unwrap_or(pos),
+            _ => pos,
+        }
+    }
 }
@@ -0,0 +1,221 @@
+use crate::{parse::HitObjectKind, Beatmap, Float, GameMode};
+
+use std::error::Error as StdError;
+use std::fmt;
+
+pub type CalcResult<T> = Result<T, CalcError>;
+
+/// Error when a map can not be used for a difficulty or pp calculation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CalcError {
+    /// The map contains no hit objects.
+    NoHitObjects,
+    /// The value is not a finite number or out of its valid range.
+    InvalidValue(&'static str),
+    /// The map's mode can not be converted into the mode of the calculation.
+    UnsupportedConversion { from: GameMode, to: GameMode },
+
+    /// The feature of the mode has not been included.
+    ///
+    /// Always defined so that matches don't depend on the enabled features.
+    UnincludedMode(GameMode),
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoHitObjects => f.write_str("map contains no hit objects"),
+            Self::InvalidValue(value) => write!(f, "invalid value for `{}`", value),
+            Self::UnsupportedConversion { from, to } => {
+                write!(f, "cannot convert {:?} map to {:?}", from, to)
+            }
+            Self::UnincludedMode(mode) => write!(
+                f,
+                "cannot calculate {:?} values; the mode's feature has not been included",
+                mode
+            ),
+        }
+    }
+}
+
+impl StdError for CalcError {}
+
+impl Beatmap {
+    /// Check that calculations of the given mode can be performed on this map.
+    ///
+    /// Parsed maps only fail if they can not be converted or have no hit objects
+    /// but manually created maps are checked for invalid values as well.
+    pub fn check_calculable(&self, mode: GameMode) -> CalcResult<()> {
+        match mode {
+            #[cfg(not(feature = "osu"))]
+            GameMode::STD => return Err(CalcError::UnincludedMode(mode)),
+            #[cfg(not(feature = "taiko"))]
+            GameMode::TKO => return Err(CalcError::UnincludedMode(mode)),
+            #[cfg(not(feature = "fruits"))]
+            GameMode::CTB => return Err(CalcError::UnincludedMode(mode)),
+            #[cfg(not(feature = "mania"))]
+            GameMode::MNA => return Err(CalcError::UnincludedMode(mode)),
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+
+        // Only osu!standard maps can be converted
+        if self.mode != mode && self.mode != GameMode::STD {
+            return Err(CalcError::UnsupportedConversion {
+                from: self.mode,
+                to: mode,
+            });
+        }
+
+        if self.hit_objects.is_empty() {
+            return Err(CalcError::NoHitObjects);
+        }
+
+        let finite = |value: Float, name| {
+            if value.is_finite() {
+                Ok(())
+            } else {
+                Err(CalcError::InvalidValue(name))
+            }
+        };
+
+        let positive = |value: Float, name| {
+            if value.is_finite() && value > 0.0 {
+                Ok(())
+            } else {
+                Err(CalcError::InvalidValue(name))
+            }
+        };
+
+        finite(self.ar, "ar")?;
+        finite(self.od, "od")?;
+        finite(self.cs, "cs")?;
+        finite(self.hp, "hp")?;
+        positive(self.sv, "sv")?;
+        positive(self.tick_rate, "tick rate")?;
+
+        for point in self.timing_points.iter() {
+            finite(point.time, "timing point time")?;
+            positive(point.beat_len, "beat len")?;
+        }
+
+        for point in self.difficulty_points.iter() {
+            finite(point.time, "difficulty point time")?;
+            positive(point.speed_multiplier, "speed multiplier")?;
+        }
+
+        for h in self.hit_objects.iter() {
+            finite(h.pos.x, "x position")?;
+            finite(h.pos.y, "y position")?;
+            finite(h.start_time, "hitobject time")?;
+
+            match &h.kind {
+                HitObjectKind::Circle => {}
+                HitObjectKind::Slider {
+                    end_time,
+                    pixel_len,
+                    ..
+                } => {
+                    finite(*end_time, "slider endtime")?;
+
+                    if !(pixel_len.is_finite() && *pixel_len >= 0.0) {
+                        return Err(CalcError::InvalidValue("pixel len"));
+                    }
+                }
+                HitObjectKind::Spinner { end_time } => finite(*end_time, "spinner endtime")?,
+                HitObjectKind::Hold { end_time } => finite(*end_time, "hold endtime")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(all(feature = "mania", feature = "taiko"))]
+    fn check_degenerate_maps() {
+        use super::*;
+        use crate::parse::{HitObject, Pos2};
+
+        let mut map = Beatmap {
+            mode: GameMode::MNA,
+            sv: 1.0,
+            tick_rate: 1.0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            map.check_calculable(GameMode::MNA),
+            Err(CalcError::NoHitObjects)
+        );
+
+        map.hit_objects.push(HitObject {
+            pos: Pos2::default(),
+            start_time: 0.0,
+            kind: HitObjectKind::Circle,
+            sound: 0,
+        });
+
+        assert_eq!(map.check_calculable(GameMode::MNA), Ok(()));
+
+        assert_eq!(
+            map.check_calculable(GameMode::TKO),
+            Err(CalcError::UnsupportedConversion {
+                from: GameMode::MNA,
+                to: GameMode::TKO
+            })
+        );
+
+        map.sv = Float::NAN;
+        assert_eq!(
+            map.check_calculable(GameMode::MNA),
+            Err(CalcError::InvalidValue("sv"))
+        );
+    }
+
+    #[cfg(feature = "osu")]
+    #[test]
+    fn try_paths_return_errors() {
+        use super::*;
+        use crate::{test_util, AnyPP, BeatmapExt};
+
+        let empty = Beatmap::default();
+        let err = Some(CalcError::NoHitObjects);
+
+        assert_eq!(empty.try_stars(0, None).err(), err);
+        assert_eq!(empty.try_max_pp(0).err(), err);
+        assert_eq!(empty.try_strains(0).err(), err);
+
+        let map = test_util::map(GameMode::STD);
+        assert!(map.try_stars(0, None).is_ok());
+
+        let result =
+            AnyPP::try_new(&map).map(|calculator| calculator.clock_rate(-1.0).try_calculate());
+
+        assert_eq!(
+            result.map(|result| result.err()),
+            Ok(Some(CalcError::InvalidValue("clock rate")))
+        );
+    }
+
+    #[cfg(not(feature = "mania"))]
+    #[test]
+    fn try_paths_return_unincluded_mode() {
+        use super::*;
+        use crate::{AnyPP, BeatmapExt};
+
+        let map = Beatmap {
+            mode: GameMode::MNA,
+            ..Default::default()
+        };
+        let err = Some(CalcError::UnincludedMode(GameMode::MNA));
+
+        assert_eq!(map.try_stars(0, None).err(), err);
+        assert_eq!(map.try_max_pp(0).err(), err);
+        assert_eq!(map.try_strains(0).err(), err);
+        assert_eq!(AnyPP::try_new(&map).err(), err);
+        assert!(map.strains(0).strains.is_empty());
+    }
+}
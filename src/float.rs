@@ -0,0 +1,21 @@
+/// Floating point type of all parsed values and calculations.
+///
+/// Defaults to `f32`. The `f64` feature switches to double precision
+/// like osu! does for most of its calculations.
+#[cfg(not(feature = "f64"))]
+pub type Float = f32;
+
+/// Floating point type of all parsed values and calculations.
+///
+/// Defaults to `f32`. The `f64` feature switches to double precision
+/// like osu! does for most of its calculations.
+#[cfg(feature = "f64")]
+pub type Float = f64;
+
+#[cfg(not(feature = "f64"))]
+#[allow(unused_imports)]
+pub(crate) use std::f32::consts;
+
+#[cfg(feature = "f64")]
+#[allow(unused_imports)]
+pub(crate) use std::f64::consts;
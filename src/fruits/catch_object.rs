@@ -1,20 +1,20 @@
-use crate::parse::Pos2;
+use crate::{parse::Pos2, Float};
 
-const PLAYFIELD_WIDTH: f32 = 512.0;
-const BASE_SPEED: f32 = 1.0;
+const PLAYFIELD_WIDTH: Float = 512.0;
+const BASE_SPEED: Float = 1.0;
 
 #[derive(Clone)]
 pub struct CatchObject {
-    pub(crate) pos: f32,
-    pub(crate) time: f32,
+    pub(crate) pos: Float,
+    pub(crate) time: Float,
 
     pub(crate) hyper_dash: bool,
-    pub(crate) hyper_dist: f32,
+    pub(crate) hyper_dist: Float,
 }
 
 impl CatchObject {
     #[inline]
-    pub(crate) fn new((pos, time): (Pos2, f32)) -> Self {
+    pub(crate) fn new((pos, time): (Pos2, Float)) -> Self {
         Self {
             pos: pos.x,
             time,
@@ -23,14 +23,14 @@ impl CatchObject {
         }
     }
 
-    pub(crate) fn with_hr(mut self, last_pos: &mut Option<f32>, last_time: &mut f32) -> Self {
+    pub(crate) fn with_hr(mut self, last_pos: &mut Option<Float>, last_time: &mut Float) -> Self {
         let mut offset_pos = self.pos;
         let time_diff = self.time - *last_time;
 
         if let Some(last_pos_ref) = last_pos.filter(|_| time_diff <= 1000.0) {
             let pos_diff = offset_pos - last_pos_ref;
 
-            if pos_diff.abs() > f32::EPSILON {
+            if pos_diff.abs() > Float::EPSILON {
                 if pos_diff.abs() < (time_diff / 3.0).floor() {
                     if pos_diff > 0.0 {
                         if offset_pos + pos_diff < PLAYFIELD_WIDTH {
@@ -56,10 +56,10 @@ impl CatchObject {
 
     pub(crate) fn init_hyper_dash(
         &mut self,
-        half_catcher_width: f32,
+        half_catcher_width: Float,
         next: &CatchObject,
         last_direction: &mut i8,
-        last_excess: &mut f32,
+        last_excess: &mut Float,
     ) {
         let next_x = next.pos;
         let curr_x = self.pos;
@@ -1,19 +1,20 @@
 use super::CatchObject;
+use crate::Float;
 
-const NORMALIZED_HITOBJECT_RADIUS: f32 = 41.0;
+const NORMALIZED_HITOBJECT_RADIUS: Float = 41.0;
 
 pub(crate) struct DifficultyObject<'o> {
     pub(crate) base: &'o CatchObject,
     pub(crate) last: &'o CatchObject,
 
-    pub(crate) delta: f32,
-    pub(crate) start_time: f32,
+    pub(crate) delta: Float,
+    pub(crate) start_time: Float,
 
-    pub(crate) normalized_pos: f32,
-    pub(crate) last_normalized_pos: f32,
+    pub(crate) normalized_pos: Float,
+    pub(crate) last_normalized_pos: Float,
 
-    pub(crate) strain_time: f32,
-    pub(crate) clock_rate: f32,
+    pub(crate) strain_time: Float,
+    pub(crate) clock_rate: Float,
 }
 
 impl<'o> DifficultyObject<'o> {
@@ -21,8 +22,8 @@ impl<'o> DifficultyObject<'o> {
     pub(crate) fn new(
         base: &'o CatchObject,
         last: &'o CatchObject,
-        half_catcher_width: f32,
-        clock_rate: f32,
+        half_catcher_width: Float,
+        clock_rate: Float,
     ) -> Self {
         let delta = (base.time - last.time) / clock_rate;
         let start_time = base.time / clock_rate;
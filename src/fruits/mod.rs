@@ -4,76 +4,116 @@ mod catch_object;
 mod difficulty_object;
 mod movement;
 mod pp;
+mod score;
 mod slider_state;
 
 use catch_object::CatchObject;
 use difficulty_object::DifficultyObject;
 use movement::Movement;
 pub use pp::*;
+pub use score::*;
 use slider_state::SliderState;
 
 use crate::{
     curve::Curve,
     parse::{HitObjectKind, Pos2},
-    Beatmap, Mods, StarResult, Strains,
+    Beatmap, CalcContext, DifficultyParams, Float, GameMode, Mods, StarResult, Strains,
 };
 
-use std::convert::identity;
+const SECTION_LENGTH: Float = 750.0;
+const STAR_SCALING_FACTOR: Float = 0.153;
 
-const SECTION_LENGTH: f32 = 750.0;
-const STAR_SCALING_FACTOR: f32 = 0.153;
+const ALLOWED_CATCH_RANGE: Float = 0.8;
+const CATCHER_SIZE: Float = 106.75;
 
-const ALLOWED_CATCH_RANGE: f32 = 0.8;
-const CATCHER_SIZE: f32 = 106.75;
-
-const LEGACY_LAST_TICK_OFFSET: f32 = 36.0;
+const LEGACY_LAST_TICK_OFFSET: Float = 36.0;
 
 /// Star calculation for osu!ctb maps
 ///
 /// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
 // Slider parsing based on https://github.com/osufx/catch-the-pp
+#[inline]
 pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> StarResult {
+    stars_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `stars` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn stars_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> StarResult {
+    stars_with_context(map, mods, passed_objects, params, &mut CalcContext::new())
+}
+
+/// Same as the `stars_with_params` function but reuses the buffers of the given context.
+pub fn stars_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> StarResult {
+    let mods = mods.for_mode(GameMode::CTB);
+
     if map.hit_objects.len() < 2 {
         return StarResult::Fruits(DifficultyAttributes::default());
     }
 
     let take = passed_objects.unwrap_or(usize::MAX);
 
-    let attributes = map.attributes().mods(mods);
+    let attributes = map.attributes().mods_with_params(mods, params);
     let with_hr = mods.hr();
-    let mut ticks = Vec::new(); // using the same buffer for all sliders
     let mut slider_state = SliderState::new(map);
 
     let mut fruits = 0;
     let mut droplets = 0;
     let mut tiny_droplets = 0;
 
+    let Buffers {
+        objects,
+        ticks, // using the same buffer for all sliders
+        curve_points: curve_buf,
+        strain_peaks,
+    } = &mut ctx.fruits;
+
+    objects.clear();
+    ticks.clear();
+
+    let mut last_pos = None;
+    let mut last_time = 0.0;
+
     // BUG: Incorrect object order on 2B maps that have fruits within sliders
-    let mut hit_objects = map
-        .hit_objects
-        .iter()
-        .scan((None, 0.0), |(last_pos, last_time), h| match &h.kind {
+    for h in map.hit_objects.iter() {
+        if objects.len() >= take {
+            break;
+        }
+
+        match &h.kind {
             HitObjectKind::Circle => {
                 let mut h = CatchObject::new((h.pos, h.start_time));
 
                 if with_hr {
-                    h = h.with_hr(last_pos, last_time);
+                    h = h.with_hr(&mut last_pos, &mut last_time);
                 }
 
                 fruits += 1;
-
-                Some(Some(FruitOrJuice::Fruit(Some(h))))
+                objects.push(h);
             }
             HitObjectKind::Slider {
                 pixel_len,
                 repeats,
                 curve_points,
                 path_type,
+                ..
             } => {
                 // HR business
                 last_pos
                     .replace(h.pos.x + curve_points[curve_points.len() - 1].x - curve_points[0].x);
-                *last_time = h.start_time;
+                last_time = h.start_time;
 
                 // Responsible for timing point values
                 slider_state.update(h.start_time);
@@ -85,15 +125,15 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
                         (100.0 / slider_state.speed_mult).max(10.0).min(1000.0) / 100.0;
                 }
 
-                let duration = *repeats as f32 * slider_state.beat_len * pixel_len
+                let duration = *repeats as Float * slider_state.beat_len * pixel_len
                     / (map.sv * slider_state.speed_mult)
                     / 100.0;
 
                 // Build the curve w.r.t. the curve points
-                let curve = Curve::new(curve_points, *path_type);
+                let curve = Curve::new(curve_points, *path_type, curve_buf);
 
                 let mut current_distance = tick_distance;
-                let time_add = duration * (tick_distance / (*pixel_len * *repeats as f32));
+                let time_add = duration * (tick_distance / (*pixel_len * *repeats as Float));
 
                 let target = *pixel_len - tick_distance / 8.0;
                 ticks.reserve((target / tick_distance) as usize);
@@ -102,7 +142,7 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
                 if current_distance < target {
                     for tick_idx in 1.. {
                         let pos = curve.point_at_distance(current_distance);
-                        let time = h.start_time + time_add * tick_idx as f32;
+                        let time = h.start_time + time_add * tick_idx as Float;
                         ticks.push((pos, time));
                         current_distance += tick_distance;
 
@@ -113,32 +153,37 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
                 }
 
                 tiny_droplets +=
-                    tiny_droplet_count(h.start_time, time_add, duration, *repeats, &ticks);
+                    tiny_droplet_count(h.start_time, time_add, duration, *repeats, ticks);
 
-                let mut slider_objects = Vec::with_capacity(repeats * (ticks.len() + 1));
-                slider_objects.push((h.pos, h.start_time));
+                let n_objects = objects.len();
+                objects.push(CatchObject::new((h.pos, h.start_time)));
 
                 // Other spans
                 if *repeats <= 1 {
-                    slider_objects.append(&mut ticks); // automatically empties buffer for next slider
+                    // Empties the buffer for the next slider
+                    objects.extend(ticks.drain(..).map(CatchObject::new));
                 } else {
-                    slider_objects.append(&mut ticks.clone());
+                    objects.extend(ticks.iter().copied().map(CatchObject::new));
 
                     for repeat_id in 1..*repeats {
-                        let dist = (repeat_id % 2) as f32 * *pixel_len;
-                        let time_offset = (duration / *repeats as f32) * repeat_id as f32;
+                        let dist = (repeat_id % 2) as Float * *pixel_len;
+                        let time_offset = (duration / *repeats as Float) * repeat_id as Float;
                         let pos = curve.point_at_distance(dist);
 
                         // Reverse tick
-                        slider_objects.push((pos, h.start_time + time_offset));
+                        objects.push(CatchObject::new((pos, h.start_time + time_offset)));
 
                         // Actual ticks
                         if repeat_id & 1 == 1 {
-                            slider_objects.extend(ticks.iter().rev().enumerate().map(
-                                |(i, (pos, time))| (*pos, *time + time_add * 2.0 * (i + 1) as f32),
+                            objects.extend(ticks.iter().rev().enumerate().map(
+                                |(i, (pos, time))| {
+                                    let time = *time + time_add * 2.0 * (i + 1) as Float;
+
+                                    CatchObject::new((*pos, time))
+                                },
                             ));
                         } else {
-                            slider_objects.extend(ticks.iter().copied());
+                            objects.extend(ticks.iter().copied().map(CatchObject::new));
                         }
                     }
 
@@ -146,22 +191,19 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
                 }
 
                 // Slider tail
-                let dist_end = (*repeats % 2) as f32 * *pixel_len;
+                let dist_end = (*repeats % 2) as Float * *pixel_len;
                 let pos = curve.point_at_distance(dist_end);
-                slider_objects.push((pos, h.start_time + duration));
+                objects.push(CatchObject::new((pos, h.start_time + duration)));
 
                 fruits += 1 + *repeats;
-                droplets += slider_objects.len() - 1 - *repeats;
-
-                let iter = slider_objects.into_iter().map(CatchObject::new);
-
-                Some(Some(FruitOrJuice::Juice(iter)))
+                droplets += objects.len() - n_objects - 1 - *repeats;
             }
-            HitObjectKind::Spinner { .. } | HitObjectKind::Hold { .. } => Some(None),
-        })
-        .filter_map(identity)
-        .flatten()
-        .take(take);
+            HitObjectKind::Spinner { .. } | HitObjectKind::Hold { .. } => {}
+        }
+    }
+
+    objects.truncate(take);
+    let mut hit_objects = objects.drain(..);
 
     // Hyper dash business
     let half_catcher_width = calculate_catch_width(attributes.cs) / 2.0 / ALLOWED_CATCH_RANGE;
@@ -169,13 +211,23 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
     let mut last_excess = half_catcher_width;
 
     // Strain business
-    let mut movement = Movement::new(attributes.cs);
     let section_len = SECTION_LENGTH * attributes.clock_rate;
     let mut current_section_end =
         (map.hit_objects[0].start_time / section_len).ceil() * section_len;
 
-    let mut prev = hit_objects.next().unwrap();
-    let mut curr = hit_objects.next().unwrap();
+    let (mut prev, mut curr) = match (hit_objects.next(), hit_objects.next()) {
+        (Some(prev), Some(curr)) => (prev, curr),
+        _ => return StarResult::Fruits(DifficultyAttributes {
+                ar: attributes.ar,
+                n_fruits: fruits,
+                n_droplets: droplets,
+                n_tiny_droplets: tiny_droplets,
+                max_combo: fruits + droplets,
+                ..Default::default()
+            }),
+    };
+
+    let mut movement = Movement::with_peaks(attributes.cs, std::mem::take(strain_peaks));
 
     prev.init_hyper_dash(
         half_catcher_width,
@@ -185,29 +237,30 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
     );
 
     // Handle second object separately to remove later if-branching
-    let next = hit_objects.next().unwrap();
-    curr.init_hyper_dash(
-        half_catcher_width,
-        &next,
-        &mut last_direction,
-        &mut last_excess,
-    );
+    if let Some(next) = hit_objects.next() {
+        curr.init_hyper_dash(
+            half_catcher_width,
+            &next,
+            &mut last_direction,
+            &mut last_excess,
+        );
 
-    let h = DifficultyObject::new(
-        &curr,
-        &prev,
-        movement.half_catcher_width,
-        attributes.clock_rate,
-    );
+        let h = DifficultyObject::new(
+            &curr,
+            &prev,
+            movement.half_catcher_width,
+            attributes.clock_rate,
+        );
 
-    while h.base.time > current_section_end {
-        current_section_end += section_len;
-    }
+        while h.base.time > current_section_end {
+            current_section_end += section_len;
+        }
 
-    movement.process(&h);
+        movement.process(&h);
 
-    prev = curr;
-    curr = next;
+        prev = curr;
+        curr = next;
+    }
 
     // Handle all other objects
     for next in hit_objects {
@@ -256,6 +309,7 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
     movement.save_current_peak();
 
     let stars = movement.difficulty_value().sqrt() * STAR_SCALING_FACTOR;
+    *strain_peaks = movement.strain_peaks;
 
     let attributes = DifficultyAttributes {
         stars,
@@ -273,40 +327,71 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
 /// evaluating the final strains, it just returns them as is.
 ///
 /// Suitable to plot the difficulty of a map over time.
+#[inline]
 pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
+    strains_with_params(map, mods, DifficultyParams::default())
+}
+
+/// Same as the `strains` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn strains_with_params(map: &Beatmap, mods: impl Mods, params: DifficultyParams) -> Strains {
+    strains_with_context(map, mods, params, &mut CalcContext::new())
+}
+
+/// Same as the `strains_with_params` function but reuses the buffers of the given context.
+pub fn strains_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> Strains {
+    let mods = mods.for_mode(GameMode::CTB);
+
     if map.hit_objects.len() < 2 {
         return Strains::default();
     }
 
-    let attributes = map.attributes().mods(mods);
+    let attributes = map.attributes().mods_with_params(mods, params);
     let with_hr = mods.hr();
-    let mut ticks = Vec::new(); // using the same buffer for all sliders
     let mut slider_state = SliderState::new(map);
 
+    let Buffers {
+        objects,
+        ticks, // using the same buffer for all sliders
+        curve_points: curve_buf,
+        ..
+    } = &mut ctx.fruits;
+
+    objects.clear();
+    ticks.clear();
+
+    let mut last_pos = None;
+    let mut last_time = 0.0;
+
     // BUG: Incorrect object order on 2B maps that have fruits within sliders
-    let mut hit_objects = map
-        .hit_objects
-        .iter()
-        .scan((None, 0.0), |(last_pos, last_time), h| match &h.kind {
+    for h in map.hit_objects.iter() {
+        match &h.kind {
             HitObjectKind::Circle => {
                 let mut h = CatchObject::new((h.pos, h.start_time));
 
                 if with_hr {
-                    h = h.with_hr(last_pos, last_time);
+                    h = h.with_hr(&mut last_pos, &mut last_time);
                 }
 
-                Some(Some(FruitOrJuice::Fruit(Some(h))))
+                objects.push(h);
             }
             HitObjectKind::Slider {
                 pixel_len,
                 repeats,
                 curve_points,
                 path_type,
+                ..
             } => {
                 // HR business
                 last_pos
                     .replace(h.pos.x + curve_points[curve_points.len() - 1].x - curve_points[0].x);
-                *last_time = h.start_time;
+                last_time = h.start_time;
 
                 // Responsible for timing point values
                 slider_state.update(h.start_time);
@@ -318,15 +403,15 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
                         (100.0 / slider_state.speed_mult).max(10.0).min(1000.0) / 100.0;
                 }
 
-                let duration = *repeats as f32 * slider_state.beat_len * pixel_len
+                let duration = *repeats as Float * slider_state.beat_len * pixel_len
                     / (map.sv * slider_state.speed_mult)
                     / 100.0;
 
                 // Build the curve w.r.t. the curve points
-                let curve = Curve::new(curve_points, *path_type);
+                let curve = Curve::new(curve_points, *path_type, curve_buf);
 
                 let mut current_distance = tick_distance;
-                let time_add = duration * (tick_distance / (*pixel_len * *repeats as f32));
+                let time_add = duration * (tick_distance / (*pixel_len * *repeats as Float));
 
                 let target = *pixel_len - tick_distance / 8.0;
                 ticks.reserve((target / tick_distance) as usize);
@@ -335,7 +420,7 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
                 if current_distance < target {
                     for tick_idx in 1.. {
                         let pos = curve.point_at_distance(current_distance);
-                        let time = h.start_time + time_add * tick_idx as f32;
+                        let time = h.start_time + time_add * tick_idx as Float;
                         ticks.push((pos, time));
                         current_distance += tick_distance;
 
@@ -345,28 +430,28 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
                     }
                 }
 
-                let mut slider_objects = Vec::with_capacity(repeats * (ticks.len() + 1));
-                slider_objects.push((h.pos, h.start_time));
+                objects.push(CatchObject::new((h.pos, h.start_time)));
 
                 // Other spans
                 if *repeats <= 1 {
-                    slider_objects.append(&mut ticks); // automatically empties buffer for next slider
+                    // Empties the buffer for the next slider
+                    objects.extend(ticks.drain(..).map(CatchObject::new));
                 } else {
-                    slider_objects.append(&mut ticks.clone());
+                    objects.extend(ticks.iter().copied().map(CatchObject::new));
 
                     for repeat_id in 1..*repeats {
-                        let dist = (repeat_id % 2) as f32 * *pixel_len;
-                        let time_offset = (duration / *repeats as f32) * repeat_id as f32;
+                        let dist = (repeat_id % 2) as Float * *pixel_len;
+                        let time_offset = (duration / *repeats as Float) * repeat_id as Float;
                         let pos = curve.point_at_distance(dist);
 
                         // Reverse tick
-                        slider_objects.push((pos, h.start_time + time_offset));
+                        objects.push(CatchObject::new((pos, h.start_time + time_offset)));
 
                         // Actual ticks
                         if repeat_id & 1 == 1 {
-                            slider_objects.extend(ticks.iter().copied().rev());
+                            objects.extend(ticks.iter().copied().rev().map(CatchObject::new));
                         } else {
-                            slider_objects.extend(ticks.iter().copied());
+                            objects.extend(ticks.iter().copied().map(CatchObject::new));
                         }
                     }
 
@@ -374,18 +459,15 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
                 }
 
                 // Slider tail
-                let dist_end = (*repeats % 2) as f32 * *pixel_len;
+                let dist_end = (*repeats % 2) as Float * *pixel_len;
                 let pos = curve.point_at_distance(dist_end);
-                slider_objects.push((pos, h.start_time + duration));
-
-                let iter = slider_objects.into_iter().map(CatchObject::new);
-
-                Some(Some(FruitOrJuice::Juice(iter)))
+                objects.push(CatchObject::new((pos, h.start_time + duration)));
             }
-            HitObjectKind::Spinner { .. } | HitObjectKind::Hold { .. } => Some(None),
-        })
-        .filter_map(identity)
-        .flatten();
+            HitObjectKind::Spinner { .. } | HitObjectKind::Hold { .. } => {}
+        }
+    }
+
+    let mut hit_objects = objects.drain(..);
 
     // Hyper dash business
     let half_catcher_width = calculate_catch_width(attributes.cs) / 2.0 / ALLOWED_CATCH_RANGE;
@@ -398,8 +480,10 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
     let mut current_section_end =
         (map.hit_objects[0].start_time / section_len).ceil() * section_len;
 
-    let mut prev = hit_objects.next().unwrap();
-    let mut curr = hit_objects.next().unwrap();
+    let (mut prev, mut curr) = match (hit_objects.next(), hit_objects.next()) {
+        (Some(prev), Some(curr)) => (prev, curr),
+        _ => return Strains::default(),
+    };
 
     prev.init_hyper_dash(
         half_catcher_width,
@@ -409,29 +493,30 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
     );
 
     // Handle second object separately to remove later if-branching
-    let next = hit_objects.next().unwrap();
-    curr.init_hyper_dash(
-        half_catcher_width,
-        &next,
-        &mut last_direction,
-        &mut last_excess,
-    );
+    if let Some(next) = hit_objects.next() {
+        curr.init_hyper_dash(
+            half_catcher_width,
+            &next,
+            &mut last_direction,
+            &mut last_excess,
+        );
 
-    let h = DifficultyObject::new(
-        &curr,
-        &prev,
-        movement.half_catcher_width,
-        attributes.clock_rate,
-    );
+        let h = DifficultyObject::new(
+            &curr,
+            &prev,
+            movement.half_catcher_width,
+            attributes.clock_rate,
+        );
 
-    while h.base.time > current_section_end {
-        current_section_end += section_len;
-    }
+        while h.base.time > current_section_end {
+            current_section_end += section_len;
+        }
 
-    movement.process(&h);
+        movement.process(&h);
 
-    prev = curr;
-    curr = next;
+        prev = curr;
+        curr = next;
+    }
 
     // Handle all other objects
     for next in hit_objects {
@@ -488,11 +573,11 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
 // BUG: Sometimes there are off-by-one errors,
 // presumably caused by floating point inaccuracies
 fn tiny_droplet_count(
-    start_time: f32,
-    time_between_ticks: f32,
-    duration: f32,
+    start_time: Float,
+    time_between_ticks: Float,
+    duration: Float,
     spans: usize,
-    ticks: &[(Pos2, f32)],
+    ticks: &[(Pos2, Float)],
 ) -> usize {
     // tiny droplets preceeding a _tick_
     let per_tick = if !ticks.is_empty() && time_between_ticks > 80.0 {
@@ -508,7 +593,7 @@ fn tiny_droplet_count(
 
     // tiny droplets preceeding a _reverse_
     let last = ticks.last().map_or(start_time, |(_, last)| *last);
-    let repeat_time = start_time + duration / spans as f32;
+    let repeat_time = start_time + duration / spans as Float;
     let since_last_tick = repeat_time - last;
 
     let span_last_section = if since_last_tick > 80.0 {
@@ -522,7 +607,7 @@ fn tiny_droplet_count(
     // tiny droplets preceeding the slider tail
     // necessary to handle distinctly because of the legacy last tick
     let last = ticks.last().map_or(start_time, |(_, last)| *last);
-    let end_time = start_time + duration / spans as f32 - LEGACY_LAST_TICK_OFFSET;
+    let end_time = start_time + duration / spans as Float - LEGACY_LAST_TICK_OFFSET;
     let since_last_tick = end_time - last;
 
     let last_section = if since_last_tick > 80.0 {
@@ -538,7 +623,7 @@ fn tiny_droplet_count(
 }
 
 #[inline]
-fn shrink_down(mut val: f32) -> f32 {
+fn shrink_down(mut val: Float) -> Float {
     while val > 100.0 {
         val /= 2.0;
     }
@@ -547,7 +632,7 @@ fn shrink_down(mut val: f32) -> f32 {
 }
 
 #[inline]
-fn count_iterations(mut start: f32, step: f32, end: f32) -> usize {
+fn count_iterations(mut start: Float, step: Float, end: Float) -> usize {
     let mut count = 0;
 
     while start < end {
@@ -559,46 +644,29 @@ fn count_iterations(mut start: f32, step: f32, end: f32) -> usize {
 }
 
 #[inline]
-pub(crate) fn calculate_catch_width(cs: f32) -> f32 {
+pub(crate) fn calculate_catch_width(cs: Float) -> Float {
     let scale = 1.0 - 0.7 * (cs - 5.0) / 5.0;
 
     CATCHER_SIZE * scale.abs() * ALLOWED_CATCH_RANGE
 }
 
-enum FruitOrJuice<I> {
-    Fruit(Option<CatchObject>),
-    Juice(I),
-}
-
-impl<I: Iterator<Item = CatchObject>> Iterator for FruitOrJuice<I> {
-    type Item = CatchObject;
-
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            Self::Fruit(fruit) => fruit.take(),
-            Self::Juice(slider) => slider.next(),
-        }
-    }
-
-    #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        match self {
-            Self::Fruit(Some(_)) => (1, Some(1)),
-            Self::Fruit(None) => (0, Some(0)),
-            Self::Juice(slider) => slider.size_hint(),
-        }
-    }
-}
-
 /// Various data created through the star calculation.
 /// This data is necessary to calculate PP.
 #[derive(Clone, Debug, Default)]
 pub struct DifficultyAttributes {
-    pub stars: f32,
+    pub stars: Float,
     pub max_combo: usize,
-    pub ar: f32,
+    pub ar: Float,
     pub n_fruits: usize,
     pub n_droplets: usize,
     pub n_tiny_droplets: usize,
 }
+
+/// Buffers of osu!ctb that are kept by a [`CalcContext`].
+#[derive(Default)]
+pub(crate) struct Buffers {
+    objects: Vec<CatchObject>,
+    ticks: Vec<(Pos2, Float)>,
+    curve_points: Vec<Pos2>,
+    strain_peaks: Vec<Float>,
+}
@@ -1,32 +1,41 @@
 use super::DifficultyObject;
+use crate::Float;
 
 use std::cmp::Ordering;
 
-const ABSOLUTE_PLAYER_POSITIONING_ERROR: f32 = 16.0;
-const NORMALIZED_HITOBJECT_RADIUS: f32 = 41.0;
-const POSITION_EPSILON: f32 = NORMALIZED_HITOBJECT_RADIUS - ABSOLUTE_PLAYER_POSITIONING_ERROR;
-const DIRECTION_CHANGE_BONUS: f32 = 21.0;
-const SKILL_MULTIPLIER: f32 = 900.0;
-const STRAIN_DECAY_BASE: f32 = 0.2;
-const DECAY_WEIGHT: f32 = 0.94;
+const ABSOLUTE_PLAYER_POSITIONING_ERROR: Float = 16.0;
+const NORMALIZED_HITOBJECT_RADIUS: Float = 41.0;
+const POSITION_EPSILON: Float = NORMALIZED_HITOBJECT_RADIUS - ABSOLUTE_PLAYER_POSITIONING_ERROR;
+const DIRECTION_CHANGE_BONUS: Float = 21.0;
+const SKILL_MULTIPLIER: Float = 900.0;
+const STRAIN_DECAY_BASE: Float = 0.2;
+const DECAY_WEIGHT: Float = 0.94;
 
 pub(crate) struct Movement {
-    pub(crate) half_catcher_width: f32,
+    pub(crate) half_catcher_width: Float,
 
-    last_player_position: Option<f32>,
-    last_distance_moved: f32,
-    last_strain_time: f32,
+    last_player_position: Option<Float>,
+    last_distance_moved: Float,
+    last_strain_time: Float,
 
-    current_strain: f32,
-    current_section_peak: f32,
+    current_strain: Float,
+    current_section_peak: Float,
 
-    pub(crate) strain_peaks: Vec<f32>,
-    prev_time: Option<f32>,
+    pub(crate) strain_peaks: Vec<Float>,
+    prev_time: Option<Float>,
 }
 
 impl Movement {
     #[inline]
-    pub(crate) fn new(cs: f32) -> Self {
+    pub(crate) fn new(cs: Float) -> Self {
+        Self::with_peaks(cs, Vec::with_capacity(128))
+    }
+
+    /// Create a new skill that pushes its peaks into the given, cleared vec.
+    #[inline]
+    pub(crate) fn with_peaks(cs: Float, mut strain_peaks: Vec<Float>) -> Self {
+        strain_peaks.clear();
+
         let mut half_catcher_width = super::calculate_catch_width(cs) * 0.5;
         half_catcher_width *= 1.0 - ((cs - 5.5).max(0.0) * 0.0625);
 
@@ -40,7 +49,7 @@ impl Movement {
             current_strain: 1.0,
             current_section_peak: 1.0,
 
-            strain_peaks: Vec::with_capacity(128),
+            strain_peaks,
             prev_time: None,
         }
     }
@@ -51,8 +60,10 @@ impl Movement {
     }
 
     #[inline]
-    pub(crate) fn start_new_section_from(&mut self, time: f32) {
-        self.current_section_peak = self.peak_strain(time - self.prev_time.unwrap());
+    pub(crate) fn start_new_section_from(&mut self, time: Float) {
+        if let Some(prev_time) = self.prev_time {
+            self.current_section_peak = self.peak_strain(time - prev_time);
+        }
     }
 
     pub(crate) fn process(&mut self, current: &DifficultyObject) {
@@ -62,7 +73,7 @@ impl Movement {
         self.prev_time.replace(current.start_time);
     }
 
-    pub(crate) fn difficulty_value(&mut self) -> f32 {
+    pub(crate) fn difficulty_value(&mut self) -> Float {
         let mut difficulty = 0.0;
         let mut weight = 1.0;
 
@@ -77,7 +88,7 @@ impl Movement {
         difficulty
     }
 
-    fn strain_value_of(&mut self, current: &DifficultyObject) -> f32 {
+    fn strain_value_of(&mut self, current: &DifficultyObject) -> Float {
         let last_player_pos = self
             .last_player_position
             .unwrap_or(current.last_normalized_pos);
@@ -132,12 +143,12 @@ impl Movement {
     }
 
     #[inline]
-    fn peak_strain(&self, delta_time: f32) -> f32 {
+    fn peak_strain(&self, delta_time: Float) -> Float {
         self.current_strain * strain_decay(delta_time)
     }
 }
 
 #[inline]
-fn strain_decay(ms: f32) -> f32 {
+fn strain_decay(ms: Float) -> Float {
     STRAIN_DECAY_BASE.powf(ms / 1000.0)
 }
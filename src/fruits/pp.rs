@@ -1,5 +1,8 @@
-use super::{stars, DifficultyAttributes};
-use crate::{Beatmap, Mods, PpRaw, PpResult, StarResult};
+use super::{stars_with_params, DifficultyAttributes};
+use crate::{
+    AlgorithmVersion, AttributeKey, AttributeStore, Beatmap, CalcResult, DifficultyParams, Float,
+    GameMode, Grade, HitResultPriority, HitResults, IfFcResult, Mods, PpRaw, PpResult, StarResult,
+};
 
 /// Calculator for pp on osu!ctb maps.
 ///
@@ -34,6 +37,7 @@ pub struct FruitsPP<'m> {
     map: &'m Beatmap,
     attributes: Option<DifficultyAttributes>,
     mods: u32,
+    params: DifficultyParams,
     combo: Option<usize>,
 
     n_fruits: Option<usize>,
@@ -42,6 +46,8 @@ pub struct FruitsPP<'m> {
     n_tiny_droplet_misses: Option<usize>,
     n_misses: usize,
     passed_objects: Option<usize>,
+    version: AlgorithmVersion,
+    hitresult_priority: HitResultPriority,
 }
 
 impl<'m> FruitsPP<'m> {
@@ -51,6 +57,7 @@ impl<'m> FruitsPP<'m> {
             map,
             attributes: None,
             mods: 0,
+            params: DifficultyParams::default(),
             combo: None,
 
             n_fruits: None,
@@ -59,6 +66,8 @@ impl<'m> FruitsPP<'m> {
             n_tiny_droplet_misses: None,
             n_misses: 0,
             passed_objects: None,
+            version: AlgorithmVersion::default(),
+            hitresult_priority: HitResultPriority::default(),
         }
     }
 
@@ -76,12 +85,87 @@ impl<'m> FruitsPP<'m> {
         self
     }
 
+    /// Fill the attributes from the given store or calculate and insert them if it has none.
+    ///
+    /// The checksum identifies the map, e.g. the MD5 hash of its .osu file.
+    /// Be sure to set the mods, clock rate, and version beforehand!
+    /// Attributes of partial plays or custom AR, OD, CS, or HP are not stored.
+    pub fn attributes_from_store(self, mut store: impl AttributeStore, checksum: &str) -> Self {
+        let key = AttributeKey::for_calculation(
+            checksum,
+            GameMode::CTB,
+            self.mods,
+            self.params,
+            self.passed_objects,
+            self.version,
+        );
+
+        match key {
+            Some(key) => {
+                let (map, mods, params) = (self.map, self.mods, self.params);
+                let attributes =
+                    store.get_or_calculate(key, || stars_with_params(map, mods, None, params));
+
+                self.attributes(attributes)
+            }
+            None => self,
+        }
+    }
+
     /// Specify mods through their bit values.
     ///
     /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    ///
+    /// Mods without effect in this mode are ignored.
     #[inline]
     pub fn mods(mut self, mods: u32) -> Self {
-        self.mods = mods;
+        self.mods = mods.for_mode(GameMode::CTB);
+
+        self
+    }
+
+    /// Specify a custom clock rate that overrides the one of the mods, e.g. `1.2` for a 1.2x speed-up.
+    ///
+    /// Invalid rates are ignored or clamped, see [`DifficultyParams::clock_rate`](crate::DifficultyParams::clock_rate).
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: Float) -> Self {
+        self.params = self.params.clock_rate(clock_rate);
+
+        self
+    }
+
+    /// Specify an approach rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn ar(mut self, ar: Float) -> Self {
+        self.params = self.params.ar(ar);
+
+        self
+    }
+
+    /// Specify an overall difficulty that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn od(mut self, od: Float) -> Self {
+        self.params = self.params.od(od);
+
+        self
+    }
+
+    /// Specify a circle size that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn cs(mut self, cs: Float) -> Self {
+        self.params = self.params.cs(cs);
+
+        self
+    }
+
+    /// Specify a drain rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn hp(mut self, hp: Float) -> Self {
+        self.params = self.params.hp(hp);
 
         self
     }
@@ -142,10 +226,31 @@ impl<'m> FruitsPP<'m> {
         self
     }
 
+    /// Specify the version of the difficulty and pp algorithm.
+    ///
+    /// Versions without an implementation for this mode fall back
+    /// to the newest older one, see [`AlgorithmVersion::resolve`].
+    #[inline]
+    pub fn version(mut self, version: AlgorithmVersion) -> Self {
+        self.version = version;
+
+        self
+    }
+
+    /// Specify how misses are distributed between fruits and droplets when the hit results
+    /// are generated from an accuracy, see [`HitResultPriority`].
+    /// Be sure to set this before the accuracy!
+    #[inline]
+    pub fn hitresult_priority(mut self, priority: HitResultPriority) -> Self {
+        self.hitresult_priority = priority;
+
+        self
+    }
+
     /// Generate the hit results with respect to the given accuracy between `0` and `100`.
     ///
     /// Be sure to set `misses` beforehand! Also, if available, set `attributes` beforehand.
-    pub fn accuracy(mut self, acc: f32) -> Self {
+    pub fn accuracy(mut self, acc: Float) -> Self {
         self.set_accuracy(acc);
         self
     }
@@ -155,6 +260,7 @@ impl<'m> FruitsPP<'m> {
     /// 
     /// If it is used to calculate the PP of multiple different ACCs, 
     /// it should be called from high to low according to the ACC value, otherwise it is invalid.
+    /// Use [`FruitsPP::calculate_accuracies`] to calculate multiple accuracies in any order.
     /// 
     /// Examples:
     /// ```
@@ -195,33 +301,55 @@ impl<'m> FruitsPP<'m> {
     /// };
     /// ```
     /// 
-    pub fn set_accuracy(&mut self, mut acc: f32) {
-        if self.attributes.is_none() {
-            self.attributes.replace(
-                stars(self.map, self.mods, self.passed_objects)
+    pub fn set_accuracy(&mut self, mut acc: Float) {
+        let (map, mods, passed_objects, params) =
+            (self.map, self.mods, self.passed_objects, self.params);
+
+        let attributes = self
+            .attributes
+            .get_or_insert_with(|| {
+                stars_with_params(map, mods, passed_objects, params)
                     .attributes()
-                    .unwrap(),
-            );
-        }
-
-        let attributes = self.attributes.as_ref().unwrap();
-
-        let n_droplets = self
-            .n_droplets
-            .unwrap_or_else(|| attributes.n_droplets.saturating_sub(self.n_misses));
-
-        let n_fruits = self.n_fruits.unwrap_or_else(|| {
-            attributes
-                .max_combo
-                .saturating_sub(self.n_misses)
-                .saturating_sub(n_droplets)
-        });
+                    .unwrap_or_default()
+            })
+            .clone();
+
+        let (n_fruits, n_droplets) = match self.hitresult_priority {
+            HitResultPriority::BestCase => {
+                let n_droplets = self
+                    .n_droplets
+                    .unwrap_or_else(|| attributes.n_droplets.saturating_sub(self.n_misses));
+
+                let n_fruits = self.n_fruits.unwrap_or_else(|| {
+                    attributes
+                        .max_combo
+                        .saturating_sub(self.n_misses)
+                        .saturating_sub(n_droplets)
+                });
+
+                (n_fruits, n_droplets)
+            }
+            HitResultPriority::WorstCase => {
+                let n_fruits = self
+                    .n_fruits
+                    .unwrap_or_else(|| attributes.n_fruits.saturating_sub(self.n_misses));
+
+                let n_droplets = self.n_droplets.unwrap_or_else(|| {
+                    attributes
+                        .max_combo
+                        .saturating_sub(self.n_misses)
+                        .saturating_sub(n_fruits)
+                });
+
+                (n_fruits, n_droplets)
+            }
+        };
 
         let max_tiny_droplets = attributes.n_tiny_droplets;
         acc /= 100.0;
 
         let n_tiny_droplets = self.n_tiny_droplets.unwrap_or_else(|| {
-            ((acc * (attributes.max_combo + max_tiny_droplets) as f32).round() as usize)
+            ((acc * (attributes.max_combo + max_tiny_droplets) as Float).round() as usize)
                 .saturating_sub(n_fruits)
                 .saturating_sub(n_droplets)
         });
@@ -237,7 +365,10 @@ impl<'m> FruitsPP<'m> {
     fn assert_hitresults(&mut self, attributes: &DifficultyAttributes) {
         let correct_combo_hits = self
             .n_fruits
-            .and_then(|f| self.n_droplets.map(|d| f + d + self.n_misses))
+            .and_then(|f| {
+                self.n_droplets
+                    .map(|d| f.saturating_add(d).saturating_add(self.n_misses))
+            })
             .filter(|h| *h == attributes.max_combo);
 
         let correct_fruits = self
@@ -250,7 +381,7 @@ impl<'m> FruitsPP<'m> {
 
         let correct_tinies = self
             .n_tiny_droplets
-            .and_then(|t| self.n_tiny_droplet_misses.map(|m| t + m))
+            .and_then(|t| self.n_tiny_droplet_misses.map(|m| t.saturating_add(m)))
             .filter(|h| *h == attributes.n_tiny_droplets);
 
         if correct_combo_hits
@@ -291,9 +422,9 @@ impl<'m> FruitsPP<'m> {
     /// containing stars and other attributes.
     pub fn calculate(&mut self) -> PpResult {
         let attributes = self.attributes.take().unwrap_or_else(|| {
-            stars(self.map, self.mods, self.passed_objects)
+            stars_with_params(self.map, self.mods, self.passed_objects, self.params)
                 .attributes()
-                .unwrap()
+                .unwrap_or_default()
         });
 
         // Make sure all objects are set
@@ -312,16 +443,16 @@ impl<'m> FruitsPP<'m> {
 
         // Longer maps are worth more
         let len_bonus = 0.95
-            + 0.3 * (combo_hits as f32 / 2500.0).min(1.0)
-            + (combo_hits > 2500) as u8 as f32 * (combo_hits as f32 / 2500.0).log10() * 0.475;
+            + 0.3 * (combo_hits as Float / 2500.0).min(1.0)
+            + (combo_hits > 2500) as u8 as Float * (combo_hits as Float / 2500.0).log10() * 0.475;
         pp *= len_bonus;
 
         // Penalize misses exponentially
-        pp *= 0.97_f32.powi(self.n_misses as i32);
+        pp *= (0.97 as Float).powi(self.n_misses as i32);
 
         // Combo scaling
         if let Some(combo) = self.combo.filter(|_| attributes.max_combo > 0) {
-            pp *= (combo as f32 / attributes.max_combo as f32)
+            pp *= (combo as Float / attributes.max_combo as Float)
                 .powf(0.8)
                 .min(1.0);
         }
@@ -330,7 +461,7 @@ impl<'m> FruitsPP<'m> {
         let ar = attributes.ar;
         let mut ar_factor = 1.0;
         if ar > 9.0 {
-            ar_factor += 0.1 * (ar - 9.0) + (ar > 10.0) as u8 as f32 * 0.1 * (ar - 10.0);
+            ar_factor += 0.1 * (ar - 9.0) + (ar > 10.0) as u8 as Float * 0.1 * (ar - 10.0);
         } else if ar < 8.0 {
             ar_factor += 0.025 * (8.0 - ar);
         }
@@ -362,41 +493,137 @@ impl<'m> FruitsPP<'m> {
             mode: 2,
             mods: self.mods,
             pp,
-            raw: PpRaw::new(Some(pp), None, None, None, pp),
+            raw: PpRaw::new(Some(pp), None, None, None, None, pp),
             attributes: StarResult::Fruits(attributes),
+            version: self.version.resolve(GameMode::CTB),
+            grade: self.grade(),
         }
     }
 
+    /// Same as `calculate` but returns an error if the map has no hit objects
+    /// or can not be converted to osu!ctb, see [`Beatmap::check_calculable`],
+    /// or if the difficulty parameters are invalid, see [`DifficultyParams::validate`](crate::DifficultyParams::validate).
+    #[inline]
+    pub fn try_calculate(&mut self) -> CalcResult<PpResult> {
+        self.map.check_calculable(GameMode::CTB)?;
+        self.params.validate()?;
+
+        Ok(self.calculate())
+    }
+
     #[inline]
     pub async fn calculate_async(&mut self) -> PpResult {
         self.calculate()
     }
 
+    /// Calculate the pp for each of the given accuracies between `0` and `100`.
+    ///
+    /// The difficulty attributes are calculated only once and the hit results
+    /// are generated from the previously given input for every accuracy
+    /// so, unlike [`set_accuracy`](FruitsPP::set_accuracy), the order does not matter.
+    pub fn calculate_accuracies(&mut self, accs: &[Float]) -> Vec<PpResult> {
+        accs.iter()
+            .map(|&acc| self.calculate_accuracy(acc))
+            .collect()
+    }
+
+    /// The minimum accuracy between `0` and `100` that is required to reach the given pp
+    /// w.r.t. all previously specified values such as mods, combo, or misses.
+    ///
+    /// Returns `None` if the pp can not be reached, not even with 100% accuracy.
+    /// The difficulty attributes are calculated only once.
+    pub fn accuracy_for_pp(&mut self, pp: Float) -> Option<Float> {
+        crate::solver::min_accuracy_for_pp(pp, |acc| self.calculate_accuracy(acc).pp)
+    }
+
+    /// Calculate the pp of the play and of the same play with a full combo.
+    ///
+    /// For the full combo, all fruits and droplets are caught and the combo is the map's
+    /// max combo. Tiny droplets don't affect the combo so they are kept as is.
+    /// In case of a partial play, the full combo only covers the passed objects.
+    /// The difficulty attributes are calculated only once.
+    pub fn calculate_if_fc(&mut self) -> IfFcResult {
+        let current = self.calculate();
+        self.attributes = current.attributes.clone().attributes();
+
+        // Calculate on a copy so that none of the hit results are overwritten
+        let mut fc = self.clone();
+        let attributes = fc.attributes.clone().unwrap_or_default();
+
+        fc.n_fruits = Some(attributes.n_fruits);
+        fc.n_droplets = Some(attributes.n_droplets);
+        fc.combo = Some(attributes.max_combo);
+        fc.n_misses = 0;
+
+        let if_fc = fc.calculate();
+
+        IfFcResult { current, if_fc }
+    }
+
+    fn calculate_accuracy(&mut self, acc: Float) -> PpResult {
+        let input = (
+            self.n_fruits,
+            self.n_droplets,
+            self.n_tiny_droplets,
+            self.n_tiny_droplet_misses,
+        );
+
+        self.set_accuracy(acc);
+        let result = self.calculate();
+        self.attributes = result.attributes.clone().attributes();
+
+        self.n_fruits = input.0;
+        self.n_droplets = input.1;
+        self.n_tiny_droplets = input.2;
+        self.n_tiny_droplet_misses = input.3;
+
+        result
+    }
+
     #[inline]
+    fn grade(&self) -> Grade {
+        let hits = HitResults {
+            n300: self.n_fruits.unwrap_or(0),
+            n_katu: self.n_tiny_droplet_misses.unwrap_or(0),
+            n100: self.n_droplets.unwrap_or(0),
+            n50: self.n_tiny_droplets.unwrap_or(0),
+            n_misses: self.n_misses,
+            ..Default::default()
+        };
+
+        Grade::new(GameMode::CTB, self.mods, &hits)
+    }
+
     fn combo_hits(&self) -> usize {
-        self.n_fruits.unwrap_or(0) + self.n_droplets.unwrap_or(0) + self.n_misses
+        self.n_fruits
+            .unwrap_or(0)
+            .saturating_add(self.n_droplets.unwrap_or(0))
+            .saturating_add(self.n_misses)
     }
 
     #[inline]
     fn successful_hits(&self) -> usize {
-        self.n_fruits.unwrap_or(0)
-            + self.n_droplets.unwrap_or(0)
-            + self.n_tiny_droplets.unwrap_or(0)
+        self.n_fruits
+            .unwrap_or(0)
+            .saturating_add(self.n_droplets.unwrap_or(0))
+            .saturating_add(self.n_tiny_droplets.unwrap_or(0))
     }
 
     #[inline]
     fn total_hits(&self) -> usize {
-        self.successful_hits() + self.n_tiny_droplet_misses.unwrap_or(0) + self.n_misses
+        self.successful_hits()
+            .saturating_add(self.n_tiny_droplet_misses.unwrap_or(0))
+            .saturating_add(self.n_misses)
     }
 
     #[inline]
-    fn acc(&self) -> f32 {
+    fn acc(&self) -> Float {
         let total_hits = self.total_hits();
 
         if total_hits == 0 {
             1.0
         } else {
-            (self.successful_hits() as f32 / total_hits as f32)
+            (self.successful_hits() as Float / total_hits as Float)
                 .max(0.0)
                 .min(1.0)
         }
@@ -466,7 +693,7 @@ mod test {
             + calculator.n_tiny_droplets.unwrap_or(0);
         let denominator =
             numerator + calculator.n_tiny_droplet_misses.unwrap_or(0) + calculator.n_misses;
-        let acc = 100.0 * numerator as f32 / denominator as f32;
+        let acc = 100.0 * numerator as Float / denominator as Float;
 
         assert!(
             (target_acc - acc).abs() < 1.0,
@@ -506,7 +733,7 @@ mod test {
             + calculator.n_tiny_droplets.unwrap_or(0);
         let denominator =
             numerator + calculator.n_tiny_droplet_misses.unwrap_or(0) + calculator.n_misses;
-        let acc = 100.0 * numerator as f32 / denominator as f32;
+        let acc = 100.0 * numerator as Float / denominator as Float;
 
         assert!(
             (target_acc - acc).abs() < 1.0,
@@ -566,4 +793,80 @@ mod test {
             calculator.n_tiny_droplets.unwrap() + calculator.n_tiny_droplet_misses.unwrap(),
         );
     }
+
+    #[test]
+    fn fruits_accuracies_any_order() {
+        let map = Beatmap::default();
+        let attributes = DifficultyAttributes {
+            stars: 4.5,
+            ar: 9.0,
+            ..attributes()
+        };
+
+        let accs = [95.0, 100.0, 98.0];
+
+        let results = FruitsPP::new(&map)
+            .attributes(attributes.clone())
+            .misses(3)
+            .calculate_accuracies(&accs);
+
+        for (&acc, result) in accs.iter().zip(results.iter()) {
+            let expected = FruitsPP::new(&map)
+                .attributes(attributes.clone())
+                .misses(3)
+                .accuracy(acc)
+                .calculate();
+
+            assert!(result.pp() > 0.0);
+            assert_eq!(result.pp(), expected.pp(), "Accuracy: {}", acc);
+        }
+    }
+
+    #[test]
+    fn fruits_hitresult_priority() {
+        let map = Beatmap::default();
+
+        let calculator = |priority| {
+            FruitsPP::new(&map)
+                .attributes(attributes())
+                .misses(10)
+                .hitresult_priority(priority)
+                .accuracy(97.5)
+        };
+
+        let best = calculator(HitResultPriority::BestCase);
+        let worst = calculator(HitResultPriority::WorstCase);
+
+        assert_eq!(best.acc(), worst.acc());
+        assert_eq!(best.n_fruits, Some(attributes().n_fruits));
+        assert_eq!(worst.n_droplets, Some(attributes().n_droplets));
+    }
+
+    #[test]
+    fn fruits_if_fc() {
+        let map = Beatmap::default();
+        let attributes = DifficultyAttributes {
+            stars: 4.5,
+            ar: 9.0,
+            ..attributes()
+        };
+
+        let IfFcResult { current, if_fc } = FruitsPP::new(&map)
+            .attributes(attributes.clone())
+            .combo(500)
+            .misses(4)
+            .tiny_droplet_misses(20)
+            .calculate_if_fc();
+
+        let fc = FruitsPP::new(&map)
+            .attributes(attributes.clone())
+            .fruits(attributes.n_fruits)
+            .droplets(attributes.n_droplets)
+            .tiny_droplets(attributes.n_tiny_droplets - 20)
+            .tiny_droplet_misses(20)
+            .calculate();
+
+        assert_eq!(if_fc.pp(), fc.pp());
+        assert!(if_fc.pp() > current.pp());
+    }
 }
@@ -0,0 +1,315 @@
+use super::{stars, DifficultyAttributes, FruitsAttributeProvider};
+use crate::{
+    parse::HitObjectKind,
+    score::{self, ScoreVersion, SCORE_V2_MAX},
+    Beatmap, Float, GameMode, Mods,
+};
+
+const FRUIT_VALUE: Float = 300.0;
+const DROPLET_VALUE: Float = 100.0;
+const TINY_DROPLET_VALUE: Float = 10.0;
+const BANANA_VALUE: Float = 1100.0;
+
+/// Bananas of a banana shower are at most this many milliseconds apart.
+const MAX_BANANA_SPACING: Float = 100.0;
+
+/// Score calculator for osu!ctb maps.
+///
+/// Fruits and droplets are assumed to alternate evenly and all bananas are assumed
+/// to be caught, so the ScoreV1 combo bonus is an estimate even for full combos.
+/// For other plays, the combo bonus is estimated from the combo and the amount of misses.
+#[derive(Clone, Debug)]
+pub struct FruitsScore<'m> {
+    map: &'m Beatmap,
+    attributes: Option<DifficultyAttributes>,
+    mods: u32,
+    version: ScoreVersion,
+    combo: Option<usize>,
+    n_fruits: Option<usize>,
+    n_droplets: Option<usize>,
+    n_tiny_droplets: Option<usize>,
+    n_misses: usize,
+    passed_objects: Option<usize>,
+}
+
+impl<'m> FruitsScore<'m> {
+    #[inline]
+    pub fn new(map: &'m Beatmap) -> Self {
+        Self {
+            map,
+            attributes: None,
+            mods: 0,
+            version: ScoreVersion::default(),
+            combo: None,
+            n_fruits: None,
+            n_droplets: None,
+            n_tiny_droplets: None,
+            n_misses: 0,
+            passed_objects: None,
+        }
+    }
+
+    /// [`FruitsAttributeProvider`] is implemented by [`DifficultyAttributes`](crate::fruits::DifficultyAttributes),
+    /// [`StarResult`](crate::StarResult), and by [`PpResult`](crate::PpResult).
+    /// Providing the attributes of the current map-mod combination
+    /// prevents them from being recalculated.
+    #[inline]
+    pub fn attributes(mut self, attributes: impl FruitsAttributeProvider) -> Self {
+        if let Some(attributes) = attributes.attributes() {
+            self.attributes.replace(attributes);
+        }
+
+        self
+    }
+
+    /// [`Mods`](crate::Mods) interface.
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods.for_mode(GameMode::CTB);
+
+        self
+    }
+
+    /// Specify the scoring system, defaults to [`ScoreVersion::V1`].
+    #[inline]
+    pub fn version(mut self, version: ScoreVersion) -> Self {
+        self.version = version;
+
+        self
+    }
+
+    /// Specify the max combo of the play.
+    #[inline]
+    pub fn combo(mut self, combo: usize) -> Self {
+        self.combo.replace(combo);
+
+        self
+    }
+
+    /// Specify the amount of fruits of a play i.e. n300.
+    #[inline]
+    pub fn fruits(mut self, n_fruits: usize) -> Self {
+        self.n_fruits.replace(n_fruits);
+
+        self
+    }
+
+    /// Specify the amount of droplets of a play i.e. n100.
+    #[inline]
+    pub fn droplets(mut self, n_droplets: usize) -> Self {
+        self.n_droplets.replace(n_droplets);
+
+        self
+    }
+
+    /// Specify the amount of tiny droplets of a play i.e. n50.
+    #[inline]
+    pub fn tiny_droplets(mut self, n_tiny_droplets: usize) -> Self {
+        self.n_tiny_droplets.replace(n_tiny_droplets);
+
+        self
+    }
+
+    /// Specify the amount of fruit / droplet misses of the play.
+    #[inline]
+    pub fn misses(mut self, n_misses: usize) -> Self {
+        self.n_misses = n_misses;
+
+        self
+    }
+
+    /// Amount of passed fruits and droplets for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects.replace(passed_objects);
+
+        self
+    }
+
+    /// Calculate the score of a full combo that catches everything.
+    pub fn max_score(&self) -> u64 {
+        let attributes = self.difficulty_attributes();
+        let bananas = self.bananas(&attributes);
+
+        let score = match self.version {
+            ScoreVersion::V1 => {
+                FRUIT_VALUE * attributes.n_fruits as Float
+                    + DROPLET_VALUE * attributes.n_droplets as Float
+                    + TINY_DROPLET_VALUE * attributes.n_tiny_droplets as Float
+                    + self.max_combo_bonus(&attributes)
+                    + bananas
+            }
+            ScoreVersion::V2 => {
+                SCORE_V2_MAX * self.mods.score_multiplier(GameMode::CTB, self.version) + bananas
+            }
+        };
+
+        score as u64
+    }
+
+    /// Calculate the score of the specified play.
+    ///
+    /// Unspecified fruits, droplets, and tiny droplets are considered to be caught and
+    /// an unspecified combo is considered to be the max combo minus the misses.
+    pub fn calculate(&self) -> u64 {
+        let attributes = self.difficulty_attributes();
+        let max_combo = attributes.n_fruits + attributes.n_droplets;
+
+        let n_misses = self.n_misses.min(max_combo);
+        let n_droplets = self.n_droplets.map_or_else(
+            || attributes.n_droplets.saturating_sub(n_misses),
+            |n| n.min(attributes.n_droplets),
+        );
+        let n_fruits = self
+            .n_fruits
+            .unwrap_or_else(|| max_combo.saturating_sub(n_misses + n_droplets))
+            .min(attributes.n_fruits);
+        let n_tiny_droplets = self
+            .n_tiny_droplets
+            .map_or(attributes.n_tiny_droplets, |n| {
+                n.min(attributes.n_tiny_droplets)
+            });
+
+        let combo_points = FRUIT_VALUE * n_fruits as Float + DROPLET_VALUE * n_droplets as Float;
+        let max_combo_points = FRUIT_VALUE * attributes.n_fruits as Float
+            + DROPLET_VALUE * attributes.n_droplets as Float;
+
+        let combo = self
+            .combo
+            .unwrap_or_else(|| max_combo.saturating_sub(n_misses));
+
+        let chains = score::combo_chains(combo, max_combo, n_misses);
+        let combo_ratio = score::combo_bonus_ratio(&chains, max_combo, |combo| combo as Float);
+
+        // The combo bonus of a catch scales with its value
+        let value_ratio = if max_combo_points <= 0.0 {
+            0.0
+        } else {
+            combo_points / max_combo_points
+        };
+
+        let bananas = self.bananas(&attributes);
+
+        let score = match self.version {
+            ScoreVersion::V1 => {
+                combo_points
+                    + TINY_DROPLET_VALUE * n_tiny_droplets as Float
+                    + self.max_combo_bonus(&attributes) * combo_ratio * value_ratio
+                    + bananas
+            }
+            ScoreVersion::V2 => {
+                let n_caught = n_fruits + n_droplets + n_tiny_droplets;
+                let n_total = max_combo + attributes.n_tiny_droplets;
+
+                let acc = if n_total == 0 {
+                    0.0
+                } else {
+                    n_caught as Float / n_total as Float
+                };
+
+                let combo_ratio_v2 = score::score_v2_combo_ratio(&chains, max_combo);
+                let multiplier = self.mods.score_multiplier(GameMode::CTB, self.version);
+
+                score::score_v2(combo_ratio_v2 * value_ratio, acc, 1.0) * multiplier + bananas
+            }
+        };
+
+        score as u64
+    }
+
+    fn difficulty_attributes(&self) -> DifficultyAttributes {
+        self.attributes.clone().unwrap_or_else(|| {
+            stars(self.map, self.mods, self.passed_objects)
+                .attributes()
+                .unwrap_or_default()
+        })
+    }
+
+    /// ScoreV1 combo bonus of a full combo, assuming fruits and droplets alternate evenly.
+    fn max_combo_bonus(&self, attributes: &DifficultyAttributes) -> Float {
+        let max_combo = attributes.n_fruits + attributes.n_droplets;
+
+        if max_combo == 0 {
+            return 0.0;
+        }
+
+        let avg_value = (FRUIT_VALUE * attributes.n_fruits as Float
+            + DROPLET_VALUE * attributes.n_droplets as Float)
+            / max_combo as Float;
+
+        let difficulty_multiplier = score::difficulty_multiplier(self.map);
+        let mod_multiplier = self.mods.score_multiplier(GameMode::CTB, ScoreVersion::V1);
+        let combo_sum = (max_combo * (max_combo - 1) / 2) as Float;
+
+        (avg_value * combo_sum * difficulty_multiplier * mod_multiplier / 25.0).floor()
+    }
+
+    /// Points of all bananas within the passed part of the map.
+    fn bananas(&self, attributes: &DifficultyAttributes) -> Float {
+        let n_bananas: usize = self
+            .map
+            .hit_objects
+            .iter()
+            .filter_map(|h| match h.kind {
+                HitObjectKind::Spinner { end_time } => Some(n_bananas(end_time - h.start_time)),
+                _ => None,
+            })
+            .sum();
+
+        let max_combo = attributes.n_fruits + attributes.n_droplets;
+        let progress = match self.passed_objects {
+            Some(passed) if max_combo > 0 => passed.min(max_combo) as Float / max_combo as Float,
+            _ => 1.0,
+        };
+
+        (BANANA_VALUE * n_bananas as Float * progress).floor()
+    }
+}
+
+/// The amount of bananas of a banana shower with the given duration.
+fn n_bananas(duration: Float) -> usize {
+    if duration <= 0.0 {
+        return 0;
+    }
+
+    let mut spacing = duration;
+
+    while spacing > MAX_BANANA_SPACING {
+        spacing /= 2.0;
+    }
+
+    (duration / spacing) as usize + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fruits_score_bananas() {
+        assert_eq!(n_bananas(0.0), 0);
+        assert_eq!(n_bananas(100.0), 2);
+        assert_eq!(n_bananas(1000.0), 17);
+    }
+
+    #[test]
+    fn fruits_score_full_combo() {
+        let map = Beatmap::default();
+
+        let attributes = DifficultyAttributes {
+            max_combo: 100,
+            n_fruits: 80,
+            n_droplets: 20,
+            n_tiny_droplets: 50,
+            ..Default::default()
+        };
+
+        let max_score = FruitsScore::new(&map)
+            .attributes(attributes.clone())
+            .max_score();
+
+        let score = FruitsScore::new(&map).attributes(attributes).calculate();
+
+        assert_eq!(score, max_score);
+    }
+}
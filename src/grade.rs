@@ -0,0 +1,202 @@
+use crate::{Float, GameMode, Mods};
+
+/// Hit results of a play in the format of the osu! API.
+///
+/// | Field | osu!standard | osu!taiko | osu!ctb | osu!mania |
+/// |-----|-----|-----|-----|-----|
+/// | `n_geki` | - | - | - | 320s |
+/// | `n300` | 300s | 300s | Fruits | 300s |
+/// | `n_katu` | - | - | Tiny droplet misses | 200s |
+/// | `n100` | 100s | 100s | Droplets | 100s |
+/// | `n50` | 50s | - | Tiny droplets | 50s |
+/// | `n_misses` | Misses | Misses | Fruit and droplet misses | Misses |
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct HitResults {
+    pub n_geki: usize,
+    pub n300: usize,
+    pub n_katu: usize,
+    pub n100: usize,
+    pub n50: usize,
+    pub n_misses: usize,
+}
+
+/// The grade of a play as it is shown on the results screen.
+///
+/// | Mode | SS | S | A | B | C |
+/// |-----|-----|-----|-----|-----|-----|
+/// | osu!standard & osu!taiko | 100% 300s | Over 90% 300s, at most 1% 50s, no misses | Over 80% 300s and no misses or over 90% 300s | Over 70% 300s and no misses or over 80% 300s | Over 60% 300s |
+/// | osu!ctb | 100% accuracy | Over 98% accuracy | Over 94% accuracy | Over 90% accuracy | Over 85% accuracy |
+/// | osu!mania | 100% accuracy | Over 95% accuracy | Over 90% accuracy | Over 80% accuracy | Over 70% accuracy |
+///
+/// Everything else is a D. SS and S turn silver with `Hidden`, `Flashlight`,
+/// or, in osu!mania, `FadeIn`.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Grade {
+    D,
+    C,
+    B,
+    A,
+    S,
+    /// Silver S
+    SH,
+    SS,
+    /// Silver SS
+    SSH,
+}
+
+impl Grade {
+    /// The grade of the given hit results with the given mods in the given mode.
+    pub fn new(mode: GameMode, mods: impl Mods, hits: &HitResults) -> Self {
+        let grade = match mode {
+            GameMode::STD | GameMode::TKO => Self::from_ratios(hits),
+            GameMode::CTB => {
+                let total = sum(&[hits.n300, hits.n_katu, hits.n100, hits.n50, hits.n_misses]);
+                let caught = sum(&[hits.n300, hits.n100, hits.n50]);
+
+                Self::from_accuracy(ratio(caught as Float, total), [0.98, 0.94, 0.9, 0.85])
+            }
+            GameMode::MNA => {
+                let total = sum(&[
+                    hits.n_geki,
+                    hits.n300,
+                    hits.n_katu,
+                    hits.n100,
+                    hits.n50,
+                    hits.n_misses,
+                ]);
+
+                let points = (hits.n_geki as Float + hits.n300 as Float) * 300.0
+                    + hits.n_katu as Float * 200.0
+                    + hits.n100 as Float * 100.0
+                    + hits.n50 as Float * 50.0;
+
+                Self::from_accuracy(ratio(points / 300.0, total), [0.95, 0.9, 0.8, 0.7])
+            }
+        };
+
+        let silver = mods.hd() || mods.fl() || (mode == GameMode::MNA && mods.fi());
+
+        match grade {
+            Self::SS if silver => Self::SSH,
+            Self::S if silver => Self::SH,
+            _ => grade,
+        }
+    }
+
+    /// Whether the grade is SS or silver SS.
+    #[inline]
+    pub fn is_ss(self) -> bool {
+        matches!(self, Self::SS | Self::SSH)
+    }
+
+    fn from_ratios(hits: &HitResults) -> Self {
+        let total = sum(&[hits.n300, hits.n100, hits.n50, hits.n_misses]);
+        let ratio300 = ratio(hits.n300 as Float, total);
+        let ratio50 = ratio(hits.n50 as Float, total);
+        let no_misses = hits.n_misses == 0;
+
+        if total > 0 && hits.n300 == total {
+            Self::SS
+        } else if ratio300 > 0.9 && ratio50 <= 0.01 && no_misses {
+            Self::S
+        } else if (ratio300 > 0.8 && no_misses) || ratio300 > 0.9 {
+            Self::A
+        } else if (ratio300 > 0.7 && no_misses) || ratio300 > 0.8 {
+            Self::B
+        } else if ratio300 > 0.6 {
+            Self::C
+        } else {
+            Self::D
+        }
+    }
+
+    /// Grade w.r.t. the accuracy and the lower bounds for S, A, B, and C.
+    fn from_accuracy(acc: Float, [s, a, b, c]: [Float; 4]) -> Self {
+        if acc >= 1.0 {
+            Self::SS
+        } else if acc > s {
+            Self::S
+        } else if acc > a {
+            Self::A
+        } else if acc > b {
+            Self::B
+        } else if acc > c {
+            Self::C
+        } else {
+            Self::D
+        }
+    }
+}
+
+/// Sum of hit counts that saturates instead of overflowing on invalid input.
+#[inline]
+fn sum(counts: &[usize]) -> usize {
+    counts
+        .iter()
+        .fold(0, |sum, count| sum.saturating_add(*count))
+}
+
+#[inline]
+fn ratio(value: Float, total: usize) -> Float {
+    if total == 0 {
+        0.0
+    } else {
+        value / total as Float
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grade_osu_and_silver() {
+        let hits = |n300, n100, n50, n_misses| HitResults {
+            n300,
+            n100,
+            n50,
+            n_misses,
+            ..Default::default()
+        };
+
+        assert_eq!(Grade::new(GameMode::STD, 0, &hits(100, 0, 0, 0)), Grade::SS);
+        assert_eq!(
+            Grade::new(GameMode::STD, 8, &hits(100, 0, 0, 0)),
+            Grade::SSH
+        );
+        assert_eq!(Grade::new(GameMode::STD, 0, &hits(95, 3, 2, 0)), Grade::A);
+        assert_eq!(
+            Grade::new(GameMode::STD, 1024, &hits(95, 4, 1, 0)),
+            Grade::SH
+        );
+        assert_eq!(Grade::new(GameMode::STD, 0, &hits(95, 4, 0, 1)), Grade::A);
+        assert_eq!(Grade::new(GameMode::STD, 0, &hits(75, 25, 0, 0)), Grade::B);
+        assert_eq!(Grade::new(GameMode::STD, 0, &hits(50, 50, 0, 0)), Grade::D);
+    }
+
+    #[test]
+    fn grade_accuracy_based() {
+        let fruits = HitResults {
+            n300: 95,
+            n100: 4,
+            n_misses: 1,
+            ..Default::default()
+        };
+
+        assert_eq!(Grade::new(GameMode::CTB, 0, &fruits), Grade::S);
+
+        let mania = HitResults {
+            n_geki: 50,
+            n300: 40,
+            n_katu: 10,
+            ..Default::default()
+        };
+
+        assert_eq!(Grade::new(GameMode::MNA, 0, &mania), Grade::S);
+        assert_eq!(Grade::new(GameMode::MNA, u32::FI, &mania), Grade::SH);
+        assert_eq!(
+            Grade::new(GameMode::MNA, u32::FI, &HitResults::default()),
+            Grade::D
+        );
+    }
+}
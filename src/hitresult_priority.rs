@@ -0,0 +1,29 @@
+/// Which hit results are preferred when they are generated from an accuracy.
+///
+/// Both priorities only decide between distributions that result in the same accuracy,
+/// i.e. they never change the accuracy, the amount of misses, or hit results that were
+/// specified manually.
+///
+/// | Mode | [`BestCase`](HitResultPriority::BestCase) | [`WorstCase`](HitResultPriority::WorstCase) |
+/// |-----|-----|-----|
+/// | osu!standard | As few 50s as possible by trading a 300 and four 50s for five 100s | As many 50s as possible |
+/// | osu!taiko | Not affected, 300s and 100s are uniquely determined by the accuracy | Not affected |
+/// | osu!ctb | Misses are put on droplets first, then on fruits | Misses are put on fruits first, then on droplets |
+/// | osu!mania | As few 50s as possible by trading a 300 and two 50s for a 200 and two 100s | As many 50s as possible |
+///
+/// In osu!mania this only affects accuracy based versions and judgements that are
+/// generated from scratch, see [`ManiaPP::accuracy`](crate::ManiaPP::accuracy).
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum HitResultPriority {
+    /// Prefer good hit results over bad ones.
+    BestCase,
+    /// Prefer bad hit results over good ones.
+    WorstCase,
+}
+
+impl Default for HitResultPriority {
+    #[inline]
+    fn default() -> Self {
+        Self::BestCase
+    }
+}
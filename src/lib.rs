@@ -2,7 +2,8 @@
 
 //! A standalone crate to calculate star ratings and performance points for all [osu!](https://osu.ppy.sh/home) gamemodes.
 //!
-//! Conversions between gamemodes are generally not supported.
+//! osu!standard maps are converted when calculating osu!taiko or osu!mania difficulty or pp.
+//! Other conversions between gamemodes are generally not supported.
 //!
 //! Async is supported through features, see below.
 //!
@@ -101,6 +102,20 @@
 //!
 //! **Note**: If the `fruits` feature is enabled, sliders will be parsed regardless, resulting in a reduced performance advantage of `no_sliders_no_leniency`.
 //!
+//! ## Algorithm versions
+//!
+//! The calculators use the difficulty & pp changes from 21.07.27 by default.
+//! Other versions can be chosen per calculation through `version`, e.g.
+//! `OsuPP::new(&map).version(AlgorithmVersion::V2024)`, and [`PpResult::version`] tells which
+//! version produced the value so that stored scores can be recomputed under the same rules.
+//!
+//! ## Error handling
+//!
+//! Calculations on parsed maps do not panic. For untrusted maps, the `try_*` variants such as
+//! [`BeatmapExt::try_stars`] or `OsuPP::try_calculate` return a [`CalcError`] for maps without
+//! hit objects or maps that can not be converted into the calculated mode instead of
+//! producing meaningless values.
+//!
 //! ## Features
 //!
 //! | Flag | Description |
@@ -115,6 +130,7 @@
 //! | `all_included` | When calculating difficulty attributes in osu!standard, consider both stack leniency and sliders. Best precision but significantly worse performance than `no_leniency`. |
 //! | `async_tokio` | Beatmap parsing will be async through [tokio](https://github.com/tokio-rs/tokio) |
 //! | `async_std` | Beatmap parsing will be async through [async-std](https://github.com/async-rs/async-std) |
+//! | `f64` | Use `f64` instead of `f32` for parsed values and all calculations. Slightly slower but more precise. |
 //!
 //! ## Roadmap
 //!
@@ -156,9 +172,28 @@ pub mod parse;
 mod pp;
 pub use pp::{AnyPP, AttributeProvider};
 
+#[cfg(feature = "batch")]
+#[cfg_attr(docsrs, doc(cfg(feature = "batch")))]
+mod batch;
+#[cfg(feature = "batch")]
+pub use batch::{BatchCalculator, BatchError, BatchGroup, BatchResult, BatchScore};
+
+mod context;
 mod curve;
+mod error;
+mod float;
+mod grade;
+mod hitresult_priority;
 mod math_util;
 mod mods;
+mod params;
+mod profile;
+mod score;
+mod solver;
+mod store;
+#[cfg(test)]
+mod test_util;
+mod version;
 
 #[cfg(any(feature = "osu", feature = "fruits"))]
 pub(crate) mod control_point_iter;
@@ -167,33 +202,55 @@ pub(crate) mod control_point_iter;
 pub(crate) use control_point_iter::{ControlPoint, ControlPointIter};
 
 #[cfg(feature = "fruits")]
-pub use fruits::FruitsPP;
+pub use fruits::{FruitsPP, FruitsScore};
 
 #[cfg(feature = "mania")]
-pub use mania::ManiaPP;
+pub use mania::{ManiaPP, ManiaScore};
 
 #[cfg(feature = "osu")]
-pub use osu::OsuPP;
+pub use osu::{OsuPP, OsuScore};
 
 #[cfg(feature = "taiko")]
-pub use taiko::TaikoPP;
-
-pub use mods::Mods;
+pub use taiko::{TaikoPP, TaikoScore};
+
+pub use context::CalcContext;
+pub use error::{CalcError, CalcResult};
+pub use float::Float;
+pub use grade::{Grade, HitResults};
+pub use hitresult_priority::HitResultPriority;
+pub use mods::{GameMods, Mods, ModsError};
+pub use params::DifficultyParams;
 pub use parse::{Beatmap, BeatmapAttributes, GameMode, ParseError, ParseResult};
+pub use profile::{PpProvider, Profile};
+pub use score::ScoreVersion;
+pub use store::{AttributeKey, AttributeStore, FileStore, LruStore};
+pub use version::AlgorithmVersion;
 
 pub trait BeatmapExt {
     /// Calculate the stars and other attributes of a beatmap which are required for pp calculation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the feature of the map's mode is not enabled, use [`try_stars`](BeatmapExt::try_stars) instead.
     fn stars(&self, mods: impl Mods, passed_objects: Option<usize>) -> StarResult;
 
     /// Calculate the max pp of a beatmap.
     ///
     /// If you seek more fine-tuning and options you need to match on the map's
     /// mode and use the mode's corresponding calculator, e.g. [`TaikoPP`](crate::TaikoPP) for taiko.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the feature of the map's mode is not enabled, use [`try_max_pp`](BeatmapExt::try_max_pp) instead.
     fn max_pp(&self, mods: u32) -> PpResult;
 
     /// Returns a builder to calculate pp and difficulty values.
     ///
     /// Convenient method that matches on the map's mode to choose the appropriate calculator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the feature of the map's mode is not enabled, use [`AnyPP::try_new`] instead.
     fn pp(&self) -> AnyPP;
 
     /// Calculate the strains of a map.
@@ -201,164 +258,196 @@ pub trait BeatmapExt {
     /// instead of evaluating the final strains, they are just returned as is.
     ///
     /// Suitable to plot the difficulty of a map over time.
+    ///
+    /// If the feature of the map's mode is not enabled, the strains are empty,
+    /// see [`try_strains`](BeatmapExt::try_strains).
     fn strains(&self, mods: impl Mods) -> Strains;
+
+    /// Same as `stars` but returns an error instead of panicking if the
+    /// map's mode is not enabled or the map is unfit for calculations,
+    /// see [`Beatmap::check_calculable`].
+    fn try_stars(&self, mods: impl Mods, passed_objects: Option<usize>) -> CalcResult<StarResult>;
+
+    /// Same as `max_pp` but returns an error instead of panicking if the
+    /// map's mode is not enabled or the map is unfit for calculations.
+    fn try_max_pp(&self, mods: u32) -> CalcResult<PpResult>;
+
+    /// Same as `strains` but returns an error instead of panicking if the
+    /// map's mode is not enabled or the map is unfit for calculations.
+    fn try_strains(&self, mods: impl Mods) -> CalcResult<Strains>;
 }
 
 impl BeatmapExt for Beatmap {
+    #[inline]
     fn stars(&self, mods: impl Mods, passed_objects: Option<usize>) -> StarResult {
-        match self.mode {
-            GameMode::STD => {
-                #[cfg(not(feature = "osu"))]
-                panic!("`osu` feature is not enabled");
-
-                #[cfg(feature = "osu")]
-                {
-                    #[cfg(feature = "no_leniency")]
-                    {
-                        osu::no_leniency::stars(self, mods, passed_objects)
-                    }
-
-                    #[cfg(all(not(feature = "no_leniency"), feature = "no_sliders_no_leniency"))]
-                    {
-                        osu::no_sliders_no_leniency::stars(self, mods, passed_objects)
-                    }
-
-                    #[cfg(all(
-                        not(feature = "no_leniency"),
-                        not(feature = "no_sliders_no_leniency"),
-                        feature = "all_included"
-                    ))]
-                    {
-                        osu::all_included::stars(self, mods, passed_objects)
-                    }
-
-                    #[cfg(not(any(
-                        feature = "no_leniency",
-                        feature = "no_sliders_no_leniency",
-                        feature = "all_included"
-                    )))]
-                    panic!("either of the features `no_leniency`, `no_sliders_no_leniency`, or `all_included` must be enabled");
-                }
-            }
-            GameMode::MNA => {
-                #[cfg(not(feature = "mania"))]
-                panic!("`mania` feature is not enabled");
+        unwrap_mode(mode_stars(self, mods, passed_objects))
+    }
 
-                #[cfg(feature = "mania")]
-                mania::stars(self, mods, passed_objects)
-            }
-            GameMode::TKO => {
-                #[cfg(not(feature = "taiko"))]
-                panic!("`osu` feature is not enabled");
+    #[inline]
+    fn max_pp(&self, mods: u32) -> PpResult {
+        unwrap_mode(mode_max_pp(self, mods))
+    }
 
-                #[cfg(feature = "taiko")]
-                taiko::stars(self, mods, passed_objects)
-            }
-            GameMode::CTB => {
-                #[cfg(not(feature = "fruits"))]
-                panic!("`fruits` feature is not enabled");
+    #[inline]
+    fn pp(&self) -> AnyPP {
+        AnyPP::new(self)
+    }
 
-                #[cfg(feature = "fruits")]
-                fruits::stars(self, mods, passed_objects)
-            }
-        }
+    #[inline]
+    fn try_stars(&self, mods: impl Mods, passed_objects: Option<usize>) -> CalcResult<StarResult> {
+        self.check_calculable(self.mode)?;
+
+        mode_stars(self, mods, passed_objects)
     }
 
-    fn max_pp(&self, mods: u32) -> PpResult {
-        match self.mode {
-            GameMode::STD => {
-                #[cfg(not(feature = "osu"))]
-                panic!("`osu` feature is not enabled");
+    #[inline]
+    fn try_max_pp(&self, mods: u32) -> CalcResult<PpResult> {
+        self.check_calculable(self.mode)?;
 
-                #[cfg(feature = "osu")]
-                OsuPP::new(self).mods(mods).calculate()
-            }
-            GameMode::MNA => {
-                #[cfg(not(feature = "mania"))]
-                panic!("`mania` feature is not enabled");
+        mode_max_pp(self, mods)
+    }
+
+    #[inline]
+    fn try_strains(&self, mods: impl Mods) -> CalcResult<Strains> {
+        self.check_calculable(self.mode)?;
+
+        mode_strains(self, mods)
+    }
+
+    #[inline]
+    fn strains(&self, mods: impl Mods) -> Strains {
+        mode_strains(self, mods).unwrap_or_default()
+    }
+}
 
-                #[cfg(feature = "mania")]
-                ManiaPP::new(self).mods(mods).calculate()
+/// Panics with the error's message, e.g. if the map's mode is not enabled.
+#[inline]
+fn unwrap_mode<T>(result: CalcResult<T>) -> T {
+    result.unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// The stars of the map's mode or an error if the mode's feature is not enabled.
+fn mode_stars(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+) -> CalcResult<StarResult> {
+    match map.mode {
+        #[cfg(feature = "osu")]
+        GameMode::STD => {
+            #[cfg(feature = "no_leniency")]
+            {
+                Ok(osu::no_leniency::stars(map, mods, passed_objects))
             }
-            GameMode::TKO => {
-                #[cfg(not(feature = "taiko"))]
-                panic!("`osu` feature is not enabled");
 
-                #[cfg(feature = "taiko")]
-                TaikoPP::new(self).mods(mods).calculate()
+            #[cfg(all(not(feature = "no_leniency"), feature = "no_sliders_no_leniency"))]
+            {
+                Ok(osu::no_sliders_no_leniency::stars(
+                    map,
+                    mods,
+                    passed_objects,
+                ))
             }
-            GameMode::CTB => {
-                #[cfg(not(feature = "fruits"))]
-                panic!("`fruits` feature is not enabled");
 
-                #[cfg(feature = "fruits")]
-                FruitsPP::new(self).mods(mods).calculate()
+            #[cfg(all(
+                not(feature = "no_leniency"),
+                not(feature = "no_sliders_no_leniency"),
+                feature = "all_included"
+            ))]
+            {
+                Ok(osu::all_included::stars(map, mods, passed_objects))
             }
+
+            // Omits an unnecessary error when enabled features are invalid
+            #[cfg(not(any(
+                feature = "no_leniency",
+                feature = "no_sliders_no_leniency",
+                feature = "all_included"
+            )))]
+            unreachable!()
         }
+        #[cfg(feature = "mania")]
+        GameMode::MNA => Ok(mania::stars(map, mods, passed_objects)),
+        #[cfg(feature = "taiko")]
+        GameMode::TKO => Ok(taiko::stars(map, mods, passed_objects)),
+        #[cfg(feature = "fruits")]
+        GameMode::CTB => Ok(fruits::stars(map, mods, passed_objects)),
+        #[cfg(not(all(
+            feature = "osu",
+            feature = "taiko",
+            feature = "fruits",
+            feature = "mania"
+        )))]
+        mode => Err(CalcError::UnincludedMode(mode)),
     }
+}
 
-    #[inline]
-    fn pp(&self) -> AnyPP {
-        AnyPP::new(self)
+/// The max pp of the map's mode or an error if the mode's feature is not enabled.
+fn mode_max_pp(map: &Beatmap, mods: u32) -> CalcResult<PpResult> {
+    match map.mode {
+        #[cfg(feature = "osu")]
+        GameMode::STD => Ok(OsuPP::new(map).mods(mods).calculate()),
+        #[cfg(feature = "mania")]
+        GameMode::MNA => Ok(ManiaPP::new(map).mods(mods).calculate()),
+        #[cfg(feature = "taiko")]
+        GameMode::TKO => Ok(TaikoPP::new(map).mods(mods).calculate()),
+        #[cfg(feature = "fruits")]
+        GameMode::CTB => Ok(FruitsPP::new(map).mods(mods).calculate()),
+        #[cfg(not(all(
+            feature = "osu",
+            feature = "taiko",
+            feature = "fruits",
+            feature = "mania"
+        )))]
+        mode => Err(CalcError::UnincludedMode(mode)),
     }
+}
 
-    fn strains(&self, mods: impl Mods) -> Strains {
-        match self.mode {
-            GameMode::STD => {
-                #[cfg(not(feature = "osu"))]
-                panic!("`osu` feature is not enabled");
-
-                #[cfg(feature = "osu")]
-                {
-                    #[cfg(feature = "no_leniency")]
-                    {
-                        osu::no_leniency::strains(self, mods)
-                    }
-
-                    #[cfg(all(not(feature = "no_leniency"), feature = "no_sliders_no_leniency"))]
-                    {
-                        osu::no_sliders_no_leniency::strains(self, mods)
-                    }
-
-                    #[cfg(all(
-                        not(feature = "no_leniency"),
-                        not(feature = "no_sliders_no_leniency"),
-                        feature = "all_included"
-                    ))]
-                    {
-                        osu::all_included::strains(self, mods)
-                    }
-
-                    #[cfg(not(any(
-                        feature = "no_leniency",
-                        feature = "no_sliders_no_leniency",
-                        feature = "all_included"
-                    )))]
-                    panic!("either of the features `no_leniency`, `no_sliders_no_leniency`, or `all_included` must be enabled");
-                }
+/// The strains of the map's mode or an error if the mode's feature is not enabled.
+fn mode_strains(map: &Beatmap, mods: impl Mods) -> CalcResult<Strains> {
+    match map.mode {
+        #[cfg(feature = "osu")]
+        GameMode::STD => {
+            #[cfg(feature = "no_leniency")]
+            {
+                Ok(osu::no_leniency::strains(map, mods))
             }
-            GameMode::MNA => {
-                #[cfg(not(feature = "mania"))]
-                panic!("`mania` feature is not enabled");
 
-                #[cfg(feature = "mania")]
-                mania::strains(self, mods)
+            #[cfg(all(not(feature = "no_leniency"), feature = "no_sliders_no_leniency"))]
+            {
+                Ok(osu::no_sliders_no_leniency::strains(map, mods))
             }
-            GameMode::TKO => {
-                #[cfg(not(feature = "taiko"))]
-                panic!("`osu` feature is not enabled");
 
-                #[cfg(feature = "taiko")]
-                taiko::strains(self, mods)
+            #[cfg(all(
+                not(feature = "no_leniency"),
+                not(feature = "no_sliders_no_leniency"),
+                feature = "all_included"
+            ))]
+            {
+                Ok(osu::all_included::strains(map, mods))
             }
-            GameMode::CTB => {
-                #[cfg(not(feature = "fruits"))]
-                panic!("`fruits` feature is not enabled");
 
-                #[cfg(feature = "fruits")]
-                fruits::strains(self, mods)
-            }
+            // Omits an unnecessary error when enabled features are invalid
+            #[cfg(not(any(
+                feature = "no_leniency",
+                feature = "no_sliders_no_leniency",
+                feature = "all_included"
+            )))]
+            unreachable!()
         }
+        #[cfg(feature = "mania")]
+        GameMode::MNA => Ok(mania::strains(map, mods)),
+        #[cfg(feature = "taiko")]
+        GameMode::TKO => Ok(taiko::strains(map, mods)),
+        #[cfg(feature = "fruits")]
+        GameMode::CTB => Ok(fruits::strains(map, mods)),
+        #[cfg(not(all(
+            feature = "osu",
+            feature = "taiko",
+            feature = "fruits",
+            feature = "mania"
+        )))]
+        mode => Err(CalcError::UnincludedMode(mode)),
     }
 }
 
@@ -370,8 +459,8 @@ impl BeatmapExt for Beatmap {
 /// `section_length` is the time in ms inbetween two strains.
 #[derive(Clone, Debug, Default)]
 pub struct Strains {
-    pub section_length: f32,
-    pub strains: Vec<f32>,
+    pub section_length: Float,
+    pub strains: Vec<Float>,
 }
 
 /// Basic enum containing the result of a star calculation based on the mode.
@@ -390,7 +479,7 @@ pub enum StarResult {
 impl StarResult {
     /// The final star value.
     #[inline]
-    pub fn stars(&self) -> f32 {
+    pub fn stars(&self) -> Float {
         match self {
             #[cfg(feature = "fruits")]
             Self::Fruits(attributes) => attributes.stars,
@@ -406,27 +495,30 @@ impl StarResult {
 
 #[derive(Clone, Debug)]
 pub struct PpRaw {
-    pub aim: Option<f32>,
-    pub spd: Option<f32>,
-    pub str: Option<f32>,
-    pub acc: Option<f32>,
-    pub total: f32,
+    pub aim: Option<Float>,
+    pub spd: Option<Float>,
+    pub str: Option<Float>,
+    pub acc: Option<Float>,
+    pub fl: Option<Float>,
+    pub total: Float,
 }
 
 impl PpRaw {
     #[inline]
     pub fn new(
-        aim: Option<f32>,
-        spd: Option<f32>,
-        str: Option<f32>,
-        acc: Option<f32>,
-        total: f32,
+        aim: Option<Float>,
+        spd: Option<Float>,
+        str: Option<Float>,
+        acc: Option<Float>,
+        fl: Option<Float>,
+        total: Float,
     ) -> Self {
         Self {
             aim,
             spd,
             str,
             acc,
+            fl,
             total,
         }
     }
@@ -437,28 +529,43 @@ impl PpRaw {
 pub struct PpResult {
     pub mode: u8,
     pub mods: u32,
-    pub pp: f32,
+    pub pp: Float,
     pub raw: PpRaw,
     pub attributes: StarResult,
+    /// The version of the algorithm that produced the values.
+    pub version: AlgorithmVersion,
+    /// The grade of the play w.r.t. the hit results that were used for the pp.
+    pub grade: Grade,
 }
 
 impl PpResult {
     /// The final pp value.
     #[inline]
-    pub fn pp(&self) -> f32 {
+    pub fn pp(&self) -> Float {
         self.pp
     }
 
     /// The final star value.
     #[inline]
-    pub fn stars(&self) -> f32 {
+    pub fn stars(&self) -> Float {
         self.attributes.stars()
     }
 }
 
+/// The pp of a play and of the same play with a full combo.
+///
+/// Both results are based on the same difficulty attributes.
+#[derive(Clone, Debug)]
+pub struct IfFcResult {
+    /// The result of the play as it was given.
+    pub current: PpResult,
+    /// The result if the play had been a full combo.
+    pub if_fc: PpResult,
+}
+
 #[cfg(any(feature = "osu", feature = "taiko"))]
 #[inline]
-fn difficulty_range(val: f32, max: f32, avg: f32, min: f32) -> f32 {
+fn difficulty_range(val: Float, max: Float, avg: Float, min: Float) -> Float {
     if val > 5.0 {
         avg + (max - avg) * (val - 5.0) / 5.0
     } else if val < 5.0 {
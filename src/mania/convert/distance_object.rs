@@ -0,0 +1,497 @@
+use super::{round_half_even, ManiaObject, Pattern, PatternGenerator, PatternType};
+use crate::Beatmap;
+use crate::{parse::HitSound, Float};
+
+/// Converts sliders into notes and hold notes.
+pub(crate) struct DistanceObjectPatternGenerator<'a> {
+    base: PatternGenerator<'a>,
+    convert_type: PatternType,
+    edge_sounds: &'a [u8],
+    pub(crate) start_time: i32,
+    pub(crate) end_time: i32,
+    pub(crate) segment_duration: i32,
+    pub(crate) span_count: i32,
+}
+
+impl<'a> DistanceObjectPatternGenerator<'a> {
+    pub(crate) fn new(
+        base: PatternGenerator<'a>,
+        map: &Beatmap,
+        pixel_len: Float,
+        repeats: usize,
+        edge_sounds: &'a [u8],
+    ) -> Self {
+        let start_time = base.hit_object.start_time;
+
+        let convert_type = if map.kiai_at(start_time) {
+            PatternType::NONE
+        } else {
+            PatternType::LOW_PROBABILITY
+        };
+
+        let (beat_len, speed_mult) = map.control_points_at(start_time);
+        let bpm_mult = (100.0 / speed_mult as f64).clamp(10.0, 10_000.0) / 100.0;
+        let beat_len = beat_len as f64 * bpm_mult;
+
+        let span_count = repeats.max(1) as i32;
+        let start_time = round_half_even(start_time as f64) as i32;
+
+        // This matches stable's calculation
+        let end_time = (start_time as f64
+            + pixel_len as f64 * beat_len * span_count as f64 * 0.01 / map.sv as f64)
+            .floor() as i32;
+
+        let segment_duration = (end_time - start_time) / span_count;
+
+        Self {
+            base,
+            convert_type,
+            edge_sounds,
+            start_time,
+            end_time,
+            segment_duration,
+            span_count,
+        }
+    }
+
+    pub(crate) fn generate(mut self) -> Vec<Pattern> {
+        let original_pattern = self.generate_core();
+
+        if original_pattern.hit_objects.len() == 1 {
+            return vec![original_pattern];
+        }
+
+        // Objects that end at the slider's end are used for further pattern generation
+        let mut intermediate_pattern = Pattern::default();
+        let mut end_time_pattern = Pattern::default();
+
+        for obj in original_pattern.hit_objects {
+            if (self.end_time as f64 - obj.end_time()).abs() > f64::EPSILON {
+                intermediate_pattern.add(obj);
+            } else {
+                end_time_pattern.add(obj);
+            }
+        }
+
+        vec![intermediate_pattern, end_time_pattern]
+    }
+
+    fn generate_core(&mut self) -> Pattern {
+        let total_columns = self.base.total_columns;
+        let conversion_difficulty = self.base.conversion_difficulty;
+        let low_probability = self.convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if total_columns == 1 {
+            let mut pattern = Pattern::default();
+            self.add_to_pattern(&mut pattern, 0, self.start_time, self.end_time);
+
+            return pattern;
+        }
+
+        if self.span_count > 1 {
+            if self.segment_duration <= 90 {
+                return self.generate_random_hold_notes(self.start_time, 1);
+            }
+
+            if self.segment_duration <= 120 {
+                self.convert_type |= PatternType::FORCE_NOT_STACK;
+
+                return self.generate_random_notes(self.start_time, self.span_count + 1);
+            }
+
+            if self.segment_duration <= 160 {
+                return self.generate_stair(self.start_time);
+            }
+
+            if self.segment_duration <= 200 && conversion_difficulty > 3.0 {
+                return self.generate_random_multiple_notes(self.start_time);
+            }
+
+            if self.end_time - self.start_time >= 4000 {
+                return self.generate_n_random_notes(self.start_time, 0.23, 0.0, 0.0);
+            }
+
+            if self.segment_duration > 400
+                && self.span_count < total_columns - 1 - self.base.random_start
+            {
+                return self.generate_tiled_hold_notes(self.start_time);
+            }
+
+            return self.generate_hold_and_normal_notes(self.start_time);
+        }
+
+        if self.segment_duration <= 110 {
+            if self.base.prev_pattern.column_with_objects() < total_columns {
+                self.convert_type |= PatternType::FORCE_NOT_STACK;
+            } else {
+                self.convert_type.remove(PatternType::FORCE_NOT_STACK);
+            }
+
+            let note_count = if self.segment_duration < 80 { 1 } else { 2 };
+
+            return self.generate_random_notes(self.start_time, note_count);
+        }
+
+        let (p2, p3, p4) = if conversion_difficulty > 6.5 {
+            if low_probability {
+                (0.78, 0.3, 0.0)
+            } else {
+                (0.85, 0.36, 0.03)
+            }
+        } else if conversion_difficulty > 4.0 {
+            if low_probability {
+                (0.43, 0.08, 0.0)
+            } else {
+                (0.56, 0.18, 0.0)
+            }
+        } else if conversion_difficulty > 2.5 {
+            if low_probability {
+                (0.3, 0.0, 0.0)
+            } else {
+                (0.37, 0.08, 0.0)
+            }
+        } else if low_probability {
+            (0.17, 0.0, 0.0)
+        } else {
+            (0.27, 0.0, 0.0)
+        };
+
+        self.generate_n_random_notes(self.start_time, p2, p3, p4)
+    }
+
+    /// Random hold notes that start and end at the same time.
+    fn generate_random_hold_notes(&mut self, start_time: i32, note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let prev_pattern = self.base.prev_pattern;
+
+        let usable_columns =
+            self.base.total_columns - self.base.random_start - prev_pattern.column_with_objects();
+        let mut next_column = self.base.get_random_column(None, None);
+
+        for _ in 0..usable_columns.min(note_count) {
+            next_column = self.base.find_available_column(
+                next_column,
+                None,
+                None,
+                false,
+                |_| true,
+                &[&pattern, prev_pattern],
+            );
+
+            self.add_to_pattern(&mut pattern, next_column, start_time, self.end_time);
+        }
+
+        // This can't be combined with the above loop due to RNG
+        for _ in 0..note_count - usable_columns {
+            next_column = self.base.find_available_column(
+                next_column,
+                None,
+                None,
+                false,
+                |_| true,
+                &[&pattern],
+            );
+
+            self.add_to_pattern(&mut pattern, next_column, start_time, self.end_time);
+        }
+
+        pattern
+    }
+
+    /// Random notes with one note per row and no stacking.
+    fn generate_random_notes(&mut self, mut start_time: i32, note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let prev_pattern = self.base.prev_pattern;
+
+        let mut next_column = self.base.get_column(self.base.hit_object.pos.x, true);
+
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && prev_pattern.column_with_objects() < self.base.total_columns
+        {
+            next_column = self.base.find_available_column(
+                next_column,
+                None,
+                None,
+                false,
+                |_| true,
+                &[prev_pattern],
+            );
+        }
+
+        let mut last_column = next_column;
+
+        for _ in 0..note_count {
+            self.add_to_pattern(&mut pattern, next_column, start_time, start_time);
+
+            next_column = self.base.find_available_column(
+                next_column,
+                None,
+                None,
+                false,
+                |column| column != last_column,
+                &[],
+            );
+
+            last_column = next_column;
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    /// A stair of notes with one note per row.
+    fn generate_stair(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+
+        let mut column = self.base.get_column(self.base.hit_object.pos.x, true);
+        let mut increasing = self.base.random.next_double() > 0.5;
+
+        for _ in 0..=self.span_count {
+            self.add_to_pattern(&mut pattern, column, start_time, start_time);
+            start_time += self.segment_duration;
+
+            // Invert the pattern at the borders of the stage
+            if increasing {
+                if column >= self.base.total_columns - 1 {
+                    increasing = false;
+                    column -= 1;
+                } else {
+                    column += 1;
+                }
+            } else if column <= self.base.random_start {
+                increasing = true;
+                column += 1;
+            } else {
+                column -= 1;
+            }
+        }
+
+        pattern
+    }
+
+    /// Random notes with one to two notes per row and no stacking.
+    fn generate_random_multiple_notes(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+
+        let total_columns = self.base.total_columns;
+        let random_start = self.base.random_start;
+        let legacy = (4..=8).contains(&total_columns);
+        let interval = self
+            .base
+            .random
+            .next_range(1, total_columns - legacy as i32);
+
+        let mut next_column = self.base.get_column(self.base.hit_object.pos.x, true);
+
+        for _ in 0..=self.span_count {
+            self.add_to_pattern(&mut pattern, next_column, start_time, start_time);
+
+            next_column += interval;
+
+            if next_column >= total_columns - random_start {
+                next_column = next_column - total_columns - random_start + legacy as i32;
+            }
+
+            next_column += random_start;
+
+            // If we're in 2K, let's not add many consecutive doubles
+            if total_columns > 2 {
+                self.add_to_pattern(&mut pattern, next_column, start_time, start_time);
+            }
+
+            next_column = self.base.get_random_column(None, None);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    /// Random hold notes whose amount is determined by probabilities.
+    fn generate_n_random_notes(
+        &mut self,
+        start_time: i32,
+        mut p2: f64,
+        mut p3: f64,
+        mut p4: f64,
+    ) -> Pattern {
+        match self.base.total_columns {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+            }
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+            }
+            4 => {
+                p2 = p2.min(0.3);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+            }
+            5 => {
+                p2 = p2.min(0.34);
+                p3 = p3.min(0.1);
+                p4 = p4.min(0.03);
+            }
+            _ => {}
+        }
+
+        let is_double_sample = |sound: u8| sound.clap() || sound.finish();
+
+        let can_generate_two_notes = !self.convert_type.contains(PatternType::LOW_PROBABILITY)
+            && (is_double_sample(self.base.hit_object.sound)
+                || is_double_sample(self.sound_at(self.start_time)));
+
+        if can_generate_two_notes {
+            p2 = 1.0;
+        }
+
+        let note_count = self.base.get_random_note_count(p2, p3, p4, 0.0, 0.0);
+
+        self.generate_random_hold_notes(start_time, note_count)
+    }
+
+    /// A stair of hold notes.
+    fn generate_tiled_hold_notes(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let prev_pattern = self.base.prev_pattern;
+
+        let column_repeat = self.span_count.min(self.base.total_columns);
+
+        // Due to integer rounding, this is not guaranteed to be the same as `self.end_time`
+        let end_time = start_time + self.segment_duration * self.span_count;
+
+        let mut next_column = self.base.get_column(self.base.hit_object.pos.x, true);
+
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && prev_pattern.column_with_objects() < self.base.total_columns
+        {
+            next_column = self.base.find_available_column(
+                next_column,
+                None,
+                None,
+                false,
+                |_| true,
+                &[prev_pattern],
+            );
+        }
+
+        for _ in 0..column_repeat {
+            next_column = self.base.find_available_column(
+                next_column,
+                None,
+                None,
+                false,
+                |_| true,
+                &[&pattern],
+            );
+
+            self.add_to_pattern(&mut pattern, next_column, start_time, end_time);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    /// A hold note alongside normal notes.
+    fn generate_hold_and_normal_notes(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let prev_pattern = self.base.prev_pattern;
+        let total_columns = self.base.total_columns;
+
+        let mut hold_column = self.base.get_column(self.base.hit_object.pos.x, true);
+
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && prev_pattern.column_with_objects() < total_columns
+        {
+            hold_column = self.base.find_available_column(
+                hold_column,
+                None,
+                None,
+                false,
+                |_| true,
+                &[prev_pattern],
+            );
+        }
+
+        // Create the hold note
+        self.add_to_pattern(&mut pattern, hold_column, start_time, self.end_time);
+
+        let mut next_column = self.base.get_random_column(None, None);
+        let conversion_difficulty = self.base.conversion_difficulty;
+
+        let note_count = if conversion_difficulty > 6.5 {
+            self.base.get_random_note_count(0.63, 0.0, 0.0, 0.0, 0.0)
+        } else if conversion_difficulty > 4.0 {
+            let p2 = if total_columns < 6 { 0.12 } else { 0.45 };
+
+            self.base.get_random_note_count(p2, 0.0, 0.0, 0.0, 0.0)
+        } else if conversion_difficulty > 2.5 {
+            let p2 = if total_columns < 6 { 0.0 } else { 0.24 };
+
+            self.base.get_random_note_count(p2, 0.0, 0.0, 0.0, 0.0)
+        } else {
+            0
+        };
+
+        let note_count = note_count.min(total_columns - 1);
+
+        let sound = self.sound_at(start_time);
+        let ignore_head = !(sound.whistle() || sound.finish() || sound.clap());
+
+        let mut row_pattern = Pattern::default();
+
+        for _ in 0..=self.span_count {
+            if !(ignore_head && start_time == self.start_time) {
+                for _ in 0..note_count {
+                    next_column = self.base.find_available_column(
+                        next_column,
+                        None,
+                        None,
+                        false,
+                        |column| column != hold_column,
+                        &[&row_pattern],
+                    );
+
+                    self.add_to_pattern(&mut row_pattern, next_column, start_time, start_time);
+                }
+            }
+
+            pattern.append(&mut row_pattern);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    /// The hitsound of the node at the given time.
+    fn sound_at(&self, time: i32) -> u8 {
+        let idx = if self.segment_duration == 0 {
+            0
+        } else {
+            (time - self.start_time) / self.segment_duration
+        };
+
+        self.edge_sounds
+            .get(idx.max(0) as usize)
+            .copied()
+            .unwrap_or(self.base.hit_object.sound)
+    }
+
+    fn add_to_pattern(&self, pattern: &mut Pattern, column: i32, start_time: i32, end_time: i32) {
+        let (end_time, sound) = if start_time == end_time {
+            (None, self.sound_at(start_time))
+        } else {
+            (Some(end_time as f64), self.base.hit_object.sound)
+        };
+
+        pattern.add(ManiaObject {
+            column,
+            start_time: start_time as f64,
+            end_time,
+            sound,
+        });
+    }
+}
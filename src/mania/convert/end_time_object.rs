@@ -0,0 +1,65 @@
+use super::{ManiaObject, Pattern, PatternGenerator, PatternType};
+use crate::{parse::HitSound, Float};
+
+/// Converts spinners into a note or a hold note.
+pub(crate) struct EndTimeObjectPatternGenerator<'a> {
+    base: PatternGenerator<'a>,
+    convert_type: PatternType,
+    end_time: i32,
+}
+
+impl<'a> EndTimeObjectPatternGenerator<'a> {
+    pub(crate) fn new(base: PatternGenerator<'a>, end_time: Float) -> Self {
+        let convert_type = if base.prev_pattern.column_with_objects() == base.total_columns {
+            PatternType::NONE
+        } else {
+            PatternType::FORCE_NOT_STACK
+        };
+
+        Self {
+            base,
+            convert_type,
+            end_time: end_time as i32,
+        }
+    }
+
+    pub(crate) fn generate(mut self) -> Pattern {
+        let mut pattern = Pattern::default();
+
+        let start_time = self.base.hit_object.start_time as f64;
+        let duration = self.end_time as f64 - start_time;
+        let generate_hold = duration >= 100.0;
+
+        let column = if self.base.total_columns == 8 {
+            if self.base.hit_object.sound.finish() && duration < 1000.0 {
+                0
+            } else {
+                self.get_random_column(None)
+            }
+        } else {
+            self.get_random_column(Some(0))
+        };
+
+        pattern.add(ManiaObject {
+            column,
+            start_time,
+            end_time: generate_hold.then_some(self.end_time as f64),
+            sound: self.base.hit_object.sound,
+        });
+
+        pattern
+    }
+
+    fn get_random_column(&mut self, lower: Option<i32>) -> i32 {
+        let column = self.base.get_random_column(lower, None);
+        let prev_pattern = self.base.prev_pattern;
+
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK) {
+            self.base
+                .find_available_column(column, lower, None, false, |_| true, &[prev_pattern])
+        } else {
+            self.base
+                .find_available_column(column, lower, None, false, |_| true, &[])
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use super::{Pattern, Random};
+use crate::{parse::HitObject, Float};
+
+/// State and helpers shared by all pattern generators.
+pub(crate) struct PatternGenerator<'a> {
+    pub(crate) hit_object: &'a HitObject,
+    pub(crate) total_columns: i32,
+    pub(crate) random_start: i32,
+    pub(crate) conversion_difficulty: f64,
+    pub(crate) prev_pattern: &'a Pattern,
+    pub(crate) random: &'a mut Random,
+}
+
+impl<'a> PatternGenerator<'a> {
+    #[inline]
+    pub(crate) fn new(
+        hit_object: &'a HitObject,
+        total_columns: i32,
+        conversion_difficulty: f64,
+        prev_pattern: &'a Pattern,
+        random: &'a mut Random,
+    ) -> Self {
+        Self {
+            hit_object,
+            total_columns,
+            random_start: (total_columns == 8) as i32,
+            conversion_difficulty,
+            prev_pattern,
+            random,
+        }
+    }
+
+    /// The column for an x position. With `allow_special`, the first column
+    /// of 8 columns is reserved for special notes.
+    pub(crate) fn get_column(&self, x: Float, allow_special: bool) -> i32 {
+        if allow_special && self.total_columns == 8 {
+            let local_x_divisor = 512.0 / 7.0;
+
+            return ((x / local_x_divisor).floor() as i32).clamp(0, 6) + 1;
+        }
+
+        let local_x_divisor = 512.0 / self.total_columns as Float;
+
+        ((x / local_x_divisor).floor() as i32).clamp(0, self.total_columns - 1)
+    }
+
+    /// A random amount of notes in `1..=6` where `pn` is the probability for at least `n` notes.
+    pub(crate) fn get_random_note_count(
+        &mut self,
+        p2: f64,
+        p3: f64,
+        p4: f64,
+        p5: f64,
+        p6: f64,
+    ) -> i32 {
+        let value = self.random.next_double();
+
+        if value >= 1.0 - p6 {
+            6
+        } else if value >= 1.0 - p5 {
+            5
+        } else if value >= 1.0 - p4 {
+            4
+        } else if value >= 1.0 - p3 {
+            3
+        } else if value >= 1.0 - p2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get_random_column(&mut self, lower: Option<i32>, upper: Option<i32>) -> i32 {
+        let lower = lower.unwrap_or(self.random_start);
+        let upper = upper.unwrap_or(self.total_columns);
+
+        self.random.next_range(lower, upper)
+    }
+
+    /// Starting from `initial`, find a column within the bounds that passes
+    /// `validation` and is not occupied by any of the patterns.
+    ///
+    /// Columns are iterated randomly unless `gathered` is set in which case
+    /// they are iterated in ascending order.
+    pub(crate) fn find_available_column(
+        &mut self,
+        mut initial: i32,
+        lower: Option<i32>,
+        upper: Option<i32>,
+        gathered: bool,
+        validation: impl Fn(i32) -> bool,
+        patterns: &[&Pattern],
+    ) -> i32 {
+        let lower = lower.unwrap_or(self.random_start);
+        let upper = upper.unwrap_or(self.total_columns);
+
+        let is_valid = |column: i32| {
+            validation(column)
+                && patterns
+                    .iter()
+                    .all(|pattern| !pattern.column_has_object(column))
+        };
+
+        if is_valid(initial) {
+            return initial;
+        }
+
+        // Ensure that we have at least one free column so that an endless loop is avoided
+        if !(lower..upper).any(is_valid) {
+            return initial;
+        }
+
+        loop {
+            initial = if gathered {
+                let next = initial + 1;
+
+                if next == self.total_columns {
+                    self.random_start
+                } else {
+                    next
+                }
+            } else {
+                self.random.next_range(lower, upper)
+            };
+
+            if is_valid(initial) {
+                return initial;
+            }
+        }
+    }
+}
@@ -0,0 +1,430 @@
+use super::{ManiaObject, Pattern, PatternGenerator, PatternType};
+use crate::{
+    parse::{HitSound, Pos2},
+    Float,
+};
+
+/// Converts circles into one or more notes.
+pub(crate) struct HitObjectPatternGenerator<'a> {
+    base: PatternGenerator<'a>,
+    convert_type: PatternType,
+    pub(crate) stair_type: PatternType,
+}
+
+impl<'a> HitObjectPatternGenerator<'a> {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        base: PatternGenerator<'a>,
+        prev_time: f64,
+        prev_pos: Pos2,
+        density: f64,
+        last_stair: PatternType,
+        beat_len: Float,
+        kiai: bool,
+    ) -> Self {
+        let h = base.hit_object;
+
+        let position_separation = h.pos.distance(prev_pos);
+        let time_separation = h.start_time as f64 - prev_time;
+
+        let mut convert_type = PatternType::NONE;
+
+        if time_separation <= 80.0 {
+            // More than 187 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE;
+        } else if time_separation <= 95.0 {
+            // More than 157 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE | last_stair;
+        } else if time_separation <= 105.0 {
+            // More than 140 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK | PatternType::LOW_PROBABILITY;
+        } else if time_separation <= 125.0 {
+            // More than 120 BPM
+            convert_type |= PatternType::FORCE_NOT_STACK;
+        } else if time_separation <= 135.0 && position_separation < 20.0 {
+            // More than 111 BPM stream
+            convert_type |= PatternType::CYCLE | PatternType::KEEP_SINGLE;
+        } else if time_separation <= 150.0 && position_separation < 20.0 {
+            // More than 100 BPM stream
+            convert_type |= PatternType::FORCE_STACK | PatternType::LOW_PROBABILITY;
+        } else if position_separation < 20.0 && density >= beat_len as f64 / 2.5 {
+            // Low density stream
+            convert_type |= PatternType::REVERSE | PatternType::LOW_PROBABILITY;
+        } else if density < beat_len as f64 / 2.5 || kiai {
+            // High density
+        } else {
+            convert_type |= PatternType::LOW_PROBABILITY;
+        }
+
+        if !convert_type.contains(PatternType::KEEP_SINGLE) {
+            if h.sound.finish() && base.total_columns != 8 {
+                convert_type |= PatternType::MIRROR;
+            } else if h.sound.clap() {
+                convert_type |= PatternType::GATHERED;
+            }
+        }
+
+        Self {
+            base,
+            convert_type,
+            stair_type: last_stair,
+        }
+    }
+
+    pub(crate) fn generate(&mut self) -> Pattern {
+        let pattern = self.generate_core();
+
+        for obj in pattern.hit_objects.iter() {
+            if self.convert_type.contains(PatternType::STAIR)
+                && obj.column == self.base.total_columns - 1
+            {
+                self.stair_type = PatternType::REVERSE_STAIR;
+            }
+
+            if self.convert_type.contains(PatternType::REVERSE_STAIR)
+                && obj.column == self.base.random_start
+            {
+                self.stair_type = PatternType::STAIR;
+            }
+        }
+
+        pattern
+    }
+
+    fn generate_core(&mut self) -> Pattern {
+        let mut pattern = Pattern::default();
+
+        let total_columns = self.base.total_columns;
+        let random_start = self.base.random_start;
+        let prev_pattern = self.base.prev_pattern;
+        let convert_type = self.convert_type;
+
+        if total_columns == 1 {
+            self.add_to_pattern(&mut pattern, 0);
+
+            return pattern;
+        }
+
+        let last_column = prev_pattern.hit_objects.first().map_or(0, |h| h.column);
+
+        if convert_type.contains(PatternType::REVERSE) && !prev_pattern.hit_objects.is_empty() {
+            // Copy the last hit objects in reverse-column order
+            for i in random_start..total_columns {
+                if prev_pattern.column_has_object(i) {
+                    self.add_to_pattern(&mut pattern, random_start + total_columns - i - 1);
+                }
+            }
+
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::CYCLE)
+            && prev_pattern.hit_objects.len() == 1
+            // If we convert to 7K + 1, let's not overload the special key
+            && (total_columns != 8 || last_column != 0)
+            // Make sure the last column was not the centre column
+            && (total_columns % 2 == 0 || last_column != total_columns / 2)
+        {
+            // Cycle backwards, similar to reverse but for only one hit object
+            let column = random_start + total_columns - last_column - 1;
+            self.add_to_pattern(&mut pattern, column);
+
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::FORCE_STACK) && !prev_pattern.hit_objects.is_empty() {
+            // Place on the already filled columns
+            for i in random_start..total_columns {
+                if prev_pattern.column_has_object(i) {
+                    self.add_to_pattern(&mut pattern, i);
+                }
+            }
+
+            return pattern;
+        }
+
+        if prev_pattern.hit_objects.len() == 1 {
+            if convert_type.contains(PatternType::STAIR) {
+                // Place on the next column, cycling back to the start if there is no "next"
+                let mut target_column = last_column + 1;
+
+                if target_column == total_columns {
+                    target_column = random_start;
+                }
+
+                self.add_to_pattern(&mut pattern, target_column);
+
+                return pattern;
+            }
+
+            if convert_type.contains(PatternType::REVERSE_STAIR) {
+                // Place on the previous column, cycling back to the end if there is no "previous"
+                let mut target_column = last_column - 1;
+
+                if target_column == random_start - 1 {
+                    target_column = total_columns - 1;
+                }
+
+                self.add_to_pattern(&mut pattern, target_column);
+
+                return pattern;
+            }
+        }
+
+        if convert_type.contains(PatternType::KEEP_SINGLE) {
+            return self.generate_random_notes(1);
+        }
+
+        let conversion_difficulty = self.base.conversion_difficulty;
+        let low_probability = convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if convert_type.contains(PatternType::MIRROR) {
+            return if conversion_difficulty > 6.5 {
+                self.generate_random_pattern_with_mirrored(0.12, 0.38, 0.12)
+            } else if conversion_difficulty > 4.0 {
+                self.generate_random_pattern_with_mirrored(0.12, 0.17, 0.0)
+            } else {
+                self.generate_random_pattern_with_mirrored(0.12, 0.0, 0.0)
+            };
+        }
+
+        let (p2, p3) = if conversion_difficulty > 6.5 {
+            if low_probability {
+                (0.78, 0.42)
+            } else {
+                (1.0, 0.62)
+            }
+        } else if conversion_difficulty > 4.0 {
+            if low_probability {
+                (0.35, 0.08)
+            } else {
+                (0.52, 0.15)
+            }
+        } else if conversion_difficulty > 2.0 {
+            if low_probability {
+                (0.18, 0.0)
+            } else {
+                (0.45, 0.0)
+            }
+        } else {
+            (0.0, 0.0)
+        };
+
+        self.generate_random_pattern(p2, p3, 0.0, 0.0)
+    }
+
+    /// Up to `note_count` random notes, less if there are not enough columns available.
+    fn generate_random_notes(&mut self, mut note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let prev_pattern = self.base.prev_pattern;
+
+        let allow_stacking = !self.convert_type.contains(PatternType::FORCE_NOT_STACK);
+        let gathered = self.convert_type.contains(PatternType::GATHERED);
+
+        if !allow_stacking {
+            note_count = note_count.min(
+                self.base.total_columns
+                    - self.base.random_start
+                    - prev_pattern.column_with_objects(),
+            );
+        }
+
+        let mut next_column = self.base.get_column(self.base.hit_object.pos.x, true);
+
+        for _ in 0..note_count {
+            next_column = if allow_stacking {
+                self.base.find_available_column(
+                    next_column,
+                    None,
+                    None,
+                    gathered,
+                    |_| true,
+                    &[&pattern],
+                )
+            } else {
+                self.base.find_available_column(
+                    next_column,
+                    None,
+                    None,
+                    gathered,
+                    |_| true,
+                    &[&pattern, prev_pattern],
+                )
+            };
+
+            self.add_to_pattern(&mut pattern, next_column);
+        }
+
+        pattern
+    }
+
+    /// Whether this hit object can generate a note in the special column.
+    fn has_special_column(&self) -> bool {
+        let sound = self.base.hit_object.sound;
+
+        sound.clap() && sound.finish()
+    }
+
+    fn generate_random_pattern(&mut self, p2: f64, p3: f64, p4: f64, p5: f64) -> Pattern {
+        let mut pattern = Pattern::default();
+
+        let note_count = self.get_random_note_count(p2, p3, p4, p5);
+        pattern.append(&mut self.generate_random_notes(note_count));
+
+        if self.base.random_start > 0 && self.has_special_column() {
+            self.add_to_pattern(&mut pattern, 0);
+        }
+
+        pattern
+    }
+
+    /// A random pattern which has both normal and mirrored notes.
+    fn generate_random_pattern_with_mirrored(
+        &mut self,
+        centre_probability: f64,
+        p2: f64,
+        p3: f64,
+    ) -> Pattern {
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK) {
+            return self.generate_random_pattern(0.5 + p2 / 2.0, p2, (p2 + p3) / 2.0, p3);
+        }
+
+        let mut pattern = Pattern::default();
+
+        let total_columns = self.base.total_columns;
+        let random_start = self.base.random_start;
+
+        let (note_count, add_to_centre) =
+            self.get_random_note_count_mirrored(centre_probability, p2, p3);
+
+        let column_limit = (if total_columns % 2 == 0 {
+            total_columns
+        } else {
+            total_columns - 1
+        }) / 2;
+
+        let mut next_column = self.base.get_random_column(None, Some(column_limit));
+
+        for _ in 0..note_count {
+            next_column = self.base.find_available_column(
+                next_column,
+                None,
+                Some(column_limit),
+                false,
+                |_| true,
+                &[&pattern],
+            );
+
+            // Add normal note
+            self.add_to_pattern(&mut pattern, next_column);
+            // Add mirrored note
+            self.add_to_pattern(&mut pattern, random_start + total_columns - next_column - 1);
+        }
+
+        if add_to_centre {
+            self.add_to_pattern(&mut pattern, total_columns / 2);
+        }
+
+        if random_start > 0 && self.has_special_column() {
+            self.add_to_pattern(&mut pattern, 0);
+        }
+
+        pattern
+    }
+
+    fn get_random_note_count(&mut self, mut p2: f64, mut p3: f64, mut p4: f64, mut p5: f64) -> i32 {
+        match self.base.total_columns {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            4 => {
+                p2 = p2.min(0.23);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            5 => {
+                p3 = p3.min(0.15);
+                p4 = p4.min(0.03);
+                p5 = 0.0;
+            }
+            _ => {}
+        }
+
+        if self.base.hit_object.sound.clap() {
+            p2 = 1.0;
+        }
+
+        self.base.get_random_note_count(p2, p3, p4, p5, 0.0)
+    }
+
+    /// Returns the amount of notes and whether a note should be added to the centre column.
+    fn get_random_note_count_mirrored(
+        &mut self,
+        mut centre_probability: f64,
+        mut p2: f64,
+        mut p3: f64,
+    ) -> (i32, bool) {
+        match self.base.total_columns {
+            2 => {
+                centre_probability = 0.0;
+                p2 = 0.0;
+                p3 = 0.0;
+            }
+            3 => {
+                centre_probability = centre_probability.min(0.03);
+                p2 = 0.0;
+                p3 = 0.0;
+            }
+            4 => {
+                centre_probability = 0.0;
+                // Stable uses inverse probabilities so doubling them has to happen on the inverse
+                p2 = 1.0 - ((1.0 - p2) * 2.0).max(0.8);
+                p3 = 0.0;
+            }
+            5 => {
+                centre_probability = centre_probability.min(0.03);
+                p3 = 0.0;
+            }
+            6 => {
+                centre_probability = 0.0;
+                p2 = 1.0 - ((1.0 - p2) * 2.0).max(0.5);
+                p3 = 1.0 - ((1.0 - p3) * 2.0).max(0.85);
+            }
+            _ => {}
+        }
+
+        // Stable allows negative probabilities which will never be hit
+        p2 = p2.max(0.0);
+        p3 = p3.max(0.0);
+
+        let centre_value = self.base.random.next_double();
+        let note_count = self.base.get_random_note_count(p2, p3, 0.0, 0.0, 0.0);
+
+        let add_to_centre = self.base.total_columns % 2 != 0
+            && note_count != 3
+            && centre_value > 1.0 - centre_probability;
+
+        (note_count, add_to_centre)
+    }
+
+    fn add_to_pattern(&self, pattern: &mut Pattern, column: i32) {
+        let h = self.base.hit_object;
+
+        pattern.add(ManiaObject {
+            column,
+            start_time: h.start_time as f64,
+            end_time: None,
+            sound: h.sound,
+        });
+    }
+}
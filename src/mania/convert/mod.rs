@@ -0,0 +1,480 @@
+// Conversions are computed in `f64` like in the game
+#![cfg_attr(feature = "f64", allow(clippy::unnecessary_cast))]
+
+mod distance_object;
+mod end_time_object;
+mod generator;
+mod hit_object;
+mod pattern;
+mod pattern_type;
+mod random;
+
+use distance_object::DistanceObjectPatternGenerator;
+use end_time_object::EndTimeObjectPatternGenerator;
+use generator::PatternGenerator;
+use hit_object::HitObjectPatternGenerator;
+use pattern::{ManiaObject, Pattern};
+use pattern_type::PatternType;
+use random::Random;
+
+use super::columns;
+use crate::{
+    parse::{legacy_sort, HitObject, HitObjectKind, Pos2},
+    Beatmap, Float, GameMode, Mods,
+};
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+const MAX_NOTES_FOR_DENSITY: usize = 7;
+
+impl Beatmap {
+    /// Convert an osu!standard map into an osu!mania map the way the game does it.
+    ///
+    /// Objects are assigned columns through the game's seeded pattern generators,
+    /// sliders and spinners may become hold notes. The amount of columns depends
+    /// on the map and on key mods, see [`Mods::key_count`].
+    /// Maps of any other mode are returned as is.
+    pub fn to_mania(&self, mods: impl Mods) -> Cow<'_, Self> {
+        if self.mode != GameMode::STD {
+            return Cow::Borrowed(self);
+        }
+
+        let total_columns = columns(self, mods).map_or(1, i32::from);
+        let mut converter = ManiaConverter::new(self, total_columns);
+        let mut hit_objects = Vec::with_capacity(self.hit_objects.len());
+
+        for h in self.hit_objects.iter() {
+            for obj in converter.convert(h) {
+                hit_objects.push(obj.into_hit_object(total_columns));
+            }
+        }
+
+        hit_objects.sort_by(|a, b| {
+            a.start_time
+                .partial_cmp(&b.start_time)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        legacy_sort(&mut hit_objects);
+
+        let n_holds = hit_objects
+            .iter()
+            .filter(|h| matches!(h.kind, HitObjectKind::Hold { .. }))
+            .count() as u32;
+
+        let map = Self {
+            mode: GameMode::MNA,
+            version: self.version,
+            n_circles: hit_objects.len() as u32 - n_holds,
+            n_sliders: n_holds,
+            n_spinners: 0,
+            ar: self.ar,
+            od: self.od,
+            cs: total_columns as Float,
+            hp: self.hp,
+            sv: self.sv,
+            tick_rate: self.tick_rate,
+            hit_objects,
+            timing_points: self.timing_points.clone(),
+            difficulty_points: self.difficulty_points.clone(),
+            effect_points: self.effect_points.clone(),
+            breaks: self.breaks.clone(),
+            #[cfg(all(feature = "osu", feature = "all_included"))]
+            stack_leniency: self.stack_leniency,
+        };
+
+        Cow::Owned(map)
+    }
+
+    /// Whether the given time is within a kiai section.
+    fn kiai_at(&self, time: Float) -> bool {
+        let idx = self.effect_points.partition_point(|p| p.time <= time);
+
+        matches!(idx.checked_sub(1), Some(idx) if self.effect_points[idx].kiai)
+    }
+}
+
+impl ManiaObject {
+    fn into_hit_object(self, total_columns: i32) -> HitObject {
+        let x_divisor = 512.0 / total_columns as Float;
+
+        HitObject {
+            // Centre of the column so that it's retrieved again in the difficulty calculation
+            pos: Pos2 {
+                x: (self.column as Float + 0.5) * x_divisor,
+                y: 192.0,
+            },
+            start_time: self.start_time as Float,
+            kind: match self.end_time {
+                Some(end_time) => HitObjectKind::Hold {
+                    end_time: end_time as Float,
+                },
+                None => HitObjectKind::Circle,
+            },
+            sound: self.sound,
+        }
+    }
+}
+
+/// Keeps track of the state between the conversion of consecutive objects.
+struct ManiaConverter<'m> {
+    map: &'m Beatmap,
+    total_columns: i32,
+    conversion_difficulty: f64,
+    random: Random,
+    prev_pattern: Pattern,
+    prev_note_times: VecDeque<f64>,
+    density: f64,
+    last_time: f64,
+    last_pos: Pos2,
+    last_stair: PatternType,
+}
+
+impl<'m> ManiaConverter<'m> {
+    fn new(map: &'m Beatmap, total_columns: i32) -> Self {
+        // Integer overflows wrap around like in the game
+        let seed = (round_half_even((map.hp + map.cs) as f64) as i32)
+            .wrapping_mul(20)
+            .wrapping_add((map.od as f64 * 41.2) as i32)
+            .wrapping_add(round_half_even(map.ar as f64) as i32);
+
+        Self {
+            map,
+            total_columns,
+            conversion_difficulty: conversion_difficulty(map),
+            random: Random::new(seed),
+            prev_pattern: Pattern::default(),
+            prev_note_times: VecDeque::with_capacity(MAX_NOTES_FOR_DENSITY),
+            density: i32::MAX as f64,
+            last_time: 0.0,
+            last_pos: Pos2::zero(),
+            last_stair: PatternType::STAIR,
+        }
+    }
+
+    fn convert(&mut self, h: &HitObject) -> Vec<ManiaObject> {
+        let base = PatternGenerator::new(
+            h,
+            self.total_columns,
+            self.conversion_difficulty,
+            &self.prev_pattern,
+            &mut self.random,
+        );
+
+        match &h.kind {
+            HitObjectKind::Slider {
+                pixel_len,
+                repeats,
+                edge_sounds,
+                ..
+            } => {
+                let generator = DistanceObjectPatternGenerator::new(
+                    base,
+                    self.map,
+                    *pixel_len,
+                    *repeats,
+                    edge_sounds,
+                );
+
+                let segment_duration = generator.segment_duration as f64;
+
+                for i in 0..=generator.span_count {
+                    let time = h.start_time as f64 + segment_duration * i as f64;
+                    self.last_time = time;
+                    self.last_pos = h.pos;
+                    compute_density(&mut self.prev_note_times, &mut self.density, time);
+                }
+
+                let mut patterns = generator.generate();
+                let last_pattern = patterns.pop().unwrap_or_default();
+
+                let mut hit_objects: Vec<_> = patterns
+                    .into_iter()
+                    .flat_map(|pattern| pattern.hit_objects)
+                    .collect();
+
+                hit_objects.extend(last_pattern.hit_objects.iter().cloned());
+                self.prev_pattern = last_pattern;
+
+                hit_objects
+            }
+            HitObjectKind::Spinner { end_time } => {
+                let generator = EndTimeObjectPatternGenerator::new(base, *end_time);
+                let end_time = *end_time as f64;
+
+                self.last_time = end_time;
+                self.last_pos = Pos2 { x: 256.0, y: 192.0 };
+                compute_density(&mut self.prev_note_times, &mut self.density, end_time);
+
+                // Spinners don't affect the previous pattern
+                generator.generate().hit_objects
+            }
+            HitObjectKind::Circle | HitObjectKind::Hold { .. } => {
+                let time = h.start_time as f64;
+                compute_density(&mut self.prev_note_times, &mut self.density, time);
+
+                let (beat_len, _) = self.map.control_points_at(h.start_time);
+                let kiai = self.map.kiai_at(h.start_time);
+
+                let mut generator = HitObjectPatternGenerator::new(
+                    base,
+                    self.last_time,
+                    self.last_pos,
+                    self.density,
+                    self.last_stair,
+                    beat_len,
+                    kiai,
+                );
+
+                self.last_time = time;
+                self.last_pos = h.pos;
+
+                let pattern = generator.generate();
+                self.last_stair = generator.stair_type;
+                let hit_objects = pattern.hit_objects.clone();
+                self.prev_pattern = pattern;
+
+                hit_objects
+            }
+        }
+    }
+}
+
+fn compute_density(prev_note_times: &mut VecDeque<f64>, density: &mut f64, time: f64) {
+    if prev_note_times.len() == MAX_NOTES_FOR_DENSITY {
+        prev_note_times.pop_front();
+    }
+
+    prev_note_times.push_back(time);
+
+    if let (Some(first), Some(last)) = (prev_note_times.front(), prev_note_times.back()) {
+        if prev_note_times.len() >= 2 {
+            *density = (last - first) / prev_note_times.len() as f64;
+        }
+    }
+}
+
+/// Difficulty estimate of the original map that steers the pattern generation.
+fn conversion_difficulty(map: &Beatmap) -> f64 {
+    let (first, last) = match (map.hit_objects.first(), map.hit_objects.last()) {
+        (Some(first), Some(last)) => (first.start_time, last.start_time),
+        _ => (0.0, 0.0),
+    };
+
+    let break_time: Float = map.breaks.iter().map(|b| b.duration()).sum();
+
+    // Drain time in seconds
+    let mut drain_time = ((last - first - break_time) as f64 / 1000.0) as i32;
+
+    if drain_time == 0 {
+        drain_time = 10_000;
+    }
+
+    let difficulty = ((map.hp + map.ar.clamp(4.0, 7.0)) as f64 / 1.5
+        + map.hit_objects.len() as f64 / drain_time as f64 * 9.0)
+        / 38.0
+        * 5.0
+        / 1.15;
+
+    difficulty.min(12.0)
+}
+
+/// Rounds to the nearest integer and, if halfway between two integers, to the even one.
+fn round_half_even(x: f64) -> f64 {
+    let rounded = x.round();
+
+    if (x - x.trunc()).abs() == 0.5 && rounded % 2.0 != 0.0 {
+        rounded - x.signum()
+    } else {
+        rounded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::TimingPoint;
+
+    #[test]
+    fn mania_convert_is_deterministic() {
+        let circle = |x: Float, start_time: Float| HitObject {
+            pos: Pos2 { x, y: 192.0 },
+            start_time,
+            kind: HitObjectKind::Circle,
+            sound: 0,
+        };
+
+        let mut hit_objects: Vec<_> = (0..32)
+            .map(|i| circle((i * 97 % 512) as Float, i as Float * 150.0))
+            .collect();
+
+        hit_objects.push(HitObject {
+            pos: Pos2 { x: 256.0, y: 192.0 },
+            start_time: 6000.0,
+            kind: HitObjectKind::Spinner { end_time: 8000.0 },
+            sound: 0,
+        });
+
+        let map = Beatmap {
+            mode: GameMode::STD,
+            n_circles: 32,
+            n_spinners: 1,
+            od: 8.0,
+            cs: 4.0,
+            hp: 5.0,
+            ar: 9.0,
+            sv: 1.4,
+            tick_rate: 1.0,
+            hit_objects,
+            timing_points: vec![TimingPoint {
+                time: 0.0,
+                beat_len: 300.0,
+            }],
+            ..Default::default()
+        };
+
+        let converted = map.to_mania(0);
+
+        assert_eq!(converted.mode, GameMode::MNA);
+        assert_eq!(converted.cs, 7.0);
+        assert_eq!(converted.hit_objects, map.to_mania(0).hit_objects);
+        assert!(converted.hit_objects.len() >= map.hit_objects.len());
+        assert!(converted.hit_objects.iter().all(|h| h.pos.x < 512.0));
+        assert!(matches!(
+            converted.hit_objects.last().map(|h| &h.kind),
+            Some(HitObjectKind::Hold { end_time }) if *end_time == 8000.0
+        ));
+
+        assert_eq!(map.to_mania(1 << 15).cs, 4.0);
+    }
+
+    /// Expected values follow the rules of the game's `ManiaBeatmapConverter`:
+    ///
+    /// - Only spinners means 5 columns for an OD above 4.
+    /// - The seed is `round(HP + CS) * 20 + (int)(OD * 41.2) + round(AR) = 391`.
+    /// - Each spinner takes a random column that the previous one doesn't use
+    ///   and becomes a hold note if it lasts at least 100ms.
+    /// - With 8 columns, the first column is only used by short spinners with a finish sound.
+    #[test]
+    fn spinner_conversion_reference() {
+        let spinner = |start_time: Float, end_time: Float, sound: u8| HitObject {
+            pos: Pos2 { x: 256.0, y: 192.0 },
+            start_time,
+            kind: HitObjectKind::Spinner { end_time },
+            sound,
+        };
+
+        let map = Beatmap {
+            mode: GameMode::STD,
+            n_spinners: 6,
+            od: 5.0,
+            cs: 4.0,
+            hp: 5.0,
+            ar: 5.0,
+            sv: 1.4,
+            tick_rate: 1.0,
+            hit_objects: vec![
+                spinner(1000.0, 3000.0, 0),
+                spinner(3500.0, 3550.0, 0),
+                spinner(4000.0, 6000.0, 4),
+                spinner(6500.0, 7000.0, 0),
+                spinner(7500.0, 9000.0, 0),
+                spinner(9500.0, 9550.0, 4),
+            ],
+            timing_points: vec![TimingPoint {
+                time: 0.0,
+                beat_len: 300.0,
+            }],
+            ..Default::default()
+        };
+
+        let columns_and_holds = |mods: u32| {
+            let converted = map.to_mania(mods);
+            let x_divisor = 512.0 / converted.cs;
+
+            let objects: Vec<_> = converted
+                .hit_objects
+                .iter()
+                .map(|h| {
+                    let end_time = match h.kind {
+                        HitObjectKind::Hold { end_time } => Some(end_time),
+                        _ => None,
+                    };
+
+                    ((h.pos.x / x_divisor) as u8, h.start_time, end_time)
+                })
+                .collect();
+
+            (converted.cs, converted.n_sliders, objects)
+        };
+
+        let expected = vec![
+            (0, 1000.0, Some(3000.0)),
+            (1, 3500.0, None),
+            (2, 4000.0, Some(6000.0)),
+            (4, 6500.0, Some(7000.0)),
+            (3, 7500.0, Some(9000.0)),
+            (2, 9500.0, None),
+        ];
+
+        assert_eq!(columns_and_holds(0), (5.0, 4, expected));
+
+        let expected = vec![
+            (1, 1000.0, Some(3000.0)),
+            (2, 3500.0, None),
+            (4, 4000.0, Some(6000.0)),
+            (7, 6500.0, Some(7000.0)),
+            (5, 7500.0, Some(9000.0)),
+            (0, 9500.0, None),
+        ];
+
+        assert_eq!(columns_and_holds(u32::K8), (8.0, 4, expected));
+    }
+
+    #[test]
+    #[cfg(feature = "osu")]
+    fn key_mods_set_columns() {
+        use crate::{mania, test_util, ManiaPP, Mods};
+
+        let map = test_util::map(GameMode::STD);
+
+        let cases = [
+            (u32::K4, 4),
+            (u32::K5, 5),
+            (u32::K6, 6),
+            (u32::K7, 7),
+            (u32::K8, 8),
+            (u32::K9, 9),
+            (u32::K4 | u32::KC, 8),
+            (u32::K7 | u32::KC, 14),
+        ];
+
+        for &(mods, columns) in cases.iter() {
+            let converted = map.to_mania(mods);
+            assert_eq!(converted.cs, columns as Float, "mods {}", mods);
+
+            let x_divisor = 512.0 / columns as Float;
+            let max_column = converted
+                .hit_objects
+                .iter()
+                .map(|h| (h.pos.x / x_divisor) as usize)
+                .max();
+
+            assert_eq!(max_column, Some(columns - 1), "mods {}", mods);
+
+            // Stars of the conversion and of the calculator agree, also with irrelevant mods
+            let stars = mania::stars(&map, mods | u32::TD, None).stars();
+            let calculated = ManiaPP::new(&map).mods(mods | u32::TD).calculate();
+
+            assert_eq!(stars, mania::stars(&converted, 0, None).stars());
+            assert_eq!(stars, calculated.stars());
+            assert_eq!(calculated.mods, mods);
+        }
+
+        let stars = |mods| mania::stars(&map, mods, None).stars();
+        assert_ne!(stars(u32::K4), stars(u32::K7));
+        assert_ne!(stars(u32::K4), stars(u32::K4 | u32::KC));
+    }
+}
@@ -0,0 +1,47 @@
+/// A note or, if it has an end time, a hold note of a converted map.
+#[derive(Clone, Debug)]
+pub(crate) struct ManiaObject {
+    pub(crate) column: i32,
+    pub(crate) start_time: f64,
+    pub(crate) end_time: Option<f64>,
+    pub(crate) sound: u8,
+}
+
+impl ManiaObject {
+    #[inline]
+    pub(crate) fn end_time(&self) -> f64 {
+        self.end_time.unwrap_or(self.start_time)
+    }
+}
+
+/// The objects generated from a single object of the original map.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Pattern {
+    pub(crate) hit_objects: Vec<ManiaObject>,
+    contained_columns: u64,
+}
+
+impl Pattern {
+    #[inline]
+    pub(crate) fn column_has_object(&self, column: i32) -> bool {
+        (0..64).contains(&column) && self.contained_columns & (1 << column) > 0
+    }
+
+    #[inline]
+    pub(crate) fn column_with_objects(&self) -> i32 {
+        self.contained_columns.count_ones() as i32
+    }
+
+    #[inline]
+    pub(crate) fn add(&mut self, hit_object: ManiaObject) {
+        self.contained_columns |= 1 << hit_object.column;
+        self.hit_objects.push(hit_object);
+    }
+
+    #[inline]
+    pub(crate) fn append(&mut self, other: &mut Self) {
+        self.contained_columns |= other.contained_columns;
+        self.hit_objects.append(&mut other.hit_objects);
+        other.contained_columns = 0;
+    }
+}
@@ -0,0 +1,54 @@
+use std::ops::{BitOr, BitOrAssign};
+
+/// Flags that steer the pattern generation of a converted object.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PatternType(u16);
+
+impl PatternType {
+    pub(crate) const NONE: Self = Self(0);
+    /// Keep the same as the last row.
+    pub(crate) const FORCE_STACK: Self = Self(1 << 0);
+    /// Keep different from the last row.
+    pub(crate) const FORCE_NOT_STACK: Self = Self(1 << 1);
+    /// Keep as a single note at its original position.
+    pub(crate) const KEEP_SINGLE: Self = Self(1 << 2);
+    /// Use a lower random value.
+    pub(crate) const LOW_PROBABILITY: Self = Self(1 << 3);
+    /// Keep the same polarity with the last row.
+    pub(crate) const GATHERED: Self = Self(1 << 7);
+    pub(crate) const MIRROR: Self = Self(1 << 8);
+    /// Change to the opposite side.
+    pub(crate) const REVERSE: Self = Self(1 << 9);
+    /// Generate a 1-column cycle.
+    pub(crate) const CYCLE: Self = Self(1 << 10);
+    /// Next note will be at column + 1.
+    pub(crate) const STAIR: Self = Self(1 << 11);
+    /// Next note will be at column - 1.
+    pub(crate) const REVERSE_STAIR: Self = Self(1 << 12);
+
+    #[inline]
+    pub(crate) fn contains(self, other: Self) -> bool {
+        self.0 & other.0 > 0
+    }
+
+    #[inline]
+    pub(crate) fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for PatternType {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for PatternType {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
@@ -0,0 +1,50 @@
+/// The xorshift generator that the game seeds with the map's difficulty
+/// so that conversions are deterministic.
+pub(crate) struct Random {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+}
+
+impl Random {
+    const INT_TO_REAL: f64 = 1.0 / (i32::MAX as f64 + 1.0);
+    const INT_MASK: u32 = 0x7FFF_FFFF;
+
+    const Y: u32 = 842_502_087;
+    const Z: u32 = 3_579_807_591;
+    const W: u32 = 273_326_509;
+
+    #[inline]
+    pub(crate) fn new(seed: i32) -> Self {
+        Self {
+            x: seed as u32,
+            y: Self::Y,
+            z: Self::Z,
+            w: Self::W,
+        }
+    }
+
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+
+        self.w
+    }
+
+    /// A random value in `[0, 1)`.
+    #[inline]
+    pub(crate) fn next_double(&mut self) -> f64 {
+        Self::INT_TO_REAL * (Self::INT_MASK & self.next_u32()) as f64
+    }
+
+    /// A random value in `[lower, upper)`.
+    #[inline]
+    pub(crate) fn next_range(&mut self, lower: i32, upper: i32) -> i32 {
+        (lower as f64 + self.next_double() * (upper - lower) as f64) as i32
+    }
+}
@@ -1,53 +1,120 @@
 #![cfg(feature = "mania")]
 
+mod convert;
 mod pp;
+mod score;
 mod strain;
 
 pub use pp::*;
+pub use score::*;
 use strain::Strain;
 
-use crate::{parse::HitObject, Beatmap, GameMode, Mods, StarResult, Strains};
+use crate::{
+    parse::{HitObject, HitObjectKind},
+    AlgorithmVersion, Beatmap, CalcContext, CalcResult, DifficultyParams, Float, GameMode, Mods,
+    StarResult, Strains,
+};
 
-const SECTION_LEN: f32 = 400.0;
-const STAR_SCALING_FACTOR: f32 = 0.018;
+const SECTION_LEN: Float = 400.0;
+const STAR_SCALING_FACTOR: Float = 0.018;
 
 /// Star calculation for osu!mania maps
 ///
 /// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+///
+/// osu!standard maps are converted first, see [`Beatmap::to_mania`].
+/// Maps of other modes have no osu!mania difficulty, see [`try_stars`] to handle them.
+#[inline]
 pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> StarResult {
-    let take = passed_objects.unwrap_or_else(|| map.hit_objects.len());
+    stars_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `stars` function but returns an error for maps that
+/// can not be converted to osu!mania or have no hit objects.
+#[inline]
+pub fn try_stars(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+) -> CalcResult<StarResult> {
+    map.check_calculable(GameMode::MNA)?;
+
+    Ok(stars(map, mods, passed_objects))
+}
+
+/// Same as the `stars` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn stars_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> StarResult {
+    stars_with_version(map, mods, passed_objects, params, AlgorithmVersion::V2021)
+}
+
+/// Same as the `stars_with_params` function but for a specific algorithm version.
+///
+/// Since [`AlgorithmVersion::V2022`], hold notes whose release is close to
+/// the release of another hold note are considered easier and notes within
+/// a chord use the hardest strain of their columns.
+#[inline]
+pub fn stars_with_version(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+    version: AlgorithmVersion,
+) -> StarResult {
+    let ctx = &mut CalcContext::new();
+
+    stars_with_context(map, mods, passed_objects, params, version, ctx)
+}
+
+/// Same as the `stars_with_version` function but reuses the buffers of the given context.
+///
+/// osu!standard maps are converted on each call so for repeated calculations
+/// convert them once with [`Beatmap::to_mania`] using the same mods and pass the converted map.
+pub fn stars_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+    version: AlgorithmVersion,
+    ctx: &mut CalcContext,
+) -> StarResult {
+    let mods = mods.for_mode(GameMode::MNA);
+    let map = map.to_mania(mods);
+    let take = passed_objects
+        .unwrap_or(map.hit_objects.len())
+        .min(map.hit_objects.len());
+
+    let n_hold_notes = map
+        .hit_objects
+        .iter()
+        .take(take)
+        .filter(|h| matches!(h.kind, HitObjectKind::Hold { .. }))
+        .count();
 
     if take < 2 {
-        return StarResult::Mania(DifficultyAttributes::default());
+        return StarResult::Mania(DifficultyAttributes {
+            stars: 0.0,
+            n_hold_notes,
+        });
     }
 
-    let rounded_cs = map.cs.round();
-
-    let columns = match map.mode {
-        GameMode::MNA => rounded_cs.max(1.0) as u8,
-        GameMode::STD => {
-            let rounded_od = map.od.round();
-
-            let n_objects = map.n_circles + map.n_sliders + map.n_spinners;
-            let slider_or_spinner_ratio = (n_objects - map.n_circles) as f32 / n_objects as f32;
-
-            if slider_or_spinner_ratio < 0.2 {
-                7
-            } else if slider_or_spinner_ratio < 0.3 || rounded_cs >= 5.0 {
-                6 + (rounded_od > 5.0) as u8
-            } else if slider_or_spinner_ratio > 0.6 {
-                4 + (rounded_od > 4.0) as u8
-            } else {
-                (rounded_od as u8 + 1).max(4).min(7)
-            }
-        }
-        other => panic!("can not calculate mania difficulty on a {:?} map", other),
+    // Maps that can not be converted have no osu!mania difficulty
+    let columns = match columns(&map, mods) {
+        Some(columns) => columns,
+        None => return StarResult::Mania(DifficultyAttributes::default()),
     };
 
-    let clock_rate = mods.speed();
+    let clock_rate = params.clock_rate_with(mods);
     let section_len = SECTION_LEN * clock_rate;
-    let mut strain = Strain::new(columns);
-    let columns = columns as f32;
+    let release_aware = version.resolve(GameMode::MNA) >= AlgorithmVersion::V2022;
+    let mut strain = ctx.mania.strain(columns, release_aware);
+    let columns = columns as Float;
 
     let mut hit_objects = map
         .hit_objects
@@ -85,29 +152,76 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
     strain.save_current_peak();
 
     let stars = strain.difficulty_value() * STAR_SCALING_FACTOR;
+    ctx.mania.strain = Some(strain);
 
-    StarResult::Mania(DifficultyAttributes { stars })
+    StarResult::Mania(DifficultyAttributes {
+        stars,
+        n_hold_notes,
+    })
 }
 
 /// Essentially the same as the `stars` function but instead of
 /// evaluating the final strains, it just returns them as is.
 ///
 /// Suitable to plot the difficulty of a map over time.
+#[inline]
 pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
+    strains_with_params(map, mods, DifficultyParams::default())
+}
+
+/// Same as the `strains` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn strains_with_params(map: &Beatmap, mods: impl Mods, params: DifficultyParams) -> Strains {
+    strains_with_version(map, mods, params, AlgorithmVersion::V2021)
+}
+
+/// Same as the `strains_with_params` function but for a specific algorithm version,
+/// see [`stars_with_version`].
+#[inline]
+pub fn strains_with_version(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    version: AlgorithmVersion,
+) -> Strains {
+    strains_with_context(map, mods, params, version, &mut CalcContext::new())
+}
+
+/// Same as the `strains_with_version` function but reuses the buffers of the given context.
+///
+/// osu!standard maps are converted on each call so for repeated calculations
+/// convert them once with [`Beatmap::to_mania`] using the same mods and pass the converted map.
+pub fn strains_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    version: AlgorithmVersion,
+    ctx: &mut CalcContext,
+) -> Strains {
+    let mods = mods.for_mode(GameMode::MNA);
+    let map = map.to_mania(mods);
+
     if map.hit_objects.len() < 2 {
         return Strains::default();
     }
 
-    let clock_rate = mods.speed();
+    let columns = match columns(&map, mods) {
+        Some(columns) => columns,
+        None => return Strains::default(),
+    };
+    let clock_rate = params.clock_rate_with(mods);
     let section_len = SECTION_LEN * clock_rate;
-    let mut strain = Strain::new(map.cs as u8);
+    let release_aware = version.resolve(GameMode::MNA) >= AlgorithmVersion::V2022;
+    let mut strain = ctx.mania.strain(columns, release_aware);
+    let columns = columns as Float;
 
     let mut hit_objects = map
         .hit_objects
         .iter()
         .skip(1)
         .zip(map.hit_objects.iter())
-        .map(|(base, prev)| DifficultyHitObject::new(base, prev, map.cs, clock_rate));
+        .map(|(base, prev)| DifficultyHitObject::new(base, prev, columns, clock_rate));
 
     // No strain for first object
     let mut current_section_end =
@@ -136,9 +250,47 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
 
     strain.save_current_peak();
 
+    let strains = std::mem::take(&mut strain.strain_peaks);
+    ctx.mania.strain = Some(strain);
+
     Strains {
         section_length: section_len,
-        strains: strain.strain_peaks,
+        strains,
+    }
+}
+
+/// The amount of columns of a map.
+///
+/// For converted osu!standard maps, the amount is either given by a key mod
+/// or derived from the map, and doubled in case of co-op.
+/// Returns `None` for modes that can not be converted.
+fn columns(map: &Beatmap, mods: impl Mods) -> Option<u8> {
+    let rounded_cs = map.cs.round();
+
+    match map.mode {
+        GameMode::MNA => Some(rounded_cs.max(1.0) as u8),
+        GameMode::STD => {
+            let columns = mods.key_count().unwrap_or_else(|| {
+                let rounded_od = map.od.round();
+
+                let n_objects = map.n_circles + map.n_sliders + map.n_spinners;
+                let slider_or_spinner_ratio =
+                    (n_objects - map.n_circles) as Float / n_objects as Float;
+
+                if slider_or_spinner_ratio < 0.2 {
+                    7
+                } else if slider_or_spinner_ratio < 0.3 || rounded_cs >= 5.0 {
+                    6 + (rounded_od > 5.0) as u8
+                } else if slider_or_spinner_ratio > 0.6 {
+                    4 + (rounded_od > 4.0) as u8
+                } else {
+                    (rounded_od as u8 + 1).clamp(4, 7)
+                }
+            });
+
+            Some(columns * (1 + mods.kc() as u8))
+        }
+        _ => None,
     }
 }
 
@@ -146,13 +298,14 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
 pub(crate) struct DifficultyHitObject<'o> {
     base: &'o HitObject,
     column: usize,
-    delta: f32,
-    start_time: f32,
+    delta: Float,
+    start_time: Float,
+    end_time: Float,
 }
 
 impl<'o> DifficultyHitObject<'o> {
     #[inline]
-    fn new(base: &'o HitObject, prev: &'o HitObject, columns: f32, clock_rate: f32) -> Self {
+    fn new(base: &'o HitObject, prev: &'o HitObject, columns: Float, clock_rate: Float) -> Self {
         let x_divisor = 512.0 / columns;
         let column = (base.pos.x / x_divisor).floor().min(columns - 1.0) as usize;
 
@@ -161,6 +314,7 @@ impl<'o> DifficultyHitObject<'o> {
             column,
             delta: (base.start_time - prev.start_time) / clock_rate,
             start_time: base.start_time / clock_rate,
+            end_time: base.end_time() / clock_rate,
         }
     }
 }
@@ -169,5 +323,28 @@ impl<'o> DifficultyHitObject<'o> {
 /// This data is necessary to calculate PP.
 #[derive(Clone, Debug, Default)]
 pub struct DifficultyAttributes {
-    pub stars: f32,
+    pub stars: Float,
+    /// Amount of hold notes, each of which has a separately judged tail.
+    pub n_hold_notes: usize,
+}
+
+/// Buffers of osu!mania that are kept by a [`CalcContext`].
+#[derive(Default)]
+pub(crate) struct Buffers {
+    strain: Option<Strain>,
+}
+
+impl Buffers {
+    /// The strain of a previous calculation after a reset or a new one.
+    #[inline]
+    fn strain(&mut self, columns: u8, release_aware: bool) -> Strain {
+        match self.strain.take() {
+            Some(mut strain) => {
+                strain.reset(columns, release_aware);
+
+                strain
+            }
+            None => Strain::new(columns, release_aware),
+        }
+    }
 }
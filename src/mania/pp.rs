@@ -1,12 +1,18 @@
-use super::{stars, DifficultyAttributes};
-use crate::{Beatmap, Mods, PpRaw, PpResult, StarResult};
+use super::{stars_with_version, DifficultyAttributes, ManiaScore};
+use crate::{
+    parse::HitObjectKind, AlgorithmVersion, AttributeKey, AttributeStore, Beatmap, CalcResult,
+    DifficultyParams, Float, GameMode, Grade, HitResultPriority, HitResults, IfFcResult, Mods,
+    PpRaw, PpResult, StarResult,
+};
+
+use std::borrow::Cow;
 
 /// Calculator for pp on osu!mania maps.
 ///
 /// # Example
 ///
 /// ```
-/// # use peace_performance::{ManiaPP, PpResult, Beatmap};
+/// # use peace_performance::{AlgorithmVersion, ManiaPP, PpResult, Beatmap};
 /// # /*
 /// let map: Beatmap = ...
 /// # */
@@ -25,30 +31,64 @@ use crate::{Beatmap, Mods, PpRaw, PpResult, StarResult};
 ///     .calculate();
 ///
 /// println!("PP: {} | Stars: {}", next_result.pp(), next_result.stars());
+///
+/// // Accuracy based pp of the 2022 rework
+/// let acc_result = ManiaPP::new(&map)
+///     .version(AlgorithmVersion::V2022)
+///     .misses(2)
+///     .accuracy(97.5)
+///     .calculate();
+///
+/// println!("PP: {}", acc_result.pp());
 /// ```
 #[derive(Clone, Debug)]
 #[allow(clippy::upper_case_acronyms)]
 pub struct ManiaPP<'m> {
-    map: &'m Beatmap,
-    stars: Option<f32>,
+    original: &'m Beatmap,
+    map: Cow<'m, Beatmap>,
+    stars: Option<Float>,
     mods: u32,
-    score: Option<f32>,
+    params: DifficultyParams,
+    score: Option<Float>,
     passed_objects: Option<usize>,
+    version: AlgorithmVersion,
+    hitresult_priority: HitResultPriority,
+    acc: Option<Float>,
+
+    n320: Option<usize>,
+    n300: Option<usize>,
+    n200: Option<usize>,
+    n100: Option<usize>,
+    n50: Option<usize>,
+    n_misses: usize,
 }
 
 impl<'m> ManiaPP<'m> {
+    /// osu!standard maps are converted first, see [`Beatmap::to_mania`].
     #[inline]
     pub fn new(map: &'m Beatmap) -> Self {
         Self {
-            map,
+            original: map,
+            map: map.to_mania(0),
             stars: None,
             mods: 0,
+            params: DifficultyParams::default(),
             score: None,
             passed_objects: None,
+            version: AlgorithmVersion::default(),
+            hitresult_priority: HitResultPriority::default(),
+            acc: None,
+
+            n320: None,
+            n300: None,
+            n200: None,
+            n100: None,
+            n50: None,
+            n_misses: 0,
         }
     }
 
-    /// [`ManiaAttributeProvider`] is implemented by `f32`, [`StarResult`](crate::StarResult),
+    /// [`ManiaAttributeProvider`] is implemented by `Float`, [`StarResult`](crate::StarResult),
     /// and by [`PpResult`](crate::PpResult) meaning you can give the star rating,
     /// the result of a star calculation, or the result of a pp calculation.
     /// If you already calculated the attributes for the current map-mod combination,
@@ -62,21 +102,108 @@ impl<'m> ManiaPP<'m> {
         self
     }
 
+    /// Fill the attributes from the given store or calculate and insert them if it has none.
+    ///
+    /// The checksum identifies the map, e.g. the MD5 hash of its .osu file.
+    /// Be sure to set the mods, clock rate, and version beforehand!
+    /// Attributes of partial plays or custom AR, OD, CS, or HP are not stored.
+    pub fn attributes_from_store(self, mut store: impl AttributeStore, checksum: &str) -> Self {
+        let key = AttributeKey::for_calculation(
+            checksum,
+            GameMode::MNA,
+            self.mods,
+            self.params,
+            self.passed_objects,
+            self.version,
+        );
+
+        match key {
+            Some(key) => {
+                let (map, mods, params, version) =
+                    (&self.map, self.mods, self.params, self.version);
+                let attributes = store
+                    .get_or_calculate(key, || stars_with_version(map, mods, None, params, version));
+
+                self.attributes(attributes)
+            }
+            None => self,
+        }
+    }
+
     /// Specify mods through their bit values.
     ///
     /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    ///
+    /// Mods without effect in this mode are ignored.
+    ///
+    /// Key mods change the amount of objects of converted maps
+    /// so they should be specified before the accuracy.
     #[inline]
     pub fn mods(mut self, mods: u32) -> Self {
-        self.mods = mods;
+        self.mods = mods.for_mode(GameMode::MNA);
+
+        // Key mods change the conversion
+        if self.original.mode != GameMode::MNA {
+            self.map = self.original.to_mania(self.mods);
+        }
+
+        self
+    }
+
+    /// Specify a custom clock rate that overrides the one of the mods, e.g. `1.2` for a 1.2x speed-up.
+    ///
+    /// Invalid rates are ignored or clamped, see [`DifficultyParams::clock_rate`](crate::DifficultyParams::clock_rate).
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: Float) -> Self {
+        self.params = self.params.clock_rate(clock_rate);
+
+        self
+    }
+
+    /// Specify an approach rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn ar(mut self, ar: Float) -> Self {
+        self.params = self.params.ar(ar);
+
+        self
+    }
+
+    /// Specify an overall difficulty that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn od(mut self, od: Float) -> Self {
+        self.params = self.params.od(od);
+
+        self
+    }
+
+    /// Specify a circle size that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn cs(mut self, cs: Float) -> Self {
+        self.params = self.params.cs(cs);
+
+        self
+    }
+
+    /// Specify a drain rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn hp(mut self, hp: Float) -> Self {
+        self.params = self.params.hp(hp);
 
         self
     }
 
     /// Specify the score of a play.
     /// On `NoMod` its between 0 and 1,000,000, on `Easy` between 0 and 500,000, etc.
+    ///
+    /// If no score is specified but judgements are, the score is calculated from them,
+    /// see [`ManiaScore`](crate::ManiaScore).
     #[inline]
     pub fn score(mut self, score: u32) -> Self {
-        self.score.replace(score as f32);
+        self.score.replace(score as Float);
 
         self
     }
@@ -89,29 +216,219 @@ impl<'m> ManiaPP<'m> {
         self
     }
 
+    /// Specify the version of the difficulty and pp algorithm.
+    ///
+    /// [`AlgorithmVersion::V2021`] calculates pp based on the score,
+    /// [`AlgorithmVersion::V2022`] and newer based on the accuracy of the judgements.
+    ///
+    /// Versions without an implementation for this mode fall back
+    /// to the newest older one, see [`AlgorithmVersion::resolve`].
+    #[inline]
+    pub fn version(mut self, version: AlgorithmVersion) -> Self {
+        self.version = version;
+
+        self
+    }
+
+    /// Specify the amount of 320s of a play.
+    #[inline]
+    pub fn n320(mut self, n320: usize) -> Self {
+        self.n320.replace(n320);
+
+        self
+    }
+
+    /// Specify the amount of 300s of a play.
+    #[inline]
+    pub fn n300(mut self, n300: usize) -> Self {
+        self.n300.replace(n300);
+
+        self
+    }
+
+    /// Specify the amount of 200s of a play.
+    #[inline]
+    pub fn n200(mut self, n200: usize) -> Self {
+        self.n200.replace(n200);
+
+        self
+    }
+
+    /// Specify the amount of 100s of a play.
+    #[inline]
+    pub fn n100(mut self, n100: usize) -> Self {
+        self.n100.replace(n100);
+
+        self
+    }
+
+    /// Specify the amount of 50s of a play.
+    #[inline]
+    pub fn n50(mut self, n50: usize) -> Self {
+        self.n50.replace(n50);
+
+        self
+    }
+
+    /// Specify the amount of misses of a play.
+    #[inline]
+    pub fn misses(mut self, n_misses: usize) -> Self {
+        self.n_misses = n_misses;
+
+        self
+    }
+
+    /// Specify how judgements are distributed when they are generated from an accuracy,
+    /// see [`HitResultPriority`]. Be sure to set this before the accuracy!
+    #[inline]
+    pub fn hitresult_priority(mut self, priority: HitResultPriority) -> Self {
+        self.hitresult_priority = priority;
+
+        self
+    }
+
+    /// Generate the judgements with respect to the given accuracy between `0` and `100`.
+    ///
+    /// The accuracy is the one of osu!stable i.e. 320s and 300s are worth the same.
+    /// Given 200s, 100s, and 50s are kept, otherwise the judgements are generated from scratch.
+    ///
+    /// Since [`AlgorithmVersion::V2022`], heads and tails of hold notes are judged separately
+    /// so there is one more judgement per hold note.
+    ///
+    /// The judgements are generated when calculating so the order in which
+    /// the accuracy, `version`, `misses`, or `passed_objects` are set does not matter.
+    #[inline]
+    pub fn accuracy(mut self, acc: Float) -> Self {
+        self.set_accuracy(acc);
+
+        self
+    }
+
+    /// Same as [`ManiaPP::accuracy`] but on a mutable reference.
+    #[inline]
+    pub fn set_accuracy(&mut self, acc: Float) {
+        self.acc.replace(acc);
+    }
+
+    /// Generates the 320s, 300s, 200s, 100s, and 50s w.r.t. the given accuracy.
+    fn accuracy_judgements(&self, acc: Float) -> [usize; 5] {
+        let n_objects = self.n_judgements();
+        let misses = self.n_misses.min(n_objects);
+        let target_points = ((acc / 100.0) * n_objects as Float * 6.0).round() as usize;
+
+        let (n_greats, n200, n100, n50) = if self.n200.or(self.n100).or(self.n50).is_some() {
+            let n200 = self.n200.unwrap_or(0);
+            let n100 = self.n100.unwrap_or(0);
+            let n50 = self.n50.unwrap_or(0);
+
+            let placed_points = n200
+                .saturating_mul(4)
+                .saturating_add(n100.saturating_mul(2))
+                .saturating_add(n50);
+            let missing_objects = n_objects
+                .saturating_sub(n200)
+                .saturating_sub(n100)
+                .saturating_sub(n50)
+                .saturating_sub(misses);
+            let missing_points = target_points.saturating_sub(placed_points);
+
+            let n_greats = missing_objects.min(missing_points / 6);
+
+            let n50 = n50.saturating_add(missing_objects - n_greats);
+
+            (n_greats, n200, n100, n50)
+        } else {
+            let remaining = n_objects - misses;
+
+            // Every non-miss is worth at least one point; 300s add 5, 200s 3, and 100s 1
+            let delta = target_points.saturating_sub(remaining);
+
+            let mut n_greats = (delta / 5).min(remaining);
+            let (mut n200, mut n100) = match delta - 5 * n_greats {
+                0 => (0, 0),
+                1 => (0, 1),
+                2 => (0, 2),
+                3 => (1, 0),
+                _ => (1, 1),
+            };
+
+            if n_greats + n200 + n100 > remaining {
+                n200 = 0;
+                n100 = 0;
+            }
+
+            let mut n50 = remaining - n_greats - n200 - n100;
+
+            // One 300 and two 50s are worth as much as one 200 and two 100s
+            if self.hitresult_priority == HitResultPriority::BestCase {
+                let n = n_greats.min(n50 / 2);
+                n_greats -= n;
+                n200 += n;
+                n100 += 2 * n;
+                n50 -= 2 * n;
+            }
+
+            (n_greats, n200, n100, n50)
+        };
+
+        let (n320, n300) = self.split_greats(n_greats);
+
+        [n320, n300, n200, n100, n50]
+    }
+
+    /// The specified 320s, 300s, 200s, 100s, and 50s,
+    /// generated from the accuracy if one was given.
+    fn specified_judgements(&self) -> [Option<usize>; 5] {
+        match self.acc {
+            Some(acc) => {
+                let [n320, n300, n200, n100, n50] = self.accuracy_judgements(acc);
+
+                [Some(n320), Some(n300), Some(n200), Some(n100), Some(n50)]
+            }
+            None => [self.n320, self.n300, self.n200, self.n100, self.n50],
+        }
+    }
+
     /// Returns an object which contains the pp and stars.
     pub fn calculate(&self) -> PpResult {
-        let stars = self
-            .stars
-            .unwrap_or_else(|| stars(self.map, self.mods, self.passed_objects).stars());
+        let stars = self.stars.unwrap_or_else(|| {
+            stars_with_version(
+                &self.map,
+                self.mods,
+                self.passed_objects,
+                self.params,
+                self.version,
+            )
+            .stars()
+        });
+
+        let version = self.version.resolve(GameMode::MNA);
+        let n_hold_notes = self.n_hold_notes();
+
+        if version == AlgorithmVersion::V2022 {
+            return self.calculate_v2022(stars, n_hold_notes);
+        }
 
         let ez = self.mods.ez();
         let nf = self.mods.nf();
         let ht = self.mods.ht();
 
-        let mut scaled_score = self.score.map_or(1_000_000.0, |score| {
-            score / 0.5_f32.powi(ez as i32 + nf as i32 + ht as i32)
+        let score = self.score.or_else(|| self.judgement_score());
+
+        let mut scaled_score = score.map_or(1_000_000.0, |score| {
+            score / (0.5 as Float).powi(ez as i32 + nf as i32 + ht as i32)
         });
 
         if let Some(passed_objects) = self.passed_objects {
             let percent_passed =
-                passed_objects as f32 / (self.map.n_circles + self.map.n_sliders) as f32;
+                passed_objects as Float / (self.map.n_circles + self.map.n_sliders) as Float;
 
             scaled_score /= percent_passed;
         }
 
-        let mut od = 34.0 + 3.0 * (10.0 - self.map.od).max(0.0).min(10.0);
-        let clock_rate = self.mods.speed();
+        let map_od = self.params.od.unwrap_or(self.map.od);
+        let mut od = 34.0 + 3.0 * (10.0 - map_od).max(0.0).min(10.0);
+        let clock_rate = self.params.clock_rate_with(self.mods);
 
         let mut multiplier = 0.8;
 
@@ -121,7 +438,10 @@ impl<'m> ManiaPP<'m> {
 
         if ez {
             multiplier *= 0.5;
-            od *= 1.4;
+
+            if self.params.od.is_none() {
+                od *= 1.4;
+            }
         }
 
         let hit_window = ((od * clock_rate).floor() / clock_rate).ceil();
@@ -135,20 +455,270 @@ impl<'m> ManiaPP<'m> {
             mode: 3,
             mods: self.mods,
             pp,
-            raw: PpRaw::new(None, None, Some(strain_value), Some(acc_value), pp),
-            attributes: StarResult::Mania(DifficultyAttributes { stars }),
+            raw: PpRaw::new(None, None, Some(strain_value), Some(acc_value), None, pp),
+            attributes: StarResult::Mania(DifficultyAttributes {
+                stars,
+                n_hold_notes,
+            }),
+            version,
+            grade: self.grade(),
+        }
+    }
+
+    fn calculate_v2022(&self, stars: Float, n_hold_notes: usize) -> PpResult {
+        let judgements = self.judgements();
+        let total_hits = judgements.total_hits() as Float;
+
+        let mut multiplier = 8.0;
+
+        if self.mods.nf() {
+            multiplier *= 0.75;
+        }
+
+        if self.mods.ez() {
+            multiplier *= 0.5;
+        }
+
+        let difficulty_value = (stars - 0.15).max(0.05).powf(2.2)
+            * (5.0 * judgements.custom_accuracy() - 4.0).max(0.0)
+            * (1.0 + 0.1 * (total_hits / 1500.0).min(1.0));
+
+        let pp = difficulty_value * multiplier;
+
+        PpResult {
+            mode: 3,
+            mods: self.mods,
+            pp,
+            raw: PpRaw::new(None, None, Some(difficulty_value), None, None, pp),
+            attributes: StarResult::Mania(DifficultyAttributes {
+                stars,
+                n_hold_notes,
+            }),
+            version: AlgorithmVersion::V2022,
+            grade: self.grade(),
+        }
+    }
+
+    /// The given judgements; unspecified ones are assumed to be 320s.
+    fn judgements(&self) -> ManiaJudgements {
+        let n_objects = self.n_judgements();
+        let [n320, n300, n200, n100, n50] = self.specified_judgements();
+
+        let n200 = n200.unwrap_or(0);
+        let n100 = n100.unwrap_or(0);
+        let n50 = n50.unwrap_or(0);
+        let n_misses = self.n_misses;
+
+        let remaining = n_objects
+            .saturating_sub(n320.unwrap_or(0))
+            .saturating_sub(n300.unwrap_or(0))
+            .saturating_sub(n200)
+            .saturating_sub(n100)
+            .saturating_sub(n50)
+            .saturating_sub(n_misses);
+
+        let (n320, n300) = match (n320, n300) {
+            (None, n300) => (remaining, n300.unwrap_or(0)),
+            (Some(n320), None) => (n320, remaining),
+            (Some(n320), Some(n300)) => (n320.saturating_add(remaining), n300),
+        };
+
+        ManiaJudgements {
+            n320,
+            n300,
+            n200,
+            n100,
+            n50,
+            n_misses,
+        }
+    }
+
+    /// ScoreV1 of the specified judgements, `None` if there are none.
+    ///
+    /// Hold note tails are not judged separately in this version so they are considered 320s.
+    fn judgement_score(&self) -> Option<Float> {
+        let specified = self.specified_judgements().iter().any(Option::is_some);
+
+        if !specified && self.n_misses == 0 {
+            return None;
+        }
+
+        let judgements = self.judgements();
+
+        let score = ManiaScore::converted(self.original, &self.map, self.mods, self.passed_objects)
+            .n320(judgements.n320.saturating_add(self.n_hold_notes()))
+            .n300(judgements.n300)
+            .n200(judgements.n200)
+            .n100(judgements.n100)
+            .n50(judgements.n50)
+            .misses(judgements.n_misses)
+            .calculate();
+
+        Some(score as Float)
+    }
+
+    fn grade(&self) -> Grade {
+        let judgements = self.judgements();
+
+        let hits = HitResults {
+            n_geki: judgements.n320,
+            n300: judgements.n300,
+            n_katu: judgements.n200,
+            n100: judgements.n100,
+            n50: judgements.n50,
+            n_misses: judgements.n_misses,
+        };
+
+        Grade::new(GameMode::MNA, self.mods, &hits)
+    }
+
+    /// Distributes the 320s and 300s w.r.t. the specified values, preferring 320s.
+    fn split_greats(&self, n_greats: usize) -> (usize, usize) {
+        match (self.n320, self.n300) {
+            (Some(n320), _) => {
+                let n320 = n320.min(n_greats);
+
+                (n320, n_greats - n320)
+            }
+            (None, Some(n300)) => {
+                let n300 = n300.min(n_greats);
+
+                (n_greats - n300, n300)
+            }
+            (None, None) => (n_greats, 0),
         }
     }
 
+    #[inline]
+    fn n_objects(&self) -> usize {
+        self.passed_objects.unwrap_or(self.map.hit_objects.len())
+    }
+
+    fn n_hold_notes(&self) -> usize {
+        self.map
+            .hit_objects
+            .iter()
+            .take(self.n_objects())
+            .filter(|h| matches!(h.kind, HitObjectKind::Hold { .. }))
+            .count()
+    }
+
+    /// Objects plus, since [`AlgorithmVersion::V2022`], hold note tails.
+    fn n_judgements(&self) -> usize {
+        if self.version.resolve(GameMode::MNA) >= AlgorithmVersion::V2022 {
+            self.n_objects() + self.n_hold_notes()
+        } else {
+            self.n_objects()
+        }
+    }
+
+    /// Same as `calculate` but returns an error if the map has no hit objects
+    /// or can not be converted to osu!mania, see [`Beatmap::check_calculable`],
+    /// or if the difficulty parameters are invalid, see [`DifficultyParams::validate`](crate::DifficultyParams::validate).
+    #[inline]
+    pub fn try_calculate(&self) -> CalcResult<PpResult> {
+        self.original.check_calculable(GameMode::MNA)?;
+        self.params.validate()?;
+
+        Ok(self.calculate())
+    }
+
     #[inline]
     pub async fn calculate_async(&self) -> PpResult {
         self.calculate()
     }
 
-    fn compute_strain(&self, score: f32, stars: f32) -> f32 {
+    /// Calculate the pp for each of the given accuracies between `0` and `100`.
+    ///
+    /// The stars are calculated only once and the judgements are generated
+    /// from the previously given input for every accuracy so the order does not matter.
+    /// Only accuracy based versions are affected, see [`ManiaPP::version`].
+    pub fn calculate_accuracies(&mut self, accs: &[Float]) -> Vec<PpResult> {
+        accs.iter()
+            .map(|&acc| self.calculate_accuracy(acc))
+            .collect()
+    }
+
+    /// The minimum accuracy between `0` and `100` that is required to reach the given pp
+    /// w.r.t. all previously specified values such as mods or misses.
+    ///
+    /// Returns `None` if the pp can not be reached, not even with 100% accuracy.
+    /// The stars are calculated only once.
+    ///
+    /// Only accuracy based versions depend on the accuracy, see [`ManiaPP::score_for_pp`]
+    /// for score based versions.
+    pub fn accuracy_for_pp(&mut self, pp: Float) -> Option<Float> {
+        crate::solver::min_accuracy_for_pp(pp, |acc| self.calculate_accuracy(acc).pp)
+    }
+
+    /// The minimum score that is required to reach the given pp
+    /// w.r.t. all previously specified values such as mods or passed objects.
+    ///
+    /// Returns `None` if the pp can not be reached, not even with the max score.
+    /// The stars are calculated only once.
+    ///
+    /// Only score based versions depend on the score, see [`ManiaPP::accuracy_for_pp`]
+    /// for accuracy based versions.
+    pub fn score_for_pp(&mut self, pp: Float) -> Option<u32> {
+        let input = self.score;
+        let mods = self.mods;
+        let max_score = 1_000_000.0
+            * (0.5 as Float).powi(mods.ez() as i32 + mods.nf() as i32 + mods.ht() as i32);
+
+        let score = crate::solver::min_value_for_pp(pp, 0.0, max_score, 1.0, |score| {
+            self.score.replace(score.ceil());
+            let result = self.calculate();
+            self.stars.get_or_insert(result.stars());
+
+            result.pp
+        });
+
+        self.score = input;
+
+        score.map(|score| score.ceil() as u32)
+    }
+
+    /// Calculate the pp of the play and of the same play with a full combo.
+    ///
+    /// For the full combo, misses become 320s. Score based versions don't depend
+    /// on misses so the given score is used for both results.
+    /// The stars are calculated only once.
+    pub fn calculate_if_fc(&mut self) -> IfFcResult {
+        let current = self.calculate();
+        self.stars.get_or_insert(current.stars());
+
+        // Calculate on a copy so that none of the hit results are overwritten
+        let mut fc = self.clone();
+        let judgements = fc.judgements();
+
+        fc.n320 = Some(judgements.n320.saturating_add(judgements.n_misses));
+        fc.n300 = Some(judgements.n300);
+        fc.n200 = Some(judgements.n200);
+        fc.n100 = Some(judgements.n100);
+        fc.n50 = Some(judgements.n50);
+        fc.n_misses = 0;
+        fc.acc = None;
+
+        let if_fc = fc.calculate();
+
+        IfFcResult { current, if_fc }
+    }
+
+    fn calculate_accuracy(&mut self, acc: Float) -> PpResult {
+        let input = self.acc.replace(acc);
+
+        let result = self.calculate();
+        self.stars.get_or_insert(result.stars());
+
+        self.acc = input;
+
+        result
+    }
+
+    fn compute_strain(&self, score: Float, stars: Float) -> Float {
         let mut strain_value = (5.0 * (stars / 0.2).max(1.0) - 4.0).powf(2.2) / 135.0;
 
-        strain_value *= 1.0 + 0.1 * (self.map.hit_objects.len() as f32 / 1500.0).min(1.0);
+        strain_value *= 1.0 + 0.1 * (self.map.hit_objects.len() as Float / 1500.0).min(1.0);
 
         if score <= 500_000.0 {
             strain_value = 0.0;
@@ -168,34 +738,70 @@ impl<'m> ManiaPP<'m> {
     }
 
     #[inline]
-    fn compute_accuracy_value(&self, score: f32, strain: f32, hit_window: f32) -> f32 {
+    fn compute_accuracy_value(&self, score: Float, strain: Float, hit_window: Float) -> Float {
         (0.2 - (hit_window - 34.0) * 0.006667).max(0.0)
             * strain
             * ((score - 960_000.0).max(0.0) / 40_000.0).powf(1.1)
     }
 }
 
+#[derive(Debug, PartialEq)]
+struct ManiaJudgements {
+    n320: usize,
+    n300: usize,
+    n200: usize,
+    n100: usize,
+    n50: usize,
+    n_misses: usize,
+}
+
+impl ManiaJudgements {
+    #[inline]
+    fn total_hits(&self) -> usize {
+        self.n320
+            .saturating_add(self.n300)
+            .saturating_add(self.n200)
+            .saturating_add(self.n100)
+            .saturating_add(self.n50)
+            .saturating_add(self.n_misses)
+    }
+
+    /// Accuracy in which 320s are worth more than 300s.
+    fn custom_accuracy(&self) -> Float {
+        let total_hits = self.total_hits();
+
+        if total_hits == 0 {
+            return 0.0;
+        }
+
+        let numerator =
+            self.n320 * 32 + self.n300 * 30 + self.n200 * 20 + self.n100 * 10 + self.n50 * 5;
+
+        numerator as Float / (total_hits * 32) as Float
+    }
+}
+
 pub trait ManiaAttributeProvider {
-    fn attributes(self) -> Option<f32>;
+    fn attributes(self) -> Option<Float>;
 }
 
-impl ManiaAttributeProvider for f32 {
+impl ManiaAttributeProvider for Float {
     #[inline]
-    fn attributes(self) -> Option<f32> {
+    fn attributes(self) -> Option<Float> {
         Some(self)
     }
 }
 
 impl ManiaAttributeProvider for DifficultyAttributes {
     #[inline]
-    fn attributes(self) -> Option<f32> {
+    fn attributes(self) -> Option<Float> {
         Some(self.stars)
     }
 }
 
 impl ManiaAttributeProvider for StarResult {
     #[inline]
-    fn attributes(self) -> Option<f32> {
+    fn attributes(self) -> Option<Float> {
         #[allow(irrefutable_let_patterns)]
         if let Self::Mania(attributes) = self {
             Some(attributes.stars)
@@ -207,7 +813,175 @@ impl ManiaAttributeProvider for StarResult {
 
 impl ManiaAttributeProvider for PpResult {
     #[inline]
-    fn attributes(self) -> Option<f32> {
+    fn attributes(self) -> Option<Float> {
         self.attributes.attributes()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse::{HitObject, Pos2},
+        Beatmap,
+    };
+
+    #[test]
+    fn mania_saturating_judgements() {
+        let map = Beatmap::default();
+        let max = usize::MAX;
+
+        let calculator = ManiaPP::new(&map)
+            .passed_objects(1234)
+            .n320(max)
+            .n300(max)
+            .n100(max)
+            .misses(max)
+            .accuracy(95.0);
+
+        assert_eq!(calculator.judgements().total_hits(), max);
+
+        let mut calculator = ManiaPP::new(&map).passed_objects(1234).n200(max).n50(max);
+        calculator.set_accuracy(95.0);
+        assert_eq!(calculator.judgements().n_misses, 0);
+    }
+
+    #[test]
+    fn mania_accuracy_judgements() {
+        let map = Beatmap::default();
+
+        let total_objects = 1234;
+        let target_acc = 96.83;
+
+        let calculator = ManiaPP::new(&map)
+            .passed_objects(total_objects)
+            .misses(5)
+            .accuracy(target_acc);
+
+        let judgements = calculator.judgements();
+
+        assert_eq!(judgements.total_hits(), total_objects);
+        assert_eq!(judgements.n_misses, 5);
+
+        let numerator = 6 * (judgements.n320 + judgements.n300)
+            + 4 * judgements.n200
+            + 2 * judgements.n100
+            + judgements.n50;
+        let acc = 100.0 * numerator as Float / (6 * total_objects) as Float;
+
+        assert!(
+            (target_acc - acc).abs() < 0.1,
+            "Expected: {} | Actual: {}",
+            target_acc,
+            acc
+        );
+    }
+
+    #[test]
+    fn mania_hold_note_tails_are_judged() {
+        let hold = |start_time: Float| HitObject {
+            pos: Pos2 { x: 64.0, y: 192.0 },
+            start_time,
+            kind: HitObjectKind::Hold {
+                end_time: start_time + 500.0,
+            },
+            sound: 0,
+        };
+
+        let map = Beatmap {
+            mode: GameMode::MNA,
+            cs: 4.0,
+            hit_objects: vec![hold(0.0), hold(1000.0), hold(2000.0)],
+            ..Default::default()
+        };
+
+        let calculator = ManiaPP::new(&map)
+            .version(AlgorithmVersion::V2022)
+            .accuracy(100.0);
+
+        assert_eq!(calculator.judgements().total_hits(), 6);
+
+        let result = calculator.calculate();
+
+        assert_eq!(result.version, AlgorithmVersion::V2022);
+        assert!(matches!(
+            result.attributes,
+            StarResult::Mania(DifficultyAttributes {
+                n_hold_notes: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn mania_accuracy_order_independent() {
+        let hold = |start_time: Float| HitObject {
+            pos: Pos2 { x: 64.0, y: 192.0 },
+            start_time,
+            kind: HitObjectKind::Hold {
+                end_time: start_time + 500.0,
+            },
+            sound: 0,
+        };
+
+        let map = Beatmap {
+            mode: GameMode::MNA,
+            cs: 4.0,
+            hit_objects: (0..20).map(|i| hold(i as Float * 1000.0)).collect(),
+            ..Default::default()
+        };
+
+        let acc_first = ManiaPP::new(&map)
+            .accuracy(90.0)
+            .misses(2)
+            .version(AlgorithmVersion::V2022);
+
+        let acc_last = ManiaPP::new(&map)
+            .version(AlgorithmVersion::V2022)
+            .misses(2)
+            .accuracy(90.0);
+
+        let judgements = acc_first.judgements();
+
+        assert_eq!(judgements.total_hits(), 40);
+        assert_eq!(judgements.n_misses, 2);
+        assert_eq!(judgements, acc_last.judgements());
+        assert_eq!(acc_first.calculate().pp(), acc_last.calculate().pp());
+    }
+
+    #[test]
+    fn mania_hitresult_priority() {
+        let map = Beatmap::default();
+
+        let calculator = |priority| {
+            ManiaPP::new(&map)
+                .passed_objects(1234)
+                .hitresult_priority(priority)
+                .accuracy(93.21)
+                .judgements()
+        };
+
+        let best = calculator(HitResultPriority::BestCase);
+        let worst = calculator(HitResultPriority::WorstCase);
+
+        let points = |j: &ManiaJudgements| 6 * (j.n320 + j.n300) + 4 * j.n200 + 2 * j.n100 + j.n50;
+
+        assert_eq!(points(&best), points(&worst));
+        assert_eq!(best.total_hits(), worst.total_hits());
+        assert!(best.n50 < worst.n50);
+    }
+
+    #[test]
+    fn mania_score_for_pp() {
+        let map = Beatmap::default();
+
+        let mut calculator = ManiaPP::new(&map).attributes(4.5).mods(2);
+
+        let target = calculator.clone().score(400_000).calculate().pp();
+        let score = calculator.score_for_pp(target).unwrap();
+
+        assert!(score <= 400_000, "{}", score);
+        assert!(calculator.clone().score(score).calculate().pp() >= target);
+        assert_eq!(calculator.score_for_pp(1000.0), None);
+    }
+}
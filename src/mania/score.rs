@@ -0,0 +1,357 @@
+use crate::{
+    parse::HitObjectKind,
+    score::{self, ScoreVersion, SCORE_V2_MAX},
+    Beatmap, Float, GameMode, Mods,
+};
+
+use std::{borrow::Cow, cmp::Reverse};
+
+/// Values of 320s, 300s, 200s, 100s, 50s, and misses.
+const HIT_VALUES: [Float; 6] = [320.0, 300.0, 200.0, 100.0, 50.0, 0.0];
+
+/// Bonus values of 320s, 300s, 200s, 100s, 50s, and misses.
+const HIT_BONUS_VALUES: [Float; 6] = [32.0, 32.0, 16.0, 8.0, 4.0, 0.0];
+
+/// How much the bonus of each judgement changes; misses reset it.
+const HIT_BONUS: [Float; 6] = [2.0, 1.0, -8.0, -24.0, -44.0, -100.0];
+
+const MAX_BONUS: Float = 100.0;
+
+/// Score calculator for osu!mania maps.
+///
+/// Each judgement's ScoreV1 bonus depends on the previous judgements so they are
+/// assumed to be spread evenly across the map. Hold notes are judged on both their
+/// head and their tail, unspecified judgements are considered to be 320s.
+///
+/// osu!standard maps are converted first, see [`Beatmap::to_mania`].
+#[derive(Clone, Debug)]
+pub struct ManiaScore<'m> {
+    original: &'m Beatmap,
+    map: Cow<'m, Beatmap>,
+    mods: u32,
+    version: ScoreVersion,
+    passed_objects: Option<usize>,
+
+    n320: Option<usize>,
+    n300: Option<usize>,
+    n200: Option<usize>,
+    n100: Option<usize>,
+    n50: Option<usize>,
+    n_misses: usize,
+}
+
+impl<'m> ManiaScore<'m> {
+    #[inline]
+    pub fn new(map: &'m Beatmap) -> Self {
+        Self {
+            original: map,
+            map: map.to_mania(0),
+            mods: 0,
+            version: ScoreVersion::default(),
+            passed_objects: None,
+
+            n320: None,
+            n300: None,
+            n200: None,
+            n100: None,
+            n50: None,
+            n_misses: 0,
+        }
+    }
+
+    /// Calculator for a map that was already converted with the given mods.
+    #[inline]
+    pub(crate) fn converted(
+        original: &'m Beatmap,
+        map: &'m Beatmap,
+        mods: u32,
+        passed_objects: Option<usize>,
+    ) -> Self {
+        Self {
+            original,
+            map: Cow::Borrowed(map),
+            mods,
+            version: ScoreVersion::V1,
+            passed_objects,
+
+            n320: None,
+            n300: None,
+            n200: None,
+            n100: None,
+            n50: None,
+            n_misses: 0,
+        }
+    }
+
+    /// [`Mods`](crate::Mods) interface.
+    ///
+    /// Key mods change the amount of objects of converted maps.
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods.for_mode(GameMode::MNA);
+
+        if self.original.mode != GameMode::MNA {
+            self.map = self.original.to_mania(self.mods);
+        }
+
+        self
+    }
+
+    /// Specify the scoring system, defaults to [`ScoreVersion::V1`].
+    #[inline]
+    pub fn version(mut self, version: ScoreVersion) -> Self {
+        self.version = version;
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects.replace(passed_objects);
+
+        self
+    }
+
+    /// Specify the amount of 320s of a play.
+    #[inline]
+    pub fn n320(mut self, n320: usize) -> Self {
+        self.n320.replace(n320);
+
+        self
+    }
+
+    /// Specify the amount of 300s of a play.
+    #[inline]
+    pub fn n300(mut self, n300: usize) -> Self {
+        self.n300.replace(n300);
+
+        self
+    }
+
+    /// Specify the amount of 200s of a play.
+    #[inline]
+    pub fn n200(mut self, n200: usize) -> Self {
+        self.n200.replace(n200);
+
+        self
+    }
+
+    /// Specify the amount of 100s of a play.
+    #[inline]
+    pub fn n100(mut self, n100: usize) -> Self {
+        self.n100.replace(n100);
+
+        self
+    }
+
+    /// Specify the amount of 50s of a play.
+    #[inline]
+    pub fn n50(mut self, n50: usize) -> Self {
+        self.n50.replace(n50);
+
+        self
+    }
+
+    /// Specify the amount of misses of a play.
+    #[inline]
+    pub fn misses(mut self, n_misses: usize) -> Self {
+        self.n_misses = n_misses;
+
+        self
+    }
+
+    /// Calculate the score of a play with only 320s.
+    #[inline]
+    pub fn max_score(&self) -> u64 {
+        let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+
+        self.score([self.n_judgements(n_objects), 0, 0, 0, 0, 0])
+    }
+
+    /// Calculate the score of the specified play.
+    #[inline]
+    pub fn calculate(&self) -> u64 {
+        self.score(self.judgements())
+    }
+
+    fn score(&self, counts: [usize; 6]) -> u64 {
+        let n_judged: usize = counts.iter().sum();
+        let n_total = self.n_judgements(self.map.hit_objects.len()).max(1);
+        let multiplier = self.mods.score_multiplier(GameMode::MNA, self.version);
+
+        let score = match self.version {
+            ScoreVersion::V1 => {
+                let note_value = SCORE_V2_MAX * multiplier * 0.5 / n_total as Float;
+                let mut bonus = MAX_BONUS;
+
+                // The note value is applied at the end to not accumulate rounding errors
+                let mut points = 0.0;
+
+                for idx in spread_evenly(&counts) {
+                    bonus = (bonus + HIT_BONUS[idx]).clamp(0.0, MAX_BONUS);
+                    points += HIT_VALUES[idx] + HIT_BONUS_VALUES[idx] * bonus.sqrt();
+                }
+
+                (points * note_value / 320.0).round()
+            }
+            ScoreVersion::V2 => {
+                let n_misses = counts[5];
+                let combo = (n_judged - n_misses) / (n_misses + 1);
+                let chains = score::combo_chains(combo, n_judged, n_misses);
+                let combo_ratio = score::score_v2_combo_ratio(&chains, n_total);
+
+                let acc = if n_judged == 0 {
+                    0.0
+                } else {
+                    let points: Float = counts
+                        .iter()
+                        .zip(HIT_BONUS_VALUES.iter())
+                        .map(|(&count, value)| count as Float * value)
+                        .sum();
+
+                    points / (32 * n_judged) as Float
+                };
+
+                let progress = n_judged as Float / n_total as Float;
+
+                score::score_v2(combo_ratio, acc, progress) * multiplier
+            }
+        };
+
+        score as u64
+    }
+
+    /// The amount of 320s, 300s, 200s, 100s, 50s, and misses.
+    fn judgements(&self) -> [usize; 6] {
+        let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+        let n_judgements = self.n_judgements(n_objects);
+
+        // Counts beyond the amount of judgements are cut off, starting with the 320s
+        let mut remaining = n_judgements;
+
+        let n_misses = take(&mut remaining, self.n_misses);
+        let n50 = take(&mut remaining, self.n50.unwrap_or(0));
+        let n100 = take(&mut remaining, self.n100.unwrap_or(0));
+        let n200 = take(&mut remaining, self.n200.unwrap_or(0));
+        let n300 = take(&mut remaining, self.n300.unwrap_or(0));
+        let n320 = self
+            .n320
+            .map_or(remaining, |n320| take(&mut remaining, n320));
+
+        [n320, n300, n200, n100, n50, n_misses]
+    }
+
+    /// Objects plus hold note tails within the first `n_objects` objects.
+    fn n_judgements(&self, n_objects: usize) -> usize {
+        let hit_objects = self.map.hit_objects.iter().take(n_objects);
+        let n_hold_notes = hit_objects
+            .filter(|h| matches!(h.kind, HitObjectKind::Hold { .. }))
+            .count();
+
+        n_objects.min(self.map.hit_objects.len()) + n_hold_notes
+    }
+}
+
+/// Takes at most the remaining amount of the count.
+#[inline]
+fn take(remaining: &mut usize, count: usize) -> usize {
+    let count = count.min(*remaining);
+    *remaining -= count;
+
+    count
+}
+
+/// Indices of the counts in an order that spreads each of them evenly.
+fn spread_evenly(counts: &[usize; 6]) -> impl Iterator<Item = usize> {
+    let total: usize = counts.iter().sum();
+    let counts = *counts;
+    let mut placed = [0; 6];
+
+    (1..=total).map(move |i| {
+        // The count that is furthest behind its even share
+        let idx = (0..counts.len())
+            .filter(|&idx| placed[idx] < counts[idx])
+            .max_by_key(|&idx| {
+                let behind = (counts[idx] * i) as isize - (placed[idx] * total) as isize;
+
+                (behind, Reverse(idx))
+            })
+            .unwrap_or(0);
+
+        placed[idx] += 1;
+
+        idx
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{HitObject, Pos2};
+
+    #[test]
+    fn mania_score_spread_evenly() {
+        let order: Vec<_> = spread_evenly(&[2, 0, 0, 0, 0, 2]).collect();
+        assert_eq!(order, vec![0, 5, 0, 5]);
+
+        let order: Vec<_> = spread_evenly(&[0, 3, 0, 0, 0, 0]).collect();
+        assert_eq!(order, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn mania_score_judgements() {
+        let hit_objects = (0..100)
+            .map(|i| HitObject {
+                pos: Pos2::default(),
+                start_time: i as Float * 100.0,
+                kind: HitObjectKind::Circle,
+                sound: 0,
+            })
+            .collect();
+
+        let map = Beatmap {
+            mode: GameMode::MNA,
+            hit_objects,
+            ..Default::default()
+        };
+
+        let mut score = ManiaScore::new(&map);
+        assert_eq!(score.calculate(), 1_000_000);
+
+        score.n_misses = 1;
+        assert!(score.calculate() < 1_000_000);
+
+        score.mods = u32::EZ;
+        score.n_misses = 0;
+        assert_eq!(score.calculate(), 500_000);
+    }
+
+    #[test]
+    fn mania_score_saturating_judgements() {
+        let hit_objects = (0..100)
+            .map(|i| HitObject {
+                pos: Pos2::default(),
+                start_time: i as Float * 100.0,
+                kind: HitObjectKind::Circle,
+                sound: 0,
+            })
+            .collect();
+
+        let map = Beatmap {
+            mode: GameMode::MNA,
+            hit_objects,
+            ..Default::default()
+        };
+
+        // Counts are cut off at the amount of judgements, starting with the 320s
+        let score = ManiaScore::new(&map)
+            .n320(usize::MAX)
+            .n300(60)
+            .n100(usize::MAX)
+            .misses(30);
+
+        assert_eq!(score.judgements(), [0, 0, 0, 70, 0, 30]);
+        assert!(score.calculate() < ManiaScore::new(&map).calculate());
+    }
+}
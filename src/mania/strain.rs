@@ -1,31 +1,38 @@
 use super::DifficultyHitObject;
+use crate::Float;
 
 use std::cmp::Ordering;
 
 pub(crate) struct Strain {
-    current_strain: f32,
-    current_section_peak: f32,
+    current_strain: Float,
+    current_section_peak: Float,
 
-    individual_strain: f32,
-    overall_strain: f32,
+    individual_strain: Float,
+    overall_strain: Float,
 
-    hold_end_times: Vec<f32>,
-    individual_strains: Vec<f32>,
-    pub(crate) strain_peaks: Vec<f32>,
+    hold_end_times: Vec<Float>,
+    individual_strains: Vec<Float>,
+    pub(crate) strain_peaks: Vec<Float>,
 
-    prev_time: Option<f32>,
+    prev_time: Option<Float>,
+
+    /// Whether close releases of hold notes are considered, see [`AlgorithmVersion::V2022`](crate::AlgorithmVersion::V2022).
+    release_aware: bool,
+    start_times: Vec<Float>,
 }
 
-const INDIVISUAL_DECAY_BASE: f32 = 0.125;
-const OVERALL_DECAY_BASE: f32 = 0.3;
-const STRAIN_DECAY_BASE: f32 = 1.0;
+const INDIVISUAL_DECAY_BASE: Float = 0.125;
+const OVERALL_DECAY_BASE: Float = 0.3;
+const STRAIN_DECAY_BASE: Float = 1.0;
+
+const RELEASE_THRESHOLD: Float = 24.0;
 
-const SKILL_MULTIPLIER: f32 = 1.0;
-const DECAY_WEIGHT: f32 = 0.9;
+const SKILL_MULTIPLIER: Float = 1.0;
+const DECAY_WEIGHT: Float = 0.9;
 
 impl Strain {
     #[inline]
-    pub(crate) fn new(column_count: u8) -> Self {
+    pub(crate) fn new(column_count: u8, release_aware: bool) -> Self {
         Self {
             current_strain: 1.0,
             current_section_peak: 1.0,
@@ -38,7 +45,32 @@ impl Strain {
             strain_peaks: Vec::with_capacity(128),
 
             prev_time: None,
+
+            release_aware,
+            start_times: vec![0.0; column_count as usize],
+        }
+    }
+
+    /// Reset to the state of a new strain while keeping the allocations.
+    pub(crate) fn reset(&mut self, column_count: u8, release_aware: bool) {
+        self.current_strain = 1.0;
+        self.current_section_peak = 1.0;
+
+        self.individual_strain = 0.0;
+        self.overall_strain = 1.0;
+
+        for column_values in [
+            &mut self.hold_end_times,
+            &mut self.individual_strains,
+            &mut self.start_times,
+        ] {
+            column_values.clear();
+            column_values.resize(column_count as usize, 0.0);
         }
+
+        self.strain_peaks.clear();
+        self.prev_time = None;
+        self.release_aware = release_aware;
     }
 
     #[inline]
@@ -47,30 +79,36 @@ impl Strain {
     }
 
     #[inline]
-    pub(crate) fn start_new_section_from(&mut self, time: f32) {
+    pub(crate) fn start_new_section_from(&mut self, time: Float) {
         self.current_section_peak = self.peak_strain(time - self.prev_time.unwrap());
     }
 
     #[inline]
-    fn peak_strain(&self, delta_time: f32) -> f32 {
+    fn peak_strain(&self, delta_time: Float) -> Float {
         apply_decay(self.individual_strain, delta_time, INDIVISUAL_DECAY_BASE)
             + apply_decay(self.overall_strain, delta_time, OVERALL_DECAY_BASE)
     }
 
     #[inline]
-    fn strain_decay(&self, ms: f32) -> f32 {
+    fn strain_decay(&self, ms: Float) -> Float {
         STRAIN_DECAY_BASE.powf(ms / 1000.0)
     }
 
     #[inline]
     pub(crate) fn process(&mut self, current: &DifficultyHitObject) {
         self.current_strain *= self.strain_decay(current.delta);
-        self.current_strain += self.strain_value_of(&current) * SKILL_MULTIPLIER;
+        let strain = if self.release_aware {
+            self.release_aware_strain_value_of(current)
+        } else {
+            self.strain_value_of(current)
+        };
+
+        self.current_strain += strain * SKILL_MULTIPLIER;
         self.current_section_peak = self.current_strain.max(self.current_section_peak);
         self.prev_time.replace(current.start_time);
     }
 
-    fn strain_value_of(&mut self, current: &DifficultyHitObject) -> f32 {
+    fn strain_value_of(&mut self, current: &DifficultyHitObject) -> Float {
         let end_time = current.base.end_time();
 
         let mut hold_factor = 1.0;
@@ -106,8 +144,57 @@ impl Strain {
         self.individual_strain + self.overall_strain - self.current_strain
     }
 
+    fn release_aware_strain_value_of(&mut self, current: &DifficultyHitObject) -> Float {
+        let start_time = current.start_time;
+        let end_time = current.end_time;
+        let column = current.column;
+
+        let mut is_overlapping = false;
+        let mut closest_end_time = (end_time - start_time).abs();
+        let mut hold_factor = 1.0;
+        let mut hold_addition = 0.0;
+
+        for &hold_end_time in self.hold_end_times.iter() {
+            // Overlapped by the body of a previous hold note
+            is_overlapping |= hold_end_time > start_time + 1.0 && end_time > hold_end_time + 1.0;
+
+            if hold_end_time > end_time + 1.0 {
+                hold_factor = 1.25;
+            }
+
+            closest_end_time = closest_end_time.min((end_time - hold_end_time).abs());
+        }
+
+        // Releasing multiple notes at once is as easy as releasing one
+        if is_overlapping {
+            hold_addition = 1.0 / (1.0 + (0.5 * (RELEASE_THRESHOLD - closest_end_time)).exp());
+        }
+
+        self.individual_strains[column] = apply_decay(
+            self.individual_strains[column],
+            start_time - self.start_times[column],
+            INDIVISUAL_DECAY_BASE,
+        );
+        self.individual_strains[column] += 2.0 * hold_factor;
+
+        // Notes within a chord use the hardest column
+        self.individual_strain = if current.delta <= 1.0 {
+            self.individual_strain.max(self.individual_strains[column])
+        } else {
+            self.individual_strains[column]
+        };
+
+        self.overall_strain = apply_decay(self.overall_strain, current.delta, OVERALL_DECAY_BASE)
+            + (1.0 + hold_addition) * hold_factor;
+
+        self.start_times[column] = start_time;
+        self.hold_end_times[column] = end_time;
+
+        self.individual_strain + self.overall_strain - self.current_strain
+    }
+
     #[inline]
-    pub(crate) fn difficulty_value(&mut self) -> f32 {
+    pub(crate) fn difficulty_value(&mut self) -> Float {
         let mut difficulty = 0.0;
         let mut weight = 1.0;
 
@@ -124,6 +211,6 @@ impl Strain {
 }
 
 #[inline]
-fn apply_decay(value: f32, delta_time: f32, decay_base: f32) -> f32 {
+fn apply_decay(value: Float, delta_time: Float, decay_base: Float) -> Float {
     value * decay_base.powf(delta_time / 1000.0)
 }
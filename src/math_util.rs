@@ -1,3 +1,6 @@
+#[cfg(feature = "osu")]
+use crate::Float;
+
 #[cfg(any(
     feature = "fruits",
     all(feature = "osu", not(feature = "no_sliders_no_leniency"))
@@ -9,10 +12,10 @@ pub(crate) use fruits_osu::*;
     all(feature = "osu", not(feature = "no_sliders_no_leniency"))
 ))]
 mod fruits_osu {
-    use crate::parse::Pos2;
+    use crate::{parse::Pos2, Float};
 
     #[inline]
-    pub(crate) fn cpn(mut p: i32, n: i32) -> f32 {
+    pub(crate) fn cpn(mut p: i32, n: i32) -> Float {
         if p < 0 || p > n {
             return 0.0;
         }
@@ -22,16 +25,16 @@ mod fruits_osu {
         let mut out = 1.0;
 
         for i in 1..=p {
-            out *= (diff + i) as f32 / i as f32;
+            out *= (diff + i) as Float / i as Float;
         }
 
         out
     }
 
-    pub(crate) fn point_at_distance(points: &[Pos2], dist: f32) -> Pos2 {
+    pub(crate) fn point_at_distance(points: &[Pos2], dist: Float) -> Pos2 {
         if points.len() < 2 {
             return Pos2::zero();
-        } else if dist.abs() <= f32::EPSILON {
+        } else if dist.abs() <= Float::EPSILON {
             return points[0];
         }
 
@@ -49,7 +52,7 @@ mod fruits_osu {
             if dist <= curr_dist {
                 let remaining_dist = dist - (curr_dist - new_dist);
 
-                return if remaining_dist.abs() <= f32::EPSILON {
+                return if remaining_dist.abs() <= Float::EPSILON {
                     curr
                 } else {
                     curr + (next - curr) * (remaining_dist / new_dist)
@@ -64,7 +67,7 @@ mod fruits_osu {
         pre_last + (last - pre_last) * (remaining_dist / new_dist)
     }
 
-    pub(crate) fn get_circum_circle(p0: Pos2, p1: Pos2, p2: Pos2) -> (Pos2, f32) {
+    pub(crate) fn get_circum_circle(p0: Pos2, p1: Pos2, p2: Pos2) -> (Pos2, Float) {
         let a = 2.0 * (p0.x * (p1.y - p2.y) - p0.y * (p1.x - p2.x) + p1.x * p2.y - p2.x * p1.y);
 
         let q0 = p0.length_squared();
@@ -86,7 +89,7 @@ mod fruits_osu {
 
     #[inline]
     pub(crate) fn is_linear(p0: Pos2, p1: Pos2, p2: Pos2) -> bool {
-        ((p1.x - p0.x) * (p2.y - p0.y) - (p1.y - p0.y) * (p2.x - p0.x)).abs() <= f32::EPSILON
+        ((p1.x - p0.x) * (p2.y - p0.y) - (p1.y - p0.y) * (p2.x - p0.x)).abs() <= Float::EPSILON
     }
 
     #[inline]
@@ -101,7 +104,7 @@ mod fruits_osu {
     }
 
     #[inline]
-    pub(crate) fn rotate(center: Pos2, origin: Pos2, theta: f32) -> Pos2 {
+    pub(crate) fn rotate(center: Pos2, origin: Pos2, theta: Float) -> Pos2 {
         let (sin, cos) = theta.sin_cos();
         let diff = origin - center;
 
@@ -116,6 +119,6 @@ mod fruits_osu {
 
 #[cfg(feature = "osu")]
 #[inline]
-pub(crate) fn lerp(start: f32, end: f32, percent: f32) -> f32 {
+pub(crate) fn lerp(start: Float, end: Float, percent: Float) -> Float {
     start + (end - start) * percent
 }
@@ -0,0 +1,25 @@
+use std::error::Error as StdError;
+use std::fmt;
+
+/// Error when parsing or validating [`GameMods`](crate::GameMods).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModsError {
+    /// The two given mods can not be enabled at the same time.
+    Incompatible(&'static str, &'static str),
+    /// The string ended with a single dangling character.
+    UnfinishedAcronym(char),
+    /// The acronym does not belong to any mod.
+    UnknownAcronym(String),
+}
+
+impl fmt::Display for ModsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Incompatible(a, b) => write!(f, "mods `{}` and `{}` are incompatible", a, b),
+            Self::UnfinishedAcronym(c) => write!(f, "unfinished mod acronym `{}`", c),
+            Self::UnknownAcronym(acronym) => write!(f, "unknown mod acronym `{}`", acronym),
+        }
+    }
+}
+
+impl StdError for ModsError {}
@@ -0,0 +1,400 @@
+use super::{Mods, ModsError};
+use crate::{Float, GameMode, ScoreVersion};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// All mods with their acronym, ordered by their bit value.
+const ACRONYMS: [(u32, &str); 31] = [
+    (u32::NF, "NF"),
+    (u32::EZ, "EZ"),
+    (u32::TD, "TD"),
+    (u32::HD, "HD"),
+    (u32::HR, "HR"),
+    (u32::SD, "SD"),
+    (u32::DT, "DT"),
+    (u32::RX, "RX"),
+    (u32::HT, "HT"),
+    (u32::NC, "NC"),
+    (u32::FL, "FL"),
+    (u32::AT, "AT"),
+    (u32::SO, "SO"),
+    (u32::AP, "AP"),
+    (u32::PF, "PF"),
+    (u32::K4, "4K"),
+    (u32::K5, "5K"),
+    (u32::K6, "6K"),
+    (u32::K7, "7K"),
+    (u32::K8, "8K"),
+    (u32::FI, "FI"),
+    (u32::RD, "RD"),
+    (u32::CN, "CN"),
+    (u32::TP, "TP"),
+    (u32::K9, "9K"),
+    (u32::KC, "CO"),
+    (u32::K1, "1K"),
+    (u32::K3, "3K"),
+    (u32::K2, "2K"),
+    (u32::V2, "V2"),
+    (u32::MR, "MR"),
+];
+
+/// All mods in the order in which osu! displays them, e.g. `HDDTHR`.
+const DISPLAY_ORDER: [u32; 31] = [
+    u32::NF,
+    u32::EZ,
+    u32::TD,
+    u32::HD,
+    u32::HT,
+    u32::DT,
+    u32::NC,
+    u32::HR,
+    u32::SD,
+    u32::PF,
+    u32::FL,
+    u32::FI,
+    u32::RX,
+    u32::AP,
+    u32::SO,
+    u32::AT,
+    u32::CN,
+    u32::TP,
+    u32::RD,
+    u32::MR,
+    u32::K1,
+    u32::K2,
+    u32::K3,
+    u32::K4,
+    u32::K5,
+    u32::K6,
+    u32::K7,
+    u32::K8,
+    u32::K9,
+    u32::KC,
+    u32::V2,
+];
+
+/// Mods that imply another mod, e.g. NC is always DT aswell.
+const IMPLIED: [(u32, u32); 2] = [(u32::NC, u32::DT), (u32::PF, u32::SD)];
+
+/// Pairs of mods that can not be enabled at the same time.
+///
+/// Mods that imply others come first so that errors mention the mod that was actually chosen.
+const INCOMPATIBLE: [(u32, u32); 19] = [
+    (u32::EZ, u32::HR),
+    (u32::NC, u32::HT),
+    (u32::DT, u32::HT),
+    (u32::NF, u32::PF),
+    (u32::NF, u32::SD),
+    (u32::NF, u32::RX),
+    (u32::NF, u32::AP),
+    (u32::PF, u32::RX),
+    (u32::PF, u32::AP),
+    (u32::SD, u32::RX),
+    (u32::SD, u32::AP),
+    (u32::RX, u32::AP),
+    (u32::SO, u32::AP),
+    (u32::AT, u32::RX),
+    (u32::AT, u32::AP),
+    (u32::AT, u32::PF),
+    (u32::AT, u32::SD),
+    (u32::HD, u32::FI),
+    (u32::FL, u32::FI),
+];
+
+const KEY_MODS: u32 =
+    u32::K1 | u32::K2 | u32::K3 | u32::K4 | u32::K5 | u32::K6 | u32::K7 | u32::K8 | u32::K9;
+
+/// Typed wrapper around mod bits.
+///
+/// Can be parsed from acronyms like `"HDDTHR"` and is displayed the same way.
+/// Mods implied by others are always included, e.g. NC also sets DT and PF also sets SD.
+///
+/// Since all calculators take raw bits, use [`bits`](GameMods::bits) to pass them along.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GameMods(u32);
+
+impl GameMods {
+    /// Create mods from their bit values, see [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods).
+    ///
+    /// Implied mods are added if missing.
+    #[inline]
+    pub fn from_bits(bits: u32) -> Self {
+        let implied = IMPLIED
+            .iter()
+            .filter(|(mods, _)| bits & mods > 0)
+            .fold(0, |implied, (_, bit)| implied | bit);
+
+        Self(bits | implied)
+    }
+
+    /// The bit values of the mods.
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Check whether all given mod bits are enabled.
+    #[inline]
+    pub fn contains(self, bits: u32) -> bool {
+        self.0 & bits == bits
+    }
+
+    /// Check whether the mods can be enabled at the same time.
+    pub fn validate(self) -> Result<(), ModsError> {
+        for &(a, b) in INCOMPATIBLE.iter() {
+            if self.contains(a | b) {
+                return Err(ModsError::Incompatible(acronym(a), acronym(b)));
+            }
+        }
+
+        let mut keys = ACRONYMS
+            .iter()
+            .filter(|(bit, _)| bit & KEY_MODS & self.0 > 0)
+            .map(|(_, acronym)| *acronym);
+
+        if let (Some(a), Some(b)) = (keys.next(), keys.next()) {
+            return Err(ModsError::Incompatible(a, b));
+        }
+
+        Ok(())
+    }
+
+    /// Same as [`validate`](GameMods::validate) but only returns whether the mods are valid.
+    #[inline]
+    pub fn is_valid(self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+fn acronym(bit: u32) -> &'static str {
+    ACRONYMS
+        .iter()
+        .find(|(mods, _)| *mods == bit)
+        .map_or("", |(_, acronym)| acronym)
+}
+
+impl From<u32> for GameMods {
+    #[inline]
+    fn from(bits: u32) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+impl From<GameMods> for u32 {
+    #[inline]
+    fn from(mods: GameMods) -> Self {
+        mods.0
+    }
+}
+
+impl FromStr for GameMods {
+    type Err = ModsError;
+
+    /// Parse mods from acronyms, e.g. `"HDDTHR"`, `"+hdhr"`, or `"NM"`.
+    ///
+    /// Casing and order are ignored.
+    /// Incompatible combinations like `DTHT` are rejected, see [`validate`](GameMods::validate).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix('+').unwrap_or(s);
+
+        if s.eq_ignore_ascii_case("NM") {
+            return Ok(Self::default());
+        }
+
+        let mut bits = 0;
+        let mut chars = s.chars();
+
+        while let Some(first) = chars.next() {
+            let second = chars.next().ok_or(ModsError::UnfinishedAcronym(first))?;
+
+            let acronym: String = [first, second]
+                .iter()
+                .map(char::to_ascii_uppercase)
+                .collect();
+
+            let (bit, _) = ACRONYMS
+                .iter()
+                .find(|(_, known)| *known == acronym)
+                .ok_or(ModsError::UnknownAcronym(acronym))?;
+
+            bits |= bit;
+        }
+
+        let mods = Self::from_bits(bits);
+        mods.validate()?;
+
+        Ok(mods)
+    }
+}
+
+impl fmt::Display for GameMods {
+    /// Writes the acronyms in the order of osu!, e.g. `HDDTHR`, or `NM` if no mods are enabled.
+    ///
+    /// Implied mods are omitted, e.g. NC is written as `NC` rather than `DTNC`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 == 0 {
+            return f.write_str("NM");
+        }
+
+        let implied = IMPLIED
+            .iter()
+            .filter(|(mods, _)| self.0 & mods > 0)
+            .fold(0, |implied, (_, bit)| implied | bit);
+
+        for &bit in DISPLAY_ORDER.iter() {
+            if self.0 & bit > 0 && implied & bit == 0 {
+                f.write_str(acronym(bit))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! delegate_mods {
+    ($($func_name:ident -> $ret:ty),*) => {
+        $(
+            #[inline]
+            fn $func_name(self) -> $ret {
+                self.0.$func_name()
+            }
+        )*
+    };
+}
+
+impl Mods for GameMods {
+    delegate_mods!(
+        change_speed -> bool,
+        change_map -> bool,
+        speed -> Float,
+        od_ar_hp_multiplier -> Float,
+        key_count -> Option<u8>,
+        nf -> bool,
+        ez -> bool,
+        td -> bool,
+        hd -> bool,
+        hr -> bool,
+        sd -> bool,
+        dt -> bool,
+        rx -> bool,
+        ht -> bool,
+        nc -> bool,
+        fl -> bool,
+        at -> bool,
+        so -> bool,
+        ap -> bool,
+        pf -> bool,
+        fi -> bool,
+        rd -> bool,
+        cn -> bool,
+        tp -> bool,
+        kc -> bool,
+        v2 -> bool,
+        mr -> bool
+    );
+
+    #[inline]
+    fn for_mode(self, mode: GameMode) -> Self {
+        Self(self.0.for_mode(mode))
+    }
+
+    #[inline]
+    fn score_multiplier(self, mode: GameMode, version: ScoreVersion) -> Float {
+        self.0.score_multiplier(mode, version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let mods: GameMods = "HDDTHR".parse().unwrap();
+        assert_eq!(mods.bits(), 8 + 64 + 16);
+        assert_eq!(mods.to_string(), "HDDTHR");
+        assert_eq!("hrhddt".parse::<GameMods>().unwrap().to_string(), "HDDTHR");
+
+        let mods: GameMods = "+hdnc".parse().unwrap();
+        assert_eq!(mods.bits(), 8 + 64 + 512);
+        assert_eq!(mods.to_string(), "HDNC");
+
+        assert_eq!("NM".parse::<GameMods>().unwrap().bits(), 0);
+        assert_eq!(GameMods::default().to_string(), "NM");
+        assert_eq!("7K".parse::<GameMods>().unwrap().bits(), u32::K7);
+
+        assert_eq!(
+            "HDX".parse::<GameMods>(),
+            Err(ModsError::UnfinishedAcronym('X'))
+        );
+        assert_eq!(
+            "HDXX".parse::<GameMods>(),
+            Err(ModsError::UnknownAcronym("XX".to_owned()))
+        );
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        let mut order: Vec<_> = DISPLAY_ORDER.to_vec();
+        order.sort_unstable();
+        let mut bits: Vec<_> = ACRONYMS.iter().map(|(bit, _)| *bit).collect();
+        bits.sort_unstable();
+        assert_eq!(order, bits);
+
+        for &s in [
+            "HDDTHR", "EZHDHT", "HDNCHRFL", "NFSO", "HDPF", "HDRX", "HD7KCO", "NM",
+        ]
+        .iter()
+        {
+            let mods: GameMods = s.parse().unwrap();
+            assert_eq!(mods.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_incompatible() {
+        assert_eq!(
+            "DTHT".parse::<GameMods>(),
+            Err(ModsError::Incompatible("DT", "HT"))
+        );
+        assert_eq!(
+            "HDHREZ".parse::<GameMods>(),
+            Err(ModsError::Incompatible("EZ", "HR"))
+        );
+        assert_eq!(
+            "4K7K".parse::<GameMods>(),
+            Err(ModsError::Incompatible("4K", "7K"))
+        );
+    }
+
+    #[test]
+    fn implied_mods() {
+        assert!(GameMods::from_bits(u32::NC).dt());
+        assert!(GameMods::from_bits(u32::PF).sd());
+        assert_eq!(GameMods::from_bits(u32::PF).to_string(), "PF");
+    }
+
+    #[test]
+    fn validation() {
+        assert!(GameMods::from_bits(8 + 16 + 64).is_valid());
+
+        let err = GameMods::from_bits(u32::HR | u32::EZ).validate();
+        assert_eq!(err, Err(ModsError::Incompatible("EZ", "HR")));
+
+        let err = GameMods::from_bits(u32::NC | u32::HT).validate();
+        assert_eq!(err, Err(ModsError::Incompatible("NC", "HT")));
+
+        let err = GameMods::from_bits(u32::K4 | u32::K7).validate();
+        assert_eq!(err, Err(ModsError::Incompatible("4K", "7K")));
+    }
+
+    #[test]
+    fn mode_mods() {
+        let mods: GameMods = "HD7KRX".parse().unwrap();
+        assert_eq!(mods.key_count(), Some(7));
+        assert_eq!(mods.for_mode(GameMode::STD).to_string(), "HDRX");
+        assert_eq!(mods.for_mode(GameMode::MNA).to_string(), "HD7K");
+    }
+}
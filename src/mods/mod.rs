@@ -0,0 +1,407 @@
+mod error;
+mod game_mods;
+
+pub use error::ModsError;
+pub use game_mods::GameMods;
+
+use crate::{Float, GameMode, ScoreVersion};
+
+macro_rules! impl_mods {
+    ($func_name:ident, $const_name:ident) => {
+        #[inline]
+        fn $func_name(self) -> bool {
+            self & Self::$const_name > 0
+        }
+    };
+}
+
+/// Key mods ordered by their amount of keys.
+const KEY_MODS: [u32; 9] = [
+    u32::K1,
+    u32::K2,
+    u32::K3,
+    u32::K4,
+    u32::K5,
+    u32::K6,
+    u32::K7,
+    u32::K8,
+    u32::K9,
+];
+
+/// Mods that have an effect in every mode.
+const COMMON_MODS: u32 = u32::NF
+    | u32::EZ
+    | u32::HD
+    | u32::HR
+    | u32::SD
+    | u32::DT
+    | u32::HT
+    | u32::NC
+    | u32::FL
+    | u32::AT
+    | u32::PF
+    | u32::CN
+    | u32::V2
+    | u32::MR;
+
+/// The mod bits that have an effect in the given mode.
+#[inline]
+fn mode_mods(mode: GameMode) -> u32 {
+    match mode {
+        GameMode::STD => COMMON_MODS | u32::TD | u32::RX | u32::SO | u32::AP | u32::TP,
+        GameMode::TKO => COMMON_MODS | u32::RX | u32::RD,
+        GameMode::CTB => COMMON_MODS | u32::RX,
+        GameMode::MNA => {
+            KEY_MODS.iter().fold(COMMON_MODS, |mods, key| mods | key) | u32::FI | u32::RD | u32::KC
+        }
+    }
+}
+
+pub trait Mods: Copy {
+    const NF: u32 = 1 << 0;
+    const EZ: u32 = 1 << 1;
+    const TD: u32 = 1 << 2;
+    const HD: u32 = 1 << 3;
+    const HR: u32 = 1 << 4;
+    const SD: u32 = 1 << 5;
+    const DT: u32 = 1 << 6;
+    const RX: u32 = 1 << 7;
+    const HT: u32 = 1 << 8;
+    const NC: u32 = 1 << 9;
+    const FL: u32 = 1 << 10;
+    const AT: u32 = 1 << 11;
+    const SO: u32 = 1 << 12;
+    const AP: u32 = 1 << 13;
+    const PF: u32 = 1 << 14;
+    const K4: u32 = 1 << 15;
+    const K5: u32 = 1 << 16;
+    const K6: u32 = 1 << 17;
+    const K7: u32 = 1 << 18;
+    const K8: u32 = 1 << 19;
+    const FI: u32 = 1 << 20;
+    const RD: u32 = 1 << 21;
+    const CN: u32 = 1 << 22;
+    const TP: u32 = 1 << 23;
+    const K9: u32 = 1 << 24;
+    const KC: u32 = 1 << 25;
+    const K1: u32 = 1 << 26;
+    const K3: u32 = 1 << 27;
+    const K2: u32 = 1 << 28;
+    const V2: u32 = 1 << 29;
+    const MR: u32 = 1 << 30;
+
+    fn change_speed(self) -> bool;
+    fn change_map(self) -> bool;
+    fn speed(self) -> Float;
+    fn od_ar_hp_multiplier(self) -> Float;
+    /// The amount of keys of a key mod, e.g. `Some(4)` for 4K.
+    ///
+    /// Defaults to `None`, i.e. the key count of the map.
+    #[inline]
+    fn key_count(self) -> Option<u8> {
+        None
+    }
+
+    /// Only keep the mods that have an effect in the given mode.
+    ///
+    /// The star and strain calculations of each mode apply this to their mods.
+    /// Defaults to keeping all mods.
+    #[inline]
+    fn for_mode(self, _mode: GameMode) -> Self {
+        self
+    }
+
+    /// The factor by which the mods scale the score in the given mode.
+    ///
+    /// Relax and Autopilot plays give no score.
+    fn score_multiplier(self, mode: GameMode, version: ScoreVersion) -> Float {
+        let mods = self.for_mode(mode);
+
+        if mods.rx() || mods.ap() {
+            return 0.0;
+        }
+
+        let v2 = version == ScoreVersion::V2;
+        let mut multiplier = 1.0;
+
+        if mods.nf() && !v2 {
+            multiplier *= 0.5;
+        }
+
+        if mods.ez() {
+            multiplier *= 0.5;
+        }
+
+        if mods.ht() {
+            multiplier *= if mode == GameMode::MNA { 0.5 } else { 0.3 };
+        }
+
+        if mode != GameMode::MNA {
+            if mods.hd() {
+                multiplier *= 1.06;
+            }
+
+            if mods.hr() {
+                multiplier *= match (mode, v2) {
+                    (GameMode::STD, true) => 1.1,
+                    (GameMode::CTB, _) => 1.12,
+                    _ => 1.06,
+                };
+            }
+
+            if mods.dt() || mods.nc() {
+                multiplier *= match (mode, v2) {
+                    (GameMode::STD, true) => 1.2,
+                    (GameMode::CTB, false) => 1.06,
+                    _ => 1.12,
+                };
+            }
+
+            if mods.fl() {
+                multiplier *= 1.12;
+            }
+
+            if mods.so() {
+                multiplier *= 0.9;
+            }
+        }
+
+        multiplier
+    }
+
+    fn nf(self) -> bool;
+    fn ez(self) -> bool;
+    fn td(self) -> bool;
+    fn hd(self) -> bool;
+    fn hr(self) -> bool;
+    fn dt(self) -> bool;
+    fn rx(self) -> bool;
+    fn ht(self) -> bool;
+    fn fl(self) -> bool;
+    fn so(self) -> bool;
+    fn ap(self) -> bool;
+    fn v2(self) -> bool;
+
+    // Mods that were added later are disabled by default
+    // so that existing implementations keep compiling.
+    #[inline]
+    fn sd(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn nc(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn at(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn pf(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn fi(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn rd(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn cn(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn tp(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn kc(self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn mr(self) -> bool {
+        false
+    }
+}
+
+impl Mods for u32 {
+    #[inline]
+    fn change_speed(self) -> bool {
+        self & (Self::HT | Self::DT) > 0
+    }
+
+    #[inline]
+    fn change_map(self) -> bool {
+        self & (Self::HT | Self::DT | Self::HR | Self::EZ) > 0
+    }
+
+    #[inline]
+    fn speed(self) -> Float {
+        if self & Self::DT > 0 {
+            1.5
+        } else if self & Self::HT > 0 {
+            0.75
+        } else {
+            1.0
+        }
+    }
+
+    #[inline]
+    fn od_ar_hp_multiplier(self) -> Float {
+        if self & Self::HR > 0 {
+            1.4
+        } else if self & Self::EZ > 0 {
+            0.5
+        } else {
+            1.0
+        }
+    }
+
+    #[inline]
+    fn key_count(self) -> Option<u8> {
+        KEY_MODS
+            .iter()
+            .position(|&key| self & key > 0)
+            .map(|idx| idx as u8 + 1)
+    }
+
+    #[inline]
+    fn for_mode(self, mode: GameMode) -> Self {
+        self & mode_mods(mode)
+    }
+
+    impl_mods!(nf, NF);
+    impl_mods!(ez, EZ);
+    impl_mods!(td, TD);
+    impl_mods!(hd, HD);
+    impl_mods!(hr, HR);
+    impl_mods!(sd, SD);
+    impl_mods!(dt, DT);
+    impl_mods!(rx, RX);
+    impl_mods!(ht, HT);
+    impl_mods!(nc, NC);
+    impl_mods!(fl, FL);
+    impl_mods!(at, AT);
+    impl_mods!(so, SO);
+    impl_mods!(ap, AP);
+    impl_mods!(pf, PF);
+    impl_mods!(fi, FI);
+    impl_mods!(rd, RD);
+    impl_mods!(cn, CN);
+    impl_mods!(tp, TP);
+    impl_mods!(kc, KC);
+    impl_mods!(v2, V2);
+    impl_mods!(mr, MR);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_multiplier_relax_autopilot() {
+        let v1 = ScoreVersion::V1;
+
+        assert_eq!(u32::RX.score_multiplier(GameMode::STD, v1), 0.0);
+        assert_eq!((u32::HD | u32::AP).score_multiplier(GameMode::STD, v1), 0.0);
+        assert_eq!(
+            u32::RX.score_multiplier(GameMode::TKO, ScoreVersion::V2),
+            0.0
+        );
+
+        // Neither has an effect in osu!mania and Autopilot only exists in osu!standard
+        assert_eq!(u32::RX.score_multiplier(GameMode::MNA, v1), 1.0);
+        assert_eq!(u32::AP.score_multiplier(GameMode::CTB, v1), 1.0);
+    }
+
+    /// Implements only the methods that `Mods` originally required.
+    #[derive(Copy, Clone)]
+    struct Hidden;
+
+    impl Mods for Hidden {
+        fn change_speed(self) -> bool {
+            false
+        }
+
+        fn change_map(self) -> bool {
+            false
+        }
+
+        fn speed(self) -> Float {
+            1.0
+        }
+
+        fn od_ar_hp_multiplier(self) -> Float {
+            1.0
+        }
+
+        fn nf(self) -> bool {
+            false
+        }
+
+        fn ez(self) -> bool {
+            false
+        }
+
+        fn td(self) -> bool {
+            false
+        }
+
+        fn hd(self) -> bool {
+            true
+        }
+
+        fn hr(self) -> bool {
+            false
+        }
+
+        fn dt(self) -> bool {
+            false
+        }
+
+        fn rx(self) -> bool {
+            false
+        }
+
+        fn ht(self) -> bool {
+            false
+        }
+
+        fn fl(self) -> bool {
+            false
+        }
+
+        fn so(self) -> bool {
+            false
+        }
+
+        fn ap(self) -> bool {
+            false
+        }
+
+        fn v2(self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn defaults_for_external_impls() {
+        assert!(!Hidden.nc() && !Hidden.kc() && !Hidden.mr());
+        assert_eq!(Hidden.key_count(), None);
+        assert!(Hidden.for_mode(GameMode::MNA).hd());
+
+        let multiplier = Hidden.score_multiplier(GameMode::STD, ScoreVersion::V1);
+        assert!((multiplier - 1.06).abs() < 1e-6);
+    }
+}
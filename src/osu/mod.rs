@@ -1,21 +1,55 @@
 #![cfg(feature = "osu")]
 
 mod pp;
+mod score;
 mod versions;
 
+use crate::Float;
+
 pub use pp::*;
+pub use score::*;
 pub use versions::*;
 
 /// Various data created through the star calculation.
 /// This data is necessary to calculate PP.
 #[derive(Clone, Debug, Default)]
 pub struct DifficultyAttributes {
-    pub stars: f32,
-    pub ar: f32,
-    pub od: f32,
-    pub speed_strain: f32,
-    pub aim_strain: f32,
+    pub stars: Float,
+    pub ar: Float,
+    pub od: Float,
+    pub speed_strain: Float,
+    pub aim_strain: Float,
+    pub flashlight_strain: Float,
+    /// Ratio of the aim strain without sliders to the aim strain with sliders.
+    /// Always `1.0` for [`AlgorithmVersion::V2021`](crate::AlgorithmVersion::V2021).
+    pub slider_factor: Float,
+    /// Weighted amount of notes that are relevant for the speed strain.
+    /// Always `0.0` for [`AlgorithmVersion::V2021`](crate::AlgorithmVersion::V2021).
+    pub speed_note_count: Float,
+    /// Weighted amount of objects with an aim strain close to the peak.
+    /// Always `0.0` for [`AlgorithmVersion::V2021`](crate::AlgorithmVersion::V2021).
+    pub aim_difficult_strain_count: Float,
+    /// Weighted amount of objects with a speed strain close to the peak.
+    /// Always `0.0` for [`AlgorithmVersion::V2021`](crate::AlgorithmVersion::V2021).
+    pub speed_difficult_strain_count: Float,
     pub max_combo: usize,
     pub n_circles: usize,
+    pub n_sliders: usize,
     pub n_spinners: usize,
 }
+
+/// Difficulty values of a single hit object, created through the star calculation.
+///
+/// All time values are in milliseconds and adjusted w.r.t. the clock rate.
+#[derive(Clone, Debug, Default)]
+pub struct ObjectDifficulty {
+    pub start_time: Float,
+    pub jump_dist: Float,
+    pub travel_dist: Float,
+    pub angle: Option<Float>,
+    pub strain_time: Float,
+    pub aim_strain: Float,
+    pub speed_strain: Float,
+    /// Always `0.0` if FL is not enabled.
+    pub flashlight_strain: Float,
+}
@@ -1,5 +1,8 @@
 use super::DifficultyAttributes;
-use crate::{Beatmap, Mods, PpRaw, PpResult, StarResult};
+use crate::{
+    AlgorithmVersion, AttributeKey, AttributeStore, Beatmap, CalcResult, DifficultyParams, Float,
+    GameMode, Grade, HitResultPriority, HitResults, IfFcResult, Mods, PpRaw, PpResult, StarResult,
+};
 
 /// Calculator for pp on osu!standard maps.
 ///
@@ -34,14 +37,17 @@ pub struct OsuPP<'m> {
     pub map: &'m Beatmap,
     pub attributes: Option<DifficultyAttributes>,
     pub mods: u32,
+    pub params: DifficultyParams,
     pub combo: Option<usize>,
-    pub acc: Option<f32>,
+    pub acc: Option<Float>,
 
     pub n300: Option<usize>,
     pub n100: Option<usize>,
     pub n50: Option<usize>,
     pub n_misses: usize,
     pub passed_objects: Option<usize>,
+    pub version: AlgorithmVersion,
+    pub hitresult_priority: HitResultPriority,
 }
 
 impl<'m> OsuPP<'m> {
@@ -51,6 +57,7 @@ impl<'m> OsuPP<'m> {
             map,
             attributes: None,
             mods: 0,
+            params: DifficultyParams::default(),
             combo: None,
             acc: None,
 
@@ -59,6 +66,8 @@ impl<'m> OsuPP<'m> {
             n50: None,
             n_misses: 0,
             passed_objects: None,
+            version: AlgorithmVersion::default(),
+            hitresult_priority: HitResultPriority::default(),
         }
     }
 
@@ -76,12 +85,88 @@ impl<'m> OsuPP<'m> {
         self
     }
 
+    /// Fill the attributes from the given store or calculate and insert them if it has none.
+    ///
+    /// The checksum identifies the map, e.g. the MD5 hash of its .osu file.
+    /// Be sure to set the mods, clock rate, and version beforehand!
+    /// Attributes of partial plays or custom AR, OD, CS, or HP are not stored.
+    pub fn attributes_from_store(self, mut store: impl AttributeStore, checksum: &str) -> Self {
+        let key = AttributeKey::for_calculation(
+            checksum,
+            GameMode::STD,
+            self.mods,
+            self.params,
+            self.passed_objects,
+            self.version,
+        );
+
+        match key {
+            Some(key) => {
+                let (map, mods, params) = (self.map, self.mods, self.params);
+                let stars_func = stars_func(self.version);
+                let attributes =
+                    store.get_or_calculate(key, || stars_func(map, mods, None, params));
+
+                self.attributes(attributes)
+            }
+            None => self,
+        }
+    }
+
     /// Specify mods through their bit values.
     ///
     /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    ///
+    /// Mods without effect in this mode are ignored.
     #[inline]
     pub fn mods(mut self, mods: u32) -> Self {
-        self.mods = mods;
+        self.mods = mods.for_mode(GameMode::STD);
+
+        self
+    }
+
+    /// Specify a custom clock rate that overrides the one of the mods, e.g. `1.2` for a 1.2x speed-up.
+    ///
+    /// Invalid rates are ignored or clamped, see [`DifficultyParams::clock_rate`](crate::DifficultyParams::clock_rate).
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: Float) -> Self {
+        self.params = self.params.clock_rate(clock_rate);
+
+        self
+    }
+
+    /// Specify an approach rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn ar(mut self, ar: Float) -> Self {
+        self.params = self.params.ar(ar);
+
+        self
+    }
+
+    /// Specify an overall difficulty that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn od(mut self, od: Float) -> Self {
+        self.params = self.params.od(od);
+
+        self
+    }
+
+    /// Specify a circle size that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn cs(mut self, cs: Float) -> Self {
+        self.params = self.params.cs(cs);
+
+        self
+    }
+
+    /// Specify a drain rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn hp(mut self, hp: Float) -> Self {
+        self.params = self.params.hp(hp);
 
         self
     }
@@ -134,11 +219,32 @@ impl<'m> OsuPP<'m> {
         self
     }
 
+    /// Specify the version of the difficulty and pp algorithm.
+    ///
+    /// If this build has no implementation of the given version,
+    /// the newest older version will be used instead, see [`AlgorithmVersion::resolve`].
+    /// Attributes that were calculated with a different version should not be reused.
+    #[inline]
+    pub fn version(mut self, version: AlgorithmVersion) -> Self {
+        self.version = version;
+
+        self
+    }
+
+    /// Specify how hit results are distributed when they are generated from an accuracy,
+    /// see [`HitResultPriority`]. Be sure to set this before the accuracy!
+    #[inline]
+    pub fn hitresult_priority(mut self, priority: HitResultPriority) -> Self {
+        self.hitresult_priority = priority;
+
+        self
+    }
+
     /// Generate the hit results with respect to the given accuracy between `0` and `100`.
     ///
     /// Be sure to set `misses` beforehand!
     /// In case of a partial play, be also sure to set `passed_objects` beforehand!
-    pub fn accuracy(mut self, acc: f32) -> Self {
+    pub fn accuracy(mut self, acc: Float) -> Self {
         self.set_accuracy(acc);
         self
     }
@@ -148,6 +254,7 @@ impl<'m> OsuPP<'m> {
     ///
     /// If it is used to calculate the PP of multiple different ACCs,
     /// it should be called from high to low according to the ACC value, otherwise it is invalid.
+    /// Use [`OsuPP::calculate_accuracies`] to calculate multiple accuracies in any order.
     ///
     /// Examples:
     /// ```
@@ -188,10 +295,8 @@ impl<'m> OsuPP<'m> {
     /// };
     /// ```
     ///
-    pub fn set_accuracy(&mut self, acc: f32) {
-        let n_objects = self
-            .passed_objects
-            .unwrap_or_else(|| self.map.hit_objects.len());
+    pub fn set_accuracy(&mut self, acc: Float) {
+        let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
 
         let acc = acc / 100.0;
 
@@ -199,15 +304,23 @@ impl<'m> OsuPP<'m> {
             let mut n100 = self.n100.unwrap_or(0);
             let mut n50 = self.n50.unwrap_or(0);
 
-            let placed_points = 2 * n100 + n50 + self.n_misses;
-            let missing_objects = n_objects - n100 - n50 - self.n_misses;
+            let placed_points = n100
+                .saturating_mul(2)
+                .saturating_add(n50)
+                .saturating_add(self.n_misses);
+            let missing_objects = n_objects
+                .saturating_sub(n100)
+                .saturating_sub(n50)
+                .saturating_sub(self.n_misses);
             let missing_points =
-                ((6.0 * acc * n_objects as f32).round() as usize).saturating_sub(placed_points);
+                ((6.0 * acc * n_objects as Float).round() as usize).saturating_sub(placed_points);
 
             let mut n300 = missing_objects.min(missing_points / 6);
-            n50 += missing_objects - n300;
+            n50 = n50.saturating_add(missing_objects - n300);
+
+            let best_case = self.hitresult_priority == HitResultPriority::BestCase;
 
-            if let Some(orig_n50) = self.n50.filter(|_| self.n100.is_none()) {
+            if let Some(orig_n50) = self.n50.filter(|_| self.n100.is_none() && best_case) {
                 // Only n50s were changed, try to load some off again onto n100s
                 let difference = n50 - orig_n50;
                 let n = n300.min(difference / 4);
@@ -222,35 +335,37 @@ impl<'m> OsuPP<'m> {
             self.n50.replace(n50);
         } else {
             let misses = self.n_misses.min(n_objects);
-            let target_total = (acc * n_objects as f32 * 6.0).round() as usize;
-            let delta = target_total - (n_objects - misses);
+            let target_total = (acc * n_objects as Float * 6.0).round() as usize;
+            let delta = target_total.saturating_sub(n_objects - misses);
 
-            let mut n300 = delta / 5;
+            let mut n300 = (delta / 5).min(n_objects - misses);
             let mut n100 = (delta % 5).min(n_objects - n300 - misses);
             let mut n50 = n_objects - n300 - n100 - misses;
 
             // Sacrifice n300s to transform n50s into n100s
-            let n = n300.min(n50 / 4);
-            n300 -= n;
-            n100 += 5 * n;
-            n50 -= 4 * n;
+            if self.hitresult_priority == HitResultPriority::BestCase {
+                let n = n300.min(n50 / 4);
+                n300 -= n;
+                n100 += 5 * n;
+                n50 -= 4 * n;
+            }
 
             self.n300.replace(n300);
             self.n100.replace(n100);
             self.n50.replace(n50);
         }
 
-        let acc = (6 * self.n300.unwrap() + 2 * self.n100.unwrap() + self.n50.unwrap()) as f32
-            / (6 * n_objects) as f32;
+        let acc = (6.0 * self.n300.unwrap_or(0) as Float
+            + 2.0 * self.n100.unwrap_or(0) as Float
+            + self.n50.unwrap_or(0) as Float)
+            / (6.0 * n_objects as Float);
 
         self.acc.replace(acc);
     }
 
     fn assert_hitresults(&mut self) {
         if self.acc.is_none() {
-            let n_objects = self
-                .passed_objects
-                .unwrap_or_else(|| self.map.hit_objects.len());
+            let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
 
             let remaining = n_objects
                 .saturating_sub(self.n300.unwrap_or(0))
@@ -266,7 +381,7 @@ impl<'m> OsuPP<'m> {
                 } else if self.n50.is_none() {
                     self.n50.replace(remaining);
                 } else {
-                    *self.n300.as_mut().unwrap() += remaining;
+                    *self.n300.get_or_insert(0) += remaining;
                 }
             }
 
@@ -274,8 +389,8 @@ impl<'m> OsuPP<'m> {
             let n100 = *self.n100.get_or_insert(0);
             let n50 = *self.n50.get_or_insert(0);
 
-            let numerator = n300 * 6 + n100 * 2 + n50;
-            self.acc.replace(numerator as f32 / n_objects as f32 / 6.0);
+            let numerator = n300 as Float * 6.0 + n100 as Float * 2.0 + n50 as Float;
+            self.acc.replace(numerator / n_objects as Float / 6.0);
         }
     }
 
@@ -283,21 +398,26 @@ impl<'m> OsuPP<'m> {
     /// containing stars and other attributes.
     #[cfg(feature = "no_leniency")]
     pub fn calculate(&mut self) -> PpResult {
-        self.calculate_with_func(super::no_leniency::stars)
+        self.calculate_with_func(super::no_leniency::stars_with_params)
     }
 
     /// Returns an object which contains the pp and [`DifficultyAttributes`](crate::osu::DifficultyAttributes)
     /// containing stars and other attributes.
     #[cfg(feature = "no_sliders_no_leniency")]
     pub fn calculate(&mut self) -> PpResult {
-        self.calculate_with_func(super::no_sliders_no_leniency::stars)
+        self.calculate_with_func(super::no_sliders_no_leniency::stars_with_params)
     }
 
     /// Returns an object which contains the pp and [`DifficultyAttributes`](crate::osu::DifficultyAttributes)
     /// containing stars and other attributes.
     #[cfg(feature = "all_included")]
     pub fn calculate(&mut self) -> PpResult {
-        self.calculate_with_func(super::all_included::stars)
+        match self.version.resolve(GameMode::STD) {
+            AlgorithmVersion::V2021 | AlgorithmVersion::V2022 => {
+                self.calculate_with_func(super::all_included::stars_with_params)
+            }
+            AlgorithmVersion::V2024 => self.calculate_with_func(super::v2024::stars_with_params),
+        }
     }
 
     // Omits an unnecessary error when enabled features are invalid
@@ -310,52 +430,136 @@ impl<'m> OsuPP<'m> {
         unreachable!()
     }
 
+    /// Same as `calculate` but returns an error if the map has no hit objects
+    /// or is not an osu!standard map, see [`Beatmap::check_calculable`],
+    /// or if the difficulty parameters are invalid, see [`DifficultyParams::validate`](crate::DifficultyParams::validate).
+    #[inline]
+    pub fn try_calculate(&mut self) -> CalcResult<PpResult> {
+        self.map.check_calculable(GameMode::STD)?;
+        self.params.validate()?;
+
+        Ok(self.calculate())
+    }
+
     #[inline]
     pub async fn calculate_async(&mut self) -> PpResult {
         self.calculate()
     }
 
+    /// Calculate the pp for each of the given accuracies between `0` and `100`.
+    ///
+    /// The difficulty attributes are calculated only once and the hit results
+    /// are generated from the previously given input for every accuracy
+    /// so, unlike [`set_accuracy`](OsuPP::set_accuracy), the order does not matter.
+    pub fn calculate_accuracies(&mut self, accs: &[Float]) -> Vec<PpResult> {
+        accs.iter()
+            .map(|&acc| self.calculate_accuracy(acc))
+            .collect()
+    }
+
+    /// The minimum accuracy between `0` and `100` that is required to reach the given pp
+    /// w.r.t. all previously specified values such as mods, combo, or misses.
+    ///
+    /// Returns `None` if the pp can not be reached, not even with 100% accuracy.
+    /// The difficulty attributes are calculated only once.
+    pub fn accuracy_for_pp(&mut self, pp: Float) -> Option<Float> {
+        crate::solver::min_accuracy_for_pp(pp, |acc| self.calculate_accuracy(acc).pp)
+    }
+
+    /// Calculate the pp of the play and of the same play with a full combo.
+    ///
+    /// For the full combo, misses become 300s and the combo is the map's max combo.
+    /// In case of a partial play, the full combo only covers the passed objects.
+    /// The difficulty attributes are calculated only once.
+    pub fn calculate_if_fc(&mut self) -> IfFcResult {
+        let current = self.calculate();
+
+        // Calculate on a copy so that none of the hit results are overwritten
+        let mut fc = self.clone();
+        let max_combo = fc.attributes.as_ref().map_or(0, |a| a.max_combo);
+
+        fc.n300 = Some(fc.n300.unwrap_or(0).saturating_add(fc.n_misses));
+        fc.acc = None;
+        fc.combo = Some(max_combo);
+        fc.n_misses = 0;
+
+        let if_fc = fc.calculate();
+
+        IfFcResult { current, if_fc }
+    }
+
+    fn calculate_accuracy(&mut self, acc: Float) -> PpResult {
+        let input = (self.n300, self.n100, self.n50, self.acc);
+
+        self.set_accuracy(acc);
+        let result = self.calculate();
+
+        self.n300 = input.0;
+        self.n100 = input.1;
+        self.n50 = input.2;
+        self.acc = input.3;
+
+        result
+    }
+
     /// Returns an object which contains the pp and [`DifficultyAttributes`](crate::osu::DifficultyAttributes)
     /// containing stars and other attributes.
     ///
     /// `stars_func` will be used to calculate the difficulty attributes if they are not yet given.
     fn calculate_with_func(
         &mut self,
-        stars_func: impl FnOnce(&Beatmap, u32, Option<usize>) -> StarResult,
+        stars_func: impl FnOnce(&Beatmap, u32, Option<usize>, DifficultyParams) -> StarResult,
     ) -> PpResult {
-        if self.attributes.is_none() {
-            let attributes = stars_func(self.map, self.mods, self.passed_objects)
-                .attributes()
-                .unwrap();
-            self.attributes.replace(attributes);
-        }
+        let (map, mods, passed_objects, params) =
+            (self.map, self.mods, self.passed_objects, self.params);
+
+        let attributes = self
+            .attributes
+            .get_or_insert_with(|| {
+                stars_func(map, mods, passed_objects, params)
+                    .attributes()
+                    .unwrap_or_default()
+            })
+            .clone();
 
         // Make sure the hitresults and accuracy are set
         self.assert_hitresults();
 
-        let total_hits = self.total_hits() as f32;
+        #[cfg(feature = "all_included")]
+        if self.version.resolve(GameMode::STD) == AlgorithmVersion::V2024 {
+            return self.calculate_v2024(attributes);
+        }
+
+        let total_hits = self.total_hits() as Float;
         let mut multiplier = 1.12;
 
         // NF penalty
         if self.mods.nf() {
-            multiplier *= (1.0 - 0.02 * self.n_misses as f32).max(0.9);
+            multiplier *= (1.0 - 0.02 * self.n_misses as Float).max(0.9);
         }
 
         // SO penalty
         if self.mods.so() {
-            let n_spinners = self.attributes.as_ref().unwrap().n_spinners;
-            multiplier *= 1.0 - (n_spinners as f32 / total_hits).powf(0.85);
+            multiplier *= 1.0 - (attributes.n_spinners as Float / total_hits).powf(0.85);
         }
 
-        let aim_value = self.compute_aim_value(total_hits);
-        let speed_value = self.compute_speed_value(total_hits);
-        let acc_value = self.compute_accuracy_value(total_hits);
+        let aim_value = self.compute_aim_value(&attributes, total_hits);
+        let speed_value = self.compute_speed_value(&attributes, total_hits);
+        let acc_value = self.compute_accuracy_value(&attributes, total_hits);
+        let flashlight_skill = self.flashlight_skill();
 
-        let pp = (aim_value.powf(1.1) + speed_value.powf(1.1) + acc_value.powf(1.1))
-            .powf(1.0 / 1.1)
-            * multiplier;
+        let fl_value = if flashlight_skill {
+            self.compute_flashlight_value(&attributes, total_hits)
+        } else {
+            0.0
+        };
 
-        let attributes = StarResult::Osu(self.attributes.clone().unwrap());
+        let pp = (aim_value.powf(1.1)
+            + speed_value.powf(1.1)
+            + acc_value.powf(1.1)
+            + fl_value.powf(1.1))
+        .powf(1.0 / 1.1)
+            * multiplier;
 
         PpResult {
             mode: 0,
@@ -366,15 +570,16 @@ impl<'m> OsuPP<'m> {
                 Some(speed_value),
                 None,
                 Some(acc_value),
+                (flashlight_skill && self.mods.fl()).then_some(fl_value),
                 pp,
             ),
-            attributes,
+            attributes: StarResult::Osu(attributes),
+            version: self.version.resolve(GameMode::STD),
+            grade: self.grade(),
         }
     }
 
-    fn compute_aim_value(&self, total_hits: f32) -> f32 {
-        let attributes = self.attributes.as_ref().unwrap();
-
+    fn compute_aim_value(&self, attributes: &DifficultyAttributes, total_hits: Float) -> Float {
         // TD penalty
         let raw_aim = if self.mods.td() {
             attributes.aim_strain.powf(0.8)
@@ -387,14 +592,14 @@ impl<'m> OsuPP<'m> {
         // Longer maps are worth more
         let len_bonus = 0.95
             + 0.4 * (total_hits / 2000.0).min(1.0)
-            + (total_hits > 2000.0) as u8 as f32 * 0.5 * (total_hits / 2000.0).log10();
+            + (total_hits > 2000.0) as u8 as Float * 0.5 * (total_hits / 2000.0).log10();
         aim_value *= len_bonus;
 
         // Penalize misses
         #[cfg(not(feature = "ppysb_edition"))]
         if self.n_misses > 0 {
             aim_value *= 0.97
-                * (1.0 - (self.n_misses as f32 / total_hits).powf(0.775))
+                * (1.0 - (self.n_misses as Float / total_hits).powf(0.775))
                     .powi(self.n_misses as i32);
         }
 
@@ -403,18 +608,18 @@ impl<'m> OsuPP<'m> {
             let n50 = self.n50.unwrap_or(0);
             if self.mods.rx() && n50 > 0 {
                 aim_value *= 0.97
-                    * (1.0 - (self.n_misses as f32 / total_hits).powf(0.775))
-                        .powf(self.n_misses as f32 + (n50 as f32 * 0.35));
+                    * (1.0 - (self.n_misses as Float / total_hits).powf(0.775))
+                        .powf(self.n_misses as Float + (n50 as Float * 0.35));
             } else {
                 aim_value *= 0.97
-                    * (1.0 - (self.n_misses as f32 / total_hits).powf(0.775))
+                    * (1.0 - (self.n_misses as Float / total_hits).powf(0.775))
                         .powi(self.n_misses as i32);
             }
         }
 
         // Combo scaling
         if let Some(combo) = self.combo.filter(|_| attributes.max_combo > 0) {
-            aim_value *= ((combo as f32 / attributes.max_combo as f32).powf(0.8)).min(1.0);
+            aim_value *= ((combo as Float / attributes.max_combo as Float).powf(0.8)).min(1.0);
         }
 
         // AR bonus
@@ -472,11 +677,13 @@ impl<'m> OsuPP<'m> {
             }
         }
 
-        // FL bonus
-        let fl_bonus = if self.mods.fl() {
+        // FL bonus, replaced by the flashlight skill
+        let fl_bonus = if self.mods.fl() && !self.flashlight_skill() {
             1.0 + 0.35 * (total_hits / 200.0).min(1.0)
-                + (total_hits > 200.0) as u8 as f32 * 0.3 * ((total_hits - 200.0) / 300.0).min(1.0)
-                + (total_hits > 500.0) as u8 as f32 * (total_hits - 500.0) / 1200.0
+                + (total_hits > 200.0) as u8 as Float
+                    * 0.3
+                    * ((total_hits - 200.0) / 300.0).min(1.0)
+                + (total_hits > 500.0) as u8 as Float * (total_hits - 500.0) / 1200.0
         } else {
             1.0
         };
@@ -510,8 +717,8 @@ impl<'m> OsuPP<'m> {
             let slider_total_combo =
                 attributes.max_combo - attributes.n_circles - attributes.n_spinners;
             let slider_combo_percentage =
-                (slider_total_combo as f32) / (attributes.max_combo as f32);
-            let combo_per_slider = slider_total_combo as f32 / self.map.n_sliders as f32;
+                (slider_total_combo as Float) / (attributes.max_combo as Float);
+            let combo_per_slider = slider_total_combo as Float / self.map.n_sliders as Float;
 
             aim_value *= if slider_combo_percentage > 0.5 && combo_per_slider < 2.1 {
                 1.0 + ((slider_combo_percentage * 100.0 - 50.0).powf(0.3)
@@ -543,28 +750,26 @@ impl<'m> OsuPP<'m> {
         aim_value
     }
 
-    fn compute_speed_value(&self, total_hits: f32) -> f32 {
-        let attributes = self.attributes.as_ref().unwrap();
-
+    fn compute_speed_value(&self, attributes: &DifficultyAttributes, total_hits: Float) -> Float {
         let mut speed_value =
             (5.0 * (attributes.speed_strain / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
 
         // Longer maps are worth more
         let len_bonus = 0.95
             + 0.4 * (total_hits / 2000.0).min(1.0)
-            + (total_hits > 2000.0) as u8 as f32 * 0.5 * (total_hits / 2000.0).log10();
+            + (total_hits > 2000.0) as u8 as Float * 0.5 * (total_hits / 2000.0).log10();
         speed_value *= len_bonus;
 
         // Penalize misses
         if self.n_misses > 0 {
             speed_value *= 0.97
-                * (1.0 - (self.n_misses as f32 / total_hits).powf(0.775))
-                    .powf((self.n_misses as f32).powf(0.875));
+                * (1.0 - (self.n_misses as Float / total_hits).powf(0.775))
+                    .powf((self.n_misses as Float).powf(0.875));
         }
 
         // Combo scaling
         if let Some(combo) = self.combo.filter(|_| attributes.max_combo > 0) {
-            speed_value *= ((combo as f32 / attributes.max_combo as f32).powf(0.8)).min(1.0);
+            speed_value *= ((combo as Float / attributes.max_combo as Float).powf(0.8)).min(1.0);
         }
 
         // AR bonus
@@ -587,14 +792,14 @@ impl<'m> OsuPP<'m> {
         let od_factor = 0.95 + attributes.od * attributes.od / 750.0;
         let acc_factor = self
             .acc
-            .unwrap()
+            .unwrap_or(1.0)
             .powf((14.5 - attributes.od.max(8.0)) / 2.0);
         speed_value *= od_factor * acc_factor;
 
         // Penalize n50s
-        speed_value *= 0.98_f32.powf(
-            (self.n50.unwrap_or(0) as f32 >= total_hits / 500.0) as u8 as f32
-                * (self.n50.unwrap_or(0) as f32 - total_hits / 500.0),
+        speed_value *= (0.98 as Float).powf(
+            (self.n50.unwrap_or(0) as Float >= total_hits / 500.0) as u8 as Float
+                * (self.n50.unwrap_or(0) as Float - total_hits / 500.0),
         );
 
         // feature relax_nerf: relax & autopilot spd nerf
@@ -618,26 +823,85 @@ impl<'m> OsuPP<'m> {
         speed_value
     }
 
-    fn compute_accuracy_value(&self, total_hits: f32) -> f32 {
-        let attributes = self.attributes.as_ref().unwrap();
-        let n_circles = attributes.n_circles as f32;
-        let n300 = self.n300.unwrap_or(0) as f32;
-        let n100 = self.n100.unwrap_or(0) as f32;
-        let n50 = self.n50.unwrap_or(0) as f32;
+    /// Whether the flashlight skill is evaluated instead of the
+    /// flashlight bonus of the aim value, see [`AlgorithmVersion::V2022`].
+    #[inline]
+    fn flashlight_skill(&self) -> bool {
+        self.version.resolve(GameMode::STD) >= AlgorithmVersion::V2022
+    }
+
+    fn compute_flashlight_value(
+        &self,
+        attributes: &DifficultyAttributes,
+        total_hits: Float,
+    ) -> Float {
+        if !self.mods.fl() {
+            return 0.0;
+        }
+
+        // TD penalty
+        let raw_flashlight = if self.mods.td() {
+            attributes.flashlight_strain.powf(0.8)
+        } else {
+            attributes.flashlight_strain
+        };
+
+        let mut flashlight_value = raw_flashlight * raw_flashlight * 25.0;
 
-        let better_acc_percentage = (n_circles > 0.0) as u8 as f32
+        // HD bonus
+        if self.mods.hd() {
+            flashlight_value *= 1.3;
+        }
+
+        // Penalize misses
+        if self.n_misses > 0 {
+            flashlight_value *= 0.97
+                * (1.0 - (self.n_misses as Float / total_hits).powf(0.775))
+                    .powf((self.n_misses as Float).powf(0.875));
+        }
+
+        // Combo scaling
+        if let Some(combo) = self.combo.filter(|_| attributes.max_combo > 0) {
+            flashlight_value *=
+                ((combo as Float / attributes.max_combo as Float).powf(0.8)).min(1.0);
+        }
+
+        // Account for shorter maps having a higher ratio of 0 combo/100 combo flashlight radius
+        flashlight_value *= 0.7
+            + 0.1 * (total_hits / 200.0).min(1.0)
+            + (total_hits > 200.0) as u8 as Float * 0.2 * ((total_hits - 200.0) / 200.0).min(1.0);
+
+        // Scale with accuracy
+        flashlight_value *= 0.5 + self.acc.unwrap_or(0.0) / 2.0;
+        flashlight_value *= 0.98 + attributes.od * attributes.od / 2500.0;
+
+        flashlight_value
+    }
+
+    fn compute_accuracy_value(
+        &self,
+        attributes: &DifficultyAttributes,
+        total_hits: Float,
+    ) -> Float {
+        let n_circles = attributes.n_circles as Float;
+        let n300 = self.n300.unwrap_or(0) as Float;
+        let n100 = self.n100.unwrap_or(0) as Float;
+        let n50 = self.n50.unwrap_or(0) as Float;
+
+        let better_acc_percentage = (n_circles > 0.0) as u8 as Float
             * (((n300 - (total_hits - n_circles)) * 6.0 + n100 * 2.0 + n50) / (n_circles * 6.0))
                 .max(0.0);
 
         #[cfg(not(feature = "ppysb_edition"))]
-        let mut acc_value = 1.52163_f32.powf(attributes.od) * better_acc_percentage.powi(24) * 2.83;
+        let mut acc_value =
+            (1.52163 as Float).powf(attributes.od) * better_acc_percentage.powi(24) * 2.83;
         #[cfg(feature = "ppysb_edition")]
-        let mut acc_value = 1.52163_f32.powf(attributes.od)
+        let mut acc_value = (1.52163 as Float).powf(attributes.od)
             * better_acc_percentage.powi(if self.mods.rx() { 28 } else { 24 })
             * 2.83;
 
         // Bonus for many hitcircles
-        acc_value *= ((n_circles as f32 / 1000.0).powf(0.3)).min(1.15);
+        acc_value *= ((n_circles as Float / 1000.0).powf(0.3)).min(1.15);
 
         // HD bonus
         if self.mods.hd() {
@@ -672,13 +936,382 @@ impl<'m> OsuPP<'m> {
 
     #[inline]
     fn total_hits(&self) -> usize {
-        let n_objects = self
-            .passed_objects
-            .unwrap_or_else(|| self.map.hit_objects.len());
+        let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
 
-        (self.n300.unwrap_or(0) + self.n100.unwrap_or(0) + self.n50.unwrap_or(0) + self.n_misses)
+        self.n300
+            .unwrap_or(0)
+            .saturating_add(self.n100.unwrap_or(0))
+            .saturating_add(self.n50.unwrap_or(0))
+            .saturating_add(self.n_misses)
             .min(n_objects)
     }
+
+    fn grade(&self) -> Grade {
+        let hits = HitResults {
+            n300: self.n300.unwrap_or(0),
+            n100: self.n100.unwrap_or(0),
+            n50: self.n50.unwrap_or(0),
+            n_misses: self.n_misses,
+            ..Default::default()
+        };
+
+        Grade::new(GameMode::STD, self.mods, &hits)
+    }
+}
+
+#[cfg(feature = "all_included")]
+impl OsuPP<'_> {
+    /// Must be called after the attributes and hitresults are set.
+    fn calculate_v2024(&self, attributes: DifficultyAttributes) -> PpResult {
+        let total_hits = self.total_hits() as Float;
+        let mut effective_misses = self.effective_miss_count(&attributes);
+        let mut multiplier = 1.14;
+
+        // NF penalty
+        if self.mods.nf() {
+            multiplier *= (1.0 - 0.02 * effective_misses).max(0.9);
+        }
+
+        // SO penalty
+        if self.mods.so() && total_hits > 0.0 {
+            multiplier *= 1.0 - (attributes.n_spinners as Float / total_hits).powf(0.85);
+        }
+
+        // Inaccuracies are treated as misses on RX
+        if self.mods.rx() {
+            let od = attributes.od;
+
+            let (n100_mult, n50_mult) = if od > 0.0 {
+                (
+                    (1.0 - (od / 13.33).powf(1.8)).max(0.0),
+                    (1.0 - (od / 13.33).powi(5)).max(0.0),
+                )
+            } else {
+                (1.0, 1.0)
+            };
+
+            effective_misses = (effective_misses
+                + self.n100.unwrap_or(0) as Float * n100_mult
+                + self.n50.unwrap_or(0) as Float * n50_mult)
+                .min(total_hits);
+        }
+
+        let aim_value = self.compute_aim_value_v2024(&attributes, total_hits, effective_misses);
+        let speed_value = self.compute_speed_value_v2024(&attributes, total_hits, effective_misses);
+        let acc_value = self.compute_accuracy_value_v2024(&attributes, total_hits);
+        let fl_value =
+            self.compute_flashlight_value_v2024(&attributes, total_hits, effective_misses);
+
+        let pp = (aim_value.powf(1.1)
+            + speed_value.powf(1.1)
+            + acc_value.powf(1.1)
+            + fl_value.powf(1.1))
+        .powf(1.0 / 1.1)
+            * multiplier;
+
+        PpResult {
+            mode: 0,
+            mods: self.mods,
+            pp,
+            raw: PpRaw::new(
+                Some(aim_value),
+                Some(speed_value),
+                None,
+                Some(acc_value),
+                self.mods.fl().then_some(fl_value),
+                pp,
+            ),
+            attributes: StarResult::Osu(attributes),
+            version: AlgorithmVersion::V2024,
+            grade: self.grade(),
+        }
+    }
+
+    fn compute_aim_value_v2024(
+        &self,
+        attributes: &DifficultyAttributes,
+        total_hits: Float,
+        effective_misses: Float,
+    ) -> Float {
+        // TD is already considered in the attributes
+        let mut aim_value =
+            (5.0 * (attributes.aim_strain / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+
+        // Longer maps are worth more
+        let len_bonus = 0.95
+            + 0.4 * (total_hits / 2000.0).min(1.0)
+            + (total_hits > 2000.0) as u8 as Float * 0.5 * (total_hits / 2000.0).log10();
+        aim_value *= len_bonus;
+
+        // Penalize misses
+        if effective_misses > 0.0 {
+            aim_value *= miss_penalty(effective_misses, attributes.aim_difficult_strain_count);
+        }
+
+        // AR bonus
+        let ar_factor = if self.mods.rx() {
+            0.0
+        } else if attributes.ar > 10.33 {
+            0.3 * (attributes.ar - 10.33)
+        } else if attributes.ar < 8.0 {
+            0.05 * (8.0 - attributes.ar)
+        } else {
+            0.0
+        };
+
+        aim_value *= 1.0 + ar_factor * len_bonus;
+
+        // HD bonus
+        if self.mods.hd() {
+            aim_value *= 1.0 + 0.04 * (12.0 - attributes.ar);
+        }
+
+        // Nerf for dropped slider ends on maps with difficult sliders
+        if attributes.n_sliders > 0 {
+            let estimate_difficult_sliders = attributes.n_sliders as Float * 0.15;
+            let combo = self.combo.unwrap_or(attributes.max_combo);
+            let n_not_great = self
+                .n100
+                .unwrap_or(0)
+                .saturating_add(self.n50.unwrap_or(0))
+                .saturating_add(self.n_misses);
+
+            let estimate_slider_ends_dropped =
+                (n_not_great.min(attributes.max_combo.saturating_sub(combo)) as Float)
+                    .clamp(0.0, estimate_difficult_sliders);
+
+            let slider_nerf_factor = (1.0 - attributes.slider_factor)
+                * (1.0 - estimate_slider_ends_dropped / estimate_difficult_sliders).powi(3)
+                + attributes.slider_factor;
+
+            aim_value *= slider_nerf_factor;
+        }
+
+        // Scale with accuracy
+        aim_value *= self.acc.unwrap_or(0.0);
+        aim_value *= 0.98 + attributes.od * attributes.od / 2500.0;
+
+        aim_value
+    }
+
+    fn compute_speed_value_v2024(
+        &self,
+        attributes: &DifficultyAttributes,
+        total_hits: Float,
+        effective_misses: Float,
+    ) -> Float {
+        if self.mods.rx() {
+            return 0.0;
+        }
+
+        let mut speed_value =
+            (5.0 * (attributes.speed_strain / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+
+        // Longer maps are worth more
+        let len_bonus = 0.95
+            + 0.4 * (total_hits / 2000.0).min(1.0)
+            + (total_hits > 2000.0) as u8 as Float * 0.5 * (total_hits / 2000.0).log10();
+        speed_value *= len_bonus;
+
+        // Penalize misses
+        if effective_misses > 0.0 {
+            speed_value *= miss_penalty(effective_misses, attributes.speed_difficult_strain_count);
+        }
+
+        // AR bonus
+        let ar_factor = if attributes.ar > 10.33 {
+            0.3 * (attributes.ar - 10.33)
+        } else {
+            0.0
+        };
+
+        speed_value *= 1.0 + ar_factor * len_bonus;
+
+        // HD bonus
+        if self.mods.hd() {
+            speed_value *= 1.0 + 0.04 * (12.0 - attributes.ar);
+        }
+
+        // Accuracy on notes that are relevant for the speed strain
+        let n300 = self.n300.unwrap_or(0) as Float;
+        let n100 = self.n100.unwrap_or(0) as Float;
+        let n50 = self.n50.unwrap_or(0) as Float;
+
+        let relevant_total_diff = total_hits - attributes.speed_note_count;
+        let relevant_n300 = (n300 - relevant_total_diff).max(0.0);
+        let relevant_n100 = (n100 - (relevant_total_diff - n300).max(0.0)).max(0.0);
+        let relevant_n50 = (n50 - (relevant_total_diff - n300 - n100).max(0.0)).max(0.0);
+
+        let relevant_acc = if attributes.speed_note_count > 0.0 {
+            (relevant_n300 * 6.0 + relevant_n100 * 2.0 + relevant_n50)
+                / (attributes.speed_note_count * 6.0)
+        } else {
+            0.0
+        };
+
+        // Scale the speed value with accuracy and OD
+        let od_factor = 0.95 + attributes.od * attributes.od / 750.0;
+        let acc_factor =
+            ((self.acc.unwrap_or(0.0) + relevant_acc) / 2.0).powf((14.5 - attributes.od) / 2.0);
+        speed_value *= od_factor * acc_factor;
+
+        // Penalize n50s
+        speed_value *= (0.99 as Float)
+            .powf((n50 >= total_hits / 500.0) as u8 as Float * (n50 - total_hits / 500.0));
+
+        speed_value
+    }
+
+    fn compute_accuracy_value_v2024(
+        &self,
+        attributes: &DifficultyAttributes,
+        total_hits: Float,
+    ) -> Float {
+        if self.mods.rx() {
+            return 0.0;
+        }
+
+        // ScoreV2 also judges slider heads
+        let n_hits_with_acc = if self.mods.v2() {
+            (attributes.n_circles + attributes.n_sliders) as Float
+        } else {
+            attributes.n_circles as Float
+        };
+
+        let n300 = self.n300.unwrap_or(0) as Float;
+        let n100 = self.n100.unwrap_or(0) as Float;
+        let n50 = self.n50.unwrap_or(0) as Float;
+
+        let better_acc_percentage = (n_hits_with_acc > 0.0) as u8 as Float
+            * (((n300 - (total_hits - n_hits_with_acc)) * 6.0 + n100 * 2.0 + n50)
+                / (n_hits_with_acc * 6.0))
+                .max(0.0);
+
+        let mut acc_value =
+            (1.52163 as Float).powf(attributes.od) * better_acc_percentage.powi(24) * 2.83;
+
+        // Bonus for many hitcircles
+        acc_value *= ((n_hits_with_acc / 1000.0).powf(0.3)).min(1.15);
+
+        // HD bonus
+        if self.mods.hd() {
+            acc_value *= 1.08;
+        }
+
+        // FL bonus
+        if self.mods.fl() {
+            acc_value *= 1.02;
+        }
+
+        acc_value
+    }
+
+    fn compute_flashlight_value_v2024(
+        &self,
+        attributes: &DifficultyAttributes,
+        total_hits: Float,
+        effective_misses: Float,
+    ) -> Float {
+        if !self.mods.fl() {
+            return 0.0;
+        }
+
+        // TD and HD are already considered in the attributes
+        let mut flashlight_value =
+            attributes.flashlight_strain * attributes.flashlight_strain * 25.0;
+
+        // Penalize misses
+        if effective_misses > 0.0 {
+            flashlight_value *= 0.97
+                * (1.0 - (effective_misses / total_hits).powf(0.775))
+                    .powf(effective_misses.powf(0.875));
+        }
+
+        // Combo scaling
+        if let Some(combo) = self.combo.filter(|_| attributes.max_combo > 0) {
+            flashlight_value *=
+                ((combo as Float / attributes.max_combo as Float).powf(0.8)).min(1.0);
+        }
+
+        // Account for shorter maps having a higher ratio of 0 combo/100 combo flashlight radius
+        flashlight_value *= 0.7
+            + 0.1 * (total_hits / 200.0).min(1.0)
+            + (total_hits > 200.0) as u8 as Float * 0.2 * ((total_hits - 200.0) / 200.0).min(1.0);
+
+        // Scale with accuracy
+        flashlight_value *= 0.5 + self.acc.unwrap_or(0.0) / 2.0;
+        flashlight_value *= 0.98 + attributes.od * attributes.od / 2500.0;
+
+        flashlight_value
+    }
+
+    /// Misses including an estimation of dropped slider ends and sliderbreaks
+    /// based on the combo.
+    fn effective_miss_count(&self, attributes: &DifficultyAttributes) -> Float {
+        let mut combo_based_misses = 0.0;
+
+        if attributes.n_sliders > 0 {
+            let full_combo_threshold =
+                attributes.max_combo as Float - 0.1 * attributes.n_sliders as Float;
+            let combo = self.combo.unwrap_or(attributes.max_combo) as Float;
+
+            if combo < full_combo_threshold {
+                combo_based_misses = full_combo_threshold / combo.max(1.0);
+            }
+        }
+
+        let n_not_great = self
+            .n100
+            .unwrap_or(0)
+            .saturating_add(self.n50.unwrap_or(0))
+            .saturating_add(self.n_misses);
+        combo_based_misses = combo_based_misses.min(n_not_great as Float);
+
+        combo_based_misses.max(self.n_misses as Float)
+    }
+}
+
+/// Penalty for misses w.r.t. the amount of difficult strains.
+#[cfg(feature = "all_included")]
+#[inline]
+fn miss_penalty(misses: Float, difficult_strain_count: Float) -> Float {
+    0.96 / (misses / (4.0 * difficult_strain_count.max(1.0).ln().powf(0.94)) + 1.0)
+}
+
+type StarsFunc = fn(&Beatmap, u32, Option<usize>, DifficultyParams) -> StarResult;
+
+/// The star calculation of the given version.
+#[cfg(feature = "no_leniency")]
+#[inline]
+fn stars_func(_: AlgorithmVersion) -> StarsFunc {
+    super::no_leniency::stars_with_params
+}
+
+/// The star calculation of the given version.
+#[cfg(feature = "no_sliders_no_leniency")]
+#[inline]
+fn stars_func(_: AlgorithmVersion) -> StarsFunc {
+    super::no_sliders_no_leniency::stars_with_params
+}
+
+/// The star calculation of the given version.
+#[cfg(feature = "all_included")]
+#[inline]
+fn stars_func(version: AlgorithmVersion) -> StarsFunc {
+    match version.resolve(GameMode::STD) {
+        AlgorithmVersion::V2021 | AlgorithmVersion::V2022 => super::all_included::stars_with_params,
+        AlgorithmVersion::V2024 => super::v2024::stars_with_params,
+    }
+}
+
+// Omits an unnecessary error when enabled features are invalid
+#[cfg(not(any(
+    feature = "no_leniency",
+    feature = "no_sliders_no_leniency",
+    feature = "all_included"
+)))]
+fn stars_func(_: AlgorithmVersion) -> StarsFunc {
+    unreachable!()
 }
 
 pub trait OsuAttributeProvider {
@@ -731,7 +1364,7 @@ mod test {
             + 2 * calculator.n100.unwrap_or(0)
             + calculator.n50.unwrap_or(0);
         let denominator = 6 * total_objects;
-        let acc = 100.0 * numerator as f32 / denominator as f32;
+        let acc = 100.0 * numerator as Float / denominator as Float;
 
         assert!(
             (target_acc - acc).abs() < 1.0,
@@ -765,7 +1398,7 @@ mod test {
             + 2 * calculator.n100.unwrap_or(0)
             + calculator.n50.unwrap_or(0);
         let denominator = 6 * total_objects;
-        let acc = 100.0 * numerator as f32 / denominator as f32;
+        let acc = 100.0 * numerator as Float / denominator as Float;
 
         assert!(
             (target_acc - acc).abs() < 1.0,
@@ -803,4 +1436,283 @@ mod test {
             total_objects, n_objects
         );
     }
+
+    #[test]
+    fn osu_accuracies_any_order() {
+        let map = Beatmap::default();
+
+        let attributes = DifficultyAttributes {
+            stars: 5.0,
+            aim_strain: 2.5,
+            speed_strain: 2.3,
+            ar: 9.3,
+            od: 8.5,
+            max_combo: 1500,
+            n_circles: 1000,
+            ..Default::default()
+        };
+
+        let accs = [95.0, 100.0, 98.0];
+
+        let results = OsuPP::new(&map)
+            .attributes(attributes.clone())
+            .passed_objects(1234)
+            .n50(5)
+            .misses(2)
+            .calculate_accuracies(&accs);
+
+        for (&acc, result) in accs.iter().zip(results.iter()) {
+            let expected = OsuPP::new(&map)
+                .attributes(attributes.clone())
+                .passed_objects(1234)
+                .n50(5)
+                .misses(2)
+                .accuracy(acc)
+                .calculate();
+
+            assert!(result.pp() > 0.0);
+            assert_eq!(result.pp(), expected.pp(), "Accuracy: {}", acc);
+        }
+    }
+
+    #[test]
+    fn osu_hitresult_priority() {
+        let map = Beatmap::default();
+
+        let calculator = |priority| {
+            OsuPP::new(&map)
+                .passed_objects(1234)
+                .misses(3)
+                .hitresult_priority(priority)
+                .accuracy(91.5)
+        };
+
+        let best = calculator(HitResultPriority::BestCase);
+        let worst = calculator(HitResultPriority::WorstCase);
+
+        assert_eq!(best.acc, worst.acc);
+        assert!(best.n50.unwrap() < worst.n50.unwrap());
+        assert!(best.n100.unwrap() > worst.n100.unwrap());
+    }
+
+    #[test]
+    fn osu_accuracy_for_pp() {
+        let map = Beatmap::default();
+
+        let attributes = DifficultyAttributes {
+            stars: 5.0,
+            aim_strain: 2.5,
+            speed_strain: 2.3,
+            ar: 9.3,
+            od: 8.5,
+            max_combo: 1500,
+            n_circles: 1000,
+            ..Default::default()
+        };
+
+        let mut calculator = OsuPP::new(&map)
+            .attributes(attributes)
+            .passed_objects(1234)
+            .misses(1);
+
+        let target = calculator.clone().accuracy(97.0).calculate().pp();
+        let acc = calculator.accuracy_for_pp(target).unwrap();
+
+        assert!(acc <= 97.0, "{}", acc);
+        assert!(calculator.clone().accuracy(acc).calculate().pp() >= target);
+        assert!(calculator.clone().accuracy(acc - 0.1).calculate().pp() < target);
+
+        let max_pp = calculator.clone().accuracy(100.0).calculate().pp();
+        assert_eq!(calculator.accuracy_for_pp(max_pp + 1.0), None);
+    }
+
+    #[test]
+    fn osu_if_fc() {
+        let map = Beatmap::default();
+
+        let attributes = DifficultyAttributes {
+            stars: 5.0,
+            aim_strain: 2.5,
+            speed_strain: 2.3,
+            ar: 9.3,
+            od: 8.5,
+            max_combo: 1500,
+            n_circles: 1000,
+            ..Default::default()
+        };
+
+        let calculator = OsuPP::new(&map)
+            .attributes(attributes.clone())
+            .passed_objects(1234)
+            .combo(700)
+            .n100(20)
+            .misses(3);
+
+        let IfFcResult { current, if_fc } = calculator.clone().calculate_if_fc();
+
+        let fc = OsuPP::new(&map)
+            .attributes(attributes)
+            .passed_objects(1234)
+            .combo(1500)
+            .n300(1234 - 20)
+            .n100(20)
+            .calculate();
+
+        assert_eq!(current.pp(), calculator.clone().calculate().pp());
+        assert_eq!(if_fc.pp(), fc.pp());
+        assert!(if_fc.pp() > current.pp());
+    }
+
+    #[test]
+    fn osu_if_fc_keeps_hitresults() {
+        let map = Beatmap::default();
+
+        let attributes = DifficultyAttributes {
+            stars: 5.0,
+            aim_strain: 2.5,
+            speed_strain: 2.3,
+            ar: 9.3,
+            od: 8.5,
+            max_combo: 1500,
+            n_circles: 1000,
+            ..Default::default()
+        };
+
+        let mut calculator = OsuPP::new(&map)
+            .attributes(attributes)
+            .passed_objects(1234)
+            .combo(700)
+            .n300(1000)
+            .misses(3);
+
+        let mut plain = calculator.clone();
+        let expected = plain.calculate();
+
+        let IfFcResult { current, .. } = calculator.calculate_if_fc();
+
+        assert_eq!(current.pp(), expected.pp());
+        assert_eq!(calculator.calculate().pp(), expected.pp());
+        assert_eq!(calculator.n300, plain.n300);
+        assert_eq!(calculator.n100, plain.n100);
+        assert_eq!(calculator.n50, plain.n50);
+        assert_eq!(calculator.n_misses, plain.n_misses);
+        assert_eq!(calculator.combo, plain.combo);
+        assert_eq!(calculator.acc, plain.acc);
+    }
+
+    #[test]
+    fn osu_saturating_hit_counts() {
+        let map = Beatmap::default();
+        let max = usize::MAX;
+
+        let attributes = DifficultyAttributes {
+            stars: 5.0,
+            aim_strain: 2.5,
+            speed_strain: 2.3,
+            ar: 9.3,
+            od: 8.5,
+            max_combo: 1500,
+            n_circles: 1000,
+            n_sliders: 234,
+            ..Default::default()
+        };
+
+        let mut calculator = OsuPP::new(&map)
+            .attributes(attributes.clone())
+            .passed_objects(1234)
+            .n300(max)
+            .n100(max)
+            .n50(max)
+            .misses(max);
+
+        assert_eq!(calculator.total_hits(), 1234);
+
+        // Nonsensical input only has to not panic
+        let _ = calculator.clone().calculate();
+        let _ = calculator.calculate_if_fc();
+
+        let mut calculator = OsuPP::new(&map)
+            .attributes(attributes)
+            .passed_objects(1234)
+            .n100(max)
+            .n50(max);
+
+        calculator.set_accuracy(95.0);
+        let _ = calculator.calculate();
+    }
+
+    #[test]
+    fn osu_grade() {
+        let map = Beatmap::default();
+
+        let attributes = DifficultyAttributes {
+            stars: 5.0,
+            max_combo: 150,
+            n_circles: 100,
+            ..Default::default()
+        };
+
+        let calculator = OsuPP::new(&map)
+            .attributes(attributes)
+            .passed_objects(100)
+            .n100(5);
+
+        assert_eq!(calculator.clone().calculate().grade, Grade::S);
+        assert_eq!(calculator.clone().mods(8).calculate().grade, Grade::SH);
+        assert_eq!(calculator.misses(1).calculate().grade, Grade::A);
+    }
+
+    #[test]
+    fn osu_flashlight_skill() {
+        let map = crate::test_util::map(GameMode::STD);
+
+        let flashlight_strain = |mods: u32| match OsuPP::new(&map).mods(mods).calculate().attributes
+        {
+            StarResult::Osu(attributes) => attributes.flashlight_strain,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        };
+
+        let nomod = flashlight_strain(0);
+        let fl = flashlight_strain(1024);
+        let hdfl = flashlight_strain(1024 + 8);
+
+        assert_eq!(nomod, 0.0);
+        assert!(fl > 0.0);
+        assert!(hdfl > fl);
+    }
+
+    #[test]
+    fn osu_flashlight_pp() {
+        let map = crate::test_util::map(GameMode::STD);
+
+        let calculate = |mods: u32, version: AlgorithmVersion| {
+            OsuPP::new(&map)
+                .mods(mods)
+                .version(version)
+                .accuracy(98.0)
+                .calculate()
+        };
+
+        // The aim bonus of FL until V2022
+        let nomod = calculate(0, AlgorithmVersion::V2021);
+        let fl = calculate(1024, AlgorithmVersion::V2021);
+
+        assert_eq!(fl.version, AlgorithmVersion::V2021);
+        assert_eq!(fl.raw.fl, None);
+        assert!(fl.raw.aim.unwrap() > nomod.raw.aim.unwrap());
+        assert_eq!(fl.raw.spd, nomod.raw.spd);
+
+        // The flashlight skill since V2022
+        let nomod = calculate(0, AlgorithmVersion::V2022);
+        let fl = calculate(1024, AlgorithmVersion::V2022);
+        let hdfl = calculate(1024 + 8, AlgorithmVersion::V2022);
+
+        assert_eq!(fl.version, AlgorithmVersion::V2022);
+        assert_eq!(nomod.raw.fl, None);
+        assert_eq!(fl.raw.aim, nomod.raw.aim);
+        assert!(fl.raw.fl.unwrap() > 0.0);
+        assert!(fl.pp() > nomod.pp());
+        assert!(hdfl.raw.fl.unwrap() > fl.raw.fl.unwrap());
+    }
 }
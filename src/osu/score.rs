@@ -0,0 +1,486 @@
+use crate::{
+    parse::HitObjectKind,
+    score::{self, ScoreVersion, SCORE_V2_MAX},
+    Beatmap, ControlPoint, ControlPointIter, DifficultyParams, Float, GameMode, Mods,
+};
+
+const CIRCLE_VALUE: Float = 300.0;
+const SLIDER_EDGE_VALUE: Float = 30.0;
+const SLIDER_TICK_VALUE: Float = 10.0;
+const SPIN_VALUE: Float = 100.0;
+/// On top of the value of the spin itself.
+const SPIN_BONUS_VALUE: Float = 1000.0;
+
+/// Highest amount of rotations per minute that count towards a spinner.
+const MAX_SPINNER_RPM: f64 = 477.0;
+
+/// Score calculator for osu!standard maps.
+///
+/// The ScoreV1 max score is simulated object by object the way osu!stable awards it.
+/// Since the order of hits is unknown for other plays, their combo bonus is an estimate
+/// based on the combo and the amount of misses, and ScoreV2 is approximated in general,
+/// see [`ScoreVersion`].
+/// Spinners are always assumed to be spun as fast as possible.
+///
+/// # Example
+///
+/// ```no_run
+/// use peace_performance::{Beatmap, OsuScore, ScoreVersion};
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// let max_score = OsuScore::new(&map).mods(8 + 64).max_score();
+///
+/// let score = OsuScore::new(&map)
+///     .version(ScoreVersion::V2)
+///     .combo(1234)
+///     .n100(12)
+///     .misses(1)
+///     .calculate();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OsuScore<'m> {
+    map: &'m Beatmap,
+    mods: u32,
+    version: ScoreVersion,
+    combo: Option<usize>,
+    n300: Option<usize>,
+    n100: Option<usize>,
+    n50: Option<usize>,
+    n_misses: usize,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+}
+
+impl<'m> OsuScore<'m> {
+    #[inline]
+    pub fn new(map: &'m Beatmap) -> Self {
+        Self {
+            map,
+            mods: 0,
+            version: ScoreVersion::default(),
+            combo: None,
+            n300: None,
+            n100: None,
+            n50: None,
+            n_misses: 0,
+            passed_objects: None,
+            params: DifficultyParams::default(),
+        }
+    }
+
+    /// [`Mods`](crate::Mods) interface.
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods.for_mode(GameMode::STD);
+
+        self
+    }
+
+    /// Specify the scoring system, defaults to [`ScoreVersion::V1`].
+    #[inline]
+    pub fn version(mut self, version: ScoreVersion) -> Self {
+        self.version = version;
+
+        self
+    }
+
+    /// Specify the max combo of the play.
+    #[inline]
+    pub fn combo(mut self, combo: usize) -> Self {
+        self.combo.replace(combo);
+
+        self
+    }
+
+    /// Specify the amount of 300s of a play.
+    #[inline]
+    pub fn n300(mut self, n300: usize) -> Self {
+        self.n300.replace(n300);
+
+        self
+    }
+
+    /// Specify the amount of 100s of a play.
+    #[inline]
+    pub fn n100(mut self, n100: usize) -> Self {
+        self.n100.replace(n100);
+
+        self
+    }
+
+    /// Specify the amount of 50s of a play.
+    #[inline]
+    pub fn n50(mut self, n50: usize) -> Self {
+        self.n50.replace(n50);
+
+        self
+    }
+
+    /// Specify the amount of misses of a play.
+    #[inline]
+    pub fn misses(mut self, n_misses: usize) -> Self {
+        self.n_misses = n_misses;
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects.replace(passed_objects);
+
+        self
+    }
+
+    /// Specify a custom clock rate that overrides the one of the mods.
+    ///
+    /// It determines how many rotations fit into a spinner.
+    /// Invalid rates are ignored or clamped, see [`DifficultyParams::clock_rate`](crate::DifficultyParams::clock_rate).
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: Float) -> Self {
+        self.params = self.params.clock_rate(clock_rate);
+
+        self
+    }
+
+    /// Calculate the score of a full combo with only 300s.
+    pub fn max_score(&self) -> u64 {
+        let points = self.simulate();
+        let multiplier = self.mods.score_multiplier(GameMode::STD, self.version);
+
+        let score = match self.version {
+            ScoreVersion::V1 => {
+                points.hits + points.combo_bonus + points.flat + points.spins + points.spin_bonus
+            }
+            ScoreVersion::V2 => SCORE_V2_MAX * multiplier + points.spin_bonus,
+        };
+
+        score as u64
+    }
+
+    /// Calculate the score of the specified play.
+    ///
+    /// Unspecified 300s fill up the remaining objects and an unspecified combo
+    /// is considered to be the max combo minus the misses.
+    ///
+    /// Only the score of a full combo with only 300s is exact, the combo bonus of
+    /// other plays is estimated by spreading the remaining combo evenly between the misses.
+    pub fn calculate(&self) -> u64 {
+        let points = self.simulate();
+        let n_objects = points.n_objects;
+
+        let n_misses = self.n_misses.min(n_objects);
+        let n100 = self.n100.unwrap_or(0).min(n_objects - n_misses);
+        let n50 = self.n50.unwrap_or(0).min(n_objects - n_misses - n100);
+        let remaining = n_objects - n_misses - n100 - n50;
+        let n300 = self.n300.map_or(remaining, |n300| n300.min(remaining));
+
+        let n_hits = n300 + n100 + n50;
+        let hit_points = CIRCLE_VALUE * n300 as Float + 100.0 * n100 as Float + 50.0 * n50 as Float;
+
+        let max_combo = points.max_combo;
+        let combo = self
+            .combo
+            .unwrap_or_else(|| max_combo.saturating_sub(n_misses));
+        let chains = score::combo_chains(combo, max_combo, n_misses);
+
+        let combo_ratio =
+            score::combo_bonus_ratio(&chains, max_combo, |combo| combo.saturating_sub(1) as Float);
+
+        // The combo bonus of a hit scales with its value
+        let value_ratio = if n_hits == 0 {
+            0.0
+        } else {
+            hit_points / (CIRCLE_VALUE * n_hits as Float)
+        };
+
+        let score = match self.version {
+            ScoreVersion::V1 => {
+                let hit_ratio = n_hits as Float / n_objects.max(1) as Float;
+
+                hit_points
+                    + points.combo_bonus * combo_ratio * value_ratio
+                    + points.flat * hit_ratio
+                    + points.spins
+                    + points.spin_bonus
+            }
+            ScoreVersion::V2 => {
+                let n_judged = n_hits + n_misses;
+
+                let acc = if n_judged == 0 {
+                    0.0
+                } else {
+                    hit_points / (CIRCLE_VALUE * n_judged as Float)
+                };
+
+                let progress = n_judged as Float / self.map.hit_objects.len().max(1) as Float;
+                let combo_ratio_v2 = score::score_v2_combo_ratio(&chains, max_combo);
+                let multiplier = self.mods.score_multiplier(GameMode::STD, self.version);
+
+                score::score_v2(combo_ratio_v2 * value_ratio, acc, progress) * multiplier
+                    + points.spin_bonus
+            }
+        };
+
+        score as u64
+    }
+
+    /// Simulate a full combo with only 300s on the passed objects.
+    ///
+    /// Computed in `f64` like in the game.
+    fn simulate(&self) -> ScorePoints {
+        let map = self.map;
+        let n_objects = self.passed_objects.unwrap_or(map.hit_objects.len());
+
+        let difficulty_multiplier = score::difficulty_multiplier(map) as f64;
+        let mod_multiplier = self.mods.score_multiplier(GameMode::STD, ScoreVersion::V1) as f64;
+        let score_multiplier = difficulty_multiplier * mod_multiplier;
+
+        let od = (map.od * self.mods.od_ar_hp_multiplier()).min(10.0);
+        let clock_rate = self.params.clock_rate_with(self.mods) as f64;
+
+        let mut ticks = TickState::new(map);
+        let mut points = ScorePoints::default();
+        let mut combo = 0;
+
+        for h in map.hit_objects.iter().take(n_objects) {
+            match &h.kind {
+                HitObjectKind::Circle => {}
+                HitObjectKind::Slider {
+                    pixel_len, repeats, ..
+                } => {
+                    let ticks_per_span = ticks.ticks_per_span(h.start_time, *pixel_len, *repeats);
+                    let n_edges = *repeats + 1;
+                    let n_ticks = ticks_per_span * *repeats;
+
+                    // Head, repeats, tail, and ticks only give flat points but increase the combo
+                    points.flat +=
+                        SLIDER_EDGE_VALUE * n_edges as Float + SLIDER_TICK_VALUE * n_ticks as Float;
+                    combo += n_edges + n_ticks;
+                }
+                HitObjectKind::Spinner { end_time } => {
+                    let secs = (*end_time - h.start_time) as f64 / 1000.0 / clock_rate;
+                    let (spins, bonus_spins) = spinner_rotations(secs, od as f64);
+
+                    points.spins += SPIN_VALUE * (spins + bonus_spins) as Float;
+                    points.spin_bonus += SPIN_BONUS_VALUE * bonus_spins as Float;
+                }
+                HitObjectKind::Hold { .. } => {}
+            }
+
+            // The 300 of circles, sliders, and spinners with the combo before it
+            let bonus_per_combo = (CIRCLE_VALUE as u32 / 25) as f64 * score_multiplier;
+            let bonus = (combo.saturating_sub(1) as f64 * bonus_per_combo) as u64;
+
+            points.hits += CIRCLE_VALUE;
+            points.combo_bonus += bonus as Float;
+
+            // Sliders already increased the combo through their tail
+            if !matches!(h.kind, HitObjectKind::Slider { .. }) {
+                combo += 1;
+            }
+        }
+
+        points.n_objects = n_objects.min(map.hit_objects.len());
+        points.max_combo = combo;
+
+        points
+    }
+}
+
+/// Points of a full combo with only 300s.
+#[derive(Default)]
+struct ScorePoints {
+    n_objects: usize,
+    max_combo: usize,
+    /// Points of the judgements without their combo bonus.
+    hits: Float,
+    /// ScoreV1 combo bonus of the judgements.
+    combo_bonus: Float,
+    /// Points of slider heads, repeats, tails, and ticks.
+    flat: Float,
+    /// Points of all spinner rotations.
+    spins: Float,
+    /// Additional points of the bonus spinner rotations.
+    spin_bonus: Float,
+}
+
+/// The amount of rotations and bonus rotations that award points
+/// for a spinner of the given length that is spun as fast as possible.
+fn spinner_rotations(secs: f64, od: f64) -> (usize, usize) {
+    let min_rps = if od > 5.0 {
+        5.0 + (od - 5.0) * 0.5
+    } else {
+        5.0 - (5.0 - od) * 0.4
+    };
+
+    // osu!stable counts half spins but requires the minimum amount of full spins in half spins
+    let total_half_spins = (secs * MAX_SPINNER_RPM / 60.0 * 2.0).max(0.0) as usize;
+    let half_spins_before_bonus = (secs * min_rps).max(0.0) as usize + 3;
+
+    // Every second half spin after the required ones is a bonus spin
+    let bonus_spins = (half_spins_before_bonus + 2..=total_half_spins)
+        .step_by(2)
+        .count();
+
+    // Spins are given on even half spins unless those are bonus spins
+    let spins = if half_spins_before_bonus % 2 == 1 {
+        total_half_spins / 2
+    } else {
+        total_half_spins.min(half_spins_before_bonus) / 2
+    };
+
+    (spins, bonus_spins)
+}
+
+struct TickState<'m> {
+    map: &'m Beatmap,
+    control_points: ControlPointIter<'m>,
+    next: Option<ControlPoint>,
+    speed_mult: Float,
+}
+
+impl<'m> TickState<'m> {
+    #[inline]
+    fn new(map: &'m Beatmap) -> Self {
+        let mut control_points = ControlPointIter::new(map);
+
+        Self {
+            map,
+            next: control_points.next(),
+            control_points,
+            speed_mult: 1.0,
+        }
+    }
+
+    /// The amount of ticks on each span of a slider.
+    fn ticks_per_span(&mut self, time: Float, pixel_len: Float, spans: usize) -> usize {
+        while let Some(next) = self.next.as_ref().filter(|n| time >= n.time()) {
+            self.speed_mult = match next {
+                ControlPoint::Timing { .. } => 1.0,
+                ControlPoint::Difficulty { speed_mult, .. } => *speed_mult,
+            };
+
+            self.next = self.control_points.next();
+        }
+
+        let px_per_beat = self.map.sv * 100.0 * self.speed_mult;
+        let spans = spans.max(1) as Float;
+        let beats = pixel_len * spans / px_per_beat;
+        let ticks = ((beats - 0.1) / spans * self.map.tick_rate).ceil();
+
+        (ticks.max(1.0) as usize) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse::{HitObject, Pos2},
+        test_util,
+    };
+
+    #[test]
+    fn osu_score_spinner_rotations() {
+        assert_eq!(spinner_rotations(1.0, 5.0), (4, 3));
+        assert_eq!(spinner_rotations(2.0, 10.0), (9, 6));
+        assert_eq!(spinner_rotations(0.0, 5.0), (0, 0));
+    }
+
+    #[test]
+    fn osu_score_circles() {
+        let hit_objects = (0..100)
+            .map(|i| HitObject {
+                pos: Pos2::default(),
+                start_time: i as Float * 1000.0,
+                kind: HitObjectKind::Circle,
+                sound: 0,
+            })
+            .collect();
+
+        let map = Beatmap {
+            hit_objects,
+            hp: 5.0,
+            cs: 4.0,
+            od: 8.0,
+            ..Default::default()
+        };
+
+        // (5 + 4 + 8 + 8) / 38 * 5 rounds to 3 so each combo gives 300 / 25 * 3 = 36
+        let bonus = 36 * (1..99).sum::<u64>();
+        let max_score = OsuScore::new(&map).max_score();
+        assert_eq!(max_score, 30_000 + bonus);
+
+        // HD and HR multiply the bonus by 1.06 * 1.06 before truncating it
+        let bonus_hdhr: u64 = (1..99)
+            .map(|combo| (combo as f64 * 12.0 * 3.0 * 1.06 * 1.06) as u64)
+            .sum();
+        let max_score_hdhr = OsuScore::new(&map).mods(8 + 16).max_score();
+        assert_eq!(max_score_hdhr, 30_000 + bonus_hdhr);
+
+        // Relax gives no combo bonus
+        assert_eq!(OsuScore::new(&map).mods(128).max_score(), 30_000);
+
+        let score = OsuScore::new(&map).n100(10).misses(2).combo(50).calculate();
+        assert!(score < max_score);
+
+        let max_score_v2 = OsuScore::new(&map).version(ScoreVersion::V2).max_score();
+        assert_eq!(max_score_v2, 1_000_000);
+
+        let score_v2 = OsuScore::new(&map).version(ScoreVersion::V2).calculate();
+        assert_eq!(score_v2, 1_000_000);
+    }
+
+    fn parse_objects(hit_objects: &str) -> Beatmap {
+        let content = format!(
+            "osu file format v14\n\n\
+            [Difficulty]\n\
+            HPDrainRate:5\n\
+            CircleSize:4\n\
+            OverallDifficulty:8\n\
+            ApproachRate:9\n\
+            SliderMultiplier:1.4\n\
+            SliderTickRate:1\n\n\
+            [TimingPoints]\n\
+            0,500,4,2,0,100,1,0\n\n\
+            [HitObjects]\n{}",
+            hit_objects
+        );
+
+        test_util::parse_str(&content)
+    }
+
+    #[test]
+    fn osu_score_slider() {
+        // Two spans of two beats with one tick each
+        let map = parse_objects(
+            "0,0,0,1,0,0:0:0:0:\n\
+            0,0,1000,2,0,L|280:0,2,280\n\
+            0,0,4000,1,0,0:0:0:0:\n",
+        );
+
+        // Circle: 300 at combo 0
+        // Slider: 3 * 30 + 2 * 10 increasing the combo to 6, then 300 + 36 * (6 - 1)
+        // Circle: 300 + 36 * (6 - 1)
+        let expected = 300 + 110 + 300 + 180 + 300 + 180;
+        assert_eq!(OsuScore::new(&map).max_score(), expected);
+    }
+
+    #[test]
+    fn osu_score_clock_rate() {
+        let map = parse_objects("256,192,0,12,0,3000\n");
+
+        let nomod = OsuScore::new(&map).max_score();
+        let dt = OsuScore::new(&map).mods(64).max_score();
+        let rate = OsuScore::new(&map).mods(64).clock_rate(1.0).max_score();
+
+        // 2s of DT: 31 half spins with 13 required, i.e. 8 spins and 7 bonus spins
+        assert_eq!(dt, 300 + 100 * 15 + 1000 * 7);
+        assert_eq!(rate, nomod);
+        assert_eq!(OsuScore::new(&map).clock_rate(1.5).max_score(), dt);
+    }
+}
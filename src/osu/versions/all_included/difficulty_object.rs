@@ -1,24 +1,25 @@
 use super::OsuObject;
+use crate::Float;
 
 pub(crate) struct DifficultyObject<'h> {
     pub(crate) base: &'h OsuObject,
-    pub(crate) prev: Option<(f32, f32)>, // (jump_dist, strain_time)
+    pub(crate) prev: Option<(Float, Float)>, // (jump_dist, strain_time)
 
-    pub(crate) jump_dist: f32,
-    pub(crate) travel_dist: f32,
-    pub(crate) angle: Option<f32>,
+    pub(crate) jump_dist: Float,
+    pub(crate) travel_dist: Float,
+    pub(crate) angle: Option<Float>,
 
-    pub(crate) delta: f32,
-    pub(crate) strain_time: f32,
+    pub(crate) delta: Float,
+    pub(crate) strain_time: Float,
 }
 
 impl<'h> DifficultyObject<'h> {
     pub(crate) fn new(
         base: &'h OsuObject,
         prev: &OsuObject,
-        prev_vals: Option<(f32, f32)>, // (jump_dist, strain_time)
+        prev_vals: Option<(Float, Float)>, // (jump_dist, strain_time)
         prev_prev: Option<OsuObject>,
-        scaling_factor: f32,
+        scaling_factor: Float,
     ) -> Self {
         let delta = base.time - prev.time;
         let strain_time = delta.max(50.0);
@@ -4,7 +4,7 @@
 
 #![cfg(feature = "all_included")]
 
-use super::super::DifficultyAttributes;
+use super::super::{DifficultyAttributes, ObjectDifficulty};
 use crate::parse::Pos2;
 
 mod difficulty_object;
@@ -16,16 +16,19 @@ mod slider_state;
 use difficulty_object::DifficultyObject;
 use osu_object::OsuObject;
 use skill::Skill;
-use skill_kind::SkillKind;
+use skill_kind::{PrevObject, SkillKind};
 use slider_state::SliderState;
 
-use crate::{Beatmap, Mods, StarResult, Strains};
+use crate::{
+    context::Pool, Beatmap, CalcContext, DifficultyParams, Float, GameMode, Mods, StarResult,
+    Strains,
+};
 
-const OBJECT_RADIUS: f32 = 64.0;
-const SECTION_LEN: f32 = 400.0;
-const DIFFICULTY_MULTIPLIER: f32 = 0.0675;
-const NORMALIZED_RADIUS: f32 = 52.0;
-const STACK_DISTANCE: f32 = 3.0;
+const OBJECT_RADIUS: Float = 64.0;
+const SECTION_LEN: Float = 400.0;
+const DIFFICULTY_MULTIPLIER: Float = 0.0675;
+const NORMALIZED_RADIUS: Float = 52.0;
+const STACK_DISTANCE: Float = 3.0;
 
 /// Star calculation for osu!standard maps.
 ///
@@ -35,16 +38,44 @@ const STACK_DISTANCE: f32 = 3.0;
 /// most precise results.
 ///
 /// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+#[inline]
 pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> StarResult {
-    let take = passed_objects.unwrap_or_else(|| map.hit_objects.len());
+    stars_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `stars` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn stars_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> StarResult {
+    stars_with_context(map, mods, passed_objects, params, &mut CalcContext::new())
+}
 
-    let map_attributes = map.attributes().mods(mods);
+/// Same as the `stars_with_params` function but reuses the buffers of the given context.
+pub fn stars_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> StarResult {
+    let mods = mods.for_mode(GameMode::STD);
+    let take = passed_objects
+        .unwrap_or_else(|| map.hit_objects.len())
+        .min(map.hit_objects.len());
+
+    let map_attributes = map.attributes().mods_with_params(mods, params);
     let hitwindow = super::difficulty_range(map_attributes.od).floor() / map_attributes.clock_rate;
     let od = (80.0 - hitwindow) / 6.0;
 
     let mut diff_attributes = DifficultyAttributes {
         ar: map_attributes.ar,
         od,
+        slider_factor: 1.0,
         ..Default::default()
     };
 
@@ -52,125 +83,34 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
         return StarResult::Osu(diff_attributes);
     }
 
-    let mut raw_ar = map.ar;
-    let hr = mods.hr();
-
-    if hr {
-        raw_ar = (raw_ar * 1.4).min(10.0);
-    } else if mods.ez() {
-        raw_ar *= 0.5;
-    }
-
-    let time_preempt = difficulty_range_ar(raw_ar);
-    let scale = (1.0 - 0.7 * (map_attributes.cs - 5.0) / 5.0) / 2.0;
-    let radius = OBJECT_RADIUS * scale;
-    let mut scaling_factor = NORMALIZED_RADIUS / radius;
-
-    if radius < 30.0 {
-        let small_circle_bonus = (30.0 - radius).min(5.0) / 50.0;
-        scaling_factor *= 1.0 + small_circle_bonus;
-    }
-
-    let mut slider_state = SliderState::new(map);
-    let mut ticks_buf = Vec::new();
-
-    let hit_objects_iter = map.hit_objects.iter().take(take).filter_map(|h| {
-        OsuObject::new(
-            h,
-            map,
-            radius,
-            scaling_factor,
-            hr,
-            &mut ticks_buf,
-            &mut diff_attributes,
-            &mut slider_state,
-        )
-    });
-
-    let mut hit_objects = Vec::with_capacity(take);
-    hit_objects.extend(hit_objects_iter);
-
-    let stack_threshold = time_preempt * map.stack_leniency;
-
-    if map.version >= 6 {
-        stacking(&mut hit_objects, stack_threshold);
-    } else {
-        old_stacking(&mut hit_objects, stack_threshold);
-    }
-
-    let scale_factor = scale * -6.4;
-
-    let mut hit_objects = hit_objects.into_iter().map(|mut h| {
-        let stack_offset = h.stack_height * scale_factor;
-
-        h.time /= map_attributes.clock_rate;
-        h.pos += Pos2 {
-            x: stack_offset,
-            y: stack_offset,
-        };
-
-        h
+    let buffers = &mut ctx.all_included;
+    let mut skills = calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        buffers,
+        |_, _| {},
+    );
+
+    let aim_rating = skills[0].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let speed_rating = skills[1].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let flashlight_rating = skills.get_mut(2).map_or(0.0, |flashlight| {
+        flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER
     });
 
-    let mut aim = Skill::new(SkillKind::Aim);
-    let mut speed = Skill::new(SkillKind::Speed);
-
-    let mut prev_prev = None;
-    let mut prev = hit_objects.next().unwrap();
-    let mut prev_vals = None;
-
-    // First object has no predecessor and thus no strain, handle distinctly
-    let mut current_section_end = (prev.time / SECTION_LEN).ceil() * SECTION_LEN;
-
-    // Handle second object separately to remove later if-branching
-    let curr = hit_objects.next().unwrap();
-    let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
-
-    while h.base.time > current_section_end {
-        current_section_end += SECTION_LEN;
-    }
-
-    aim.process(&h);
-    speed.process(&h);
-
-    prev_prev = Some(prev);
-    prev_vals = Some((h.jump_dist, h.strain_time));
-    prev = curr;
-
-    // Handle all other objects
-    for curr in hit_objects {
-        let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
-
-        while h.base.time > current_section_end {
-            aim.save_current_peak();
-            aim.start_new_section_from(current_section_end);
-            speed.save_current_peak();
-            speed.start_new_section_from(current_section_end);
-
-            current_section_end += SECTION_LEN;
-        }
-
-        aim.process(&h);
-        speed.process(&h);
-
-        prev_prev = Some(prev);
-        prev_vals = Some((h.jump_dist, h.strain_time));
-        prev = curr;
-    }
-
-    aim.save_current_peak();
-    speed.save_current_peak();
-
-    let aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
-    let speed_rating = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    buffers.recycle(skills);
 
     let stars = aim_rating + speed_rating + (aim_rating - speed_rating).abs() / 2.0;
 
     diff_attributes.n_circles = map.n_circles as usize;
+    diff_attributes.n_sliders = map.n_sliders as usize;
     diff_attributes.n_spinners = map.n_spinners as usize;
     diff_attributes.stars = stars;
     diff_attributes.speed_strain = speed_rating;
     diff_attributes.aim_strain = aim_rating;
+    diff_attributes.flashlight_strain = flashlight_rating;
 
     StarResult::Osu(diff_attributes)
 }
@@ -179,30 +119,149 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
 /// evaluating the final strains, it just returns them as is.
 ///
 /// Suitable to plot the difficulty of a map over time.
+#[inline]
 pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
-    let map_attributes = map.attributes().mods(mods);
-    let hitwindow = super::difficulty_range(map_attributes.od).floor() / map_attributes.clock_rate;
-    let od = (80.0 - hitwindow) / 6.0;
+    strains_with_params(map, mods, DifficultyParams::default())
+}
 
-    let mut diff_attributes = DifficultyAttributes {
-        ar: map_attributes.ar,
-        od,
-        ..Default::default()
-    };
+/// Same as the `strains` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn strains_with_params(map: &Beatmap, mods: impl Mods, params: DifficultyParams) -> Strains {
+    strains_with_context(map, mods, params, &mut CalcContext::new())
+}
+
+/// Same as the `strains_with_params` function but reuses the buffers of the given context.
+pub fn strains_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> Strains {
+    let mods = mods.for_mode(GameMode::STD);
 
     if map.hit_objects.len() < 2 {
         return Strains::default();
     }
 
-    let mut raw_ar = map.ar;
-    let hr = mods.hr();
+    let mut diff_attributes = DifficultyAttributes::default();
+    let take = map.hit_objects.len();
+
+    let buffers = &mut ctx.all_included;
+    let skills = calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        buffers,
+        |_, _| {},
+    );
+
+    let mut strains = vec![0.0; skills[0].strain_peaks.len()];
+
+    // Only aim and speed, flashlight does not contribute to the stars either
+    for skill in skills.iter().take(2) {
+        for (strain, peak) in strains.iter_mut().zip(skill.strain_peaks.iter()) {
+            *strain += peak;
+        }
+    }
+
+    buffers.recycle(skills);
+
+    Strains {
+        section_length: SECTION_LEN,
+        strains,
+    }
+}
+
+/// Essentially the same as the `stars` function but instead of
+/// evaluating the final strains, it returns the difficulty values
+/// of each hit object after it has been processed.
+///
+/// The first hit object has no predecessor and thus no strain so it is not included.
+///
+/// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+#[inline]
+pub fn object_difficulties(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+) -> Vec<ObjectDifficulty> {
+    object_difficulties_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `object_difficulties` function but with further difficulty parameters
+/// such as a custom clock rate.
+pub fn object_difficulties_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> Vec<ObjectDifficulty> {
+    let mods = mods.for_mode(GameMode::STD);
+    let take = passed_objects
+        .unwrap_or(map.hit_objects.len())
+        .min(map.hit_objects.len());
 
-    if hr {
-        raw_ar *= 1.4;
-    } else if mods.ez() {
-        raw_ar *= 0.5;
+    if take < 2 {
+        return Vec::new();
     }
 
+    let mut diff_attributes = DifficultyAttributes::default();
+    let mut objects = Vec::with_capacity(take - 1);
+
+    calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        &mut Buffers::default(),
+        |h, skills| {
+            objects.push(ObjectDifficulty {
+                start_time: h.base.time,
+                jump_dist: h.jump_dist,
+                travel_dist: h.travel_dist,
+                angle: h.angle,
+                strain_time: h.strain_time,
+                aim_strain: skills[0].current_strain(),
+                speed_strain: skills[1].current_strain(),
+                flashlight_strain: skills.get(2).map_or(0.0, Skill::current_strain),
+            })
+        },
+    );
+
+    objects
+}
+
+/// Processes the first `take` hit objects and returns the aim, speed,
+/// and, in case of FL, flashlight skill in that order.
+///
+/// `on_object` is called right after each difficulty object has been processed.
+fn calculate_skills(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    take: usize,
+    diff_attributes: &mut DifficultyAttributes,
+    buffers: &mut Buffers,
+    mut on_object: impl FnMut(&DifficultyObject, &[Skill]),
+) -> Vec<Skill> {
+    let map_attributes = map.attributes().mods_with_params(mods, params);
+
+    let hr = mods.hr();
+
+    let raw_ar = params.ar.unwrap_or_else(|| {
+        if hr {
+            (map.ar * 1.4).min(10.0)
+        } else if mods.ez() {
+            map.ar * 0.5
+        } else {
+            map.ar
+        }
+    });
+
     let time_preempt = difficulty_range_ar(raw_ar);
     let scale = (1.0 - 0.7 * (map_attributes.cs - 5.0) / 5.0) / 2.0;
     let radius = OBJECT_RADIUS * scale;
@@ -214,35 +273,39 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
     }
 
     let mut slider_state = SliderState::new(map);
-    let mut ticks_buf = Vec::new();
+    let ticks_buf = &mut buffers.ticks;
+    ticks_buf.clear();
+    let curve_buf = &mut buffers.curve_points;
 
-    let hit_objects_iter = map.hit_objects.iter().filter_map(|h| {
+    let hit_objects_iter = map.hit_objects.iter().take(take).filter_map(|h| {
         OsuObject::new(
             h,
             map,
             radius,
             scaling_factor,
             hr,
-            &mut ticks_buf,
-            &mut diff_attributes,
+            ticks_buf,
+            curve_buf,
+            diff_attributes,
             &mut slider_state,
         )
     });
 
-    let mut hit_objects = Vec::with_capacity(map.hit_objects.len());
+    let hit_objects = &mut buffers.hit_objects;
+    hit_objects.clear();
     hit_objects.extend(hit_objects_iter);
 
     let stack_threshold = time_preempt * map.stack_leniency;
 
     if map.version >= 6 {
-        stacking(&mut hit_objects, stack_threshold);
+        stacking(hit_objects, stack_threshold);
     } else {
-        old_stacking(&mut hit_objects, stack_threshold);
+        old_stacking(hit_objects, stack_threshold);
     }
 
     let scale_factor = scale * -6.4;
 
-    let mut hit_objects = hit_objects.into_iter().map(|mut h| {
+    let mut hit_objects = hit_objects.drain(..).map(|mut h| {
         let stack_offset = h.stack_height * scale_factor;
 
         h.time /= map_attributes.clock_rate;
@@ -254,26 +317,43 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
         h
     });
 
-    let mut aim = Skill::new(SkillKind::Aim);
-    let mut speed = Skill::new(SkillKind::Speed);
+    let pool = &mut buffers.skill_pool;
+    let mut skills = std::mem::take(&mut buffers.skills);
+    skills.push(Skill::from_pool(pool, SkillKind::Aim));
+    skills.push(Skill::from_pool(pool, SkillKind::Speed));
+
+    if mods.fl() {
+        let kind = SkillKind::Flashlight {
+            scaling_factor: NORMALIZED_RADIUS / radius,
+            hidden: mods.hd(),
+        };
+
+        skills.push(Skill::from_pool(pool, kind));
+    }
+
+    let (mut prev, curr) = match (hit_objects.next(), hit_objects.next()) {
+        (Some(prev), Some(curr)) => (prev, curr),
+        _ => return skills,
+    };
 
     let mut prev_prev = None;
-    let mut prev = hit_objects.next().unwrap();
     let mut prev_vals = None;
 
     // First object has no predecessor and thus no strain, handle distinctly
     let mut current_section_end = (prev.time / SECTION_LEN).ceil() * SECTION_LEN;
 
     // Handle second object separately to remove later if-branching
-    let curr = hit_objects.next().unwrap();
     let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
 
     while h.base.time > current_section_end {
         current_section_end += SECTION_LEN;
     }
 
-    aim.process(&h);
-    speed.process(&h);
+    for skill in skills.iter_mut() {
+        skill.process(&h);
+    }
+
+    on_object(&h, &skills);
 
     prev_prev = Some(prev);
     prev_vals = Some((h.jump_dist, h.strain_time));
@@ -284,39 +364,37 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
         let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
 
         while h.base.time > current_section_end {
-            aim.save_current_peak();
-            aim.start_new_section_from(current_section_end);
-            speed.save_current_peak();
-            speed.start_new_section_from(current_section_end);
+            for skill in skills.iter_mut() {
+                skill.save_current_peak();
+                skill.start_new_section_from(current_section_end);
+            }
 
             current_section_end += SECTION_LEN;
         }
 
-        aim.process(&h);
-        speed.process(&h);
+        for skill in skills.iter_mut() {
+            skill.process(&h);
+        }
+
+        on_object(&h, &skills);
 
         prev_prev = Some(prev);
         prev_vals = Some((h.jump_dist, h.strain_time));
         prev = curr;
     }
 
-    aim.save_current_peak();
-    speed.save_current_peak();
+    for skill in skills.iter_mut() {
+        skill.save_current_peak();
+    }
 
-    let strains = aim
-        .strain_peaks
-        .into_iter()
-        .zip(speed.strain_peaks.into_iter())
-        .map(|(aim, speed)| aim + speed)
-        .collect();
+    skills
+}
 
-    Strains {
-        section_length: SECTION_LEN,
-        strains,
+fn stacking(hit_objects: &mut [OsuObject], stack_threshold: Float) {
+    if hit_objects.is_empty() {
+        return;
     }
-}
 
-fn stacking(hit_objects: &mut [OsuObject], stack_threshold: f32) {
     let mut extended_start_idx = 0;
     let extended_end_idx = hit_objects.len() - 1;
 
@@ -380,7 +458,7 @@ fn stacking(hit_objects: &mut [OsuObject], stack_threshold: f32) {
     }
 }
 
-fn old_stacking(hit_objects: &mut [OsuObject], stack_threshold: f32) {
+fn old_stacking(hit_objects: &mut [OsuObject], stack_threshold: Float) {
     for i in 0..hit_objects.len() {
         if hit_objects[i].stack_height != 0.0 && !hit_objects[i].is_slider() {
             continue;
@@ -408,11 +486,147 @@ fn old_stacking(hit_objects: &mut [OsuObject], stack_threshold: f32) {
     }
 }
 
-const OSU_AR_MAX: f32 = 450.0;
-const OSU_AR_AVG: f32 = 1200.0;
-const OSU_AR_MIN: f32 = 1800.0;
+const OSU_AR_MAX: Float = 450.0;
+const OSU_AR_AVG: Float = 1200.0;
+const OSU_AR_MIN: Float = 1800.0;
 
 #[inline]
-fn difficulty_range_ar(ar: f32) -> f32 {
+fn difficulty_range_ar(ar: Float) -> Float {
     crate::difficulty_range(ar, OSU_AR_MAX, OSU_AR_AVG, OSU_AR_MIN)
 }
+
+/// Buffers of this version that are kept by a [`CalcContext`].
+#[derive(Default)]
+pub(crate) struct Buffers {
+    hit_objects: Vec<OsuObject>,
+    ticks: Vec<Float>,
+    curve_points: Vec<Pos2>,
+    skills: Vec<Skill>,
+    skill_pool: Pool<Skill>,
+}
+
+impl Buffers {
+    /// Keeps the skills of a finished calculation for the next one.
+    #[inline]
+    fn recycle(&mut self, mut skills: Vec<Skill>) {
+        self.skill_pool.put_all(&mut skills);
+        self.skills = skills;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, GameMode};
+
+    /// Rebuilds the section peaks of a skill from the strains of the individual objects.
+    fn section_peaks(
+        objects: &[ObjectDifficulty],
+        first_time: Float,
+        decay_base: Float,
+        strain: fn(&ObjectDifficulty) -> Float,
+    ) -> Vec<Float> {
+        let mut peaks = Vec::new();
+        let mut section_end = (first_time / SECTION_LEN).ceil() * SECTION_LEN;
+        let mut peak = 1.0;
+        let mut prev: Option<&ObjectDifficulty> = None;
+
+        for h in objects {
+            while h.start_time > section_end {
+                if let Some(prev) = prev {
+                    peaks.push(peak);
+                    peak = strain(prev) * decay_base.powf((section_end - prev.start_time) / 1000.0);
+                }
+
+                section_end += SECTION_LEN;
+            }
+
+            peak = peak.max(strain(h));
+            prev = Some(h);
+        }
+
+        peaks.push(peak);
+
+        peaks
+    }
+
+    fn rating(kind: SkillKind, peaks: Vec<Float>) -> Float {
+        let mut skill = Skill::new(kind);
+        skill.strain_peaks = peaks;
+
+        skill.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER
+    }
+
+    #[test]
+    fn object_difficulties_reproduce_stars() {
+        let map = test_util::map(GameMode::STD);
+        let clock_rate = 1.3;
+        let params = DifficultyParams::default().clock_rate(clock_rate);
+        let first_time = map.hit_objects[0].start_time / clock_rate;
+
+        for &mods in [0, 16 + 8, 2 + 1024].iter() {
+            let objects = object_difficulties_with_params(&map, mods, None, params);
+            assert_eq!(objects.len(), map.hit_objects.len() - 1);
+
+            let attributes = match stars_with_params(&map, mods, None, params) {
+                StarResult::Osu(attributes) => attributes,
+                #[allow(unreachable_patterns)]
+                _ => unreachable!(),
+            };
+
+            let aim_peaks = section_peaks(&objects, first_time, 0.15, |h| h.aim_strain);
+            let speed_peaks = section_peaks(&objects, first_time, 0.3, |h| h.speed_strain);
+
+            let strains = strains_with_params(&map, mods, params);
+            assert_eq!(strains.strains.len(), aim_peaks.len());
+
+            for ((strain, aim), speed) in strains.strains.iter().zip(&aim_peaks).zip(&speed_peaks) {
+                assert!((strain - (aim + speed)).abs() < 1e-3);
+            }
+
+            let aim_rating = rating(SkillKind::Aim, aim_peaks);
+            let speed_rating = rating(SkillKind::Speed, speed_peaks);
+
+            assert!((aim_rating - attributes.aim_strain).abs() < 1e-5);
+            assert!((speed_rating - attributes.speed_strain).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn strains_compared_to_original() {
+        let map = test_util::parse_str(test_util::SHORT_STD_MAP);
+
+        // Strains of the original implementation
+        let nomod = [
+            67.12796, 87.04998, 92.835945, 81.86905, 81.86905, 149.85356, 215.25667, 157.33264,
+            152.04974, 162.03754, 158.70703,
+        ];
+
+        let original_hr = [
+            76.29057, 99.11743, 101.278755, 86.770386, 86.770386, 175.48569, 259.4248, 188.9077,
+            177.57057, 185.59863, 181.1961,
+        ];
+
+        let dt = [
+            105.967186, 165.66824, 157.4108, 260.82397, 363.64835, 310.77063, 326.07803, 288.36142,
+        ];
+
+        test_util::assert_close(&strains(&map, 0).strains, &nomod);
+        test_util::assert_close(&strains(&map, 64).strains, &dt);
+
+        // The AR of HR is capped at 10 as for the stars so the circles at 2200ms
+        // are stacked now which changes all subsequent strains
+        let hr = [
+            76.29057, 99.11743, 100.09651, 86.998184, 85.09529, 174.70462, 258.79138, 188.39655,
+            177.22815, 185.40457, 181.06366,
+        ];
+
+        let strains = strains(&map, 16).strains;
+        test_util::assert_close(&strains, &hr);
+        test_util::assert_close(&strains[..2], &original_hr[..2]);
+        assert!(strains[2..]
+            .iter()
+            .zip(&original_hr[2..])
+            .all(|(a, b)| a != b));
+    }
+}
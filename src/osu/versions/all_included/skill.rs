@@ -1,39 +1,75 @@
-use crate::math_util;
+use crate::{context::Pool, math_util, Float};
 
-use super::{DifficultyObject, SkillKind};
+use super::{DifficultyObject, PrevObject, SkillKind};
 
 use std::cmp::Ordering;
+use std::collections::VecDeque;
 
-const SPEED_SKILL_MULTIPLIER: f32 = 1400.0;
-const SPEED_STRAIN_DECAY_BASE: f32 = 0.3;
-const REDUCED_STRAIN_BASELINE: f32 = 0.75;
+const SPEED_SKILL_MULTIPLIER: Float = 1400.0;
+const SPEED_STRAIN_DECAY_BASE: Float = 0.3;
+const REDUCED_STRAIN_BASELINE: Float = 0.75;
 
-const AIM_SKILL_MULTIPLIER: f32 = 26.25;
-const AIM_STRAIN_DECAY_BASE: f32 = 0.15;
+const AIM_SKILL_MULTIPLIER: Float = 26.25;
+const AIM_STRAIN_DECAY_BASE: Float = 0.15;
 
-const DECAY_WEIGHT: f32 = 0.9;
+const FLASHLIGHT_SKILL_MULTIPLIER: Float = 0.15;
+const FLASHLIGHT_STRAIN_DECAY_BASE: Float = 0.15;
+const FLASHLIGHT_HISTORY_LEN: usize = 10;
+
+const DECAY_WEIGHT: Float = 0.9;
 
 pub(crate) struct Skill {
-    current_strain: f32,
-    current_section_peak: f32,
+    current_strain: Float,
+    current_section_peak: Float,
 
     kind: SkillKind,
-    pub(crate) strain_peaks: Vec<f32>,
+    pub(crate) strain_peaks: Vec<Float>,
 
-    prev_time: Option<f32>,
+    prev_time: Option<Float>,
+    history: VecDeque<PrevObject>,
 }
 
 impl Skill {
     #[inline]
     pub(crate) fn new(kind: SkillKind) -> Self {
+        Self::with_buffers(kind, Vec::with_capacity(128), VecDeque::new())
+    }
+
+    /// Same as `new` but reuses the buffers of a skill from the pool.
+    #[inline]
+    pub(crate) fn from_pool(pool: &mut Pool<Self>, kind: SkillKind) -> Self {
+        match pool.take() {
+            Some(skill) => {
+                let Self {
+                    mut strain_peaks,
+                    mut history,
+                    ..
+                } = skill;
+
+                strain_peaks.clear();
+                history.clear();
+
+                Self::with_buffers(kind, strain_peaks, history)
+            }
+            None => Self::new(kind),
+        }
+    }
+
+    #[inline]
+    fn with_buffers(
+        kind: SkillKind,
+        strain_peaks: Vec<Float>,
+        history: VecDeque<PrevObject>,
+    ) -> Self {
         Self {
             current_strain: 1.0,
             current_section_peak: 1.0,
 
             kind,
-            strain_peaks: Vec::with_capacity(128),
+            strain_peaks,
 
             prev_time: None,
+            history,
         }
     }
 
@@ -43,24 +79,41 @@ impl Skill {
     }
 
     #[inline]
-    pub(crate) fn start_new_section_from(&mut self, time: f32) {
+    pub(crate) fn current_strain(&self) -> Float {
+        self.current_strain
+    }
+
+    #[inline]
+    pub(crate) fn start_new_section_from(&mut self, time: Float) {
         self.current_section_peak = self.peak_strain(time - self.prev_time.unwrap());
     }
 
     #[inline]
     pub(crate) fn process(&mut self, current: &DifficultyObject) {
         self.current_strain *= self.strain_decay(current.delta);
-        self.current_strain += self.kind.strain_value_of(&current) * self.skill_multiplier();
+        self.current_strain +=
+            self.kind.strain_value_of(&current, &self.history) * self.skill_multiplier();
         self.current_section_peak = self.current_section_peak.max(self.current_strain);
         self.prev_time.replace(current.base.time);
+
+        if let SkillKind::Flashlight { .. } = self.kind {
+            self.history.truncate(FLASHLIGHT_HISTORY_LEN - 1);
+            self.history.push_front(PrevObject::new(current));
+        }
     }
 
-    pub(crate) fn difficulty_value(&mut self) -> f32 {
+    pub(crate) fn difficulty_value(&mut self) -> Float {
+        if let SkillKind::Flashlight { .. } = self.kind {
+            let (_, difficulty_multiplier) = self.kind.difficulty_values();
+
+            return self.strain_peaks.iter().sum::<Float>() * difficulty_multiplier;
+        }
+
         let mut difficulty = 0.0;
         let mut weight = 1.0;
 
         let (reduced_section_count, difficulty_multiplier) = self.kind.difficulty_values();
-        let reduced_section_count_f32 = reduced_section_count as f32;
+        let reduced_section_count_f32 = reduced_section_count as Float;
 
         self.strain_peaks
             .sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
@@ -71,7 +124,7 @@ impl Skill {
             .take(reduced_section_count)
             .enumerate()
         {
-            let clamped = (i as f32 / reduced_section_count_f32).clamp(0.0, 1.0);
+            let clamped = (i as Float / reduced_section_count_f32).clamp(0.0, 1.0);
             let scale = (math_util::lerp(1.0, 10.0, clamped)).log10();
             *strain *= math_util::lerp(REDUCED_STRAIN_BASELINE, 1.0, scale);
         }
@@ -88,28 +141,30 @@ impl Skill {
     }
 
     #[inline]
-    fn skill_multiplier(&self) -> f32 {
+    fn skill_multiplier(&self) -> Float {
         match self.kind {
             SkillKind::Aim => AIM_SKILL_MULTIPLIER,
             SkillKind::Speed => SPEED_SKILL_MULTIPLIER,
+            SkillKind::Flashlight { .. } => FLASHLIGHT_SKILL_MULTIPLIER,
         }
     }
 
     #[inline]
-    fn strain_decay_base(&self) -> f32 {
+    fn strain_decay_base(&self) -> Float {
         match self.kind {
             SkillKind::Aim => AIM_STRAIN_DECAY_BASE,
             SkillKind::Speed => SPEED_STRAIN_DECAY_BASE,
+            SkillKind::Flashlight { .. } => FLASHLIGHT_STRAIN_DECAY_BASE,
         }
     }
 
     #[inline]
-    fn peak_strain(&self, delta_time: f32) -> f32 {
+    fn peak_strain(&self, delta_time: Float) -> Float {
         self.current_strain * self.strain_decay(delta_time)
     }
 
     #[inline]
-    fn strain_decay(&self, ms: f32) -> f32 {
+    fn strain_decay(&self, ms: Float) -> Float {
         self.strain_decay_base().powf(ms / 1000.0)
     }
 }
@@ -10,11 +10,17 @@ pub mod no_leniency;
 #[cfg_attr(docsrs, doc(cfg(feature = "no_sliders_no_leniency")))]
 pub mod no_sliders_no_leniency;
 
-const OSU_OD_MAX: f32 = 20.0;
-const OSU_OD_AVG: f32 = 50.0;
-const OSU_OD_MIN: f32 = 80.0;
+#[cfg(feature = "all_included")]
+#[cfg_attr(docsrs, doc(cfg(feature = "all_included")))]
+pub mod v2024;
+
+use crate::Float;
+
+const OSU_OD_MAX: Float = 20.0;
+const OSU_OD_AVG: Float = 50.0;
+const OSU_OD_MIN: Float = 80.0;
 
 #[inline]
-fn difficulty_range(od: f32) -> f32 {
+fn difficulty_range(od: Float) -> Float {
     super::super::difficulty_range(od, OSU_OD_MAX, OSU_OD_AVG, OSU_OD_MIN)
 }
@@ -5,7 +5,7 @@
 
 #![cfg(feature = "no_leniency")]
 
-use super::super::DifficultyAttributes;
+use super::super::{DifficultyAttributes, ObjectDifficulty};
 
 mod difficulty_object;
 mod osu_object;
@@ -16,15 +16,18 @@ mod slider_state;
 use difficulty_object::DifficultyObject;
 use osu_object::OsuObject;
 use skill::Skill;
-use skill_kind::SkillKind;
+use skill_kind::{PrevObject, SkillKind};
 use slider_state::SliderState;
 
-use crate::{Beatmap, Mods, StarResult, Strains};
+use crate::{
+    context::Pool, parse::Pos2, Beatmap, CalcContext, DifficultyParams, Float, GameMode, Mods,
+    StarResult, Strains,
+};
 
-const OBJECT_RADIUS: f32 = 64.0;
-const SECTION_LEN: f32 = 400.0;
-const DIFFICULTY_MULTIPLIER: f32 = 0.0675;
-const NORMALIZED_RADIUS: f32 = 52.0;
+const OBJECT_RADIUS: Float = 64.0;
+const SECTION_LEN: Float = 400.0;
+const DIFFICULTY_MULTIPLIER: Float = 0.0675;
+const NORMALIZED_RADIUS: Float = 52.0;
 
 /// Star calculation for osu!standard maps.
 ///
@@ -35,16 +38,44 @@ const NORMALIZED_RADIUS: f32 = 52.0;
 /// processing stack leniency is relatively expensive.
 ///
 /// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+#[inline]
 pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> StarResult {
-    let take = passed_objects.unwrap_or_else(|| map.hit_objects.len());
+    stars_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `stars` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn stars_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> StarResult {
+    stars_with_context(map, mods, passed_objects, params, &mut CalcContext::new())
+}
 
-    let map_attributes = map.attributes().mods(mods);
+/// Same as the `stars_with_params` function but reuses the buffers of the given context.
+pub fn stars_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> StarResult {
+    let mods = mods.for_mode(GameMode::STD);
+    let take = passed_objects
+        .unwrap_or_else(|| map.hit_objects.len())
+        .min(map.hit_objects.len());
+
+    let map_attributes = map.attributes().mods_with_params(mods, params);
     let hitwindow = super::difficulty_range(map_attributes.od).floor() / map_attributes.clock_rate;
     let od = (80.0 - hitwindow) / 6.0;
 
     let mut diff_attributes = DifficultyAttributes {
         ar: map_attributes.ar,
         od,
+        slider_factor: 1.0,
         ..Default::default()
     };
 
@@ -52,6 +83,173 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
         return StarResult::Osu(diff_attributes);
     }
 
+    let buffers = &mut ctx.no_leniency;
+    let mut skills = calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        buffers,
+        |_, _| {},
+    );
+
+    let aim_rating = skills[0].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let speed_rating = skills[1].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let flashlight_rating = skills.get_mut(2).map_or(0.0, |flashlight| {
+        flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER
+    });
+
+    buffers.recycle(skills);
+
+    let stars = aim_rating + speed_rating + (aim_rating - speed_rating).abs() / 2.0;
+
+    diff_attributes.n_circles = map.n_circles as usize;
+    diff_attributes.n_sliders = map.n_sliders as usize;
+    diff_attributes.n_spinners = map.n_spinners as usize;
+    diff_attributes.stars = stars;
+    diff_attributes.speed_strain = speed_rating;
+    diff_attributes.aim_strain = aim_rating;
+    diff_attributes.flashlight_strain = flashlight_rating;
+
+    StarResult::Osu(diff_attributes)
+}
+
+/// Essentially the same as the `stars` function but instead of
+/// evaluating the final strains, it just returns them as is.
+///
+/// Suitable to plot the difficulty of a map over time.
+#[inline]
+pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
+    strains_with_params(map, mods, DifficultyParams::default())
+}
+
+/// Same as the `strains` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn strains_with_params(map: &Beatmap, mods: impl Mods, params: DifficultyParams) -> Strains {
+    strains_with_context(map, mods, params, &mut CalcContext::new())
+}
+
+/// Same as the `strains_with_params` function but reuses the buffers of the given context.
+pub fn strains_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> Strains {
+    let mods = mods.for_mode(GameMode::STD);
+
+    if map.hit_objects.len() < 2 {
+        return Strains::default();
+    }
+
+    let mut diff_attributes = DifficultyAttributes::default();
+    let take = map.hit_objects.len();
+
+    let buffers = &mut ctx.no_leniency;
+    let skills = calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        buffers,
+        |_, _| {},
+    );
+
+    let mut strains = vec![0.0; skills[0].strain_peaks.len()];
+
+    // Only aim and speed, flashlight does not contribute to the stars either
+    for skill in skills.iter().take(2) {
+        for (strain, peak) in strains.iter_mut().zip(skill.strain_peaks.iter()) {
+            *strain += peak;
+        }
+    }
+
+    buffers.recycle(skills);
+
+    Strains {
+        section_length: SECTION_LEN,
+        strains,
+    }
+}
+
+/// Essentially the same as the `stars` function but instead of
+/// evaluating the final strains, it returns the difficulty values
+/// of each hit object after it has been processed.
+///
+/// The first hit object has no predecessor and thus no strain so it is not included.
+///
+/// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+#[inline]
+pub fn object_difficulties(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+) -> Vec<ObjectDifficulty> {
+    object_difficulties_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `object_difficulties` function but with further difficulty parameters
+/// such as a custom clock rate.
+pub fn object_difficulties_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> Vec<ObjectDifficulty> {
+    let mods = mods.for_mode(GameMode::STD);
+    let take = passed_objects
+        .unwrap_or(map.hit_objects.len())
+        .min(map.hit_objects.len());
+
+    if take < 2 {
+        return Vec::new();
+    }
+
+    let mut diff_attributes = DifficultyAttributes::default();
+    let mut objects = Vec::with_capacity(take - 1);
+
+    calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        &mut Buffers::default(),
+        |h, skills| {
+            objects.push(ObjectDifficulty {
+                start_time: h.base.time,
+                jump_dist: h.jump_dist,
+                travel_dist: h.travel_dist,
+                angle: h.angle,
+                strain_time: h.strain_time,
+                aim_strain: skills[0].current_strain(),
+                speed_strain: skills[1].current_strain(),
+                flashlight_strain: skills.get(2).map_or(0.0, Skill::current_strain),
+            })
+        },
+    );
+
+    objects
+}
+
+/// Processes the first `take` hit objects and returns the aim, speed,
+/// and, in case of FL, flashlight skill in that order.
+///
+/// `on_object` is called right after each difficulty object has been processed.
+fn calculate_skills(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    take: usize,
+    diff_attributes: &mut DifficultyAttributes,
+    buffers: &mut Buffers,
+    mut on_object: impl FnMut(&DifficultyObject, &[Skill]),
+) -> Vec<Skill> {
+    let map_attributes = map.attributes().mods_with_params(mods, params);
+
     let radius = OBJECT_RADIUS * (1.0 - 0.7 * (map_attributes.cs - 5.0) / 5.0) / 2.0;
     let mut scaling_factor = NORMALIZED_RADIUS / radius;
 
@@ -61,7 +259,9 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
     }
 
     let mut slider_state = SliderState::new(map);
-    let mut ticks_buf = Vec::new();
+    let ticks_buf = &mut buffers.ticks;
+    ticks_buf.clear();
+    let curve_buf = &mut buffers.curve_points;
 
     let mut hit_objects = map
         .hit_objects
@@ -73,8 +273,9 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
                 map,
                 radius,
                 scaling_factor,
-                &mut ticks_buf,
-                &mut diff_attributes,
+                ticks_buf,
+                curve_buf,
+                diff_attributes,
                 &mut slider_state,
             )
         })
@@ -84,26 +285,43 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
             h
         });
 
-    let mut aim = Skill::new(SkillKind::Aim);
-    let mut speed = Skill::new(SkillKind::Speed);
+    let pool = &mut buffers.skill_pool;
+    let mut skills = std::mem::take(&mut buffers.skills);
+    skills.push(Skill::from_pool(pool, SkillKind::Aim));
+    skills.push(Skill::from_pool(pool, SkillKind::Speed));
+
+    if mods.fl() {
+        let kind = SkillKind::Flashlight {
+            scaling_factor: NORMALIZED_RADIUS / radius,
+            hidden: mods.hd(),
+        };
+
+        skills.push(Skill::from_pool(pool, kind));
+    }
+
+    let (mut prev, curr) = match (hit_objects.next(), hit_objects.next()) {
+        (Some(prev), Some(curr)) => (prev, curr),
+        _ => return skills,
+    };
 
     let mut prev_prev = None;
-    let mut prev = hit_objects.next().unwrap();
     let mut prev_vals = None;
 
     // First object has no predecessor and thus no strain, handle distinctly
     let mut current_section_end = (prev.time / SECTION_LEN).ceil() * SECTION_LEN;
 
     // Handle second object separately to remove later if-branching
-    let curr = hit_objects.next().unwrap();
     let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
 
     while h.base.time > current_section_end {
         current_section_end += SECTION_LEN;
     }
 
-    aim.process(&h);
-    speed.process(&h);
+    for skill in skills.iter_mut() {
+        skill.process(&h);
+    }
+
+    on_object(&h, &skills);
 
     prev_prev = Some(prev);
     prev_vals = Some((h.jump_dist, h.strain_time));
@@ -114,139 +332,72 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
         let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
 
         while h.base.time > current_section_end {
-            aim.save_current_peak();
-            aim.start_new_section_from(current_section_end);
-            speed.save_current_peak();
-            speed.start_new_section_from(current_section_end);
+            for skill in skills.iter_mut() {
+                skill.save_current_peak();
+                skill.start_new_section_from(current_section_end);
+            }
 
             current_section_end += SECTION_LEN;
         }
 
-        aim.process(&h);
-        speed.process(&h);
+        for skill in skills.iter_mut() {
+            skill.process(&h);
+        }
+
+        on_object(&h, &skills);
 
         prev_prev = Some(prev);
         prev_vals = Some((h.jump_dist, h.strain_time));
         prev = curr;
     }
 
-    aim.save_current_peak();
-    speed.save_current_peak();
-
-    let aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
-    let speed_rating = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
-
-    let stars = aim_rating + speed_rating + (aim_rating - speed_rating).abs() / 2.0;
-
-    diff_attributes.n_circles = map.n_circles as usize;
-    diff_attributes.n_spinners = map.n_spinners as usize;
-    diff_attributes.stars = stars;
-    diff_attributes.speed_strain = speed_rating;
-    diff_attributes.aim_strain = aim_rating;
-
-    StarResult::Osu(diff_attributes)
-}
-
-/// Essentially the same as the `stars` function but instead of
-/// evaluating the final strains, it just returns them as is.
-///
-/// Suitable to plot the difficulty of a map over time.
-pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
-    let map_attributes = map.attributes().mods(mods);
-    let hitwindow = super::difficulty_range(map_attributes.od).floor() / map_attributes.clock_rate;
-    let od = (80.0 - hitwindow) / 6.0;
-
-    let mut diff_attributes = DifficultyAttributes {
-        ar: map_attributes.ar,
-        od,
-        ..Default::default()
-    };
-
-    if map.hit_objects.len() < 2 {
-        return Strains::default();
+    for skill in skills.iter_mut() {
+        skill.save_current_peak();
     }
 
-    let radius = OBJECT_RADIUS * (1.0 - 0.7 * (map_attributes.cs - 5.0) / 5.0) / 2.0;
-    let mut scaling_factor = NORMALIZED_RADIUS / radius;
-
-    if radius < 30.0 {
-        let small_circle_bonus = (30.0 - radius).min(5.0) / 50.0;
-        scaling_factor *= 1.0 + small_circle_bonus;
-    }
-
-    let mut slider_state = SliderState::new(map);
-    let mut ticks_buf = Vec::new();
-
-    let mut hit_objects = map.hit_objects.iter().filter_map(|h| {
-        OsuObject::new(
-            h,
-            map,
-            radius,
-            scaling_factor,
-            &mut ticks_buf,
-            &mut diff_attributes,
-            &mut slider_state,
-        )
-    });
-
-    let mut aim = Skill::new(SkillKind::Aim);
-    let mut speed = Skill::new(SkillKind::Speed);
-
-    let mut prev_prev = None;
-    let mut prev = hit_objects.next().unwrap();
-    let mut prev_vals = None;
-
-    // First object has no predecessor and thus no strain, handle distinctly
-    let mut current_section_end = (prev.time / SECTION_LEN).ceil() * SECTION_LEN;
+    skills
+}
 
-    // Handle second object separately to remove later if-branching
-    let curr = hit_objects.next().unwrap();
-    let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
+/// Buffers of this version that are kept by a [`CalcContext`].
+#[derive(Default)]
+pub(crate) struct Buffers {
+    ticks: Vec<Float>,
+    curve_points: Vec<Pos2>,
+    skills: Vec<Skill>,
+    skill_pool: Pool<Skill>,
+}
 
-    while h.base.time > current_section_end {
-        current_section_end += SECTION_LEN;
+impl Buffers {
+    /// Keeps the skills of a finished calculation for the next one.
+    #[inline]
+    fn recycle(&mut self, mut skills: Vec<Skill>) {
+        self.skill_pool.put_all(&mut skills);
+        self.skills = skills;
     }
+}
 
-    aim.process(&h);
-    speed.process(&h);
-
-    prev_prev = Some(prev);
-    prev_vals = Some((h.jump_dist, h.strain_time));
-    prev = curr;
-
-    // Handle all other objects
-    for curr in hit_objects {
-        let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
 
-        while h.base.time > current_section_end {
-            aim.save_current_peak();
-            aim.start_new_section_from(current_section_end);
-            speed.save_current_peak();
-            speed.start_new_section_from(current_section_end);
+    #[test]
+    fn strains_compared_to_original() {
+        let map = test_util::parse_str(test_util::SHORT_STD_MAP);
 
-            current_section_end += SECTION_LEN;
-        }
+        // Strains of the original implementation which ignored the clock rate of DT
+        let nomod = [
+            67.12796, 87.04998, 90.62271, 79.28134, 79.28134, 149.05962, 214.65921, 156.88123,
+            151.85077, 161.91365, 158.61688,
+        ];
 
-        aim.process(&h);
-        speed.process(&h);
+        test_util::assert_close(&strains(&map, 0).strains, &nomod);
 
-        prev_prev = Some(prev);
-        prev_vals = Some((h.jump_dist, h.strain_time));
-        prev = curr;
-    }
-
-    aim.save_current_peak();
-    speed.save_current_peak();
+        // The clock rate of DT is considered as for the stars
+        let dt = [
+            105.967186, 162.34839, 152.66692, 258.69165, 361.89337, 309.78894, 325.5059, 288.00082,
+        ];
 
-    let strains = aim
-        .strain_peaks
-        .into_iter()
-        .zip(speed.strain_peaks.into_iter())
-        .map(|(aim, speed)| aim + speed)
-        .collect();
-
-    Strains {
-        section_length: SECTION_LEN,
-        strains,
+        test_util::assert_close(&strains(&map, 64).strains, &dt);
     }
 }
@@ -4,26 +4,28 @@ use super::slider_state::SliderState;
 use crate::{
     curve::Curve,
     parse::{HitObject, HitObjectKind, Pos2},
-    Beatmap,
+    Beatmap, Float,
 };
 
-const LEGACY_LAST_TICK_OFFSET: f32 = 36.0;
+const LEGACY_LAST_TICK_OFFSET: Float = 36.0;
 
 pub(crate) struct OsuObject {
-    pub(crate) time: f32,
+    pub(crate) time: Float,
     pub(crate) pos: Pos2,
     pub(crate) end_pos: Pos2,
     // circle: Some(0.0) | slider: Some(_) | spinner: None
-    pub(crate) travel_dist: Option<f32>,
+    pub(crate) travel_dist: Option<Float>,
 }
 
 impl OsuObject {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         h: &HitObject,
         map: &Beatmap,
-        radius: f32,
-        scaling_factor: f32,
-        ticks: &mut Vec<f32>,
+        radius: Float,
+        scaling_factor: Float,
+        ticks: &mut Vec<Float>,
+        curve_buf: &mut Vec<Pos2>,
         attributes: &mut DifficultyAttributes,
         slider_state: &mut SliderState,
     ) -> Option<Self> {
@@ -41,6 +43,7 @@ impl OsuObject {
                 repeats,
                 curve_points,
                 path_type,
+                ..
             } => {
                 // Key values which are computed here
                 let mut end_pos = h.pos;
@@ -57,18 +60,18 @@ impl OsuObject {
                         (100.0 / slider_state.speed_mult).max(10.0).min(1000.0) / 100.0;
                 }
 
-                let duration = *repeats as f32 * slider_state.beat_len * pixel_len
+                let duration = *repeats as Float * slider_state.beat_len * pixel_len
                     / (map.sv * slider_state.speed_mult)
                     / 100.0;
-                let span_duration = duration / *repeats as f32;
+                let span_duration = duration / *repeats as Float;
 
                 // Build the curve w.r.t. the curve points
-                let curve = Curve::new(curve_points, *path_type);
+                let curve = Curve::new(curve_points, *path_type, curve_buf);
 
                 // Called on each slider object except for the head.
                 // Increases combo and adjusts `end_pos` and `travel_dist`
                 // w.r.t. the object position at the given time on the slider curve.
-                let mut compute_vertex = |time: f32| {
+                let mut compute_vertex = |time: Float| {
                     attributes.max_combo += 1;
 
                     let mut progress = (time - h.start_time) / span_duration;
@@ -93,7 +96,7 @@ impl OsuObject {
                 };
 
                 let mut current_distance = tick_distance;
-                let time_add = duration * (tick_distance / (pixel_len * *repeats as f32));
+                let time_add = duration * (tick_distance / (pixel_len * *repeats as Float));
 
                 let target = pixel_len - tick_distance / 8.0;
                 ticks.reserve((target / tick_distance) as usize);
@@ -101,7 +104,7 @@ impl OsuObject {
                 // Tick of the first span
                 if current_distance < target {
                     for tick_idx in 1.. {
-                        let time = h.start_time + time_add * tick_idx as f32;
+                        let time = h.start_time + time_add * tick_idx as Float;
                         compute_vertex(time);
                         ticks.push(time);
                         current_distance += tick_distance;
@@ -115,7 +118,7 @@ impl OsuObject {
                 // Other spans
                 if *repeats > 1 {
                     for repeat_id in 1..*repeats {
-                        let time_offset = (duration / *repeats as f32) * repeat_id as f32;
+                        let time_offset = (duration / *repeats as Float) * repeat_id as Float;
 
                         // Reverse tick
                         compute_vertex(h.start_time + time_offset);
@@ -131,7 +134,7 @@ impl OsuObject {
 
                 // Slider tail
                 let final_span_idx = repeats.saturating_sub(1);
-                let final_span_start_time = h.start_time + final_span_idx as f32 * span_duration;
+                let final_span_start_time = h.start_time + final_span_idx as Float * span_duration;
                 let final_span_end_time = (h.start_time + duration / 2.0)
                     .max(final_span_start_time + span_duration - LEGACY_LAST_TICK_OFFSET);
                 compute_vertex(final_span_end_time);
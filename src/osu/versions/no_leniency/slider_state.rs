@@ -1,10 +1,10 @@
-use crate::{Beatmap, ControlPoint, ControlPointIter};
+use crate::{Beatmap, ControlPoint, ControlPointIter, Float};
 
 pub(crate) struct SliderState<'p> {
     control_points: ControlPointIter<'p>,
     next: Option<ControlPoint>,
-    pub(crate) beat_len: f32,
-    pub(crate) speed_mult: f32,
+    pub(crate) beat_len: Float,
+    pub(crate) speed_mult: Float,
 }
 
 impl<'p> SliderState<'p> {
@@ -27,7 +27,7 @@ impl<'p> SliderState<'p> {
     }
 
     #[inline]
-    pub(crate) fn update(&mut self, time: f32) {
+    pub(crate) fn update(&mut self, time: Float) {
         while let Some(next) = self.next.as_ref().filter(|n| time >= n.time()) {
             match next {
                 ControlPoint::Timing { beat_len, .. } => {
@@ -4,7 +4,7 @@
 
 #![cfg(feature = "no_sliders_no_leniency")]
 
-use super::super::DifficultyAttributes;
+use super::super::{DifficultyAttributes, ObjectDifficulty};
 
 mod difficulty_object;
 mod osu_object;
@@ -15,15 +15,18 @@ mod slider_state;
 use difficulty_object::DifficultyObject;
 use osu_object::OsuObject;
 use skill::Skill;
-use skill_kind::SkillKind;
+use skill_kind::{PrevObject, SkillKind};
 use slider_state::SliderState;
 
-use crate::{parse::HitObjectKind, Beatmap, Mods, StarResult, Strains};
+use crate::{
+    context::Pool, parse::HitObjectKind, Beatmap, CalcContext, DifficultyParams, Float, GameMode,
+    Mods, StarResult, Strains,
+};
 
-const OBJECT_RADIUS: f32 = 64.0;
-const SECTION_LEN: f32 = 400.0;
-const DIFFICULTY_MULTIPLIER: f32 = 0.0675;
-const NORMALIZED_RADIUS: f32 = 52.0;
+const OBJECT_RADIUS: Float = 64.0;
+const SECTION_LEN: Float = 400.0;
+const DIFFICULTY_MULTIPLIER: Float = 0.0675;
+const NORMALIZED_RADIUS: Float = 52.0;
 
 /// Star calculation for osu!standard maps.
 ///
@@ -32,10 +35,37 @@ const NORMALIZED_RADIUS: f32 = 52.0;
 /// However, this is the most efficient one.
 ///
 /// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+#[inline]
 pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> StarResult {
-    let take = passed_objects.unwrap_or_else(|| map.hit_objects.len());
+    stars_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `stars` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn stars_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> StarResult {
+    stars_with_context(map, mods, passed_objects, params, &mut CalcContext::new())
+}
 
-    let attributes = map.attributes().mods(mods);
+/// Same as the `stars_with_params` function but reuses the buffers of the given context.
+pub fn stars_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> StarResult {
+    let mods = mods.for_mode(GameMode::STD);
+    let take = passed_objects
+        .unwrap_or_else(|| map.hit_objects.len())
+        .min(map.hit_objects.len());
+
+    let attributes = map.attributes().mods_with_params(mods, params);
     let hitwindow = super::difficulty_range(attributes.od).floor() / attributes.clock_rate;
     let od = (80.0 - hitwindow) / 6.0;
 
@@ -43,99 +73,22 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
         return StarResult::Osu(DifficultyAttributes {
             ar: attributes.ar,
             od,
+            slider_factor: 1.0,
             ..Default::default()
         });
     }
 
-    let radius = OBJECT_RADIUS * (1.0 - 0.7 * (attributes.cs - 5.0) / 5.0) / 2.0;
-    let mut scaling_factor = NORMALIZED_RADIUS / radius;
-
-    if radius < 30.0 {
-        let small_circle_bonus = (30.0 - radius).min(5.0) / 50.0;
-        scaling_factor *= 1.0 + small_circle_bonus;
-    }
-
-    let clock_rate = attributes.clock_rate;
-
     let mut max_combo = 0;
-    let mut state = SliderState::new(&map);
-
-    let mut hit_objects = map
-        .hit_objects
-        .iter()
-        .take(take)
-        .filter_map(|h| match &h.kind {
-            HitObjectKind::Circle => {
-                max_combo += 1;
-
-                Some(OsuObject::new(h.pos, h.start_time, false, clock_rate))
-            }
-            HitObjectKind::Slider {
-                pixel_len, repeats, ..
-            } => {
-                max_combo += state.count_ticks(h.start_time, *pixel_len, *repeats, &map);
-
-                Some(OsuObject::new(h.pos, h.start_time, false, clock_rate))
-            }
-            HitObjectKind::Spinner { .. } => {
-                max_combo += 1;
-
-                Some(OsuObject::new(h.pos, h.start_time, true, clock_rate))
-            }
-            HitObjectKind::Hold { .. } => None,
-        });
+    let buffers = &mut ctx.no_sliders_no_leniency;
+    let mut skills = calculate_skills(map, mods, params, take, &mut max_combo, buffers, |_, _| {});
 
-    let mut aim = Skill::new(SkillKind::Aim);
-    let mut speed = Skill::new(SkillKind::Speed);
-
-    let mut prev_prev = None;
-    let mut prev = hit_objects.next().unwrap();
-    let mut prev_vals = None;
-
-    // First object has no predecessor and thus no strain, handle distinctly
-    let mut current_section_end = (prev.time / SECTION_LEN).ceil() * SECTION_LEN;
-
-    // Handle second object separately to remove later if-branching
-    let curr = hit_objects.next().unwrap();
-    let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
-
-    while h.base.time > current_section_end {
-        current_section_end += SECTION_LEN;
-    }
-
-    aim.process(&h);
-    speed.process(&h);
-
-    prev_prev = Some(prev);
-    prev_vals = Some((h.jump_dist, h.strain_time));
-    prev = curr;
-
-    // Handle all other objects
-    for curr in hit_objects {
-        let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
-
-        while h.base.time > current_section_end {
-            aim.save_current_peak();
-            aim.start_new_section_from(current_section_end);
-            speed.save_current_peak();
-            speed.start_new_section_from(current_section_end);
-
-            current_section_end += SECTION_LEN;
-        }
-
-        aim.process(&h);
-        speed.process(&h);
-
-        prev_prev = Some(prev);
-        prev_vals = Some((h.jump_dist, h.strain_time));
-        prev = curr;
-    }
-
-    aim.save_current_peak();
-    speed.save_current_peak();
+    let aim_strain = skills[0].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let speed_strain = skills[1].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let flashlight_strain = skills.get_mut(2).map_or(0.0, |flashlight| {
+        flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER
+    });
 
-    let aim_strain = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
-    let speed_strain = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    buffers.recycle(skills);
 
     let stars = aim_strain + speed_strain + (aim_strain - speed_strain).abs() / 2.0;
 
@@ -145,8 +98,14 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
         od,
         speed_strain,
         aim_strain,
+        flashlight_strain,
+        slider_factor: 1.0,
+        speed_note_count: 0.0,
+        aim_difficult_strain_count: 0.0,
+        speed_difficult_strain_count: 0.0,
         max_combo,
         n_circles: map.n_circles as usize,
+        n_sliders: map.n_sliders as usize,
         n_spinners: map.n_spinners as usize,
     })
 }
@@ -155,13 +114,129 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
 /// evaluating the final strains, it just returns them as is.
 ///
 /// Suitable to plot the difficulty of a map over time.
+#[inline]
 pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
-    let attributes = map.attributes().mods(mods);
+    strains_with_params(map, mods, DifficultyParams::default())
+}
+
+/// Same as the `strains` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn strains_with_params(map: &Beatmap, mods: impl Mods, params: DifficultyParams) -> Strains {
+    strains_with_context(map, mods, params, &mut CalcContext::new())
+}
+
+/// Same as the `strains_with_params` function but reuses the buffers of the given context.
+pub fn strains_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> Strains {
+    let mods = mods.for_mode(GameMode::STD);
 
     if map.hit_objects.len() < 2 {
         return Strains::default();
     }
 
+    let take = map.hit_objects.len();
+    let buffers = &mut ctx.no_sliders_no_leniency;
+    let skills = calculate_skills(map, mods, params, take, &mut 0, buffers, |_, _| {});
+
+    let mut strains = vec![0.0; skills[0].strain_peaks.len()];
+
+    // Only aim and speed, flashlight does not contribute to the stars either
+    for skill in skills.iter().take(2) {
+        for (strain, peak) in strains.iter_mut().zip(skill.strain_peaks.iter()) {
+            *strain += peak;
+        }
+    }
+
+    buffers.recycle(skills);
+
+    Strains {
+        section_length: SECTION_LEN,
+        strains,
+    }
+}
+
+/// Essentially the same as the `stars` function but instead of
+/// evaluating the final strains, it returns the difficulty values
+/// of each hit object after it has been processed.
+///
+/// The first hit object has no predecessor and thus no strain so it is not included.
+/// Since slider paths are ignored, the travel distance is always zero.
+///
+/// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+#[inline]
+pub fn object_difficulties(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+) -> Vec<ObjectDifficulty> {
+    object_difficulties_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `object_difficulties` function but with further difficulty parameters
+/// such as a custom clock rate.
+pub fn object_difficulties_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> Vec<ObjectDifficulty> {
+    let mods = mods.for_mode(GameMode::STD);
+    let take = passed_objects
+        .unwrap_or(map.hit_objects.len())
+        .min(map.hit_objects.len());
+
+    if take < 2 {
+        return Vec::new();
+    }
+
+    let mut objects = Vec::with_capacity(take - 1);
+
+    let mut buffers = Buffers::default();
+
+    calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut 0,
+        &mut buffers,
+        |h, skills| {
+            objects.push(ObjectDifficulty {
+                start_time: h.base.time,
+                jump_dist: h.jump_dist,
+                travel_dist: 0.0,
+                angle: h.angle,
+                strain_time: h.strain_time,
+                aim_strain: skills[0].current_strain(),
+                speed_strain: skills[1].current_strain(),
+                flashlight_strain: skills.get(2).map_or(0.0, Skill::current_strain),
+            })
+        },
+    );
+
+    objects
+}
+
+/// Processes the first `take` hit objects and returns the aim, speed,
+/// and, in case of FL, flashlight skill in that order.
+///
+/// `on_object` is called right after each difficulty object has been processed.
+fn calculate_skills(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    take: usize,
+    max_combo: &mut usize,
+    buffers: &mut Buffers,
+    mut on_object: impl FnMut(&DifficultyObject, &[Skill]),
+) -> Vec<Skill> {
+    let attributes = map.attributes().mods_with_params(mods, params);
+
     let radius = OBJECT_RADIUS * (1.0 - 0.7 * (attributes.cs - 5.0) / 5.0) / 2.0;
     let mut scaling_factor = NORMALIZED_RADIUS / radius;
 
@@ -172,36 +247,70 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
 
     let clock_rate = attributes.clock_rate;
 
-    let mut hit_objects = map.hit_objects.iter().filter_map(|h| match &h.kind {
-        HitObjectKind::Circle | HitObjectKind::Slider { .. } => {
-            Some(OsuObject::new(h.pos, h.start_time, false, clock_rate))
-        }
-        HitObjectKind::Spinner { .. } => {
-            Some(OsuObject::new(h.pos, h.start_time, true, clock_rate))
-        }
-        HitObjectKind::Hold { .. } => None,
-    });
+    let mut state = SliderState::new(&map);
 
-    let mut aim = Skill::new(SkillKind::Aim);
-    let mut speed = Skill::new(SkillKind::Speed);
+    let mut hit_objects = map
+        .hit_objects
+        .iter()
+        .take(take)
+        .filter_map(|h| match &h.kind {
+            HitObjectKind::Circle => {
+                *max_combo += 1;
+
+                Some(OsuObject::new(h.pos, h.start_time, false, clock_rate))
+            }
+            HitObjectKind::Slider {
+                pixel_len, repeats, ..
+            } => {
+                *max_combo += state.count_ticks(h.start_time, *pixel_len, *repeats, &map);
+
+                Some(OsuObject::new(h.pos, h.start_time, false, clock_rate))
+            }
+            HitObjectKind::Spinner { .. } => {
+                *max_combo += 1;
+
+                Some(OsuObject::new(h.pos, h.start_time, true, clock_rate))
+            }
+            HitObjectKind::Hold { .. } => None,
+        });
+
+    let pool = &mut buffers.skill_pool;
+    let mut skills = std::mem::take(&mut buffers.skills);
+    skills.push(Skill::from_pool(pool, SkillKind::Aim));
+    skills.push(Skill::from_pool(pool, SkillKind::Speed));
+
+    if mods.fl() {
+        let kind = SkillKind::Flashlight {
+            scaling_factor: NORMALIZED_RADIUS / radius,
+            hidden: mods.hd(),
+        };
+
+        skills.push(Skill::from_pool(pool, kind));
+    }
+
+    let (mut prev, curr) = match (hit_objects.next(), hit_objects.next()) {
+        (Some(prev), Some(curr)) => (prev, curr),
+        _ => return skills,
+    };
 
     let mut prev_prev = None;
-    let mut prev = hit_objects.next().unwrap();
     let mut prev_vals = None;
 
     // First object has no predecessor and thus no strain, handle distinctly
     let mut current_section_end = (prev.time / SECTION_LEN).ceil() * SECTION_LEN;
 
     // Handle second object separately to remove later if-branching
-    let curr = hit_objects.next().unwrap();
     let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
 
     while h.base.time > current_section_end {
         current_section_end += SECTION_LEN;
     }
 
-    aim.process(&h);
-    speed.process(&h);
+    for skill in skills.iter_mut() {
+        skill.process(&h);
+    }
+
+    on_object(&h, &skills);
 
     prev_prev = Some(prev);
     prev_vals = Some((h.jump_dist, h.strain_time));
@@ -212,34 +321,44 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
         let h = DifficultyObject::new(&curr, &prev, prev_vals, prev_prev, scaling_factor);
 
         while h.base.time > current_section_end {
-            aim.save_current_peak();
-            aim.start_new_section_from(current_section_end);
-            speed.save_current_peak();
-            speed.start_new_section_from(current_section_end);
+            for skill in skills.iter_mut() {
+                skill.save_current_peak();
+                skill.start_new_section_from(current_section_end);
+            }
 
             current_section_end += SECTION_LEN;
         }
 
-        aim.process(&h);
-        speed.process(&h);
+        for skill in skills.iter_mut() {
+            skill.process(&h);
+        }
+
+        on_object(&h, &skills);
 
         prev_prev = Some(prev);
         prev_vals = Some((h.jump_dist, h.strain_time));
         prev = curr;
     }
 
-    aim.save_current_peak();
-    speed.save_current_peak();
+    for skill in skills.iter_mut() {
+        skill.save_current_peak();
+    }
 
-    let strains = aim
-        .strain_peaks
-        .into_iter()
-        .zip(speed.strain_peaks.into_iter())
-        .map(|(aim, speed)| aim + speed)
-        .collect();
+    skills
+}
 
-    Strains {
-        section_length: SECTION_LEN,
-        strains,
+/// Buffers of this version that are kept by a [`CalcContext`].
+#[derive(Default)]
+pub(crate) struct Buffers {
+    skills: Vec<Skill>,
+    skill_pool: Pool<Skill>,
+}
+
+impl Buffers {
+    /// Keeps the skills of a finished calculation for the next one.
+    #[inline]
+    fn recycle(&mut self, mut skills: Vec<Skill>) {
+        self.skill_pool.put_all(&mut skills);
+        self.skills = skills;
     }
 }
@@ -1,31 +1,68 @@
 use super::DifficultyObject;
+use crate::{parse::Pos2, Float};
 
-const SINGLE_SPACING_TRESHOLD: f32 = 125.0;
-const SPEED_ANGLE_BONUS_BEGIN: f32 = 5.0 * std::f32::consts::FRAC_PI_6;
-const PI_OVER_4: f32 = std::f32::consts::FRAC_PI_4;
-const PI_OVER_2: f32 = std::f32::consts::FRAC_PI_2;
+use std::collections::VecDeque;
 
-const MIN_SPEED_BONUS: f32 = 75.0;
-const MAX_SPEED_BONUS: f32 = 45.0;
-const SPEED_BALANCING_FACTOR: f32 = 40.0;
+const SINGLE_SPACING_TRESHOLD: Float = 125.0;
+const SPEED_ANGLE_BONUS_BEGIN: Float = 5.0 * crate::float::consts::FRAC_PI_6;
+const PI_OVER_4: Float = crate::float::consts::FRAC_PI_4;
+const PI_OVER_2: Float = crate::float::consts::FRAC_PI_2;
 
-const AIM_ANGLE_BONUS_BEGIN: f32 = std::f32::consts::FRAC_PI_3;
-const TIMING_THRESHOLD: f32 = 107.0;
+const MIN_SPEED_BONUS: Float = 75.0;
+const MAX_SPEED_BONUS: Float = 45.0;
+const SPEED_BALANCING_FACTOR: Float = 40.0;
+
+const AIM_ANGLE_BONUS_BEGIN: Float = crate::float::consts::FRAC_PI_3;
+const TIMING_THRESHOLD: Float = 107.0;
 
 const AIM_REDUCED_SECTION_COUNT: usize = 10;
 const SPEED_REDUCED_SECTION_COUNT: usize = 5;
 
-const AIM_DIFFICULTY_MULTIPLIER: f32 = 1.06;
-const SPEED_DIFFICULTY_MULTIPLIER: f32 = 1.04;
+const FLASHLIGHT_HIDDEN_BONUS: Float = 0.2;
+
+const AIM_DIFFICULTY_MULTIPLIER: Float = 1.06;
+const SPEED_DIFFICULTY_MULTIPLIER: Float = 1.04;
 
 #[derive(Copy, Clone)]
 pub(crate) enum SkillKind {
     Aim,
     Speed,
+    Flashlight {
+        /// Scaling factor w.r.t. the radius, without small circle bonus
+        scaling_factor: Float,
+        hidden: bool,
+    },
+}
+
+/// A previously processed object as required by the flashlight skill.
+#[derive(Copy, Clone)]
+pub(crate) struct PrevObject {
+    pub(crate) end_pos: Pos2,
+    pub(crate) jump_dist: Float,
+    pub(crate) strain_time: Float,
+    pub(crate) is_spinner: bool,
+}
+
+impl PrevObject {
+    #[inline]
+    pub(crate) fn new(current: &DifficultyObject) -> Self {
+        Self {
+            end_pos: current.base.pos,
+            jump_dist: current.jump_dist,
+            strain_time: current.strain_time,
+            is_spinner: current.base.is_spinner,
+        }
+    }
 }
 
 impl SkillKind {
-    pub(crate) fn strain_value_of(self, current: &DifficultyObject) -> f32 {
+    /// `history` contains the previous objects, most recent first.
+    /// It is only filled for the flashlight skill.
+    pub(crate) fn strain_value_of(
+        self,
+        current: &DifficultyObject,
+        history: &VecDeque<PrevObject>,
+    ) -> Float {
         match self {
             Self::Aim => {
                 if current.base.is_spinner {
@@ -92,19 +129,63 @@ impl SkillKind {
                     * (0.95 + speed_bonus * (dist / SINGLE_SPACING_TRESHOLD).powf(3.5))
                     / current.strain_time
             }
+            Self::Flashlight {
+                scaling_factor,
+                hidden,
+            } => {
+                if current.base.is_spinner {
+                    return 0.0;
+                }
+
+                let pos = current.base.pos;
+                let mut small_dist_nerf = 1.0;
+                let mut cumulative_strain_time = 0.0;
+                let mut result = 0.0;
+
+                for (i, prev) in history.iter().enumerate() {
+                    if prev.is_spinner {
+                        continue;
+                    }
+
+                    let jump_dist = (pos - prev.end_pos).length();
+                    cumulative_strain_time += prev.strain_time;
+
+                    // Nerf objects that can be easily seen within the flashlight radius
+                    if i == 0 {
+                        small_dist_nerf = (jump_dist / 75.0).min(1.0);
+                    }
+
+                    // Nerf stacks so that only the first object of the stack is considered
+                    let stack_nerf = ((prev.jump_dist / scaling_factor) / 25.0).min(1.0);
+
+                    result +=
+                        (0.8 as Float).powi(i as i32) * stack_nerf * scaling_factor * jump_dist
+                            / cumulative_strain_time;
+                }
+
+                result = (small_dist_nerf * result).powi(2);
+
+                // No approach circles with hidden
+                if hidden {
+                    result *= 1.0 + FLASHLIGHT_HIDDEN_BONUS;
+                }
+
+                result
+            }
         }
     }
 
     #[inline]
-    pub(crate) fn difficulty_values(&self) -> (usize, f32) {
+    pub(crate) fn difficulty_values(&self) -> (usize, Float) {
         match self {
             Self::Aim => (AIM_REDUCED_SECTION_COUNT, AIM_DIFFICULTY_MULTIPLIER),
             Self::Speed => (SPEED_REDUCED_SECTION_COUNT, SPEED_DIFFICULTY_MULTIPLIER),
+            Self::Flashlight { .. } => (0, AIM_DIFFICULTY_MULTIPLIER),
         }
     }
 }
 
 #[inline]
-fn apply_diminishing_exp(val: f32) -> f32 {
+fn apply_diminishing_exp(val: Float) -> Float {
     val.powf(0.99)
 }
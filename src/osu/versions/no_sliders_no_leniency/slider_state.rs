@@ -1,10 +1,10 @@
-use crate::{Beatmap, ControlPoint, ControlPointIter};
+use crate::{Beatmap, ControlPoint, ControlPointIter, Float};
 
 pub(crate) struct SliderState<'p> {
     control_points: ControlPointIter<'p>,
-    next_time: f32,
-    px_per_beat: f32,
-    prev_sv: f32,
+    next_time: Float,
+    px_per_beat: Float,
+    prev_sv: Float,
 }
 
 impl<'p> SliderState<'p> {
@@ -12,7 +12,7 @@ impl<'p> SliderState<'p> {
     pub(crate) fn new(map: &'p Beatmap) -> Self {
         Self {
             control_points: ControlPointIter::new(map),
-            next_time: std::f32::NEG_INFINITY,
+            next_time: Float::NEG_INFINITY,
             px_per_beat: 1.0,
             prev_sv: 1.0,
         }
@@ -20,8 +20,8 @@ impl<'p> SliderState<'p> {
 
     pub(crate) fn count_ticks(
         &mut self,
-        time: f32,
-        pixel_len: f32,
+        time: Float,
+        pixel_len: Float,
         repeats: usize,
         map: &Beatmap,
     ) -> usize {
@@ -41,7 +41,7 @@ impl<'p> SliderState<'p> {
             }
         }
 
-        let spans = repeats as f32;
+        let spans = repeats as Float;
         let beats = pixel_len * spans / self.px_per_beat;
         let ticks = ((beats - 0.1) / spans * map.tick_rate).ceil() as usize;
 
@@ -0,0 +1,97 @@
+use super::osu_object::{ASSUMED_SLIDER_RADIUS, NORMALIZED_RADIUS};
+use super::OsuObject;
+use crate::Float;
+
+const MIN_DELTA_TIME: Float = 25.0;
+const MAXIMUM_SLIDER_RADIUS: Float = NORMALIZED_RADIUS * 2.4;
+
+pub(crate) struct DifficultyObject {
+    pub(crate) base: OsuObject,
+    /// Index among all difficulty objects i.e. `0` for the second hit object.
+    pub(crate) idx: usize,
+
+    pub(crate) lazy_jump_dist: Float,
+    pub(crate) min_jump_dist: Float,
+    pub(crate) min_jump_time: Float,
+    pub(crate) travel_dist: Float,
+    pub(crate) travel_time: Float,
+    pub(crate) angle: Option<Float>,
+
+    pub(crate) delta: Float,
+    pub(crate) strain_time: Float,
+}
+
+impl DifficultyObject {
+    pub(crate) fn new(
+        base: &OsuObject,
+        prev: &OsuObject,
+        prev_prev: Option<&OsuObject>,
+        idx: usize,
+        scaling_factor: Float,
+    ) -> Self {
+        let delta = base.time - prev.time;
+        let strain_time = delta.max(MIN_DELTA_TIME);
+
+        let (travel_dist, travel_time) = if base.is_slider() {
+            let repeat_bonus = (1.0 + base.repeat_count() as Float / 2.5).powf(1.0 / 2.5);
+
+            (
+                base.lazy_travel_dist() * repeat_bonus,
+                base.lazy_travel_time().max(MIN_DELTA_TIME),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        let mut this = Self {
+            base: *base,
+            idx,
+
+            lazy_jump_dist: 0.0,
+            min_jump_dist: 0.0,
+            min_jump_time: strain_time,
+            travel_dist,
+            travel_time,
+            angle: None,
+
+            delta,
+            strain_time,
+        };
+
+        if base.is_spinner() || prev.is_spinner() {
+            return this;
+        }
+
+        let prev_cursor_pos = prev.lazy_end_pos();
+        this.lazy_jump_dist = ((base.pos - prev_cursor_pos) * scaling_factor).length();
+        this.min_jump_dist = this.lazy_jump_dist;
+
+        if prev.is_slider() {
+            let prev_travel_time = prev.lazy_travel_time().max(MIN_DELTA_TIME);
+            this.min_jump_time = (strain_time - prev_travel_time).max(MIN_DELTA_TIME);
+
+            // The cursor may have left the follow circle before the tail
+            // so the actual jump might be shorter than the lazy one.
+            let tail_jump_dist = (prev.tail_pos() - base.pos).length() * scaling_factor;
+
+            this.min_jump_dist = (this.lazy_jump_dist
+                - (MAXIMUM_SLIDER_RADIUS - ASSUMED_SLIDER_RADIUS))
+                .min(tail_jump_dist - MAXIMUM_SLIDER_RADIUS)
+                .max(0.0);
+        }
+
+        this.angle = prev_prev
+            .filter(|prev_prev| !prev_prev.is_spinner())
+            .map(|prev_prev| {
+                let v1 = prev_prev.lazy_end_pos() - prev.pos;
+                let v2 = base.pos - prev_cursor_pos;
+
+                let dot = v1.dot(v2);
+                let det = v1.x * v2.y - v1.y * v2.x;
+
+                det.atan2(dot).abs()
+            });
+
+        this
+    }
+}
@@ -0,0 +1,569 @@
+//! The osu!standard difficulty algorithm as of March 2024.
+//!
+//! Aim is additionally calculated without sliders to get the slider factor,
+//! speed considers the rhythm complexity, and the amount of difficult strains
+//! are counted so that misses can be penalized accordingly.
+//!
+//! Both slider paths and stack leniency are considered.
+
+#![cfg(feature = "all_included")]
+
+use super::super::{DifficultyAttributes, ObjectDifficulty};
+use crate::parse::Pos2;
+
+mod difficulty_object;
+mod osu_object;
+mod skill;
+mod skill_kind;
+mod slider_state;
+
+use difficulty_object::DifficultyObject;
+use osu_object::{OsuObject, NORMALIZED_RADIUS};
+use skill::Skill;
+use skill_kind::SkillKind;
+use slider_state::SliderState;
+
+use crate::{
+    context::Pool,
+    Beatmap, CalcContext, DifficultyParams, Float, GameMode, Mods, StarResult, Strains,
+};
+
+const OBJECT_RADIUS: Float = 64.0;
+const SECTION_LEN: Float = 400.0;
+const DIFFICULTY_MULTIPLIER: Float = 0.0675;
+const FLASHLIGHT_NORMALIZED_RADIUS: Float = 52.0;
+const STACK_DISTANCE: Float = 3.0;
+const PERFORMANCE_BASE_MULTIPLIER: Float = 1.14;
+
+const PREEMPT_MIN: Float = 450.0;
+const FADE_IN_DURATION: Float = 400.0;
+
+/// Star calculation for osu!standard maps.
+///
+/// Both slider paths and stack leniency are considered.
+///
+/// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+#[inline]
+pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> StarResult {
+    stars_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `stars` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn stars_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> StarResult {
+    stars_with_context(map, mods, passed_objects, params, &mut CalcContext::new())
+}
+
+/// Same as the `stars_with_params` function but reuses the buffers of the given context.
+pub fn stars_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> StarResult {
+    let mods = mods.for_mode(GameMode::STD);
+    let take = passed_objects
+        .unwrap_or(map.hit_objects.len())
+        .min(map.hit_objects.len());
+
+    let map_attributes = map.attributes().mods_with_params(mods, params);
+    let hitwindow = super::difficulty_range(map_attributes.od).floor() / map_attributes.clock_rate;
+    let od = (80.0 - hitwindow) / 6.0;
+
+    let mut diff_attributes = DifficultyAttributes {
+        ar: map_attributes.ar,
+        od,
+        slider_factor: 1.0,
+        n_circles: map.n_circles as usize,
+        n_sliders: map.n_sliders as usize,
+        n_spinners: map.n_spinners as usize,
+        ..Default::default()
+    };
+
+    if take < 2 {
+        return StarResult::Osu(diff_attributes);
+    }
+
+    let buffers = &mut ctx.v2024;
+    let mut skills = calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        buffers,
+        |_, _| {},
+    );
+
+    let mut aim_rating = skills[0].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let aim_difficult_strain_count = skills[0].count_difficult_strains();
+    let aim_rating_no_sliders = skills[1].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+
+    let mut speed_rating = skills[2].difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let speed_note_count = skills[2].relevant_note_count();
+    let speed_difficult_strain_count = skills[2].count_difficult_strains();
+
+    let mut flashlight_rating = skills.get_mut(3).map_or(0.0, |flashlight| {
+        flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER
+    });
+
+    buffers.recycle(skills);
+
+    let slider_factor = if aim_rating > 0.0 {
+        aim_rating_no_sliders / aim_rating
+    } else {
+        1.0
+    };
+
+    if mods.td() {
+        aim_rating = aim_rating.powf(0.8);
+        flashlight_rating = flashlight_rating.powf(0.8);
+    }
+
+    if mods.rx() {
+        aim_rating *= 0.9;
+        speed_rating = 0.0;
+        flashlight_rating *= 0.7;
+    }
+
+    let base_aim_performance = (5.0 * (aim_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+    let base_speed_performance = (5.0 * (speed_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+
+    let base_flashlight_performance = if mods.fl() {
+        flashlight_rating * flashlight_rating * 25.0
+    } else {
+        0.0
+    };
+
+    let base_performance = (base_aim_performance.powf(1.1)
+        + base_speed_performance.powf(1.1)
+        + base_flashlight_performance.powf(1.1))
+    .powf(1.0 / 1.1);
+
+    let stars = if base_performance > 0.00001 {
+        PERFORMANCE_BASE_MULTIPLIER.cbrt()
+            * 0.027
+            * ((100_000.0 / (2.0 as Float).powf(1.0 / 1.1) * base_performance).cbrt() + 4.0)
+    } else {
+        0.0
+    };
+
+    diff_attributes.stars = stars;
+    diff_attributes.aim_strain = aim_rating;
+    diff_attributes.speed_strain = speed_rating;
+    diff_attributes.flashlight_strain = flashlight_rating;
+    diff_attributes.slider_factor = slider_factor;
+    diff_attributes.speed_note_count = speed_note_count;
+    diff_attributes.aim_difficult_strain_count = aim_difficult_strain_count;
+    diff_attributes.speed_difficult_strain_count = speed_difficult_strain_count;
+
+    StarResult::Osu(diff_attributes)
+}
+
+/// Essentially the same as the `stars` function but instead of
+/// evaluating the final strains, it just returns them as is.
+///
+/// Suitable to plot the difficulty of a map over time.
+#[inline]
+pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
+    strains_with_params(map, mods, DifficultyParams::default())
+}
+
+/// Same as the `strains` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn strains_with_params(map: &Beatmap, mods: impl Mods, params: DifficultyParams) -> Strains {
+    strains_with_context(map, mods, params, &mut CalcContext::new())
+}
+
+/// Same as the `strains_with_params` function but reuses the buffers of the given context.
+pub fn strains_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> Strains {
+    let mods = mods.for_mode(GameMode::STD);
+
+    if map.hit_objects.len() < 2 {
+        return Strains::default();
+    }
+
+    let mut diff_attributes = DifficultyAttributes::default();
+    let take = map.hit_objects.len();
+
+    let buffers = &mut ctx.v2024;
+    let skills = calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        buffers,
+        |_, _| {},
+    );
+
+    let mut strains = vec![0.0; skills[0].strain_peaks.len()];
+
+    // Aim without sliders is skipped
+    for skill in skills.iter().take(1).chain(skills.iter().skip(2)) {
+        for (strain, peak) in strains.iter_mut().zip(skill.strain_peaks.iter()) {
+            *strain += peak;
+        }
+    }
+
+    buffers.recycle(skills);
+
+    Strains {
+        section_length: SECTION_LEN,
+        strains,
+    }
+}
+
+/// Essentially the same as the `stars` function but instead of
+/// evaluating the final strains, it returns the difficulty values
+/// of each hit object after it has been processed.
+///
+/// The first hit object has no predecessor and thus no strain so it is not included.
+///
+/// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+#[inline]
+pub fn object_difficulties(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+) -> Vec<ObjectDifficulty> {
+    object_difficulties_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `object_difficulties` function but with further difficulty parameters
+/// such as a custom clock rate.
+pub fn object_difficulties_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> Vec<ObjectDifficulty> {
+    let mods = mods.for_mode(GameMode::STD);
+    let take = passed_objects
+        .unwrap_or(map.hit_objects.len())
+        .min(map.hit_objects.len());
+
+    if take < 2 {
+        return Vec::new();
+    }
+
+    let mut diff_attributes = DifficultyAttributes::default();
+    let mut objects = Vec::with_capacity(take - 1);
+
+    calculate_skills(
+        map,
+        mods,
+        params,
+        take,
+        &mut diff_attributes,
+        &mut Buffers::default(),
+        |h, skills| {
+            objects.push(ObjectDifficulty {
+                start_time: h.base.time,
+                jump_dist: h.lazy_jump_dist,
+                travel_dist: h.travel_dist,
+                angle: h.angle,
+                strain_time: h.strain_time,
+                aim_strain: skills[0].current_strain(),
+                speed_strain: skills[2].current_strain(),
+                flashlight_strain: skills.get(3).map_or(0.0, Skill::current_strain),
+            })
+        },
+    );
+
+    objects
+}
+
+/// Processes the first `take` hit objects and returns the aim, aim without sliders,
+/// speed, and, in case of FL, flashlight skill in that order.
+///
+/// `on_object` is called right after each difficulty object has been processed.
+fn calculate_skills(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    take: usize,
+    diff_attributes: &mut DifficultyAttributes,
+    buffers: &mut Buffers,
+    mut on_object: impl FnMut(&DifficultyObject, &[Skill]),
+) -> Vec<Skill> {
+    let map_attributes = map.attributes().mods_with_params(mods, params);
+    let clock_rate = map_attributes.clock_rate;
+
+    let hr = mods.hr();
+
+    let raw_ar = params.ar.unwrap_or_else(|| {
+        if hr {
+            (map.ar * 1.4).min(10.0)
+        } else if mods.ez() {
+            map.ar * 0.5
+        } else {
+            map.ar
+        }
+    });
+
+    let time_preempt = difficulty_range_ar(raw_ar);
+    let scale = (1.0 - 0.7 * (map_attributes.cs - 5.0) / 5.0) / 2.0;
+    let radius = OBJECT_RADIUS * scale;
+    let lazy_scaling_factor = NORMALIZED_RADIUS / radius;
+    let mut scaling_factor = lazy_scaling_factor;
+
+    if radius < 30.0 {
+        let small_circle_bonus = (30.0 - radius).min(5.0) / 50.0;
+        scaling_factor *= 1.0 + small_circle_bonus;
+    }
+
+    let mut slider_state = SliderState::new(map);
+    let ticks_buf = &mut buffers.ticks;
+    ticks_buf.clear();
+    let curve_buf = &mut buffers.curve_points;
+
+    let hit_objects_iter = map.hit_objects.iter().take(take).filter_map(|h| {
+        OsuObject::new(
+            h,
+            map,
+            lazy_scaling_factor,
+            hr,
+            ticks_buf,
+            curve_buf,
+            diff_attributes,
+            &mut slider_state,
+        )
+    });
+
+    let hit_objects = &mut buffers.hit_objects;
+    hit_objects.clear();
+    hit_objects.extend(hit_objects_iter);
+
+    let stack_threshold = time_preempt * map.stack_leniency;
+
+    if map.version >= 6 {
+        stacking(hit_objects, stack_threshold);
+    } else {
+        old_stacking(hit_objects, stack_threshold);
+    }
+
+    let scale_factor = scale * -6.4;
+
+    for h in hit_objects.iter_mut() {
+        let stack_offset = h.stack_height * scale_factor;
+
+        let stack_offset = Pos2 {
+            x: stack_offset,
+            y: stack_offset,
+        };
+
+        h.finalize(stack_offset, clock_rate);
+    }
+
+    let hit_objects: &[OsuObject] = hit_objects;
+    let diff_objects = &mut buffers.diff_objects;
+    diff_objects.clear();
+
+    let diff_objects_iter = (1..hit_objects.len()).map(|i| {
+        let prev_prev = i.checked_sub(2).map(|idx| &hit_objects[idx]);
+
+        DifficultyObject::new(
+            &hit_objects[i],
+            &hit_objects[i - 1],
+            prev_prev,
+            i - 1,
+            scaling_factor,
+        )
+    });
+
+    diff_objects.extend(diff_objects_iter);
+
+    let hit_window = 2.0 * super::difficulty_range(map_attributes.od) / clock_rate;
+
+    let pool = &mut buffers.skill_pool;
+    let mut skills = std::mem::take(&mut buffers.skills);
+    skills.push(Skill::from_pool(
+        pool,
+        SkillKind::Aim { with_sliders: true },
+    ));
+    skills.push(Skill::from_pool(
+        pool,
+        SkillKind::Aim {
+            with_sliders: false,
+        },
+    ));
+    skills.push(Skill::from_pool(pool, SkillKind::Speed { hit_window }));
+
+    if mods.fl() {
+        let kind = SkillKind::Flashlight {
+            scaling_factor: FLASHLIGHT_NORMALIZED_RADIUS / radius,
+            hidden: mods.hd(),
+            time_preempt: time_preempt / clock_rate,
+            time_fade_in: FADE_IN_DURATION * (time_preempt / PREEMPT_MIN).min(1.0) / clock_rate,
+        };
+
+        skills.push(Skill::from_pool(pool, kind));
+    }
+
+    let mut current_section_end = match diff_objects.first() {
+        Some(h) => (h.base.time / SECTION_LEN).ceil() * SECTION_LEN,
+        None => return skills,
+    };
+
+    for h in diff_objects.iter() {
+        while h.base.time > current_section_end {
+            for skill in skills.iter_mut() {
+                skill.save_current_peak();
+                skill.start_new_section_from(current_section_end);
+            }
+
+            current_section_end += SECTION_LEN;
+        }
+
+        for skill in skills.iter_mut() {
+            skill.process(h, diff_objects);
+        }
+
+        on_object(h, &skills);
+    }
+
+    for skill in skills.iter_mut() {
+        skill.save_current_peak();
+    }
+
+    skills
+}
+
+fn stacking(hit_objects: &mut [OsuObject], stack_threshold: Float) {
+    if hit_objects.is_empty() {
+        return;
+    }
+
+    let mut extended_start_idx = 0;
+    let extended_end_idx = hit_objects.len() - 1;
+
+    for mut i in (1..=extended_end_idx).rev() {
+        let mut n = i;
+
+        if hit_objects[i].stack_height.abs() > 0.0 || hit_objects[i].is_spinner() {
+            continue;
+        }
+
+        if hit_objects[i].is_circle() {
+            loop {
+                n = match n.checked_sub(1) {
+                    Some(n) => n,
+                    None => break,
+                };
+
+                if hit_objects[n].is_spinner() {
+                    continue;
+                } else if hit_objects[i].time - hit_objects[n].end_time() > stack_threshold {
+                    break;
+                } else if n < extended_start_idx {
+                    hit_objects[n].stack_height = 0.0;
+                    extended_start_idx = n;
+                }
+
+                if hit_objects[n].is_slider()
+                    && hit_objects[n].end_pos().distance(hit_objects[i].pos) < STACK_DISTANCE
+                {
+                    let offset = hit_objects[i].stack_height - hit_objects[n].stack_height + 1.0;
+
+                    for j in n + 1..=i {
+                        if hit_objects[n].pos.distance(hit_objects[j].pos) < STACK_DISTANCE {
+                            hit_objects[j].stack_height -= offset;
+                        }
+                    }
+
+                    break;
+                } else if hit_objects[n].pos.distance(hit_objects[i].pos) < STACK_DISTANCE {
+                    hit_objects[n].stack_height = hit_objects[i].stack_height + 1.0;
+                    i = n;
+                }
+            }
+        } else if hit_objects[i].is_slider() {
+            loop {
+                n = match n.checked_sub(1) {
+                    Some(n) => n,
+                    None => break,
+                };
+
+                if hit_objects[n].is_spinner() {
+                    continue;
+                } else if hit_objects[i].time - hit_objects[n].time > stack_threshold {
+                    break;
+                } else if hit_objects[n].end_pos().distance(hit_objects[i].pos) < STACK_DISTANCE {
+                    hit_objects[n].stack_height = hit_objects[i].stack_height + 1.0;
+                    i = n;
+                }
+            }
+        }
+    }
+}
+
+fn old_stacking(hit_objects: &mut [OsuObject], stack_threshold: Float) {
+    for i in 0..hit_objects.len() {
+        if hit_objects[i].stack_height != 0.0 && !hit_objects[i].is_slider() {
+            continue;
+        }
+
+        let mut start_time = hit_objects[i].end_time();
+        let end_pos = hit_objects[i].end_pos();
+
+        let mut slider_stack = 0.0;
+
+        for j in i + 1..hit_objects.len() {
+            if hit_objects[j].time - stack_threshold > start_time {
+                break;
+            }
+
+            if hit_objects[j].pos.distance(hit_objects[i].pos) < STACK_DISTANCE {
+                hit_objects[i].stack_height += 1.0;
+                start_time = hit_objects[j].end_time();
+            } else if hit_objects[j].pos.distance(end_pos) < STACK_DISTANCE {
+                slider_stack += 1.0;
+                hit_objects[j].stack_height -= slider_stack;
+                start_time = hit_objects[j].end_time();
+            }
+        }
+    }
+}
+
+const OSU_AR_MAX: Float = 450.0;
+const OSU_AR_AVG: Float = 1200.0;
+const OSU_AR_MIN: Float = 1800.0;
+
+#[inline]
+fn difficulty_range_ar(ar: Float) -> Float {
+    crate::difficulty_range(ar, OSU_AR_MAX, OSU_AR_AVG, OSU_AR_MIN)
+}
+
+/// Buffers of this version that are kept by a [`CalcContext`].
+#[derive(Default)]
+pub(crate) struct Buffers {
+    hit_objects: Vec<OsuObject>,
+    diff_objects: Vec<DifficultyObject>,
+    ticks: Vec<Float>,
+    curve_points: Vec<Pos2>,
+    skills: Vec<Skill>,
+    skill_pool: Pool<Skill>,
+}
+
+impl Buffers {
+    /// Keeps the skills of a finished calculation for the next one.
+    #[inline]
+    fn recycle(&mut self, mut skills: Vec<Skill>) {
+        self.skill_pool.put_all(&mut skills);
+        self.skills = skills;
+    }
+}
@@ -0,0 +1,333 @@
+use super::super::super::DifficultyAttributes;
+use super::slider_state::SliderState;
+
+use crate::{
+    curve::Curve,
+    parse::{HitObject, HitObjectKind, Pos2},
+    Beatmap, Float,
+};
+
+const LEGACY_LAST_TICK_OFFSET: Float = 36.0;
+
+pub(crate) const NORMALIZED_RADIUS: Float = 50.0;
+pub(crate) const ASSUMED_SLIDER_RADIUS: Float = NORMALIZED_RADIUS * 1.8;
+
+#[derive(Clone, Copy)]
+pub(crate) struct OsuObject {
+    pub(crate) time: Float,
+    pub(crate) pos: Pos2,
+    pub(crate) stack_height: Float,
+    kind: OsuObjectKind,
+}
+
+#[derive(Clone, Copy)]
+enum OsuObjectKind {
+    Circle,
+    Slider {
+        end_time: Float,
+        end_pos: Pos2,
+        tail_pos: Pos2,
+        lazy_end_pos: Pos2,
+        lazy_travel_dist: Float,
+        lazy_travel_time: Float,
+        repeats: usize,
+    },
+    Spinner {
+        end_time: Float,
+    },
+}
+
+impl OsuObject {
+    /// `scaling_factor` should be w.r.t. the normalized radius without small circle bonus.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        h: &HitObject,
+        map: &Beatmap,
+        scaling_factor: Float,
+        hr: bool,
+        ticks: &mut Vec<Float>,
+        curve_buf: &mut Vec<Pos2>,
+        attributes: &mut DifficultyAttributes,
+        slider_state: &mut SliderState,
+    ) -> Option<Self> {
+        attributes.max_combo += 1; // hitcircle, slider head, or spinner
+        let mut pos = h.pos;
+
+        if hr {
+            pos.y = 384.0 - pos.y;
+        }
+
+        let obj = match &h.kind {
+            HitObjectKind::Circle => Self {
+                time: h.start_time,
+                pos,
+                stack_height: 0.0,
+                kind: OsuObjectKind::Circle,
+            },
+            HitObjectKind::Slider {
+                pixel_len,
+                repeats,
+                curve_points,
+                path_type,
+                ..
+            } => {
+                // Responsible for timing point values
+                slider_state.update(h.start_time);
+
+                let mut tick_distance = 100.0 * map.sv / map.tick_rate;
+
+                if map.version >= 8 {
+                    tick_distance /= (100.0 / slider_state.speed_mult).clamp(10.0, 1000.0) / 100.0;
+                }
+
+                let duration = *repeats as Float * slider_state.beat_len * pixel_len
+                    / (map.sv * slider_state.speed_mult)
+                    / 100.0;
+                let span_duration = duration / *repeats as Float;
+
+                // Build the curve w.r.t. the curve points
+                let curve = Curve::new(curve_points, *path_type, curve_buf);
+
+                let position_at = |mut progress: Float| {
+                    if progress % 2.0 >= 1.0 {
+                        progress = 1.0 - progress % 1.0;
+                    } else {
+                        progress %= 1.0;
+                    }
+
+                    let mut pos = curve.point_at_distance_unextended(pixel_len * progress);
+
+                    if hr {
+                        pos.y = 384.0 - pos.y;
+                    }
+
+                    pos
+                };
+
+                // The cursor only has to follow until shortly before the actual end
+                let tracking_end_time = (h.start_time + duration - LEGACY_LAST_TICK_OFFSET)
+                    .max(h.start_time + duration / 2.0);
+                let lazy_travel_time = tracking_end_time - h.start_time;
+                let mut lazy_end_pos = position_at(lazy_travel_time / span_duration);
+
+                let mut cursor_pos = pos;
+                let mut lazy_travel_dist = 0.0;
+
+                // Called on each nested object except for the head and the tail.
+                // Increases combo and moves the cursor just enough to
+                // stay within the follow circle.
+                let mut process_nested = |time: Float, required_movement: Float| {
+                    attributes.max_combo += 1;
+
+                    let movement = position_at((time - h.start_time) / span_duration) - cursor_pos;
+                    let movement_len = scaling_factor * movement.length();
+
+                    if movement_len > required_movement {
+                        let remaining = movement_len - required_movement;
+                        cursor_pos += movement * (remaining / movement_len);
+                        lazy_travel_dist += remaining;
+                    }
+                };
+
+                let mut current_distance = tick_distance;
+                let time_add = duration * (tick_distance / (pixel_len * *repeats as Float));
+
+                let target = pixel_len - tick_distance / 8.0;
+                ticks.reserve((target / tick_distance) as usize);
+
+                // Tick of the first span
+                if current_distance < target {
+                    for tick_idx in 1.. {
+                        let time = h.start_time + time_add * tick_idx as Float;
+                        process_nested(time, ASSUMED_SLIDER_RADIUS);
+                        ticks.push(time);
+                        current_distance += tick_distance;
+
+                        if current_distance >= target {
+                            break;
+                        }
+                    }
+                }
+
+                // Other spans
+                if *repeats > 1 {
+                    for repeat_id in 1..*repeats {
+                        let time_offset = span_duration * repeat_id as Float;
+
+                        // Reverse tick
+                        process_nested(h.start_time + time_offset, NORMALIZED_RADIUS);
+
+                        // Actual ticks
+                        if repeat_id & 1 == 1 {
+                            ticks
+                                .iter()
+                                .rev()
+                                .for_each(|&time| process_nested(time, ASSUMED_SLIDER_RADIUS));
+                        } else {
+                            ticks
+                                .iter()
+                                .for_each(|&time| process_nested(time, ASSUMED_SLIDER_RADIUS));
+                        }
+                    }
+                }
+
+                ticks.clear();
+
+                // Slider tail; the cursor may take the shorter path to the tracking end
+                attributes.max_combo += 1;
+
+                let tail_pos = position_at(*repeats as Float);
+                let mut movement = tail_pos - cursor_pos;
+                let lazy_movement = lazy_end_pos - cursor_pos;
+
+                if lazy_movement.length() < movement.length() {
+                    movement = lazy_movement;
+                }
+
+                let movement_len = scaling_factor * movement.length();
+
+                if movement_len > ASSUMED_SLIDER_RADIUS {
+                    let remaining = movement_len - ASSUMED_SLIDER_RADIUS;
+                    cursor_pos += movement * (remaining / movement_len);
+                    lazy_travel_dist += remaining;
+                }
+
+                lazy_end_pos = cursor_pos;
+
+                let mut end_pos = curve.point_at_distance_unextended(*pixel_len);
+
+                if hr {
+                    end_pos.y = 384.0 - end_pos.y;
+                }
+
+                Self {
+                    time: h.start_time,
+                    pos,
+                    stack_height: 0.0,
+                    kind: OsuObjectKind::Slider {
+                        end_time: tracking_end_time,
+                        end_pos,
+                        tail_pos,
+                        lazy_end_pos,
+                        lazy_travel_dist,
+                        lazy_travel_time,
+                        repeats: *repeats,
+                    },
+                }
+            }
+            HitObjectKind::Spinner { end_time } => Self {
+                time: h.start_time,
+                pos,
+                stack_height: 0.0,
+                kind: OsuObjectKind::Spinner {
+                    end_time: *end_time,
+                },
+            },
+            HitObjectKind::Hold { .. } => return None,
+        };
+
+        Some(obj)
+    }
+
+    /// Apply the stack offset to all positions and the clock rate to all timings.
+    pub(crate) fn finalize(&mut self, stack_offset: Pos2, clock_rate: Float) {
+        self.pos += stack_offset;
+        self.time /= clock_rate;
+
+        match &mut self.kind {
+            OsuObjectKind::Circle => {}
+            OsuObjectKind::Slider {
+                end_time,
+                end_pos,
+                tail_pos,
+                lazy_end_pos,
+                lazy_travel_time,
+                ..
+            } => {
+                *end_time /= clock_rate;
+                *end_pos += stack_offset;
+                *tail_pos += stack_offset;
+                *lazy_end_pos += stack_offset;
+                *lazy_travel_time /= clock_rate;
+            }
+            OsuObjectKind::Spinner { end_time } => *end_time /= clock_rate,
+        }
+    }
+
+    /// The travelled distance including repeats and w.r.t. the normalized radius.
+    #[inline]
+    pub(crate) fn lazy_travel_dist(&self) -> Float {
+        match &self.kind {
+            OsuObjectKind::Slider {
+                lazy_travel_dist, ..
+            } => *lazy_travel_dist,
+            OsuObjectKind::Circle | OsuObjectKind::Spinner { .. } => 0.0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn lazy_travel_time(&self) -> Float {
+        match &self.kind {
+            OsuObjectKind::Slider {
+                lazy_travel_time, ..
+            } => *lazy_travel_time,
+            OsuObjectKind::Circle | OsuObjectKind::Spinner { .. } => 0.0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn repeat_count(&self) -> usize {
+        match &self.kind {
+            OsuObjectKind::Slider { repeats, .. } => repeats.saturating_sub(1),
+            OsuObjectKind::Circle | OsuObjectKind::Spinner { .. } => 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn end_time(&self) -> Float {
+        match &self.kind {
+            OsuObjectKind::Circle => self.time,
+            OsuObjectKind::Slider { end_time, .. } => *end_time,
+            OsuObjectKind::Spinner { end_time } => *end_time,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn end_pos(&self) -> Pos2 {
+        match &self.kind {
+            OsuObjectKind::Circle | OsuObjectKind::Spinner { .. } => self.pos,
+            OsuObjectKind::Slider { end_pos, .. } => *end_pos,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn tail_pos(&self) -> Pos2 {
+        match &self.kind {
+            OsuObjectKind::Circle | OsuObjectKind::Spinner { .. } => self.pos,
+            OsuObjectKind::Slider { tail_pos, .. } => *tail_pos,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn lazy_end_pos(&self) -> Pos2 {
+        match &self.kind {
+            OsuObjectKind::Circle | OsuObjectKind::Spinner { .. } => self.pos,
+            OsuObjectKind::Slider { lazy_end_pos, .. } => *lazy_end_pos,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_circle(&self) -> bool {
+        matches!(self.kind, OsuObjectKind::Circle)
+    }
+
+    #[inline]
+    pub(crate) fn is_slider(&self) -> bool {
+        matches!(self.kind, OsuObjectKind::Slider { .. })
+    }
+
+    #[inline]
+    pub(crate) fn is_spinner(&self) -> bool {
+        matches!(self.kind, OsuObjectKind::Spinner { .. })
+    }
+}
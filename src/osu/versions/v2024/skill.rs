@@ -0,0 +1,214 @@
+use crate::{context::Pool, math_util, Float};
+
+use super::skill_kind::rhythm_complexity;
+use super::{DifficultyObject, SkillKind};
+
+use std::cmp::Ordering;
+
+const SPEED_SKILL_MULTIPLIER: Float = 1375.0;
+const SPEED_STRAIN_DECAY_BASE: Float = 0.3;
+const REDUCED_STRAIN_BASELINE: Float = 0.75;
+
+const AIM_SKILL_MULTIPLIER: Float = 23.55;
+const AIM_STRAIN_DECAY_BASE: Float = 0.15;
+
+const FLASHLIGHT_SKILL_MULTIPLIER: Float = 0.05;
+const FLASHLIGHT_STRAIN_DECAY_BASE: Float = 0.15;
+
+const DECAY_WEIGHT: Float = 0.9;
+
+pub(crate) struct Skill {
+    current_strain: Float,
+    current_rhythm: Float,
+    current_section_peak: Float,
+
+    kind: SkillKind,
+    pub(crate) strain_peaks: Vec<Float>,
+    pub(crate) object_strains: Vec<Float>,
+
+    prev_time: Option<Float>,
+    difficulty: Float,
+}
+
+impl Skill {
+    #[inline]
+    pub(crate) fn new(kind: SkillKind) -> Self {
+        Self::with_buffers(kind, Vec::with_capacity(128), Vec::with_capacity(256))
+    }
+
+    /// Same as `new` but reuses the buffers of a skill from the pool.
+    #[inline]
+    pub(crate) fn from_pool(pool: &mut Pool<Self>, kind: SkillKind) -> Self {
+        match pool.take() {
+            Some(skill) => {
+                let Self {
+                    mut strain_peaks,
+                    mut object_strains,
+                    ..
+                } = skill;
+
+                strain_peaks.clear();
+                object_strains.clear();
+
+                Self::with_buffers(kind, strain_peaks, object_strains)
+            }
+            None => Self::new(kind),
+        }
+    }
+
+    #[inline]
+    fn with_buffers(kind: SkillKind, strain_peaks: Vec<Float>, object_strains: Vec<Float>) -> Self {
+        Self {
+            current_strain: 0.0,
+            current_rhythm: 1.0,
+            current_section_peak: 0.0,
+
+            kind,
+            strain_peaks,
+            object_strains,
+
+            prev_time: None,
+            difficulty: 0.0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn save_current_peak(&mut self) {
+        self.strain_peaks.push(self.current_section_peak);
+    }
+
+    /// The strain after the latest processed object, including the rhythm in case of speed.
+    #[inline]
+    pub(crate) fn current_strain(&self) -> Float {
+        self.current_strain * self.current_rhythm
+    }
+
+    #[inline]
+    pub(crate) fn start_new_section_from(&mut self, time: Float) {
+        self.current_section_peak = self.peak_strain(time - self.prev_time.unwrap());
+    }
+
+    pub(crate) fn process(
+        &mut self,
+        current: &DifficultyObject,
+        diff_objects: &[DifficultyObject],
+    ) {
+        let strain = self.kind.strain_value_of(current, diff_objects) * self.skill_multiplier();
+
+        if let SkillKind::Speed { hit_window } = self.kind {
+            self.current_strain *= self.strain_decay(current.strain_time);
+            self.current_strain += strain;
+            self.current_rhythm = rhythm_complexity(current, diff_objects, hit_window);
+        } else {
+            self.current_strain *= self.strain_decay(current.delta);
+            self.current_strain += strain;
+        }
+
+        let total_strain = self.current_strain();
+        self.object_strains.push(total_strain);
+        self.current_section_peak = self.current_section_peak.max(total_strain);
+        self.prev_time.replace(current.base.time);
+    }
+
+    pub(crate) fn difficulty_value(&mut self) -> Float {
+        if let SkillKind::Flashlight { .. } = self.kind {
+            let (_, difficulty_multiplier) = self.kind.difficulty_values();
+
+            return self.strain_peaks.iter().sum::<Float>() * difficulty_multiplier;
+        }
+
+        let mut difficulty = 0.0;
+        let mut weight = 1.0;
+
+        let (reduced_section_count, difficulty_multiplier) = self.kind.difficulty_values();
+        let reduced_section_count_f32 = reduced_section_count as Float;
+
+        // Sections with 0 strain are excluded to avoid worst-case time complexity
+        self.strain_peaks.retain(|&peak| peak > 0.0);
+
+        self.strain_peaks
+            .sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        for (i, strain) in self
+            .strain_peaks
+            .iter_mut()
+            .take(reduced_section_count)
+            .enumerate()
+        {
+            let clamped = (i as Float / reduced_section_count_f32).clamp(0.0, 1.0);
+            let scale = (math_util::lerp(1.0, 10.0, clamped)).log10();
+            *strain *= math_util::lerp(REDUCED_STRAIN_BASELINE, 1.0, scale);
+        }
+
+        self.strain_peaks
+            .sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        for &strain in self.strain_peaks.iter() {
+            difficulty += strain * weight;
+            weight *= DECAY_WEIGHT;
+        }
+
+        self.difficulty = difficulty;
+
+        difficulty * difficulty_multiplier
+    }
+
+    /// Weighted amount of objects whose strain is close to the top strain.
+    ///
+    /// Must be called after [`difficulty_value`](Skill::difficulty_value).
+    pub(crate) fn count_difficult_strains(&self) -> Float {
+        if self.difficulty <= 0.0 {
+            return 0.0;
+        }
+
+        // What would the top strain be if all strain values were identical
+        let consistent_top_strain = self.difficulty / 10.0;
+
+        self.object_strains
+            .iter()
+            .map(|strain| 1.1 / (1.0 + (-10.0 * (strain / consistent_top_strain - 0.88)).exp()))
+            .sum()
+    }
+
+    /// Weighted amount of objects that are relevant for the strain.
+    pub(crate) fn relevant_note_count(&self) -> Float {
+        let max_strain = self.object_strains.iter().copied().fold(0.0, Float::max);
+
+        if max_strain <= 0.0 {
+            return 0.0;
+        }
+
+        self.object_strains
+            .iter()
+            .map(|strain| 1.0 / (1.0 + (-(strain / max_strain * 12.0 - 6.0)).exp()))
+            .sum()
+    }
+
+    #[inline]
+    fn skill_multiplier(&self) -> Float {
+        match self.kind {
+            SkillKind::Aim { .. } => AIM_SKILL_MULTIPLIER,
+            SkillKind::Speed { .. } => SPEED_SKILL_MULTIPLIER,
+            SkillKind::Flashlight { .. } => FLASHLIGHT_SKILL_MULTIPLIER,
+        }
+    }
+
+    #[inline]
+    fn strain_decay_base(&self) -> Float {
+        match self.kind {
+            SkillKind::Aim { .. } => AIM_STRAIN_DECAY_BASE,
+            SkillKind::Speed { .. } => SPEED_STRAIN_DECAY_BASE,
+            SkillKind::Flashlight { .. } => FLASHLIGHT_STRAIN_DECAY_BASE,
+        }
+    }
+
+    #[inline]
+    fn peak_strain(&self, delta_time: Float) -> Float {
+        self.current_strain() * self.strain_decay(delta_time)
+    }
+
+    #[inline]
+    fn strain_decay(&self, ms: Float) -> Float {
+        self.strain_decay_base().powf(ms / 1000.0)
+    }
+}
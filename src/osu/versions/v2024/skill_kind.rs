@@ -0,0 +1,461 @@
+use super::DifficultyObject;
+
+use crate::{
+    float::consts::{FRAC_PI_2, FRAC_PI_6, PI},
+    Float,
+};
+
+const WIDE_ANGLE_MULTIPLIER: Float = 1.5;
+const ACUTE_ANGLE_MULTIPLIER: Float = 1.95;
+const SLIDER_MULTIPLIER: Float = 1.35;
+const VELOCITY_CHANGE_MULTIPLIER: Float = 0.75;
+
+const SINGLE_SPACING_THRESHOLD: Float = 125.0;
+const MIN_SPEED_BONUS: Float = 75.0;
+const SPEED_BALANCING_FACTOR: Float = 40.0;
+
+const HISTORY_TIME_MAX: Float = 5000.0;
+const HISTORY_OBJECTS_MAX: usize = 32;
+const RHYTHM_MULTIPLIER: Float = 0.75;
+
+const FLASHLIGHT_HISTORY_LEN: usize = 10;
+const FLASHLIGHT_MAX_OPACITY_BONUS: Float = 0.4;
+const FLASHLIGHT_HIDDEN_BONUS: Float = 0.2;
+const FLASHLIGHT_MIN_VELOCITY: Float = 0.5;
+const FLASHLIGHT_SLIDER_MULTIPLIER: Float = 1.3;
+const FLASHLIGHT_MIN_ANGLE_MULTIPLIER: Float = 0.2;
+
+const AIM_REDUCED_SECTION_COUNT: usize = 10;
+const SPEED_REDUCED_SECTION_COUNT: usize = 5;
+
+const AIM_DIFFICULTY_MULTIPLIER: Float = 1.06;
+const SPEED_DIFFICULTY_MULTIPLIER: Float = 1.04;
+
+#[derive(Copy, Clone)]
+pub(crate) enum SkillKind {
+    Aim {
+        with_sliders: bool,
+    },
+    Speed {
+        /// Full window of a 300 w.r.t. the clock rate
+        hit_window: Float,
+    },
+    Flashlight {
+        /// Scaling factor w.r.t. the radius, without small circle bonus
+        scaling_factor: Float,
+        hidden: bool,
+        time_preempt: Float,
+        time_fade_in: Float,
+    },
+}
+
+impl SkillKind {
+    /// `diff_objects` contains all difficulty objects, including `current`.
+    pub(crate) fn strain_value_of(
+        self,
+        current: &DifficultyObject,
+        diff_objects: &[DifficultyObject],
+    ) -> Float {
+        match self {
+            Self::Aim { with_sliders } => {
+                let (last, last_last) = match (
+                    previous(current, diff_objects, 0),
+                    previous(current, diff_objects, 1),
+                ) {
+                    (Some(last), Some(last_last)) => (last, last_last),
+                    _ => return 0.0,
+                };
+
+                if current.base.is_spinner() || last.base.is_spinner() {
+                    return 0.0;
+                }
+
+                // Velocity from the end of the previous object to the current object
+                let mut curr_vel = current.lazy_jump_dist / current.strain_time;
+
+                if last.base.is_slider() && with_sliders {
+                    let travel_vel = last.travel_dist / last.travel_time;
+                    let movement_vel = current.min_jump_dist / current.min_jump_time;
+                    curr_vel = curr_vel.max(movement_vel + travel_vel);
+                }
+
+                let mut prev_vel = last.lazy_jump_dist / last.strain_time;
+
+                if last_last.base.is_slider() && with_sliders {
+                    let travel_vel = last_last.travel_dist / last_last.travel_time;
+                    let movement_vel = last.min_jump_dist / last.min_jump_time;
+                    prev_vel = prev_vel.max(movement_vel + travel_vel);
+                }
+
+                let mut wide_angle_bonus = 0.0;
+                let mut acute_angle_bonus = 0.0;
+                let mut velocity_change_bonus = 0.0;
+
+                let mut aim_strain = curr_vel;
+
+                // Only bother with angles if the rhythms are similar
+                if current.strain_time.max(last.strain_time)
+                    < 1.25 * current.strain_time.min(last.strain_time)
+                {
+                    if let (Some(curr_angle), Some(last_angle), Some(last_last_angle)) =
+                        (current.angle, last.angle, last_last.angle)
+                    {
+                        let angle_bonus = curr_vel.min(prev_vel);
+
+                        wide_angle_bonus = calc_wide_angle_bonus(curr_angle);
+                        acute_angle_bonus = calc_acute_angle_bonus(curr_angle);
+
+                        // Only buff deltas slower than 300 bpm 1/2
+                        if current.strain_time > 100.0 {
+                            acute_angle_bonus = 0.0;
+                        } else {
+                            acute_angle_bonus *= calc_acute_angle_bonus(last_angle)
+                                * angle_bonus.min(125.0 / current.strain_time)
+                                * (FRAC_PI_2 * ((100.0 - current.strain_time) / 25.0).min(1.0))
+                                    .sin()
+                                    .powi(2)
+                                * (FRAC_PI_2 * (current.lazy_jump_dist.clamp(50.0, 100.0) - 50.0)
+                                    / 50.0)
+                                    .sin()
+                                    .powi(2);
+                        }
+
+                        // Penalize repeated angles
+                        wide_angle_bonus *= angle_bonus
+                            * (1.0
+                                - wide_angle_bonus.min(calc_wide_angle_bonus(last_angle).powi(3)));
+                        acute_angle_bonus *= 0.5
+                            + 0.5
+                                * (1.0
+                                    - acute_angle_bonus
+                                        .min(calc_acute_angle_bonus(last_last_angle).powi(3)));
+                    }
+                }
+
+                if prev_vel.max(curr_vel) > 0.0 {
+                    // Use the average velocity over the whole object when awarding differences
+                    prev_vel = (last.lazy_jump_dist + last_last.travel_dist) / last.strain_time;
+                    curr_vel = (current.lazy_jump_dist + last.travel_dist) / current.strain_time;
+
+                    let dist_ratio = (FRAC_PI_2 * (prev_vel - curr_vel).abs()
+                        / prev_vel.max(curr_vel))
+                    .sin()
+                    .powi(2);
+
+                    // Reward for % distance up to 125 / strain time for overlaps
+                    let overlap_vel_buff = (125.0 / current.strain_time.min(last.strain_time))
+                        .min((prev_vel - curr_vel).abs());
+
+                    velocity_change_bonus = overlap_vel_buff
+                        * dist_ratio
+                        * (current.strain_time.min(last.strain_time)
+                            / current.strain_time.max(last.strain_time))
+                        .powi(2);
+                }
+
+                aim_strain += (acute_angle_bonus * ACUTE_ANGLE_MULTIPLIER).max(
+                    wide_angle_bonus * WIDE_ANGLE_MULTIPLIER
+                        + velocity_change_bonus * VELOCITY_CHANGE_MULTIPLIER,
+                );
+
+                // Reward sliders based on their velocity
+                if with_sliders && last.base.is_slider() {
+                    aim_strain += last.travel_dist / last.travel_time * SLIDER_MULTIPLIER;
+                }
+
+                aim_strain
+            }
+            Self::Speed { hit_window } => {
+                if current.base.is_spinner() {
+                    return 0.0;
+                }
+
+                let mut strain_time = current.strain_time;
+                let mut doubletapness = 1.0;
+
+                // Nerf doubletappable doubles
+                if let Some(next) = diff_objects.get(current.idx + 1) {
+                    let curr_delta = current.delta.max(1.0);
+                    let next_delta = next.delta.max(1.0);
+                    let delta_diff = (next_delta - curr_delta).abs();
+                    let speed_ratio = curr_delta / curr_delta.max(delta_diff);
+                    let window_ratio = (curr_delta / hit_window).min(1.0).powi(2);
+                    doubletapness = speed_ratio.powf(1.0 - window_ratio);
+                }
+
+                // Cap the delta time to the OD 300 hit window
+                strain_time /= (strain_time / hit_window / 0.93).clamp(0.92, 1.0);
+
+                let speed_bonus = if strain_time < MIN_SPEED_BONUS {
+                    1.0 + 0.75 * ((MIN_SPEED_BONUS - strain_time) / SPEED_BALANCING_FACTOR).powi(2)
+                } else {
+                    1.0
+                };
+
+                let travel_dist =
+                    previous(current, diff_objects, 0).map_or(0.0, |prev| prev.travel_dist);
+                let dist = SINGLE_SPACING_THRESHOLD.min(travel_dist + current.min_jump_dist);
+
+                (speed_bonus + speed_bonus * (dist / SINGLE_SPACING_THRESHOLD).powf(3.5))
+                    * doubletapness
+                    / strain_time
+            }
+            Self::Flashlight {
+                scaling_factor,
+                hidden,
+                time_preempt,
+                time_fade_in,
+            } => {
+                if current.base.is_spinner() {
+                    return 0.0;
+                }
+
+                let pos = current.base.pos;
+                let mut small_dist_nerf = 1.0;
+                let mut cumulative_strain_time = 0.0;
+                let mut result = 0.0;
+                let mut last = current;
+                let mut angle_repeat_count = 0.0;
+
+                for i in 0..current.idx.min(FLASHLIGHT_HISTORY_LEN) {
+                    let prev = &diff_objects[current.idx - i - 1];
+                    cumulative_strain_time += last.strain_time;
+
+                    if !prev.base.is_spinner() {
+                        let jump_dist = (pos - prev.base.end_pos()).length();
+
+                        // Nerf objects that can be easily seen within the flashlight radius
+                        if i == 0 {
+                            small_dist_nerf = (jump_dist / 75.0).min(1.0);
+                        }
+
+                        // Nerf stacks so that only the first object of the stack is considered
+                        let stack_nerf = ((prev.lazy_jump_dist / scaling_factor) / 25.0).min(1.0);
+
+                        // Bonus based on how visible the object is
+                        let opacity = opacity_at(
+                            current.base.time,
+                            prev.base.time,
+                            hidden,
+                            time_preempt,
+                            time_fade_in,
+                        );
+                        let opacity_bonus = 1.0 + FLASHLIGHT_MAX_OPACITY_BONUS * (1.0 - opacity);
+
+                        result += stack_nerf * opacity_bonus * scaling_factor * jump_dist
+                            / cumulative_strain_time;
+
+                        if let (Some(prev_angle), Some(curr_angle)) = (prev.angle, current.angle) {
+                            // Objects further back in time should count less for the nerf
+                            if (prev_angle - curr_angle).abs() < 0.02 {
+                                angle_repeat_count += (1.0 - 0.1 * i as Float).max(0.0);
+                            }
+                        }
+                    }
+
+                    last = prev;
+                }
+
+                result = (small_dist_nerf * result).powi(2);
+
+                // Additional bonus for hidden due to there being no approach circles
+                if hidden {
+                    result *= 1.0 + FLASHLIGHT_HIDDEN_BONUS;
+                }
+
+                // Nerf patterns with repeated angles
+                result *= FLASHLIGHT_MIN_ANGLE_MULTIPLIER
+                    + (1.0 - FLASHLIGHT_MIN_ANGLE_MULTIPLIER) / (angle_repeat_count + 1.0);
+
+                if current.base.is_slider() {
+                    // Invert the scaling factor to determine the true travel distance independent of circle size
+                    let pixel_travel_dist = current.base.lazy_travel_dist() / scaling_factor;
+
+                    // Reward sliders based on their velocity
+                    let slider_bonus = (pixel_travel_dist / current.travel_time
+                        - FLASHLIGHT_MIN_VELOCITY)
+                        .max(0.0)
+                        .sqrt()
+                        * pixel_travel_dist
+                        / (current.base.repeat_count() + 1) as Float;
+
+                    result += slider_bonus * FLASHLIGHT_SLIDER_MULTIPLIER;
+                }
+
+                result
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn difficulty_values(self) -> (usize, Float) {
+        match self {
+            Self::Aim { .. } => (AIM_REDUCED_SECTION_COUNT, AIM_DIFFICULTY_MULTIPLIER),
+            Self::Speed { .. } => (SPEED_REDUCED_SECTION_COUNT, SPEED_DIFFICULTY_MULTIPLIER),
+            Self::Flashlight { .. } => (0, AIM_DIFFICULTY_MULTIPLIER),
+        }
+    }
+}
+
+/// Rhythm complexity of the current object w.r.t. its recent predecessors.
+pub(crate) fn rhythm_complexity(
+    current: &DifficultyObject,
+    diff_objects: &[DifficultyObject],
+    hit_window: Float,
+) -> Float {
+    if current.base.is_spinner() {
+        return 0.0;
+    }
+
+    let prev = |i: usize| &diff_objects[current.idx - i - 1];
+
+    let mut prev_island_size = 0;
+    let mut rhythm_complexity_sum = 0.0;
+    let mut island_size = 1;
+    let mut start_ratio = 0.0;
+    let mut first_delta_switch = false;
+
+    let historical_note_count = current.idx.min(HISTORY_OBJECTS_MAX);
+    let mut rhythm_start = 0;
+
+    while rhythm_start + 2 < historical_note_count
+        && current.base.time - prev(rhythm_start).base.time < HISTORY_TIME_MAX
+    {
+        rhythm_start += 1;
+    }
+
+    for i in (1..=rhythm_start).rev() {
+        let curr_obj = prev(i - 1);
+        let prev_obj = prev(i);
+        let last_obj = prev(i + 1);
+
+        let curr_historical_decay = ((HISTORY_TIME_MAX - (current.base.time - curr_obj.base.time))
+            / HISTORY_TIME_MAX)
+            .min((historical_note_count - i) as Float / historical_note_count as Float);
+
+        let curr_delta = curr_obj.strain_time;
+        let prev_delta = prev_obj.strain_time;
+        let last_delta = last_obj.strain_time;
+
+        let curr_ratio = 1.0
+            + 6.0
+                * (PI / (prev_delta.min(curr_delta) / prev_delta.max(curr_delta)))
+                    .sin()
+                    .powi(2)
+                    .min(0.5);
+
+        let window_penalty = (((prev_delta - curr_delta).abs() - hit_window * 0.3).max(0.0)
+            / (hit_window * 0.3))
+            .min(1.0);
+
+        let mut effective_ratio = window_penalty * curr_ratio;
+
+        if first_delta_switch {
+            if !(prev_delta > 1.25 * curr_delta || prev_delta * 1.25 < curr_delta) {
+                // Island is still progressing
+                if island_size < 7 {
+                    island_size += 1;
+                }
+            } else {
+                // Sliders are generally easier to keep rhythm on
+                if curr_obj.base.is_slider() {
+                    effective_ratio *= 0.125;
+                }
+
+                if prev_obj.base.is_slider() {
+                    effective_ratio *= 0.25;
+                }
+
+                // Repeated island sizes are easier
+                if prev_island_size == island_size {
+                    effective_ratio *= 0.25;
+                }
+
+                // Repeated island polarity, i.e. 2 -> 4 or 3 -> 5
+                if prev_island_size % 2 == island_size % 2 {
+                    effective_ratio *= 0.5;
+                }
+
+                // Previous increase happened a note ago, i.e. 1/1 -> 1/2 -> 1/4
+                if last_delta > prev_delta + 10.0 && prev_delta > curr_delta + 10.0 {
+                    effective_ratio *= 0.125;
+                }
+
+                rhythm_complexity_sum += (effective_ratio * start_ratio).sqrt()
+                    * curr_historical_decay
+                    * ((4 + island_size) as Float).sqrt()
+                    / 2.0
+                    * ((4 + prev_island_size) as Float).sqrt()
+                    / 2.0;
+
+                start_ratio = effective_ratio;
+                prev_island_size = island_size;
+
+                // We're slowing down, stop counting
+                if prev_delta * 1.25 < curr_delta {
+                    first_delta_switch = false;
+                }
+
+                island_size = 1;
+            }
+        } else if prev_delta > 1.25 * curr_delta {
+            // We want to be speeding up
+            first_delta_switch = true;
+            start_ratio = effective_ratio;
+            island_size = 1;
+        }
+    }
+
+    (4.0 + rhythm_complexity_sum * RHYTHM_MULTIPLIER).sqrt() / 2.0
+}
+
+#[inline]
+fn previous<'o>(
+    current: &DifficultyObject,
+    diff_objects: &'o [DifficultyObject],
+    backwards_idx: usize,
+) -> Option<&'o DifficultyObject> {
+    current
+        .idx
+        .checked_sub(backwards_idx + 1)
+        .map(|idx| &diff_objects[idx])
+}
+
+/// Opacity of the object at `obj_time` at the given `time`.
+#[inline]
+fn opacity_at(
+    obj_time: Float,
+    time: Float,
+    hidden: bool,
+    time_preempt: Float,
+    time_fade_in: Float,
+) -> Float {
+    if time > obj_time {
+        return 0.0;
+    }
+
+    let fade_in_start_time = obj_time - time_preempt;
+    let fade_in = ((time - fade_in_start_time) / time_fade_in).clamp(0.0, 1.0);
+
+    if hidden {
+        let fade_out_start_time = fade_in_start_time + time_fade_in;
+        let fade_out_duration = time_preempt * 0.3;
+        let fade_out = ((time - fade_out_start_time) / fade_out_duration).clamp(0.0, 1.0);
+
+        fade_in.min(1.0 - fade_out)
+    } else {
+        fade_in
+    }
+}
+
+#[inline]
+fn calc_wide_angle_bonus(angle: Float) -> Float {
+    (0.75 * (angle.clamp(FRAC_PI_6, 5.0 * FRAC_PI_6) - FRAC_PI_6))
+        .sin()
+        .powi(2)
+}
+
+#[inline]
+fn calc_acute_angle_bonus(angle: Float) -> Float {
+    1.0 - calc_wide_angle_bonus(angle)
+}
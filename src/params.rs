@@ -0,0 +1,291 @@
+use crate::{CalcError, CalcResult, Float, Mods};
+
+/// Settings on top of the mods that affect the difficulty and pp calculation.
+///
+/// Every value is optional; if unspecified, the value derived from the mods is used instead.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DifficultyParams {
+    /// Custom clock rate, e.g. `1.2` for a 1.2x speed-up.
+    /// Overrides the speed of DT and HT.
+    ///
+    /// Non-finite or non-positive rates are ignored in favor of the mods' speed,
+    /// other rates are clamped between [`MIN_CLOCK_RATE`](Self::MIN_CLOCK_RATE)
+    /// and [`MAX_CLOCK_RATE`](Self::MAX_CLOCK_RATE).
+    pub clock_rate: Option<Float>,
+    /// Custom approach rate, not scaled by HR or EZ but still adjusted by the clock rate.
+    pub ar: Option<Float>,
+    /// Custom overall difficulty, not scaled by HR or EZ.
+    pub od: Option<Float>,
+    /// Custom circle size, not scaled by HR or EZ.
+    /// Does not change the key count of osu!mania maps.
+    pub cs: Option<Float>,
+    /// Custom drain rate, not scaled by HR or EZ.
+    pub hp: Option<Float>,
+}
+
+impl DifficultyParams {
+    /// Smallest clock rate that will be used for calculations.
+    pub const MIN_CLOCK_RATE: Float = 0.01;
+    /// Largest clock rate that will be used for calculations.
+    pub const MAX_CLOCK_RATE: Float = 100.0;
+
+    /// Specify a custom clock rate that overrides the one of the mods.
+    ///
+    /// Non-finite or non-positive rates are ignored, other rates are clamped
+    /// between [`MIN_CLOCK_RATE`](Self::MIN_CLOCK_RATE) and [`MAX_CLOCK_RATE`](Self::MAX_CLOCK_RATE).
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: Float) -> Self {
+        self.clock_rate.replace(clock_rate);
+
+        self
+    }
+
+    /// Specify an approach rate that overrides the map's value.
+    #[inline]
+    pub fn ar(mut self, ar: Float) -> Self {
+        self.ar.replace(ar);
+
+        self
+    }
+
+    /// Specify an overall difficulty that overrides the map's value.
+    #[inline]
+    pub fn od(mut self, od: Float) -> Self {
+        self.od.replace(od);
+
+        self
+    }
+
+    /// Specify a circle size that overrides the map's value.
+    #[inline]
+    pub fn cs(mut self, cs: Float) -> Self {
+        self.cs.replace(cs);
+
+        self
+    }
+
+    /// Specify a drain rate that overrides the map's value.
+    #[inline]
+    pub fn hp(mut self, hp: Float) -> Self {
+        self.hp.replace(hp);
+
+        self
+    }
+
+    /// Check that all specified values can be used for a calculation.
+    ///
+    /// The clock rate must be finite and between [`MIN_CLOCK_RATE`](Self::MIN_CLOCK_RATE)
+    /// and [`MAX_CLOCK_RATE`](Self::MAX_CLOCK_RATE), all other values must be finite.
+    pub fn validate(&self) -> CalcResult<()> {
+        if let Some(clock_rate) = self.clock_rate {
+            if !(Self::MIN_CLOCK_RATE..=Self::MAX_CLOCK_RATE).contains(&clock_rate) {
+                return Err(CalcError::InvalidValue("clock rate"));
+            }
+        }
+
+        let values = [
+            (self.ar, "approach rate"),
+            (self.od, "overall difficulty"),
+            (self.cs, "circle size"),
+            (self.hp, "drain rate"),
+        ];
+
+        for (value, name) in values.iter() {
+            if matches!(value, Some(value) if !value.is_finite()) {
+                return Err(CalcError::InvalidValue(name));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The custom clock rate if specified and valid, otherwise the mods' speed.
+    #[inline]
+    pub(crate) fn clock_rate_with(self, mods: impl Mods) -> Float {
+        match self.clock_rate {
+            Some(clock_rate) if clock_rate.is_finite() && clock_rate > 0.0 => {
+                clock_rate.clamp(Self::MIN_CLOCK_RATE, Self::MAX_CLOCK_RATE)
+            }
+            _ => mods.speed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, GameMode, PpResult};
+
+    const INVALID: [Float; 5] = [-1.0, 0.0, Float::NAN, Float::INFINITY, Float::NEG_INFINITY];
+
+    fn assert_same(result: PpResult, expected: &PpResult) {
+        assert!(result.stars().is_finite() && result.pp().is_finite());
+        assert!((result.stars() - expected.stars()).abs() < 1e-4);
+        assert!((result.pp() - expected.pp()).abs() < 1e-2);
+    }
+
+    #[test]
+    fn clock_rate_sanitized() {
+        let dt = 64_u32;
+
+        for &clock_rate in INVALID.iter() {
+            let params = DifficultyParams::default().clock_rate(clock_rate);
+            assert_eq!(params.clock_rate_with(dt), 1.5);
+            assert_eq!(
+                params.validate(),
+                Err(CalcError::InvalidValue("clock rate"))
+            );
+        }
+
+        let params = DifficultyParams::default().clock_rate(0.0001);
+        assert_eq!(params.clock_rate_with(dt), DifficultyParams::MIN_CLOCK_RATE);
+        assert!(params.validate().is_err());
+
+        let params = DifficultyParams::default().clock_rate(1e9);
+        assert_eq!(params.clock_rate_with(dt), DifficultyParams::MAX_CLOCK_RATE);
+        assert!(params.validate().is_err());
+
+        let params = DifficultyParams::default().clock_rate(1.2);
+        assert_eq!(params.clock_rate_with(dt), 1.2);
+        assert_eq!(params.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_overrides() {
+        assert_eq!(DifficultyParams::default().validate(), Ok(()));
+
+        let params = DifficultyParams::default()
+            .ar(11.0)
+            .od(-1.0)
+            .cs(0.0)
+            .hp(10.0);
+        assert_eq!(params.validate(), Ok(()));
+
+        let params = DifficultyParams::default().od(Float::NAN);
+        assert_eq!(
+            params.validate(),
+            Err(CalcError::InvalidValue("overall difficulty"))
+        );
+
+        let params = DifficultyParams::default().ar(Float::INFINITY);
+        assert_eq!(
+            params.validate(),
+            Err(CalcError::InvalidValue("approach rate"))
+        );
+    }
+
+    #[cfg(feature = "osu")]
+    #[test]
+    fn osu_invalid_clock_rate() {
+        use crate::OsuPP;
+
+        let map = test_util::map(GameMode::STD);
+        let expected = OsuPP::new(&map).mods(64).calculate();
+
+        for &clock_rate in INVALID.iter() {
+            let result = OsuPP::new(&map).mods(64).clock_rate(clock_rate).calculate();
+            assert_same(result, &expected);
+        }
+    }
+
+    #[cfg(feature = "osu")]
+    #[test]
+    fn osu_clock_rate_replaces_mod_speed() {
+        use crate::OsuPP;
+
+        let map = test_util::map(GameMode::STD);
+
+        let nomod = OsuPP::new(&map).calculate();
+        let dt = OsuPP::new(&map).mods(64).calculate();
+
+        // The custom clock rate is used instead of the speed of DT
+        let custom = OsuPP::new(&map).clock_rate(1.5).calculate();
+        assert_eq!(custom.stars(), dt.stars());
+
+        let custom = OsuPP::new(&map).mods(64).clock_rate(1.0).calculate();
+        assert_eq!(custom.stars(), nomod.stars());
+    }
+
+    #[cfg(feature = "mania")]
+    #[test]
+    fn mania_clock_rate_replaces_mod_speed() {
+        use crate::ManiaPP;
+
+        let map = test_util::map(GameMode::MNA);
+
+        let nomod = ManiaPP::new(&map).calculate();
+        let ht = ManiaPP::new(&map).mods(256).calculate();
+
+        let custom = ManiaPP::new(&map).clock_rate(0.75).calculate();
+        assert_eq!(custom.stars(), ht.stars());
+
+        let custom = ManiaPP::new(&map).mods(256).clock_rate(1.0).calculate();
+        assert_eq!(custom.stars(), nomod.stars());
+    }
+
+    #[cfg(feature = "osu")]
+    #[test]
+    fn osu_overrides_match_map_values() {
+        use crate::OsuPP;
+
+        let map = test_util::map(GameMode::STD);
+
+        let mut changed = map.clone();
+        changed.ar = 8.0;
+        changed.od = 7.0;
+        changed.cs = 5.0;
+        changed.hp = 6.0;
+
+        let expected = OsuPP::new(&changed).calculate();
+        let result = OsuPP::new(&map).ar(8.0).od(7.0).cs(5.0).hp(6.0).calculate();
+        assert_same(result, &expected);
+    }
+
+    #[cfg(feature = "taiko")]
+    #[test]
+    fn taiko_invalid_clock_rate() {
+        use crate::TaikoPP;
+
+        let map = test_util::map(GameMode::TKO);
+        let expected = TaikoPP::new(&map).calculate();
+
+        for &clock_rate in INVALID.iter() {
+            let result = TaikoPP::new(&map).clock_rate(clock_rate).calculate();
+            assert_same(result, &expected);
+        }
+
+        let result = TaikoPP::new(&map).clock_rate(0.0001).calculate();
+        assert!(result.stars().is_finite() && result.pp().is_finite());
+    }
+
+    #[cfg(feature = "fruits")]
+    #[test]
+    fn fruits_invalid_clock_rate() {
+        use crate::FruitsPP;
+
+        let map = test_util::map(GameMode::CTB);
+        let expected = FruitsPP::new(&map).calculate();
+
+        for &clock_rate in INVALID.iter() {
+            let result = FruitsPP::new(&map).clock_rate(clock_rate).calculate();
+            assert_same(result, &expected);
+        }
+    }
+
+    #[cfg(feature = "mania")]
+    #[test]
+    fn mania_invalid_clock_rate() {
+        use crate::ManiaPP;
+
+        let map = test_util::map(GameMode::MNA);
+        let expected = ManiaPP::new(&map).calculate();
+
+        for &clock_rate in INVALID.iter() {
+            let result = ManiaPP::new(&map).clock_rate(clock_rate).calculate();
+            assert_same(result, &expected);
+        }
+
+        let result = ManiaPP::new(&map).clock_rate(1e9).calculate();
+        assert!(result.stars().is_finite() && result.pp().is_finite());
+    }
+}
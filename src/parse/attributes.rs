@@ -1,24 +1,24 @@
-use crate::Mods;
+use crate::{DifficultyParams, Float, Mods};
 
 /// Summary struct for a [`Beatmap`](crate::Beatmap)'s attributes.
 #[derive(Clone, Debug)]
 pub struct BeatmapAttributes {
-    pub ar: f32,
-    pub od: f32,
-    pub cs: f32,
-    pub hp: f32,
-    pub clock_rate: f32,
+    pub ar: Float,
+    pub od: Float,
+    pub cs: Float,
+    pub hp: Float,
+    pub clock_rate: Float,
 }
 
 impl BeatmapAttributes {
-    const AR0_MS: f32 = 1800.0;
-    const AR5_MS: f32 = 1200.0;
-    const AR10_MS: f32 = 450.0;
-    const AR_MS_STEP_1: f32 = (Self::AR0_MS - Self::AR5_MS) / 5.0;
-    const AR_MS_STEP_2: f32 = (Self::AR5_MS - Self::AR10_MS) / 5.0;
+    const AR0_MS: Float = 1800.0;
+    const AR5_MS: Float = 1200.0;
+    const AR10_MS: Float = 450.0;
+    const AR_MS_STEP_1: Float = (Self::AR0_MS - Self::AR5_MS) / 5.0;
+    const AR_MS_STEP_2: Float = (Self::AR5_MS - Self::AR10_MS) / 5.0;
 
     #[inline]
-    pub(crate) fn new(ar: f32, od: f32, cs: f32, hp: f32) -> Self {
+    pub(crate) fn new(ar: Float, od: Float, cs: Float, hp: Float) -> Self {
         Self {
             ar,
             od,
@@ -31,23 +31,42 @@ impl BeatmapAttributes {
     /// Adjusts attributes w.r.t. mods.
     /// AR is further adjusted by its hitwindow.
     /// OD is __not__ adjusted by its hitwindow.
+    #[inline]
     pub fn mods(self, mods: impl Mods) -> Self {
-        if !mods.change_map() {
-            return self;
+        self.mods_with_params(mods, DifficultyParams::default())
+    }
+
+    /// Adjusts attributes w.r.t. mods and further difficulty parameters
+    /// such as a custom clock rate or overridden values.
+    /// Overridden values are not scaled by HR or EZ but AR is still adjusted by the clock rate.
+    /// AR is further adjusted by its hitwindow.
+    /// OD is __not__ adjusted by its hitwindow.
+    pub fn mods_with_params(self, mods: impl Mods, params: DifficultyParams) -> Self {
+        if !mods.change_map() && params.clock_rate.is_none() {
+            return Self {
+                ar: params.ar.unwrap_or(self.ar),
+                od: params.od.unwrap_or(self.od),
+                cs: params.cs.unwrap_or(self.cs),
+                hp: params.hp.unwrap_or(self.hp),
+                clock_rate: self.clock_rate,
+            };
         }
 
-        let clock_rate = mods.speed();
+        let clock_rate = params.clock_rate_with(mods);
         let multiplier = mods.od_ar_hp_multiplier();
 
         // AR
-        let mut ar = self.ar * multiplier;
+        let mut ar = params.ar.unwrap_or(self.ar * multiplier);
         let mut ar_ms = if ar <= 5.0 {
             Self::AR0_MS - Self::AR_MS_STEP_1 * ar
         } else {
             Self::AR5_MS - Self::AR_MS_STEP_2 * (ar - 5.0)
         };
 
-        ar_ms = ar_ms.max(Self::AR10_MS).min(Self::AR0_MS);
+        if params.ar.is_none() {
+            ar_ms = ar_ms.clamp(Self::AR10_MS, Self::AR0_MS);
+        }
+
         ar_ms /= clock_rate;
 
         ar = if ar_ms > Self::AR5_MS {
@@ -57,19 +76,27 @@ impl BeatmapAttributes {
         };
 
         // OD
-        let od = (self.od * multiplier).min(10.0);
+        let od = params
+            .od
+            .unwrap_or_else(|| (self.od * multiplier).min(10.0));
 
         // CS
-        let mut cs = self.cs;
-        if mods.hr() {
-            cs *= 1.3;
-        } else if mods.ez() {
-            cs *= 0.5;
-        }
-        cs = cs.min(10.0);
+        let cs = params.cs.unwrap_or_else(|| {
+            let mut cs = self.cs;
+
+            if mods.hr() {
+                cs *= 1.3;
+            } else if mods.ez() {
+                cs *= 0.5;
+            }
+
+            cs.min(10.0)
+        });
 
         // HP
-        let hp = (self.hp * multiplier).min(10.0);
+        let hp = params
+            .hp
+            .unwrap_or_else(|| (self.hp * multiplier).min(10.0));
 
         Self {
             ar,
@@ -80,3 +107,101 @@ impl BeatmapAttributes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HR: u32 = 16;
+    const EZ: u32 = 2;
+    const DT: u32 = 64;
+
+    fn attributes() -> BeatmapAttributes {
+        BeatmapAttributes::new(9.0, 8.0, 4.0, 5.0)
+    }
+
+    fn assert_attributes(attributes: BeatmapAttributes, [ar, od, cs, hp]: [Float; 4]) {
+        let actual = [attributes.ar, attributes.od, attributes.cs, attributes.hp];
+
+        for (actual, expected) in actual.iter().zip([ar, od, cs, hp].iter()) {
+            assert!(
+                (actual - expected).abs() < 1e-4,
+                "{:?} != {:?}",
+                actual,
+                [ar, od, cs, hp]
+            );
+        }
+    }
+
+    fn overrides() -> DifficultyParams {
+        DifficultyParams::default().ar(7.0).od(6.0).cs(3.0).hp(2.0)
+    }
+
+    #[test]
+    fn overrides_without_mods() {
+        assert_attributes(attributes().mods(0), [9.0, 8.0, 4.0, 5.0]);
+        assert_attributes(
+            attributes().mods_with_params(0, overrides()),
+            [7.0, 6.0, 3.0, 2.0],
+        );
+
+        let params = DifficultyParams::default().od(9.5);
+        assert_attributes(
+            attributes().mods_with_params(0, params),
+            [9.0, 9.5, 4.0, 5.0],
+        );
+    }
+
+    #[test]
+    fn overrides_not_scaled_by_hr_or_ez() {
+        // HR scales OD and HP by 1.4 up to 10 and CS by 1.3, AR is capped at 10
+        assert_attributes(attributes().mods(HR), [10.0, 10.0, 5.2, 7.0]);
+        assert_attributes(
+            attributes().mods_with_params(HR, overrides()),
+            [7.0, 6.0, 3.0, 2.0],
+        );
+
+        // EZ halves all values
+        assert_attributes(attributes().mods(EZ), [4.5, 4.0, 2.0, 2.5]);
+        assert_attributes(
+            attributes().mods_with_params(EZ, overrides()),
+            [7.0, 6.0, 3.0, 2.0],
+        );
+
+        // Only the overridden value is kept
+        let params = DifficultyParams::default().cs(3.0);
+        assert_attributes(
+            attributes().mods_with_params(HR, params),
+            [10.0, 10.0, 3.0, 7.0],
+        );
+    }
+
+    #[test]
+    fn ar_override_adjusted_by_clock_rate() {
+        // AR 7 is 900ms, 600ms with DT which is AR 9
+        let params = DifficultyParams::default().ar(7.0);
+        let dt = attributes().mods_with_params(DT, params);
+        assert_attributes(dt.clone(), [9.0, 8.0, 4.0, 5.0]);
+        assert!((dt.clock_rate - 1.5).abs() < 1e-4);
+
+        // The same for a custom clock rate without mods
+        let params = params.clock_rate(1.5);
+        assert_attributes(
+            attributes().mods_with_params(0, params),
+            [9.0, 8.0, 4.0, 5.0],
+        );
+
+        // Overridden AR is not clamped to AR 10 before the clock rate is applied:
+        // AR 11 is 300ms, 200ms with DT
+        let params = DifficultyParams::default().ar(11.0);
+        let expected_ar = 5.0 + (1200.0 - 200.0) / 150.0;
+        assert_attributes(
+            attributes().mods_with_params(DT, params),
+            [expected_ar, 8.0, 4.0, 5.0],
+        );
+
+        // Without override, HR's AR 12.6 is clamped to AR 10 first
+        let expected_ar = 5.0 + (1200.0 - 300.0) / 150.0;
+        assert_attributes(attributes().mods(HR | DT), [expected_ar, 10.0, 5.2, 7.0]);
+    }
+}
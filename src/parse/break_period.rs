@@ -0,0 +1,15 @@
+use crate::Float;
+
+/// A break during which no objects need to be hit.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Break {
+    pub start_time: Float,
+    pub end_time: Float,
+}
+
+impl Break {
+    #[inline]
+    pub fn duration(&self) -> Float {
+        self.end_time - self.start_time
+    }
+}
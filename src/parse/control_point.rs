@@ -1,10 +1,12 @@
+use crate::Float;
+
 use std::cmp::Ordering;
 
 /// New rhythm speed change.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct TimingPoint {
-    pub beat_len: f32,
-    pub time: f32,
+    pub beat_len: Float,
+    pub time: Float,
 }
 
 impl PartialOrd for TimingPoint {
@@ -16,8 +18,8 @@ impl PartialOrd for TimingPoint {
 /// [`TimingPoint`](crate::parse::TimingPoint) that depends on a previous one.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct DifficultyPoint {
-    pub time: f32,
-    pub speed_multiplier: f32,
+    pub time: Float,
+    pub speed_multiplier: Float,
 }
 
 impl PartialOrd for DifficultyPoint {
@@ -25,3 +27,16 @@ impl PartialOrd for DifficultyPoint {
         self.time.partial_cmp(&other.time)
     }
 }
+
+/// Start or end of a kiai section.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EffectPoint {
+    pub time: Float,
+    pub kiai: bool,
+}
+
+impl PartialOrd for EffectPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.time.partial_cmp(&other.time)
+    }
+}
@@ -5,6 +5,7 @@ use super::Pos2;
     all(feature = "osu", not(feature = "no_sliders_no_leniency"))
 ))]
 use super::PathType;
+use crate::Float;
 
 use std::cmp::Ordering;
 
@@ -13,18 +14,17 @@ use std::cmp::Ordering;
 #[derive(Clone, Debug, PartialEq)]
 pub struct HitObject {
     pub pos: Pos2,
-    pub start_time: f32,
+    pub start_time: Float,
     pub kind: HitObjectKind,
     pub sound: u8,
 }
 
 impl HitObject {
     #[inline]
-    pub fn end_time(&self) -> f32 {
+    pub fn end_time(&self) -> Float {
         match &self.kind {
             HitObjectKind::Circle { .. } => self.start_time,
-            // incorrect, only called in mania which has no sliders though
-            HitObjectKind::Slider { .. } => self.start_time,
+            HitObjectKind::Slider { end_time, .. } => *end_time,
             HitObjectKind::Spinner { end_time } => *end_time,
             HitObjectKind::Hold { end_time, .. } => *end_time,
         }
@@ -61,8 +61,13 @@ pub enum HitObjectKind {
         all(feature = "osu", not(feature = "no_sliders_no_leniency"))
     ))]
     Slider {
-        pixel_len: f32,
+        /// End time including all repeats.
+        end_time: Float,
+        pixel_len: Float,
         repeats: usize,
+        /// Hitsounds of the head, the repeats, and the tail.
+        /// Nodes without a specified hitsound use the one of the slider.
+        edge_sounds: Vec<u8>,
         curve_points: Vec<Pos2>,
         path_type: PathType,
     },
@@ -71,13 +76,18 @@ pub enum HitObjectKind {
         all(feature = "osu", not(feature = "no_sliders_no_leniency"))
     )))]
     Slider {
-        pixel_len: f32,
+        /// End time including all repeats.
+        end_time: Float,
+        pixel_len: Float,
         repeats: usize,
+        /// Hitsounds of the head, the repeats, and the tail.
+        /// Nodes without a specified hitsound use the one of the slider.
+        edge_sounds: Vec<u8>,
     },
     Spinner {
-        end_time: f32,
+        end_time: Float,
     },
     Hold {
-        end_time: f32,
+        end_time: Float,
     },
 }
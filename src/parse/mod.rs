@@ -3,8 +3,10 @@
     all(feature = "osu", not(feature = "no_sliders_no_leniency"))
 ))]
 use crate::math_util;
+use crate::Float;
 
 mod attributes;
+mod break_period;
 mod control_point;
 mod error;
 mod hitobject;
@@ -13,12 +15,13 @@ mod pos2;
 mod sort;
 
 pub use attributes::BeatmapAttributes;
-pub use control_point::{DifficultyPoint, TimingPoint};
+pub use break_period::Break;
+pub use control_point::{DifficultyPoint, EffectPoint, TimingPoint};
 pub use error::{ParseError, ParseResult};
 pub use hitobject::{HitObject, HitObjectKind};
 pub use hitsound::HitSound;
 pub use pos2::Pos2;
-use sort::legacy_sort;
+pub(crate) use sort::legacy_sort;
 
 use std::cmp::Ordering;
 use std::str::FromStr;
@@ -49,8 +52,10 @@ macro_rules! next_field {
 
 macro_rules! validate_float {
     ($x:expr) => {{
-        if $x.is_finite() {
-            $x
+        let x: Float = $x;
+
+        if x.is_finite() {
+            x
         } else {
             return Err(ParseError::InvalidFloatingPoint);
         }
@@ -144,7 +149,7 @@ macro_rules! parse_general_body {
 
             #[cfg(all(feature = "osu", feature = "all_included"))]
             if key == "StackLeniency" {
-                stack_leniency = Some(value.parse()?);
+                stack_leniency = Some(validate_float!(value.parse()?));
             }
 
             $buf.clear();
@@ -238,12 +243,17 @@ macro_rules! parse_difficulty_body {
             let (key, value) = split_colon(&line).ok_or(ParseError::BadLine)?;
 
             match key {
-                "ApproachRate" => ar = Some(value.parse()?),
-                "OverallDifficulty" => od = Some(value.parse()?),
-                "CircleSize" => cs = Some(value.parse()?),
-                "HPDrainRate" => hp = Some(value.parse()?),
-                "SliderTickRate" => tick_rate = Some(value.parse()?),
-                "SliderMultiplier" => sv = Some(value.parse()?),
+                "ApproachRate" => ar = Some(validate_float!(value.parse()?)),
+                "OverallDifficulty" => od = Some(validate_float!(value.parse()?)),
+                "CircleSize" => cs = Some(validate_float!(value.parse()?)),
+                "HPDrainRate" => hp = Some(validate_float!(value.parse()?)),
+                // Clamped the same way the game does
+                "SliderTickRate" => {
+                    tick_rate = Some(validate_float!(value.parse()?).max(0.5).min(8.0))
+                }
+                "SliderMultiplier" => {
+                    sv = Some(validate_float!(value.parse()?).max(0.4).min(3.6))
+                }
                 _ => {}
             }
 
@@ -294,8 +304,8 @@ macro_rules! parse_difficulty {
     };
 }
 
-macro_rules! parse_timingpoints_body {
-    ($read_method:ident, short => $self:ident, $reader:ident, $buf:ident, $section:ident) => {{
+macro_rules! parse_events_body {
+    ($read_method:ident, $self:ident, $reader:ident, $buf:ident, $section:ident) => {{
         let mut empty = true;
 
         while $read_method!($reader, $buf)? != 0 {
@@ -308,18 +318,77 @@ macro_rules! parse_timingpoints_body {
                 break;
             }
 
+            let mut split = line.split(',');
+
+            if let Some("2") | Some("Break") = split.next() {
+                let start_time = next_field!(split.next(), "break start")
+                    .trim()
+                    .parse::<Float>()?;
+
+                let end_time = next_field!(split.next(), "break end")
+                    .trim()
+                    .parse::<Float>()?;
+
+                validate_float!(start_time);
+                validate_float!(end_time);
+
+                $self.breaks.push(Break {
+                    start_time,
+                    end_time: end_time.max(start_time),
+                });
+            }
+
             $buf.clear();
         }
 
         Ok(empty)
     }};
+}
+
+macro_rules! parse_events {
+    ($reader:ident<$inner:ident>) => {
+        fn parse_events<R: $inner>(
+            &mut self,
+            reader: &mut $reader<R>,
+            buf: &mut String,
+            section: &mut Section,
+        ) -> ParseResult<bool> {
+            parse_events_body!(read_line_sync, self, reader, buf, section)
+        }
+    };
 
+    (async $reader:ident<$inner:ident>, $reader_sync:ident<$inner_sync:ident>) => {
+        async fn parse_events<R: $inner + Unpin>(
+            &mut self,
+            reader: &mut $reader<R>,
+            buf: &mut String,
+            section: &mut Section,
+        ) -> ParseResult<bool> {
+            parse_events_body!(read_line_async, self, reader, buf, section)
+        }
+
+        fn parse_events_sync<R: $inner_sync>(
+            &mut self,
+            reader: &mut $reader_sync<R>,
+            buf: &mut String,
+            section: &mut Section,
+        ) -> ParseResult<bool> {
+            parse_events_body!(read_line_sync, self, reader, buf, section)
+        }
+    };
+}
+
+macro_rules! parse_timingpoints_body {
     ($read_method:ident, $self:ident, $reader:ident, $buf:ident, $section:ident) => {{
         let mut unsorted_timings = false;
         let mut unsorted_difficulties = false;
 
+        let mut unsorted_effects = false;
+
         let mut prev_diff = 0.0;
         let mut prev_time = 0.0;
+        let mut prev_effect = 0.0;
+        let mut kiai = false;
 
         let mut empty = true;
 
@@ -337,17 +406,36 @@ macro_rules! parse_timingpoints_body {
 
             let time = next_field!(split.next(), "timing point time")
                 .trim()
-                .parse::<f32>()?;
+                .parse::<Float>()?;
             validate_float!(time);
 
             let beat_len = next_field!(split.next(), "beat len")
                 .trim()
-                .parse::<f32>()?;
+                .parse::<Float>()?;
+
+            validate_float!(beat_len);
+
+            let effects = split.nth(5).and_then(|effects| effects.trim().parse::<u8>().ok());
 
+            if let Some(effect_kiai) = effects.map(|effects| effects & Self::KIAI_FLAG > 0) {
+                if effect_kiai != kiai {
+                    kiai = effect_kiai;
+
+                    $self.effect_points.push(EffectPoint { time, kiai });
+
+                    if time < prev_effect {
+                        unsorted_effects = true;
+                    } else {
+                        prev_effect = time;
+                    }
+                }
+            }
+
+            // Out-of-range values are clamped the same way the game does
             if beat_len < 0.0 {
                 let point = DifficultyPoint {
                     time,
-                    speed_multiplier: -100.0 / beat_len,
+                    speed_multiplier: (-100.0 / beat_len).max(0.1).min(10.0),
                 };
 
                 $self.difficulty_points.push(point);
@@ -358,6 +446,8 @@ macro_rules! parse_timingpoints_body {
                     prev_diff = time;
                 }
             } else {
+                let beat_len = beat_len.max(6.0).min(60_000.0);
+
                 $self.timing_points.push(TimingPoint { time, beat_len });
 
                 if time < prev_time {
@@ -378,6 +468,10 @@ macro_rules! parse_timingpoints_body {
             sort!($self.difficulty_points);
         }
 
+        if unsorted_effects {
+            sort!($self.effect_points);
+        }
+
         Ok(empty)
     }};
 }
@@ -390,12 +484,6 @@ macro_rules! parse_timingpoints {
             buf: &mut String,
             section: &mut Section,
         ) -> ParseResult<bool> {
-            #[cfg(not(any(feature = "osu", feature = "fruits")))]
-            {
-                parse_timingpoints_body!(read_line_sync, short => self, reader, buf, section)
-            }
-
-            #[cfg(any(feature = "osu", feature = "fruits"))]
             parse_timingpoints_body!(read_line_sync, self, reader, buf, section)
         }
     };
@@ -407,12 +495,6 @@ macro_rules! parse_timingpoints {
             buf: &mut String,
             section: &mut Section,
         ) -> ParseResult<bool> {
-            #[cfg(not(any(feature = "osu", feature = "fruits")))]
-            {
-                parse_timingpoints_body!(read_line_async, short => self, reader, buf, section)
-            }
-
-            #[cfg(any(feature = "osu", feature = "fruits"))]
             parse_timingpoints_body!(read_line_async, self, reader, buf, section)
         }
 
@@ -422,12 +504,6 @@ macro_rules! parse_timingpoints {
             buf: &mut String,
             section: &mut Section,
         ) -> ParseResult<bool> {
-            #[cfg(not(any(feature = "osu", feature = "fruits")))]
-            {
-                parse_timingpoints_body!(read_line_sync, short => self, reader, buf, section)
-            }
-
-            #[cfg(any(feature = "osu", feature = "fruits"))]
             parse_timingpoints_body!(read_line_sync, self, reader, buf, section)
         }
     };
@@ -453,13 +529,13 @@ macro_rules! parse_hitobjects_body {
             let mut split = line.split(',');
 
             let pos = Pos2 {
-                x: next_field!(split.next(), "x position").parse()?,
-                y: next_field!(split.next(), "y position").parse()?,
+                x: validate_float!(next_field!(split.next(), "x position").parse()?),
+                y: validate_float!(next_field!(split.next(), "y position").parse()?),
             };
 
             let time = next_field!(split.next(), "hitobject time")
                 .trim()
-                .parse::<f32>()?;
+                .parse::<Float>()?;
 
             validate_float!(time);
 
@@ -491,10 +567,12 @@ macro_rules! parse_hitobjects_body {
                         next_field!(curve_point_iter.next(), "path kind").parse()?;
 
                     for pos in curve_point_iter {
-                        let mut v = pos.split(':').map(str::parse);
+                        let mut v = pos.split(':').map(str::parse::<Float>);
 
                         match (v.next(), v.next()) {
-                            (Some(Ok(x)), Some(Ok(y))) => curve_points.push(Pos2 { x, y }),
+                            (Some(Ok(x)), Some(Ok(y))) if x.is_finite() && y.is_finite() => {
+                                curve_points.push(Pos2 { x, y })
+                            }
                             _ => return Err(ParseError::InvalidCurvePoints),
                         }
                     }
@@ -542,13 +620,16 @@ macro_rules! parse_hitobjects_body {
                             .min(9000);
 
                         let pixel_len = next_field!(split.next(), "pixel len")
-                            .parse::<f32>()?
+                            .parse::<Float>()?
                             .max(0.0)
                             .min(MAX_COORDINATE_VALUE);
 
                         HitObjectKind::Slider {
+                            // Set once all timing points are known
+                            end_time: time,
                             repeats,
                             pixel_len,
+                            edge_sounds: parse_edge_sounds(split.next(), sound),
                             curve_points,
                             path_type,
                         }
@@ -561,16 +642,20 @@ macro_rules! parse_hitobjects_body {
                 )))]
                 {
                     let repeats = next_field!(split.nth(1), "repeats").parse::<usize>()?;
-                    let len: f32 = next_field!(split.next(), "pixel len").parse()?;
+                    let len = validate_float!(next_field!(split.next(), "pixel len").parse()?);
 
                     HitObjectKind::Slider {
+                        // Set once all timing points are known
+                        end_time: time,
                         repeats,
                         pixel_len: len,
+                        edge_sounds: parse_edge_sounds(split.next(), sound),
                     }
                 }
             } else if kind & Self::SPINNER_FLAG > 0 {
                 $self.n_spinners += 1;
                 let end_time = next_field!(split.next(), "spinner endtime").parse()?;
+                validate_float!(end_time);
 
                 HitObjectKind::Spinner { end_time }
             } else if kind & Self::HOLD_FLAG > 0 {
@@ -578,7 +663,9 @@ macro_rules! parse_hitobjects_body {
                 let mut end = time;
 
                 if let Some(next) = split.next() {
-                    end = end.max(next_field!(next.split(':').next(), "hold endtime").parse()?);
+                    end = end.max(validate_float!(
+                        next_field!(next.split(':').next(), "hold endtime").parse()?
+                    ));
                 }
 
                 HitObjectKind::Hold { end_time: end }
@@ -647,7 +734,7 @@ macro_rules! parse_hitobjects {
 }
 
 macro_rules! parse_body {
-    ($read_method:ident, $section_method:ident, $general:ident, $diff:ident, $timing:ident, $hitobj:ident, $reader:ident<$inner:ident>: $input:ident) => {{
+    ($read_method:ident, $section_method:ident, $general:ident, $diff:ident, $events:ident, $timing:ident, $hitobj:ident, $reader:ident<$inner:ident>: $input:ident) => {{
         let mut reader = $reader::new($input);
         let mut buf = String::new();
 
@@ -682,6 +769,7 @@ macro_rules! parse_body {
             match section {
                 Section::General => $section_method!(map, $general, reader, buf, section),
                 Section::Difficulty => $section_method!(map, $diff, reader, buf, section),
+                Section::Events => $section_method!(map, $events, reader, buf, section),
                 Section::TimingPoints => $section_method!(map, $timing, reader, buf, section),
                 Section::HitObjects => $section_method!(map, $hitobj, reader, buf, section),
                 Section::None => {
@@ -700,6 +788,8 @@ macro_rules! parse_body {
             }
         }
 
+        map.set_slider_end_times();
+
         Ok(map)
     }};
 }
@@ -712,6 +802,7 @@ macro_rules! parse {
                 section_sync,
                 parse_general,
                 parse_difficulty,
+                parse_events,
                 parse_timingpoints,
                 parse_hitobjects,
                 $reader<$inner>: input
@@ -726,6 +817,7 @@ macro_rules! parse {
                 section_async,
                 parse_general,
                 parse_difficulty,
+                parse_events,
                 parse_timingpoints,
                 parse_hitobjects,
                 $reader<$inner>: input
@@ -738,6 +830,7 @@ macro_rules! parse {
                 section_sync,
                 parse_general_sync,
                 parse_difficulty_sync,
+                parse_events_sync,
                 parse_timingpoints_sync,
                 parse_hitobjects_sync,
                 $reader_sync<$inner_sync>: input
@@ -774,22 +867,21 @@ pub struct Beatmap {
     pub n_sliders: u32,
     pub n_spinners: u32,
 
-    pub ar: f32,
-    pub od: f32,
-    pub cs: f32,
-    pub hp: f32,
-    pub sv: f32,
-    pub tick_rate: f32,
+    pub ar: Float,
+    pub od: Float,
+    pub cs: Float,
+    pub hp: Float,
+    pub sv: Float,
+    pub tick_rate: Float,
     pub hit_objects: Vec<HitObject>,
 
-    #[cfg(any(feature = "osu", feature = "fruits"))]
     pub timing_points: Vec<TimingPoint>,
-
-    #[cfg(any(feature = "osu", feature = "fruits"))]
     pub difficulty_points: Vec<DifficultyPoint>,
+    pub effect_points: Vec<EffectPoint>,
+    pub breaks: Vec<Break>,
 
     #[cfg(all(feature = "osu", feature = "all_included"))]
-    pub stack_leniency: f32,
+    pub stack_leniency: Float,
 }
 
 pub(crate) const OSU_FILE_HEADER: &str = "osu file format v";
@@ -804,7 +896,7 @@ const CURVE_POINT_THRESHOLD: usize = 256;
     feature = "fruits",
     all(feature = "osu", not(feature = "no_sliders_no_leniency"))
 ))]
-const MAX_COORDINATE_VALUE: f32 = 131_072.0;
+const MAX_COORDINATE_VALUE: Float = 131_072.0;
 
 impl Beatmap {
     const CIRCLE_FLAG: u8 = 1 << 0;
@@ -814,10 +906,58 @@ impl Beatmap {
     // const COMBO_OFFSET_FLAG: u8 = (1 << 4) | (1 << 5) | (1 << 6);
     const HOLD_FLAG: u8 = 1 << 7;
 
+    const KIAI_FLAG: u8 = 1 << 0;
+
     #[inline]
     pub fn attributes(&self) -> BeatmapAttributes {
         BeatmapAttributes::new(self.ar, self.od, self.cs, self.hp)
     }
+
+    /// The beat length and the speed multiplier that are active at the given time.
+    pub(crate) fn control_points_at(&self, time: Float) -> (Float, Float) {
+        let timing_idx = self.timing_points.partition_point(|p| p.time <= time);
+        let difficulty_idx = self.difficulty_points.partition_point(|p| p.time <= time);
+
+        let timing = timing_idx
+            .checked_sub(1)
+            .map_or(self.timing_points.first(), |idx| self.timing_points.get(idx));
+
+        let beat_len = timing.map_or(1000.0, |p| p.beat_len);
+
+        // Timing points reset the speed multiplier
+        let speed_mult = difficulty_idx
+            .checked_sub(1)
+            .map(|idx| &self.difficulty_points[idx])
+            .filter(|p| !matches!(timing, Some(t) if p.time < t.time))
+            .map_or(1.0, |p| p.speed_multiplier);
+
+        (beat_len, speed_mult)
+    }
+
+    /// Calculate the end time of all sliders w.r.t. the timing points and slider velocity.
+    fn set_slider_end_times(&mut self) {
+        let mut hit_objects = std::mem::take(&mut self.hit_objects);
+
+        for h in hit_objects.iter_mut() {
+            if let HitObjectKind::Slider {
+                end_time,
+                pixel_len,
+                repeats,
+                ..
+            } = &mut h.kind
+            {
+                let (beat_len, speed_mult) = self.control_points_at(h.start_time);
+                let duration =
+                    *repeats as Float * beat_len * *pixel_len / (self.sv * speed_mult) / 100.0;
+
+                if duration.is_finite() {
+                    *end_time = h.start_time + duration.max(0.0);
+                }
+            }
+        }
+
+        self.hit_objects = hit_objects;
+    }
 }
 
 #[cfg(not(any(feature = "async_std", feature = "async_tokio")))]
@@ -825,6 +965,7 @@ impl Beatmap {
     parse!(SyncBufReader<SyncRead>);
     parse_general!(SyncBufReader<SyncRead>);
     parse_difficulty!(SyncBufReader<SyncRead>);
+    parse_events!(SyncBufReader<SyncRead>);
     parse_timingpoints!(SyncBufReader<SyncRead>);
     parse_hitobjects!(SyncBufReader<SyncRead>);
 }
@@ -834,6 +975,7 @@ impl Beatmap {
     parse!(async BufReader<AsyncRead>, SyncBufReader<SyncRead>);
     parse_general!(async BufReader<AsyncRead>, SyncBufReader<SyncRead>);
     parse_difficulty!(async BufReader<AsyncRead>, SyncBufReader<SyncRead>);
+    parse_events!(async BufReader<AsyncRead>, SyncBufReader<SyncRead>);
     parse_timingpoints!(async BufReader<AsyncRead>, SyncBufReader<SyncRead>);
     parse_hitobjects!(async BufReader<AsyncRead>, SyncBufReader<SyncRead>);
 }
@@ -843,10 +985,21 @@ impl Beatmap {
     parse!(async AsyncBufReader<AsyncRead>, SyncBufReader<SyncRead>);
     parse_general!(async AsyncBufReader<AsyncRead>, SyncBufReader<SyncRead>);
     parse_difficulty!(async AsyncBufReader<AsyncRead>, SyncBufReader<SyncRead>);
+    parse_events!(async AsyncBufReader<AsyncRead>, SyncBufReader<SyncRead>);
     parse_timingpoints!(async AsyncBufReader<AsyncRead>, SyncBufReader<SyncRead>);
     parse_hitobjects!(async AsyncBufReader<AsyncRead>, SyncBufReader<SyncRead>);
 }
 
+#[inline]
+fn parse_edge_sounds(sounds: Option<&str>, default: u8) -> Vec<u8> {
+    sounds.map_or_else(Vec::new, |sounds| {
+        sounds
+            .split('|')
+            .map(|sound| sound.parse().unwrap_or(default))
+            .collect()
+    })
+}
+
 #[inline]
 fn split_colon(line: &str) -> Option<(&str, &str)> {
     let mut split = line.split(':');
@@ -883,6 +1036,7 @@ enum Section {
     None,
     General,
     Difficulty,
+    Events,
     TimingPoints,
     HitObjects,
 }
@@ -893,6 +1047,7 @@ impl Section {
         match s {
             "General" => Self::General,
             "Difficulty" => Self::Difficulty,
+            "Events" => Self::Events,
             "TimingPoints" => Self::TimingPoints,
             "HitObjects" => Self::HitObjects,
             _ => Self::None,
@@ -999,13 +1154,10 @@ mod tests {
         println!("tick_rate: {}", map.tick_rate);
         println!("hit_objects: {}", map.hit_objects.len());
 
-        #[cfg(any(feature = "osu", feature = "fruits"))]
-        {
-            #[cfg(feature = "all_included")]
-            println!("stack_leniency: {}", map.stack_leniency);
+        #[cfg(all(feature = "osu", feature = "all_included"))]
+        println!("stack_leniency: {}", map.stack_leniency);
 
-            println!("timing_points: {}", map.timing_points.len());
-            println!("difficulty_points: {}", map.difficulty_points.len());
-        }
+        println!("timing_points: {}", map.timing_points.len());
+        println!("difficulty_points: {}", map.difficulty_points.len());
     }
 }
@@ -1,11 +1,13 @@
+use crate::Float;
+
 use std::fmt;
 use std::ops;
 
 /// Simple (x, y) coordinate / vector
 #[derive(Clone, Copy, Default, PartialEq)]
 pub struct Pos2 {
-    pub x: f32,
-    pub y: f32,
+    pub x: Float,
+    pub y: Float,
 }
 
 impl Pos2 {
@@ -15,22 +17,22 @@ impl Pos2 {
     }
 
     #[inline]
-    pub fn length_squared(&self) -> f32 {
+    pub fn length_squared(&self) -> Float {
         self.dot(*self)
     }
 
     #[inline]
-    pub fn length(&self) -> f32 {
+    pub fn length(&self) -> Float {
         self.x.hypot(self.y)
     }
 
     #[inline]
-    pub fn dot(&self, other: Self) -> f32 {
+    pub fn dot(&self, other: Self) -> Float {
         self.x.mul_add(other.x, self.y * other.y)
     }
 
     #[inline]
-    pub fn distance(&self, other: Self) -> f32 {
+    pub fn distance(&self, other: Self) -> Float {
         (*self - other).length()
     }
 
@@ -38,6 +40,12 @@ impl Pos2 {
     pub fn normalize(self) -> Pos2 {
         self / self.length()
     }
+
+    #[cfg(feature = "all_included")]
+    #[inline]
+    pub(crate) fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
 }
 
 impl ops::Add<Pos2> for Pos2 {
@@ -64,11 +72,11 @@ impl ops::Sub<Pos2> for Pos2 {
     }
 }
 
-impl ops::Mul<f32> for Pos2 {
+impl ops::Mul<Float> for Pos2 {
     type Output = Self;
 
     #[inline]
-    fn mul(self, rhs: f32) -> Self::Output {
+    fn mul(self, rhs: Float) -> Self::Output {
         Self {
             x: self.x * rhs,
             y: self.y * rhs,
@@ -76,11 +84,11 @@ impl ops::Mul<f32> for Pos2 {
     }
 }
 
-impl ops::Div<f32> for Pos2 {
+impl ops::Div<Float> for Pos2 {
     type Output = Self;
 
     #[inline]
-    fn div(self, rhs: f32) -> Self::Output {
+    fn div(self, rhs: Float) -> Self::Output {
         Self {
             x: self.x / rhs,
             y: self.y / rhs,
@@ -68,7 +68,7 @@ fn depth_limited_quick_sort(
 
         depth_limit -= 1;
 
-        if j.saturating_sub(left) <= right - i {
+        if j.saturating_sub(left) <= right.saturating_sub(i) {
             if left < j {
                 depth_limited_quick_sort(keys, left, j, depth_limit);
             }
@@ -1,4 +1,7 @@
-use crate::{Beatmap, GameMode, PpResult, StarResult};
+use crate::{
+    AlgorithmVersion, AttributeStore, Beatmap, CalcResult, Float, GameMode, HitResultPriority,
+    IfFcResult, PpResult, StarResult,
+};
 
 #[cfg(feature = "fruits")]
 use crate::FruitsPP;
@@ -27,19 +30,43 @@ pub enum AnyPP<'m> {
 }
 
 impl<'m> AnyPP<'m> {
+    /// Create a calculator for the map's mode.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the feature of the map's mode is not enabled, use [`try_new`](AnyPP::try_new) instead.
     #[inline]
     pub fn new(map: &'m Beatmap) -> Self {
+        Self::of_mode(map).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `new` but returns an error instead of panicking if the map's
+    /// mode is not enabled or the map is unfit for calculations,
+    /// see [`Beatmap::check_calculable`].
+    #[inline]
+    pub fn try_new(map: &'m Beatmap) -> CalcResult<Self> {
+        map.check_calculable(map.mode)?;
+
+        Self::of_mode(map)
+    }
+
+    fn of_mode(map: &'m Beatmap) -> CalcResult<Self> {
         match map.mode {
             #[cfg(feature = "fruits")]
-            GameMode::CTB => Self::Fruits(FruitsPP::new(map)),
+            GameMode::CTB => Ok(Self::Fruits(FruitsPP::new(map))),
             #[cfg(feature = "mania")]
-            GameMode::MNA => Self::Mania(ManiaPP::new(map)),
+            GameMode::MNA => Ok(Self::Mania(ManiaPP::new(map))),
             #[cfg(feature = "osu")]
-            GameMode::STD => Self::Osu(OsuPP::new(map)),
+            GameMode::STD => Ok(Self::Osu(OsuPP::new(map))),
             #[cfg(feature = "taiko")]
-            GameMode::TKO => Self::Taiko(TaikoPP::new(map)),
-            #[allow(unreachable_patterns)]
-            _ => panic!("feature for mode {:?} is not enabled", map.mode),
+            GameMode::TKO => Ok(Self::Taiko(TaikoPP::new(map))),
+            #[cfg(not(all(
+                feature = "osu",
+                feature = "taiko",
+                feature = "fruits",
+                feature = "mania"
+            )))]
+            mode => Err(crate::CalcError::UnincludedMode(mode)),
         }
     }
 
@@ -73,6 +100,24 @@ impl<'m> AnyPP<'m> {
         }
     }
 
+    /// Same as `calculate` but returns an error if the map is unfit for calculations
+    /// or if the difficulty parameters are invalid.
+    ///
+    /// Performs the calculation synchronously, even for the async features.
+    #[inline]
+    pub fn try_calculate(&mut self) -> CalcResult<PpResult> {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => f.try_calculate(),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => m.try_calculate(),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => o.try_calculate(),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => t.try_calculate(),
+        }
+    }
+
     /// [`AttributeProvider`] is implemented by [`StarResult`](crate::StarResult)
     /// and [`PpResult`](crate::PpResult) meaning you can give the result of a \
     /// star calculation or the result of a pp calculation.
@@ -92,6 +137,25 @@ impl<'m> AnyPP<'m> {
         }
     }
 
+    /// Fill the attributes from the given store or calculate and insert them if it has none.
+    ///
+    /// The checksum identifies the map, e.g. the MD5 hash of its .osu file.
+    /// Be sure to set the mods, clock rate, and version beforehand!
+    /// Attributes of partial plays or custom AR, OD, CS, or HP are not stored.
+    #[inline]
+    pub fn attributes_from_store(self, store: impl AttributeStore, checksum: &str) -> Self {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => Self::Fruits(f.attributes_from_store(store, checksum)),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => Self::Mania(m.attributes_from_store(store, checksum)),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => Self::Osu(o.attributes_from_store(store, checksum)),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => Self::Taiko(t.attributes_from_store(store, checksum)),
+        }
+    }
+
     /// Specify mods through their bit values.
     ///
     /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
@@ -109,6 +173,87 @@ impl<'m> AnyPP<'m> {
         }
     }
 
+    /// Specify a custom clock rate that overrides the one of the mods, e.g. `1.2` for a 1.2x speed-up.
+    ///
+    /// Invalid rates are ignored or clamped, see [`DifficultyParams::clock_rate`](crate::DifficultyParams::clock_rate).
+    #[inline]
+    pub fn clock_rate(self, clock_rate: Float) -> Self {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => Self::Fruits(f.clock_rate(clock_rate)),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => Self::Mania(m.clock_rate(clock_rate)),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => Self::Osu(o.clock_rate(clock_rate)),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => Self::Taiko(t.clock_rate(clock_rate)),
+        }
+    }
+
+    /// Specify an approach rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn ar(self, ar: Float) -> Self {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => Self::Fruits(f.ar(ar)),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => Self::Mania(m.ar(ar)),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => Self::Osu(o.ar(ar)),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => Self::Taiko(t.ar(ar)),
+        }
+    }
+
+    /// Specify an overall difficulty that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn od(self, od: Float) -> Self {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => Self::Fruits(f.od(od)),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => Self::Mania(m.od(od)),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => Self::Osu(o.od(od)),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => Self::Taiko(t.od(od)),
+        }
+    }
+
+    /// Specify a circle size that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn cs(self, cs: Float) -> Self {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => Self::Fruits(f.cs(cs)),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => Self::Mania(m.cs(cs)),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => Self::Osu(o.cs(cs)),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => Self::Taiko(t.cs(cs)),
+        }
+    }
+
+    /// Specify a drain rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn hp(self, hp: Float) -> Self {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => Self::Fruits(f.hp(hp)),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => Self::Mania(m.hp(hp)),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => Self::Osu(o.hp(hp)),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => Self::Taiko(t.hp(hp)),
+        }
+    }
+
     /// Amount of passed objects for partial plays, e.g. a fail.
     #[inline]
     pub fn passed_objects(self, passed_objects: usize) -> Self {
@@ -124,20 +269,39 @@ impl<'m> AnyPP<'m> {
         }
     }
 
+    /// Specify the version of the difficulty and pp algorithm.
+    ///
+    /// Versions without an implementation for the map's mode fall back
+    /// to the newest older one, see [`AlgorithmVersion::resolve`].
+    #[inline]
+    pub fn version(self, version: AlgorithmVersion) -> Self {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => Self::Fruits(f.version(version)),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => Self::Mania(m.version(version)),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => Self::Osu(o.version(version)),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => Self::Taiko(t.version(version)),
+        }
+    }
+
     /// Set the accuracy between 0.0 and 100.0.
     ///
     /// For some modes this method depends on previously set values
     /// be sure to call this last before calling `calculate`.
     ///
-    /// Irrelevant for osu!mania.
+    /// For osu!mania, this is only relevant for accuracy based versions,
+    /// see [`ManiaPP::version`](crate::ManiaPP::version).
     #[allow(unused_variables)]
     #[inline]
-    pub fn accuracy(self, acc: f32) -> Self {
+    pub fn accuracy(self, acc: Float) -> Self {
         match self {
             #[cfg(feature = "fruits")]
             Self::Fruits(f) => Self::Fruits(f.accuracy(acc)),
             #[cfg(feature = "mania")]
-            Self::Mania(_) => self,
+            Self::Mania(m) => Self::Mania(m.accuracy(acc)),
             #[cfg(feature = "osu")]
             Self::Osu(o) => Self::Osu(o.accuracy(acc)),
             #[cfg(feature = "taiko")]
@@ -153,10 +317,11 @@ impl<'m> AnyPP<'m> {
     /// For some modes this method depends on previously set values
     /// be sure to call this last before calling `calculate`.
     ///
-    /// Irrelevant for osu!mania.
+    /// For osu!mania, this is only relevant for accuracy based versions.
     /// 
     /// If it is used to calculate the PP of multiple different ACCs, 
     /// it should be called from high to low according to the ACC value, otherwise it is invalid.
+    /// Use [`AnyPP::calculate_accuracies`] to calculate multiple accuracies in any order.
     /// 
     /// Examples:
     /// ```
@@ -197,12 +362,12 @@ impl<'m> AnyPP<'m> {
     /// };
     /// ```
     /// 
-    pub fn set_accuracy(&mut self, acc: f32) {
+    pub fn set_accuracy(&mut self, acc: Float) {
         match self {
             #[cfg(feature = "fruits")]
             Self::Fruits(f) => f.set_accuracy(acc),
             #[cfg(feature = "mania")]
-            Self::Mania(_) => {}
+            Self::Mania(m) => m.set_accuracy(acc),
             #[cfg(feature = "osu")]
             Self::Osu(o) => o.set_accuracy(acc),
             #[cfg(feature = "taiko")]
@@ -210,9 +375,26 @@ impl<'m> AnyPP<'m> {
         }
     }
 
-    /// Specify the amount of misses of a play.
+    /// Calculate the pp for each of the given accuracies between `0` and `100`.
     ///
-    /// Irrelevant for osu!mania.
+    /// The difficulty attributes are calculated only once and the hit results
+    /// are generated from the previously given input for every accuracy
+    /// so, unlike [`set_accuracy`](AnyPP::set_accuracy), the order does not matter.
+    #[inline]
+    pub fn calculate_accuracies(&mut self, accs: &[Float]) -> Vec<PpResult> {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => f.calculate_accuracies(accs),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => m.calculate_accuracies(accs),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => o.calculate_accuracies(accs),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => t.calculate_accuracies(accs),
+        }
+    }
+
+    /// Specify the amount of misses of a play.
     #[allow(unused_variables)]
     #[inline]
     pub fn misses(self, misses: usize) -> Self {
@@ -220,7 +402,7 @@ impl<'m> AnyPP<'m> {
             #[cfg(feature = "fruits")]
             Self::Fruits(f) => Self::Fruits(f.misses(misses)),
             #[cfg(feature = "mania")]
-            Self::Mania(_) => self,
+            Self::Mania(m) => Self::Mania(m.misses(misses)),
             #[cfg(feature = "osu")]
             Self::Osu(o) => Self::Osu(o.misses(misses)),
             #[cfg(feature = "taiko")]
@@ -247,8 +429,6 @@ impl<'m> AnyPP<'m> {
     }
 
     /// Specify the amount of 300s of a play.
-    ///
-    /// Irrelevant for osu!mania.
     #[allow(unused_variables)]
     #[inline]
     pub fn n300(self, n300: usize) -> Self {
@@ -256,7 +436,7 @@ impl<'m> AnyPP<'m> {
             #[cfg(feature = "fruits")]
             Self::Fruits(f) => Self::Fruits(f.fruits(n300)),
             #[cfg(feature = "mania")]
-            Self::Mania(_) => self,
+            Self::Mania(m) => Self::Mania(m.n300(n300)),
             #[cfg(feature = "osu")]
             Self::Osu(o) => Self::Osu(o.n300(n300)),
             #[cfg(feature = "taiko")]
@@ -265,8 +445,6 @@ impl<'m> AnyPP<'m> {
     }
 
     /// Specify the amount of 100s of a play.
-    ///
-    /// Irrelevant for osu!mania.
     #[allow(unused_variables)]
     #[inline]
     pub fn n100(self, n100: usize) -> Self {
@@ -274,7 +452,7 @@ impl<'m> AnyPP<'m> {
             #[cfg(feature = "fruits")]
             Self::Fruits(f) => Self::Fruits(f.droplets(n100)),
             #[cfg(feature = "mania")]
-            Self::Mania(_) => self,
+            Self::Mania(m) => Self::Mania(m.n100(n100)),
             #[cfg(feature = "osu")]
             Self::Osu(o) => Self::Osu(o.n100(n100)),
             #[cfg(feature = "taiko")]
@@ -284,7 +462,7 @@ impl<'m> AnyPP<'m> {
 
     /// Specify the amount of 50s of a play.
     ///
-    /// Irrelevant for osu!mania and osu!taiko.
+    /// Irrelevant for osu!taiko.
     #[allow(unused_variables)]
     #[inline]
     pub fn n50(self, n50: usize) -> Self {
@@ -292,7 +470,7 @@ impl<'m> AnyPP<'m> {
             #[cfg(feature = "fruits")]
             Self::Fruits(f) => Self::Fruits(f.tiny_droplets(n50)),
             #[cfg(feature = "mania")]
-            Self::Mania(_) => self,
+            Self::Mania(m) => Self::Mania(m.n50(n50)),
             #[cfg(feature = "osu")]
             Self::Osu(o) => Self::Osu(o.n50(n50)),
             #[cfg(feature = "taiko")]
@@ -300,6 +478,64 @@ impl<'m> AnyPP<'m> {
         }
     }
 
+    /// Calculate the pp of the play and of the same play with a full combo.
+    ///
+    /// The difficulty attributes are calculated only once.
+    #[inline]
+    pub fn calculate_if_fc(&mut self) -> IfFcResult {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => f.calculate_if_fc(),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => m.calculate_if_fc(),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => o.calculate_if_fc(),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => t.calculate_if_fc(),
+        }
+    }
+
+    /// The minimum accuracy between `0` and `100` that is required to reach the given pp
+    /// w.r.t. all previously specified values such as mods, combo, or misses.
+    ///
+    /// Returns `None` if the pp can not be reached, not even with 100% accuracy.
+    /// The difficulty attributes are calculated only once.
+    ///
+    /// For score based osu!mania versions,
+    /// use [`ManiaPP::score_for_pp`](crate::ManiaPP::score_for_pp) instead.
+    #[inline]
+    pub fn accuracy_for_pp(&mut self, pp: Float) -> Option<Float> {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => f.accuracy_for_pp(pp),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => m.accuracy_for_pp(pp),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => o.accuracy_for_pp(pp),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(t) => t.accuracy_for_pp(pp),
+        }
+    }
+
+    /// Specify how hit results are distributed when they are generated from an accuracy,
+    /// see [`HitResultPriority`].
+    ///
+    /// This value is not relevant for osu!taiko. Be sure to set this before the accuracy!
+    #[allow(unused_variables)]
+    #[inline]
+    pub fn hitresult_priority(self, priority: HitResultPriority) -> Self {
+        match self {
+            #[cfg(feature = "fruits")]
+            Self::Fruits(f) => Self::Fruits(f.hitresult_priority(priority)),
+            #[cfg(feature = "mania")]
+            Self::Mania(m) => Self::Mania(m.hitresult_priority(priority)),
+            #[cfg(feature = "osu")]
+            Self::Osu(o) => Self::Osu(o.hitresult_priority(priority)),
+            #[cfg(feature = "taiko")]
+            Self::Taiko(_) => self,
+        }
+    }
+
     /// Specify the amount of katus of a play.
     ///
     /// This value is only relevant for osu!ctb for which it represent
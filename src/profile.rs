@@ -0,0 +1,192 @@
+use crate::{Float, PpResult};
+
+/// Factor by which the weight of each play decreases w.r.t. the previous one.
+const WEIGHT_DECAY: Float = 0.95;
+
+/// Bonus pp approach this value with an increasing amount of ranked scores.
+const MAX_BONUS_PP: Float = 416.6667;
+
+/// Only this many ranked scores count towards the bonus pp.
+const MAX_BONUS_SCORES: usize = 1000;
+
+/// The total pp of a player, aggregated from their top plays.
+///
+/// The n-th best play (starting at zero) is weighted by `0.95^n` and bonus pp
+/// are added w.r.t. the amount of ranked scores.
+/// Only the best play on each map should be inserted, deduplication is up to the caller.
+///
+/// # Example
+///
+/// ```
+/// use peace_performance::Profile;
+///
+/// let mut profile = Profile::new(vec![250.0, 300.0, 275.0]);
+/// let total = profile.total_pp();
+///
+/// // How much a new play would add to the total
+/// let gain = profile.gain(280.0);
+///
+/// profile.insert(280.0);
+/// assert!((profile.total_pp() - (total + gain)).abs() < 0.001);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    /// The pp of the plays in descending order.
+    plays: Vec<Float>,
+    n_scores: usize,
+}
+
+impl Profile {
+    /// Create a new profile from the given plays in any order.
+    ///
+    /// The amount of ranked scores is considered to be the amount of plays,
+    /// see [`Profile::ranked_scores`] to specify it separately.
+    pub fn new<P: PpProvider>(plays: impl IntoIterator<Item = P>) -> Self {
+        let mut plays: Vec<Float> = plays.into_iter().map(PpProvider::pp).collect();
+        plays.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        Self {
+            n_scores: plays.len(),
+            plays,
+        }
+    }
+
+    /// Specify the amount of ranked scores of the player which determines the bonus pp.
+    ///
+    /// It's usually higher than the amount of plays that are kept in the profile.
+    #[inline]
+    pub fn ranked_scores(mut self, n_scores: usize) -> Self {
+        self.n_scores = n_scores;
+
+        self
+    }
+
+    /// Insert a new play and return its index within the sorted plays.
+    ///
+    /// The amount of ranked scores increases by one.
+    pub fn insert(&mut self, play: impl PpProvider) -> usize {
+        let pp = play.pp();
+        let idx = self.position(pp);
+
+        self.plays.insert(idx, pp);
+        self.n_scores += 1;
+
+        idx
+    }
+
+    /// How much the total pp would increase by inserting the given play.
+    pub fn gain(&self, play: impl PpProvider) -> Float {
+        let pp = play.pp();
+        let idx = self.position(pp);
+
+        // All plays after the new one drop by one position
+        let shifted: Float = self.plays[idx..]
+            .iter()
+            .zip(self.weights().skip(idx))
+            .map(|(pp, weight)| pp * weight * (1.0 - WEIGHT_DECAY))
+            .sum();
+
+        let bonus_gain = bonus_pp(self.n_scores + 1) - bonus_pp(self.n_scores);
+
+        pp * WEIGHT_DECAY.powi(idx as i32) - shifted + bonus_gain
+    }
+
+    /// The sum of all weighted plays without bonus pp.
+    #[inline]
+    pub fn weighted_pp(&self) -> Float {
+        self.plays
+            .iter()
+            .zip(self.weights())
+            .map(|(pp, weight)| pp * weight)
+            .sum()
+    }
+
+    /// The bonus pp w.r.t. the amount of ranked scores.
+    #[inline]
+    pub fn bonus_pp(&self) -> Float {
+        bonus_pp(self.n_scores)
+    }
+
+    /// The weighted pp plus the bonus pp.
+    #[inline]
+    pub fn total_pp(&self) -> Float {
+        self.weighted_pp() + self.bonus_pp()
+    }
+
+    /// The pp of all plays in descending order.
+    #[inline]
+    pub fn plays(&self) -> &[Float] {
+        &self.plays
+    }
+
+    /// Index at which a play with the given pp would be inserted.
+    #[inline]
+    fn position(&self, pp: Float) -> usize {
+        self.plays.partition_point(|&play| play >= pp)
+    }
+
+    #[inline]
+    fn weights(&self) -> impl Iterator<Item = Float> {
+        std::iter::successors(Some(1.0), |weight| Some(weight * WEIGHT_DECAY))
+    }
+}
+
+#[inline]
+fn bonus_pp(n_scores: usize) -> Float {
+    MAX_BONUS_PP * (1.0 - (0.995 as Float).powi(n_scores.min(MAX_BONUS_SCORES) as i32))
+}
+
+/// Implemented by `Float` and [`PpResult`](crate::PpResult)
+/// so that both can be put into a [`Profile`].
+pub trait PpProvider {
+    fn pp(self) -> Float;
+}
+
+impl PpProvider for Float {
+    #[inline]
+    fn pp(self) -> Float {
+        self
+    }
+}
+
+impl PpProvider for PpResult {
+    #[inline]
+    fn pp(self) -> Float {
+        self.pp
+    }
+}
+
+impl PpProvider for &PpResult {
+    #[inline]
+    fn pp(self) -> Float {
+        self.pp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_weighting() {
+        let profile = Profile::new(vec![100.0, 200.0]).ranked_scores(0);
+
+        assert_eq!(profile.plays(), &[200.0, 100.0]);
+        assert!((profile.total_pp() - 295.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn profile_gain() {
+        let mut profile = Profile::new(vec![300.0, 150.0, 200.0, 120.0]);
+
+        for &pp in &[400.0, 175.0, 10.0, 150.0] {
+            let expected = profile.total_pp() + profile.gain(pp);
+            profile.insert(pp);
+
+            assert!((profile.total_pp() - expected).abs() < 0.01);
+        }
+
+        assert_eq!(profile.plays()[0], 400.0);
+        assert!(profile.bonus_pp() > 0.0);
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(any(feature = "osu", feature = "taiko", feature = "fruits"))]
+use crate::Beatmap;
+use crate::Float;
+
+/// The scoring system that is used to calculate a score.
+///
+/// Only the max score of ScoreV1 is simulated exactly. Since the order of hits is unknown,
+/// the combo bonus of any other play is an estimate that spreads the combo evenly
+/// between the misses.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ScoreVersion {
+    /// osu!stable's default scoring where the combo bonus depends on the map's difficulty.
+    V1,
+    /// An approximation of osu!stable's ScoreV2 which caps the score at one million points
+    /// before mods and bonus. Its combo portion does not follow the game's formula.
+    V2,
+}
+
+impl Default for ScoreVersion {
+    #[inline]
+    fn default() -> Self {
+        Self::V1
+    }
+}
+
+/// Maximum points of ScoreV2 before mod multipliers and bonus.
+pub(crate) const SCORE_V2_MAX: Float = 1_000_000.0;
+
+/// Portion of the ScoreV2 points that is given for the combo.
+const SCORE_V2_COMBO_PORTION: Float = 0.7;
+
+/// ScoreV1's difficulty multiplier of the map based on its unmodded HP, CS, OD,
+/// and the amount of objects per second of drain time.
+#[cfg(any(feature = "osu", feature = "taiko", feature = "fruits"))]
+pub(crate) fn difficulty_multiplier(map: &Beatmap) -> Float {
+    let first_time = map.hit_objects.first().map_or(0.0, |h| h.start_time);
+    let last_time = map.hit_objects.last().map_or(0.0, |h| h.end_time());
+    let break_time: Float = map.breaks.iter().map(|b| b.duration()).sum();
+    let drain_secs = ((last_time - first_time - break_time) / 1000.0).max(1.0);
+
+    let density = (map.hit_objects.len() as Float / drain_secs * 8.0).clamp(0.0, 16.0);
+
+    ((map.hp + map.cs + map.od + density) / 38.0 * 5.0).round()
+}
+
+/// Lengths of the combo chains of a play whose longest chain is `combo`.
+///
+/// Since the order of hits is unknown, the remaining combo is assumed to be
+/// spread evenly between as many chains as there are misses, at least one.
+pub(crate) fn combo_chains(combo: usize, max_combo: usize, n_misses: usize) -> Vec<usize> {
+    let combo = combo.min(max_combo);
+    let remaining = max_combo.saturating_sub(combo + n_misses);
+    let mut chains = vec![combo];
+
+    if remaining > 0 {
+        let n_chains = n_misses.max(1);
+        let len = remaining / n_chains;
+        let extra = remaining % n_chains;
+
+        chains.extend((0..n_chains).map(|i| len + (i < extra) as usize));
+    }
+
+    chains
+}
+
+/// Ratio of a combo bonus that is reached by the given chains compared to a full combo,
+/// where `bonus` is the bonus factor of a hit with the given combo before it.
+pub(crate) fn combo_bonus_ratio(
+    chains: &[usize],
+    max_combo: usize,
+    bonus: impl Fn(usize) -> Float,
+) -> Float {
+    let chain_bonus = |len: usize| (0..len).map(&bonus).sum::<Float>();
+    let max_bonus = chain_bonus(max_combo);
+
+    if max_bonus <= 0.0 {
+        return 1.0;
+    }
+
+    chains.iter().map(|&len| chain_bonus(len)).sum::<Float>() / max_bonus
+}
+
+/// Ratio of ScoreV2's combo portion that is reached by the given chains.
+///
+/// This is an approximation rather than the game's formula: hits are weighted by the
+/// logarithm of their combo so breaks cost less than in ScoreV1.
+#[inline]
+pub(crate) fn score_v2_combo_ratio(chains: &[usize], max_combo: usize) -> Float {
+    combo_bonus_ratio(chains, max_combo, |combo| (1.0 + combo as Float).ln())
+}
+
+/// ScoreV2 from the reached portion of the combo, the accuracy, and the portion
+/// of judged objects; the mod multiplier and bonus points are applied afterwards.
+#[inline]
+pub(crate) fn score_v2(combo_ratio: Float, acc: Float, progress: Float) -> Float {
+    let combo_score = SCORE_V2_COMBO_PORTION * combo_ratio;
+    let acc_score = (1.0 - SCORE_V2_COMBO_PORTION) * acc.powi(10) * progress;
+
+    SCORE_V2_MAX * (combo_score + acc_score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_combo_chains() {
+        assert_eq!(combo_chains(100, 100, 0), vec![100]);
+        assert_eq!(combo_chains(50, 100, 2), vec![50, 24, 24]);
+        assert_eq!(combo_chains(50, 100, 0), vec![50, 50]);
+        assert_eq!(combo_chains(10, 15, 3), vec![10, 1, 1, 0]);
+
+        let ratio = combo_bonus_ratio(&[100], 100, |combo| combo as Float);
+        assert!((ratio - 1.0).abs() < Float::EPSILON);
+
+        let ratio = combo_bonus_ratio(&[50, 50], 100, |combo| combo as Float);
+        assert!(ratio < 0.5);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::Float;
+
+/// Precision of solved accuracies in percent.
+const ACC_PRECISION: Float = 0.001;
+
+/// Step size in percent when looking for lower accuracies after the binary search.
+const ACC_SCAN_STEP: Float = 0.01;
+
+/// How far below the best accuracy so far lower accuracies are looked for.
+const ACC_SCAN_RANGE: Float = 1.0;
+
+/// Smallest accuracy between `0` and `100` whose pp reach `target`.
+///
+/// Since hit results are discrete, the pp only roughly increase with the accuracy,
+/// e.g. when a 300 replaces a few 100s but adds 50s. After the binary search,
+/// slightly lower accuracies are checked as well to not miss such cases.
+pub(crate) fn min_accuracy_for_pp(
+    target: Float,
+    mut pp_for: impl FnMut(Float) -> Float,
+) -> Option<Float> {
+    let mut acc = min_value_for_pp(target, 0.0, 100.0, ACC_PRECISION, &mut pp_for)?;
+    let mut probe = acc - ACC_SCAN_STEP;
+
+    while probe >= 0.0 && acc - probe <= ACC_SCAN_RANGE {
+        if pp_for(probe) >= target {
+            acc = probe;
+        }
+
+        probe -= ACC_SCAN_STEP;
+    }
+
+    // Refine between the lowest reaching accuracy and the next lower step
+    let min = (acc - ACC_SCAN_STEP).max(0.0);
+
+    min_value_for_pp(target, min, acc, ACC_PRECISION, pp_for).or(Some(acc))
+}
+
+/// Binary search for the smallest value between `min` and `max` whose pp reach `target`.
+///
+/// The pp are assumed to be non-decreasing w.r.t. the value.
+/// Returns `None` if even `max` does not reach the target.
+pub(crate) fn min_value_for_pp(
+    target: Float,
+    min: Float,
+    max: Float,
+    precision: Float,
+    mut pp_for: impl FnMut(Float) -> Float,
+) -> Option<Float> {
+    if target.is_nan() || pp_for(max) < target {
+        return None;
+    } else if pp_for(min) >= target {
+        return Some(min);
+    }
+
+    let mut low = min;
+    let mut high = max;
+
+    while high - low > precision {
+        let mid = (low + high) / 2.0;
+
+        if pp_for(mid) >= target {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    Some(high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solver_bounds() {
+        let pp_for = |acc: Float| acc * 3.0;
+
+        assert_eq!(min_value_for_pp(301.0, 0.0, 100.0, 0.001, pp_for), None);
+        assert_eq!(min_value_for_pp(-1.0, 0.0, 100.0, 0.001, pp_for), Some(0.0));
+
+        let acc = min_value_for_pp(150.0, 0.0, 100.0, 0.001, pp_for).unwrap();
+        assert!(acc >= 50.0 && acc - 50.0 <= 0.001);
+    }
+}
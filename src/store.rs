@@ -0,0 +1,708 @@
+use crate::{AlgorithmVersion, DifficultyParams, Float, GameMode, Mods, StarResult};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Identifies the difficulty attributes of a map for the settings they were calculated with.
+#[derive(Clone, Debug)]
+pub struct AttributeKey {
+    /// Checksum of the .osu file, e.g. its MD5 hash as used by osu!.
+    pub checksum: String,
+    /// The mode the attributes were calculated for, converted maps have their own attributes.
+    pub mode: GameMode,
+    pub mods: u32,
+    pub clock_rate: Float,
+    pub version: AlgorithmVersion,
+    /// The build of this crate the attributes were calculated with, see [`AttributeKey::build`].
+    pub build: &'static str,
+}
+
+impl AttributeKey {
+    /// Mods without effect in the given mode are ignored and the version is
+    /// resolved w.r.t. the mode, see [`AlgorithmVersion::resolve`].
+    /// The build is the one of the mode, see [`AttributeKey::build`].
+    #[inline]
+    pub fn new(
+        checksum: impl Into<String>,
+        mode: GameMode,
+        mods: u32,
+        clock_rate: Float,
+        version: AlgorithmVersion,
+    ) -> Self {
+        Self {
+            checksum: checksum.into(),
+            mode,
+            mods: mods.for_mode(mode),
+            clock_rate,
+            version: version.resolve(mode),
+            build: Self::build(mode),
+        }
+    }
+
+    /// Identifies the enabled features that affect the attributes of the given mode,
+    /// i.e. the float precision and, for osu!standard, the variant of the algorithm.
+    ///
+    /// Attributes of other builds are not interchangeable, e.g. `"all_included-f32"`
+    /// and `"no_leniency-f32"` attributes of the same map and mods differ.
+    #[inline]
+    pub fn build(mode: GameMode) -> &'static str {
+        match mode {
+            GameMode::STD => OSU_BUILD,
+            _ => PRECISION,
+        }
+    }
+
+    /// The key of a calculator's attributes or `None` if they should not be stored,
+    /// i.e. for partial plays or custom AR, OD, CS, or HP.
+    pub(crate) fn for_calculation(
+        checksum: &str,
+        mode: GameMode,
+        mods: u32,
+        params: DifficultyParams,
+        passed_objects: Option<usize>,
+        version: AlgorithmVersion,
+    ) -> Option<Self> {
+        let custom_values = params.ar.or(params.od).or(params.cs).or(params.hp);
+
+        if passed_objects.is_some() || custom_values.is_some() {
+            return None;
+        }
+
+        let clock_rate = params.clock_rate_with(mods);
+
+        Some(Self::new(checksum, mode, mods, clock_rate, version))
+    }
+}
+
+impl PartialEq for AttributeKey {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.checksum == other.checksum
+            && self.mode == other.mode
+            && self.mods == other.mods
+            && self.clock_rate.to_bits() == other.clock_rate.to_bits()
+            && self.version == other.version
+            && self.build == other.build
+    }
+}
+
+impl Eq for AttributeKey {}
+
+const PRECISION: &str = if cfg!(feature = "f64") { "f64" } else { "f32" };
+
+#[cfg(feature = "osu")]
+macro_rules! osu_build {
+    ($variant:literal) => {
+        if cfg!(feature = "f64") {
+            concat!($variant, "-f64")
+        } else {
+            concat!($variant, "-f32")
+        }
+    };
+}
+
+#[cfg(feature = "all_included")]
+const OSU_BUILD: &str = osu_build!("all_included");
+
+#[cfg(feature = "no_leniency")]
+const OSU_BUILD: &str = osu_build!("no_leniency");
+
+#[cfg(feature = "no_sliders_no_leniency")]
+const OSU_BUILD: &str = osu_build!("no_sliders_no_leniency");
+
+// osu!standard attributes can not be calculated without a variant
+#[cfg(not(any(
+    feature = "all_included",
+    feature = "no_leniency",
+    feature = "no_sliders_no_leniency"
+)))]
+const OSU_BUILD: &str = PRECISION;
+
+impl Hash for AttributeKey {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.checksum.hash(state);
+        self.mode.hash(state);
+        self.mods.hash(state);
+        self.clock_rate.to_bits().hash(state);
+        self.version.hash(state);
+        self.build.hash(state);
+    }
+}
+
+/// Storage of difficulty attributes so that they don't have to be recalculated.
+///
+/// Calculators fill their attributes from a store through their
+/// `attributes_from_store` method, e.g. [`OsuPP::attributes_from_store`](crate::OsuPP::attributes_from_store).
+/// Stores can not be passed to the `attributes` methods like attribute providers
+/// because the key depends on the calculator's mods, clock rate, and version.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "taiko")]
+/// # {
+/// use peace_performance::{Beatmap, FileStore, TaikoPP};
+///
+/// # let map = Beatmap::default();
+/// # let checksum = "";
+/// let mut store = FileStore::open("./attributes.txt").unwrap();
+///
+/// let result = TaikoPP::new(&map)
+///     .mods(64)
+///     .attributes_from_store(&mut store, checksum)
+///     .calculate();
+///
+/// store.flush().unwrap();
+/// # }
+/// ```
+pub trait AttributeStore {
+    /// The attributes stored for the given key.
+    fn get(&mut self, key: &AttributeKey) -> Option<StarResult>;
+
+    /// Store the attributes for the given key, replacing previous attributes.
+    fn insert(&mut self, key: AttributeKey, attributes: StarResult);
+
+    /// The stored attributes of the key or the result of `calculate` which is then inserted.
+    fn get_or_calculate(
+        &mut self,
+        key: AttributeKey,
+        calculate: impl FnOnce() -> StarResult,
+    ) -> StarResult
+    where
+        Self: Sized,
+    {
+        if let Some(attributes) = self.get(&key) {
+            return attributes;
+        }
+
+        let attributes = calculate();
+        self.insert(key, attributes.clone());
+
+        attributes
+    }
+}
+
+impl<S: AttributeStore + ?Sized> AttributeStore for &mut S {
+    #[inline]
+    fn get(&mut self, key: &AttributeKey) -> Option<StarResult> {
+        (**self).get(key)
+    }
+
+    #[inline]
+    fn insert(&mut self, key: AttributeKey, attributes: StarResult) {
+        (**self).insert(key, attributes)
+    }
+}
+
+/// In-memory [`AttributeStore`] that evicts the least recently used
+/// attributes once its capacity is reached.
+#[derive(Clone, Debug)]
+pub struct LruStore {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<AttributeKey, (StarResult, u64)>,
+    /// Keys by the tick of their last use.
+    recency: BTreeMap<u64, AttributeKey>,
+}
+
+impl LruStore {
+    /// Create a store that keeps at most `capacity` attributes, but at least one.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+
+        self.tick
+    }
+}
+
+impl AttributeStore for LruStore {
+    fn get(&mut self, key: &AttributeKey) -> Option<StarResult> {
+        let tick = self.next_tick();
+        let (attributes, last_used) = self.entries.get_mut(key)?;
+
+        if let Some(key) = self.recency.remove(last_used) {
+            self.recency.insert(tick, key);
+        }
+
+        *last_used = tick;
+
+        Some(attributes.clone())
+    }
+
+    fn insert(&mut self, key: AttributeKey, attributes: StarResult) {
+        let tick = self.next_tick();
+
+        if let Some((_, last_used)) = self.entries.get(&key) {
+            self.recency.remove(last_used);
+        } else if self.entries.len() >= self.capacity {
+            let oldest = self.recency.keys().next().copied();
+
+            if let Some(oldest) = oldest.and_then(|tick| self.recency.remove(&tick)) {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.recency.insert(tick, key.clone());
+        self.entries.insert(key, (attributes, tick));
+    }
+}
+
+/// [`AttributeStore`] that persists its attributes in a file so that they
+/// survive restarts.
+///
+/// All attributes of the file are loaded on [`FileStore::open`] and new attributes
+/// are appended to it, one line each.
+/// Lines that can not be parsed, e.g. of modes whose feature is not enabled
+/// or of another [build](AttributeKey::build), are skipped.
+/// Appended lines are buffered, see [`FileStore::flush`].
+pub struct FileStore {
+    entries: HashMap<AttributeKey, StarResult>,
+    file: BufWriter<File>,
+    error: Option<io::Error>,
+}
+
+impl FileStore {
+    /// Load the attributes of the given file, creating it if it does not exist yet.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut entries = HashMap::new();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        let mut complete = true;
+
+        while reader.read_line(&mut line)? > 0 {
+            if let Some((key, attributes)) = parse_entry(line.trim_end()) {
+                entries.insert(key, attributes);
+            }
+
+            complete = line.ends_with('\n');
+            line.clear();
+        }
+
+        let mut file = reader.into_inner();
+
+        // e.g. an interrupted write, new lines must not be appended to it
+        if !complete {
+            file.write_all(b"\n")?;
+        }
+
+        Ok(Self {
+            entries,
+            file: BufWriter::new(file),
+            error: None,
+        })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write all buffered attributes to the file.
+    ///
+    /// Returns the first error that occurred while appending attributes since the last flush.
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.file.flush(),
+        }
+    }
+}
+
+impl AttributeStore for FileStore {
+    #[inline]
+    fn get(&mut self, key: &AttributeKey) -> Option<StarResult> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: AttributeKey, attributes: StarResult) {
+        // Such checksums would break the line format so they are only kept in memory
+        let valid_checksum =
+            !key.checksum.is_empty() && !key.checksum.contains(|c: char| c.is_whitespace());
+
+        if valid_checksum && self.error.is_none() {
+            if let Err(err) = writeln!(self.file, "{}", format_entry(&key, &attributes)) {
+                self.error = Some(err);
+            }
+        }
+
+        self.entries.insert(key, attributes);
+    }
+}
+
+/// Tab-separated key followed by the mode's attribute values.
+fn format_entry(key: &AttributeKey, attributes: &StarResult) -> String {
+    let mut line = format!(
+        "{}\t{}\t{}\t{}\t{:?}\t{}",
+        key.checksum, key.mode as u8, key.mods, key.clock_rate, key.version, key.build
+    );
+
+    match attributes {
+        #[cfg(feature = "fruits")]
+        StarResult::Fruits(attributes) => line.push_str(&format!(
+            "\t{}\t{}\t{}\t{}\t{}\t{}",
+            attributes.stars,
+            attributes.max_combo,
+            attributes.ar,
+            attributes.n_fruits,
+            attributes.n_droplets,
+            attributes.n_tiny_droplets
+        )),
+        #[cfg(feature = "mania")]
+        StarResult::Mania(attributes) => line.push_str(&format!(
+            "\t{}\t{}",
+            attributes.stars, attributes.n_hold_notes
+        )),
+        #[cfg(feature = "osu")]
+        StarResult::Osu(attributes) => line.push_str(&format!(
+            "\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            attributes.stars,
+            attributes.ar,
+            attributes.od,
+            attributes.speed_strain,
+            attributes.aim_strain,
+            attributes.flashlight_strain,
+            attributes.slider_factor,
+            attributes.speed_note_count,
+            attributes.aim_difficult_strain_count,
+            attributes.speed_difficult_strain_count,
+            attributes.max_combo,
+            attributes.n_circles,
+            attributes.n_sliders,
+            attributes.n_spinners
+        )),
+        #[cfg(feature = "taiko")]
+        StarResult::Taiko(attributes) => line.push_str(&format!("\t{}", attributes.stars)),
+    }
+
+    line
+}
+
+fn parse_entry(line: &str) -> Option<(AttributeKey, StarResult)> {
+    let mut values = line.split('\t');
+
+    let checksum = values.next()?.to_owned();
+
+    let mode = match values.next()? {
+        "0" => GameMode::STD,
+        "1" => GameMode::TKO,
+        "2" => GameMode::CTB,
+        "3" => GameMode::MNA,
+        _ => return None,
+    };
+
+    let mods = values.next()?.parse().ok()?;
+    let clock_rate = values.next()?.parse().ok()?;
+
+    let version = match values.next()? {
+        "V2021" => AlgorithmVersion::V2021,
+        "V2022" => AlgorithmVersion::V2022,
+        "V2024" => AlgorithmVersion::V2024,
+        _ => return None,
+    };
+
+    let build = AttributeKey::build(mode);
+
+    if values.next()? != build {
+        return None;
+    }
+
+    let attributes = parse_attributes(mode, &mut values)?;
+
+    // Lines with too many values are considered malformed
+    if values.next().is_some() {
+        return None;
+    }
+
+    let key = AttributeKey {
+        checksum,
+        mode,
+        mods,
+        clock_rate,
+        version,
+        build,
+    };
+
+    Some((key, attributes))
+}
+
+#[allow(unused_variables)]
+fn parse_attributes<'l>(
+    mode: GameMode,
+    values: &mut impl Iterator<Item = &'l str>,
+) -> Option<StarResult> {
+    // Parses the next value into the inferred type
+    macro_rules! next {
+        () => {
+            values.next()?.parse().ok()?
+        };
+    }
+
+    let attributes = match mode {
+        #[cfg(feature = "fruits")]
+        GameMode::CTB => StarResult::Fruits(crate::fruits::DifficultyAttributes {
+            stars: next!(),
+            max_combo: next!(),
+            ar: next!(),
+            n_fruits: next!(),
+            n_droplets: next!(),
+            n_tiny_droplets: next!(),
+        }),
+        #[cfg(feature = "mania")]
+        GameMode::MNA => StarResult::Mania(crate::mania::DifficultyAttributes {
+            stars: next!(),
+            n_hold_notes: next!(),
+        }),
+        #[cfg(feature = "osu")]
+        GameMode::STD => StarResult::Osu(crate::osu::DifficultyAttributes {
+            stars: next!(),
+            ar: next!(),
+            od: next!(),
+            speed_strain: next!(),
+            aim_strain: next!(),
+            flashlight_strain: next!(),
+            slider_factor: next!(),
+            speed_note_count: next!(),
+            aim_difficult_strain_count: next!(),
+            speed_difficult_strain_count: next!(),
+            max_combo: next!(),
+            n_circles: next!(),
+            n_sliders: next!(),
+            n_spinners: next!(),
+        }),
+        #[cfg(feature = "taiko")]
+        GameMode::TKO => StarResult::Taiko(crate::taiko::DifficultyAttributes { stars: next!() }),
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    };
+
+    Some(attributes)
+}
+
+#[cfg(all(test, any(feature = "osu", feature = "taiko", feature = "mania")))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "taiko")]
+    fn taiko(stars: Float) -> StarResult {
+        StarResult::Taiko(crate::taiko::DifficultyAttributes { stars })
+    }
+
+    #[test]
+    #[cfg(feature = "taiko")]
+    fn lru_evicts_least_recently_used() {
+        let key = |checksum: &str| {
+            AttributeKey::new(checksum, GameMode::TKO, 0, 1.0, AlgorithmVersion::V2021)
+        };
+
+        let mut store = LruStore::new(2);
+        store.insert(key("a"), taiko(1.0));
+        store.insert(key("b"), taiko(2.0));
+
+        // "a" becomes the most recently used
+        assert!(store.get(&key("a")).is_some());
+
+        store.insert(key("c"), taiko(3.0));
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&key("b")).is_none());
+        assert_eq!(store.get(&key("a")).map(|a| a.stars()), Some(1.0));
+
+        let calculated = store.get_or_calculate(key("c"), || unreachable!());
+        assert_eq!(calculated.stars(), 3.0);
+    }
+
+    #[test]
+    #[cfg(feature = "mania")]
+    fn calculator_fills_from_store() {
+        use crate::{
+            parse::{HitObject, HitObjectKind, Pos2},
+            Beatmap, ManiaPP,
+        };
+
+        let hit_objects = (0..50)
+            .map(|i| HitObject {
+                pos: Pos2 {
+                    x: (i % 4 * 128) as Float,
+                    y: 192.0,
+                },
+                start_time: (i * 150) as Float,
+                kind: HitObjectKind::Circle,
+                sound: 0,
+            })
+            .collect();
+
+        let map = Beatmap {
+            mode: GameMode::MNA,
+            cs: 4.0,
+            hit_objects,
+            ..Default::default()
+        };
+
+        let mut store = LruStore::new(8);
+
+        let calculated = ManiaPP::new(&map)
+            .mods(64)
+            .attributes_from_store(&mut store, "map")
+            .calculate();
+
+        let key = AttributeKey::new("map", GameMode::MNA, 64, 1.5, AlgorithmVersion::V2021);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&key).map(|a| a.stars()), Some(calculated.stars()));
+
+        // Filled from the store instead of being recalculated
+        store.insert(key, StarResult::Mania(Default::default()));
+
+        let stored = ManiaPP::new(&map)
+            .mods(64)
+            .attributes_from_store(&mut store, "map")
+            .calculate();
+
+        assert_eq!(stored.stars(), 0.0);
+
+        // Partial plays are not stored
+        ManiaPP::new(&map)
+            .passed_objects(10)
+            .attributes_from_store(&mut store, "map");
+
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "osu", feature = "taiko"))]
+    fn file_store_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "peace-performance-store-{}.txt",
+            std::process::id()
+        ));
+
+        let osu_key = AttributeKey::new("abc", GameMode::STD, 24, 1.5, AlgorithmVersion::V2022);
+        let taiko_key = AttributeKey::new("abc", GameMode::TKO, 64, 1.5, AlgorithmVersion::V2022);
+
+        let osu = crate::osu::DifficultyAttributes {
+            stars: 6.543_21,
+            aim_strain: 3.1,
+            max_combo: 1234,
+            ..Default::default()
+        };
+
+        {
+            let mut store = FileStore::open(&path).unwrap();
+            store.insert(osu_key.clone(), StarResult::Osu(osu.clone()));
+            store.insert(taiko_key.clone(), taiko(4.2));
+            store.flush().unwrap();
+        }
+
+        let mut store = FileStore::open(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(osu_key.version, AlgorithmVersion::V2022);
+        assert_eq!(taiko_key.version, AlgorithmVersion::V2021);
+
+        match store.get(&osu_key) {
+            Some(StarResult::Osu(attributes)) => {
+                assert_eq!(attributes.stars, osu.stars);
+                assert_eq!(attributes.aim_strain, osu.aim_strain);
+                assert_eq!(attributes.max_combo, osu.max_combo);
+            }
+            _ => panic!("missing osu attributes"),
+        }
+
+        assert_eq!(store.get(&taiko_key).map(|a| a.stars()), Some(4.2));
+    }
+
+    #[test]
+    #[cfg(feature = "taiko")]
+    fn file_store_skips_other_builds() {
+        let path = std::env::temp_dir().join(format!(
+            "peace-performance-builds-{}.txt",
+            std::process::id()
+        ));
+
+        let other_precision = if cfg!(feature = "f64") { "f32" } else { "f64" };
+
+        let content = format!(
+            "current\t1\t0\t1\tV2021\t{}\t4.2\n\
+            other\t1\t0\t1\tV2021\t{}\t4.2\n\
+            no_build\t1\t0\t1\tV2021\t4.2\n",
+            AttributeKey::build(GameMode::TKO),
+            other_precision,
+        );
+
+        std::fs::write(&path, content).unwrap();
+        let mut store = FileStore::open(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let key = |checksum: &str| {
+            AttributeKey::new(checksum, GameMode::TKO, 0, 1.0, AlgorithmVersion::V2021)
+        };
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&key("current")).map(|a| a.stars()), Some(4.2));
+        assert!(store.get(&key("other")).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "osu")]
+    fn build_distinguishes_osu_variant() {
+        let key = |mode| AttributeKey::new("abc", mode, 0, 1.0, AlgorithmVersion::V2021);
+
+        #[cfg(feature = "all_included")]
+        let variant = "all_included";
+        #[cfg(feature = "no_leniency")]
+        let variant = "no_leniency";
+        #[cfg(feature = "no_sliders_no_leniency")]
+        let variant = "no_sliders_no_leniency";
+
+        let precision = if cfg!(feature = "f64") { "f64" } else { "f32" };
+
+        assert_eq!(
+            key(GameMode::STD).build,
+            format!("{}-{}", variant, precision)
+        );
+        assert_eq!(key(GameMode::TKO).build, precision);
+
+        let other_variant = AttributeKey {
+            build: "other-f32",
+            ..key(GameMode::STD)
+        };
+
+        assert_ne!(key(GameMode::STD), other_variant);
+    }
+}
@@ -0,0 +1,186 @@
+// Conversions are computed in `f64` like in the game
+#![cfg_attr(feature = "f64", allow(clippy::unnecessary_cast))]
+
+use crate::{
+    parse::{HitObject, HitObjectKind},
+    Beatmap, Float, GameMode,
+};
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+const LEGACY_VELOCITY_MULTIPLIER: f64 = 1.4;
+const OSU_BASE_SCORING_DISTANCE: f64 = 100.0;
+
+impl Beatmap {
+    /// Convert an osu!standard map into an osu!taiko map the way the game does it.
+    ///
+    /// Sliders become drumrolls or, if they are short enough, a stream of hits
+    /// which use the hitsounds of the slider's nodes. Spinners become swells.
+    /// Maps of any other mode are returned as is.
+    pub fn to_taiko(&self) -> Cow<'_, Self> {
+        if self.mode != GameMode::STD {
+            return Cow::Borrowed(self);
+        }
+
+        let mut hit_objects = Vec::with_capacity(self.hit_objects.len());
+
+        for h in self.hit_objects.iter() {
+            match &h.kind {
+                HitObjectKind::Slider {
+                    pixel_len,
+                    repeats,
+                    edge_sounds,
+                    ..
+                } => match self.split_slider(h.start_time, *pixel_len, *repeats) {
+                    Some((duration, tick_spacing)) => {
+                        let end = h.start_time as f64 + duration + tick_spacing / 8.0;
+                        let mut time = h.start_time as f64;
+
+                        // Hitsounds cycle through the head, repeats, and tail
+                        let n_nodes = (*repeats).max(1) + 1;
+                        let mut node = 0;
+
+                        while time <= end {
+                            hit_objects.push(HitObject {
+                                pos: h.pos,
+                                start_time: time as Float,
+                                kind: HitObjectKind::Circle,
+                                sound: edge_sounds.get(node).copied().unwrap_or(h.sound),
+                            });
+
+                            time += tick_spacing;
+                            node = (node + 1) % n_nodes;
+                        }
+                    }
+                    None => hit_objects.push(h.clone()),
+                },
+                _ => hit_objects.push(h.clone()),
+            }
+        }
+
+        // Split hits may overlap with the following objects
+        hit_objects.sort_by(|a, b| {
+            a.start_time
+                .partial_cmp(&b.start_time)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        // Only the first of multiple objects at the same time remains
+        hit_objects.dedup_by(|curr, prev| curr.start_time == prev.start_time);
+
+        let mut map = Self {
+            mode: GameMode::TKO,
+            version: self.version,
+            n_circles: 0,
+            n_sliders: 0,
+            n_spinners: 0,
+            ar: self.ar,
+            od: self.od,
+            cs: self.cs,
+            hp: self.hp,
+            sv: self.sv,
+            tick_rate: self.tick_rate,
+            hit_objects,
+            timing_points: self.timing_points.clone(),
+            difficulty_points: self.difficulty_points.clone(),
+            effect_points: self.effect_points.clone(),
+            breaks: self.breaks.clone(),
+            #[cfg(all(feature = "osu", feature = "all_included"))]
+            stack_leniency: self.stack_leniency,
+        };
+
+        for h in map.hit_objects.iter() {
+            match h.kind {
+                HitObjectKind::Circle => map.n_circles += 1,
+                HitObjectKind::Spinner { .. } => map.n_spinners += 1,
+                _ => map.n_sliders += 1,
+            }
+        }
+
+        Cow::Owned(map)
+    }
+
+    /// Returns the duration and the tick spacing if the slider is to be
+    /// split into hits instead of becoming a drumroll.
+    fn split_slider(
+        &self,
+        start_time: Float,
+        pixel_len: Float,
+        repeats: usize,
+    ) -> Option<(f64, f64)> {
+        let (beat_len, speed_mult) = self.control_points_at(start_time);
+
+        let spans = repeats.max(1) as f64;
+        let distance = pixel_len as f64 * spans * LEGACY_VELOCITY_MULTIPLIER;
+
+        let taiko_velocity =
+            OSU_BASE_SCORING_DISTANCE * self.sv as f64 * LEGACY_VELOCITY_MULTIPLIER;
+        let mut beat_len = beat_len as f64 / speed_mult as f64;
+
+        let duration = (distance / taiko_velocity * beat_len).trunc();
+        let osu_velocity = taiko_velocity * 1000.0 / beat_len;
+
+        // Only maps before v8 use the speed-adjusted beat length for the conversion
+        if self.version >= 8 {
+            beat_len *= speed_mult as f64;
+        }
+
+        let tick_spacing = (beat_len / self.tick_rate as f64).min(duration / spans);
+
+        let split = tick_spacing > 0.0 && distance / osu_velocity * 1000.0 < 2.0 * beat_len;
+
+        split.then_some((duration, tick_spacing))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{Pos2, TimingPoint};
+
+    #[test]
+    fn taiko_convert_short_slider_into_hits() {
+        let slider = |start_time: Float, pixel_len: Float| HitObject {
+            pos: Pos2::default(),
+            start_time,
+            kind: HitObjectKind::Slider {
+                end_time: start_time,
+                pixel_len,
+                repeats: 1,
+                edge_sounds: Vec::new(),
+                #[cfg(any(
+                    feature = "fruits",
+                    all(feature = "osu", not(feature = "no_sliders_no_leniency"))
+                ))]
+                curve_points: Vec::new(),
+                #[cfg(any(
+                    feature = "fruits",
+                    all(feature = "osu", not(feature = "no_sliders_no_leniency"))
+                ))]
+                path_type: crate::parse::PathType::Bezier,
+            },
+            sound: 0,
+        };
+
+        let map = Beatmap {
+            mode: GameMode::STD,
+            version: 14,
+            sv: 1.0,
+            tick_rate: 1.0,
+            hit_objects: vec![slider(0.0, 70.0), slider(2000.0, 1000.0)],
+            timing_points: vec![TimingPoint {
+                time: 0.0,
+                beat_len: 500.0,
+            }],
+            ..Default::default()
+        };
+
+        let converted = map.to_taiko();
+
+        assert_eq!(converted.mode, GameMode::TKO);
+        assert_eq!(converted.n_circles, 2);
+        assert_eq!(converted.n_sliders, 1);
+        assert_eq!(converted.hit_objects[1].start_time, 350.0);
+    }
+}
@@ -1,14 +1,14 @@
 use super::{closest_rhythm, HitObjectRhythm};
-use crate::parse::HitObject;
+use crate::{parse::HitObject, Float};
 
 #[derive(Clone, Debug)]
 pub(crate) struct DifficultyObject<'o> {
     pub(crate) idx: usize,
     pub(crate) base: &'o HitObject,
     pub(crate) prev: &'o HitObject,
-    pub(crate) delta: f32,
+    pub(crate) delta: Float,
     pub(crate) rhythm: &'static HitObjectRhythm,
-    pub(crate) start_time: f32,
+    pub(crate) start_time: Float,
 }
 
 impl<'o> DifficultyObject<'o> {
@@ -18,7 +18,7 @@ impl<'o> DifficultyObject<'o> {
         base: &'o HitObject,
         prev: &'o HitObject,
         prev_prev: &HitObject,
-        clock_rate: f32,
+        clock_rate: Float,
     ) -> Self {
         let delta = (base.start_time - prev.start_time) / clock_rate;
         let rhythm = closest_rhythm(delta, prev, prev_prev, clock_rate);
@@ -1,7 +1,5 @@
 use std::cmp::Ordering;
-use std::iter::{Cycle, Skip, Take};
 use std::ops::Index;
-use std::slice::Iter;
 
 pub(crate) struct LimitedQueue<T> {
     queue: Vec<T>,
@@ -48,23 +46,6 @@ impl<T> LimitedQueue<T> {
         self.end = self.queue.capacity() - 1;
         self.queue.clear();
     }
-
-    #[inline]
-    pub(crate) fn full(&self) -> bool {
-        self.queue.len() == self.queue.capacity()
-    }
-
-    #[inline]
-    pub(crate) fn iter(&self) -> LimitedQueueIter<T> {
-        let iter = self
-            .queue
-            .iter()
-            .cycle()
-            .skip(self.start)
-            .take(self.queue.len());
-
-        LimitedQueueIter { iter }
-    }
 }
 
 impl<T: PartialOrd> LimitedQueue<T> {
@@ -88,23 +69,3 @@ impl<T> Index<usize> for LimitedQueue<T> {
         &self.queue[(self.start + idx) % self.queue.capacity()]
     }
 }
-
-pub(crate) struct LimitedQueueIter<'a, T> {
-    iter: Take<Skip<Cycle<Iter<'a, T>>>>,
-}
-
-impl<'a, T> Iterator for LimitedQueueIter<'a, T> {
-    type Item = &'a T;
-
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next()
-    }
-
-    #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.iter.size_hint()
-    }
-}
-
-impl<'a, T> ExactSizeIterator for LimitedQueueIter<'a, T> {}
@@ -1,10 +1,12 @@
 #![cfg(feature = "taiko")]
 
+mod convert;
 mod difficulty_object;
 mod hitobject_rhythm;
 mod limited_queue;
 mod pp;
 mod rim;
+mod score;
 mod skill;
 mod skill_kind;
 mod stamina_cheese;
@@ -14,42 +16,76 @@ use hitobject_rhythm::{closest_rhythm, HitObjectRhythm};
 use limited_queue::LimitedQueue;
 pub use pp::*;
 use rim::Rim;
+pub use score::*;
 use skill::Skill;
 use skill_kind::SkillKind;
 use stamina_cheese::StaminaCheeseDetector;
 
-use crate::{Beatmap, Mods, StarResult, Strains};
+use crate::{Beatmap, CalcContext, DifficultyParams, Float, GameMode, Mods, StarResult, Strains};
 
+use crate::float::consts::PI;
 use std::cmp::Ordering;
-use std::f32::consts::PI;
 
-const SECTION_LEN: f32 = 400.0;
+const SECTION_LEN: Float = 400.0;
 
-const COLOR_SKILL_MULTIPLIER: f32 = 0.01;
-const RHYTHM_SKILL_MULTIPLIER: f32 = 0.014;
-const STAMINA_SKILL_MULTIPLIER: f32 = 0.02;
+const COLOR_SKILL_MULTIPLIER: Float = 0.01;
+const RHYTHM_SKILL_MULTIPLIER: Float = 0.014;
+const STAMINA_SKILL_MULTIPLIER: Float = 0.02;
 
 /// Star calculation for osu!taiko maps.
 ///
 /// In case of a partial play, e.g. a fail, one can specify the amount of passed objects.
+///
+/// osu!standard maps are converted first, see [`Beatmap::to_taiko`].
+#[inline]
 pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> StarResult {
-    let take = passed_objects.unwrap_or_else(|| map.hit_objects.len());
+    stars_with_params(map, mods, passed_objects, DifficultyParams::default())
+}
+
+/// Same as the `stars` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn stars_with_params(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+) -> StarResult {
+    stars_with_context(map, mods, passed_objects, params, &mut CalcContext::new())
+}
+
+/// Same as the `stars_with_params` function but reuses the buffers of the given context.
+///
+/// osu!standard maps are converted on each call so for repeated calculations
+/// convert them once with [`Beatmap::to_taiko`] and pass the converted map.
+pub fn stars_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    passed_objects: Option<usize>,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> StarResult {
+    let mods = mods.for_mode(GameMode::TKO);
+    let map = map.to_taiko();
+    let take = passed_objects
+        .unwrap_or_else(|| map.hit_objects.len())
+        .min(map.hit_objects.len());
 
     if take < 2 {
         return StarResult::Taiko(DifficultyAttributes { stars: 0.0 });
     }
 
-    // True if the object at that index is stamina cheese
-    let cheese = map.find_cheese();
+    let Buffers {
+        skills,
+        cheese,
+        buf,
+    } = &mut ctx.taiko;
 
-    let mut skills = vec![
-        Skill::new(SkillKind::color()),
-        Skill::new(SkillKind::rhythm()),
-        Skill::new(SkillKind::stamina(true)),
-        Skill::new(SkillKind::stamina(false)),
-    ];
+    // True if the object at that index is stamina cheese
+    map.find_cheese(cheese);
+    reset_skills(skills);
 
-    let clock_rate = mods.speed();
+    let clock_rate = params.clock_rate_with(mods);
     let section_len = SECTION_LEN * clock_rate;
 
     // No strain for first object
@@ -69,14 +105,17 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
         });
 
     // Handle second object separately to remove later if-branching
-    let h = hit_objects.next().unwrap();
+    let h = match hit_objects.next() {
+        Some(h) => h,
+        None => return StarResult::Taiko(DifficultyAttributes { stars: 0.0 }),
+    };
 
     while h.base.start_time > current_section_end {
         current_section_end += section_len;
     }
 
     for skill in skills.iter_mut() {
-        skill.process(&h, &cheese);
+        skill.process(&h, cheese);
     }
 
     // Handle all other objects
@@ -91,7 +130,7 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
         }
 
         for skill in skills.iter_mut() {
-            skill.process(&h, &cheese);
+            skill.process(&h, cheese);
         }
     }
 
@@ -99,19 +138,19 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
         skill.save_current_peak();
     }
 
-    let mut buf = vec![0.0; skills[0].strain_peaks.len()];
+    buf.clear();
+    buf.resize(skills[0].strain_peaks.len(), 0.0);
 
-    let color_rating = skills[0].difficulty_value(&mut buf) * COLOR_SKILL_MULTIPLIER;
-    let rhythm_rating = skills[1].difficulty_value(&mut buf) * RHYTHM_SKILL_MULTIPLIER;
+    let color_rating = skills[0].difficulty_value(buf) * COLOR_SKILL_MULTIPLIER;
+    let rhythm_rating = skills[1].difficulty_value(buf) * RHYTHM_SKILL_MULTIPLIER;
 
-    let mut stamina_rating = (skills[2].difficulty_value(&mut buf)
-        + skills[3].difficulty_value(&mut buf))
+    let mut stamina_rating = (skills[2].difficulty_value(buf) + skills[3].difficulty_value(buf))
         * STAMINA_SKILL_MULTIPLIER;
 
     let stamina_penalty = simple_color_penalty(stamina_rating, color_rating);
     stamina_rating *= stamina_penalty;
 
-    let combined_rating = locally_combined_difficulty(&skills, stamina_penalty);
+    let combined_rating = locally_combined_difficulty(skills, stamina_penalty, buf);
     let separate_rating = norm(1.5, color_rating, rhythm_rating, stamina_rating);
 
     let stars = rescale(1.4 * separate_rating + 0.5 * combined_rating);
@@ -123,22 +162,42 @@ pub fn stars(map: &Beatmap, mods: impl Mods, passed_objects: Option<usize>) -> S
 /// evaluating the final strains, it just returns them as is.
 ///
 /// Suitable to plot the difficulty of a map over time.
+#[inline]
 pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
+    strains_with_params(map, mods, DifficultyParams::default())
+}
+
+/// Same as the `strains` function but with further difficulty parameters
+/// such as a custom clock rate.
+#[inline]
+pub fn strains_with_params(map: &Beatmap, mods: impl Mods, params: DifficultyParams) -> Strains {
+    strains_with_context(map, mods, params, &mut CalcContext::new())
+}
+
+/// Same as the `strains_with_params` function but reuses the buffers of the given context.
+///
+/// osu!standard maps are converted on each call so for repeated calculations
+/// convert them once with [`Beatmap::to_taiko`] and pass the converted map.
+pub fn strains_with_context(
+    map: &Beatmap,
+    mods: impl Mods,
+    params: DifficultyParams,
+    ctx: &mut CalcContext,
+) -> Strains {
+    let mods = mods.for_mode(GameMode::TKO);
+    let map = map.to_taiko();
+
     if map.hit_objects.len() < 2 {
         return Strains::default();
     }
 
-    // True if the object at that index is stamina cheese
-    let cheese = map.find_cheese();
+    let Buffers { skills, cheese, .. } = &mut ctx.taiko;
 
-    let mut skills = vec![
-        Skill::new(SkillKind::color()),
-        Skill::new(SkillKind::rhythm()),
-        Skill::new(SkillKind::stamina(true)),
-        Skill::new(SkillKind::stamina(false)),
-    ];
+    // True if the object at that index is stamina cheese
+    map.find_cheese(cheese);
+    reset_skills(skills);
 
-    let clock_rate = mods.speed();
+    let clock_rate = params.clock_rate_with(mods);
     let section_len = SECTION_LEN * clock_rate;
 
     // No strain for first object
@@ -157,14 +216,17 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
         });
 
     // Handle second object separately to remove later if-branching
-    let h = hit_objects.next().unwrap();
+    let h = match hit_objects.next() {
+        Some(h) => h,
+        None => return Strains::default(),
+    };
 
     while h.base.start_time > current_section_end {
         current_section_end += section_len;
     }
 
     for skill in skills.iter_mut() {
-        skill.process(&h, &cheese);
+        skill.process(&h, cheese);
     }
 
     // Handle all other objects
@@ -179,7 +241,7 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
         }
 
         for skill in skills.iter_mut() {
-            skill.process(&h, &cheese);
+            skill.process(&h, cheese);
         }
     }
 
@@ -205,7 +267,7 @@ pub fn strains(map: &Beatmap, mods: impl Mods) -> Strains {
 }
 
 #[inline]
-fn rescale(stars: f32) -> f32 {
+fn rescale(stars: Float) -> Float {
     if stars < 0.0 {
         stars
     } else {
@@ -214,7 +276,7 @@ fn rescale(stars: f32) -> f32 {
 }
 
 #[inline]
-fn simple_color_penalty(stamina: f32, color: f32) -> f32 {
+fn simple_color_penalty(stamina: Float, color: Float) -> Float {
     if color <= 0.0 {
         0.79 - 0.25
     } else {
@@ -222,9 +284,12 @@ fn simple_color_penalty(stamina: f32, color: f32) -> f32 {
     }
 }
 
-fn locally_combined_difficulty(skills: &[Skill], stamina_penalty: f32) -> f32 {
-    let mut peaks = Vec::with_capacity(skills[0].strain_peaks.len());
-
+/// `peaks` must have the same length as the strain peaks of the skills.
+fn locally_combined_difficulty(
+    skills: &[Skill],
+    stamina_penalty: Float,
+    peaks: &mut [Float],
+) -> Float {
     let iter = skills[0]
         .strain_peaks
         .iter()
@@ -240,13 +305,16 @@ fn locally_combined_difficulty(skills: &[Skill], stamina_penalty: f32) -> f32 {
             )
         });
 
-    peaks.extend(iter);
+    for (peak, strain) in peaks.iter_mut().zip(iter) {
+        *peak = strain;
+    }
+
     peaks.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
 
     let mut difficulty = 0.0;
     let mut weight = 1.0;
 
-    for strain in peaks {
+    for &strain in peaks.iter() {
         difficulty += strain * weight;
         weight *= 0.9;
     }
@@ -255,7 +323,7 @@ fn locally_combined_difficulty(skills: &[Skill], stamina_penalty: f32) -> f32 {
 }
 
 #[inline]
-fn norm(p: f32, a: f32, b: f32, c: f32) -> f32 {
+fn norm(p: Float, a: Float, b: Float, c: Float) -> Float {
     (a.powf(p) + b.powf(p) + c.powf(p)).powf(p.recip())
 }
 
@@ -263,5 +331,27 @@ fn norm(p: f32, a: f32, b: f32, c: f32) -> f32 {
 /// This data is necessary to calculate PP.
 #[derive(Clone, Debug, Default)]
 pub struct DifficultyAttributes {
-    pub stars: f32,
+    pub stars: Float,
+}
+
+/// Buffers of osu!taiko that are kept by a [`CalcContext`].
+#[derive(Default)]
+pub(crate) struct Buffers {
+    skills: Vec<Skill>,
+    cheese: Vec<bool>,
+    buf: Vec<Float>,
+}
+
+/// Reset the skills of a previous calculation or create them if there are none.
+fn reset_skills(skills: &mut Vec<Skill>) {
+    if skills.is_empty() {
+        skills.push(Skill::new(SkillKind::color()));
+        skills.push(Skill::new(SkillKind::rhythm()));
+        skills.push(Skill::new(SkillKind::stamina(true)));
+        skills.push(Skill::new(SkillKind::stamina(false)));
+    } else {
+        for skill in skills.iter_mut() {
+            skill.reset();
+        }
+    }
 }
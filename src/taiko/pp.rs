@@ -1,5 +1,10 @@
-use super::{stars, DifficultyAttributes};
-use crate::{Beatmap, Mods, PpRaw, PpResult, StarResult};
+use super::{stars_with_params, DifficultyAttributes};
+use crate::{
+    AlgorithmVersion, AttributeKey, AttributeStore, Beatmap, CalcResult, DifficultyParams, Float,
+    GameMode, Grade, HitResults, IfFcResult, Mods, PpRaw, PpResult, StarResult,
+};
+
+use std::borrow::Cow;
 
 /// Calculator for pp on osu!taiko maps.
 ///
@@ -31,37 +36,45 @@ use crate::{Beatmap, Mods, PpRaw, PpResult, StarResult};
 #[derive(Clone, Debug)]
 #[allow(clippy::upper_case_acronyms)]
 pub struct TaikoPP<'m> {
-    map: &'m Beatmap,
-    stars: Option<f32>,
+    map: Cow<'m, Beatmap>,
+    stars: Option<Float>,
     mods: u32,
+    params: DifficultyParams,
     max_combo: usize,
     combo: Option<usize>,
-    acc: f32,
+    acc: Float,
     n_misses: usize,
     passed_objects: Option<usize>,
+    version: AlgorithmVersion,
 
     n300: Option<usize>,
     n100: Option<usize>,
 }
 
 impl<'m> TaikoPP<'m> {
+    /// osu!standard maps are converted first, see [`Beatmap::to_taiko`].
     #[inline]
     pub fn new(map: &'m Beatmap) -> Self {
+        let map = map.to_taiko();
+        let max_combo = map.n_circles as usize;
+
         Self {
             map,
             stars: None,
             mods: 0,
-            max_combo: map.n_circles as usize,
+            params: DifficultyParams::default(),
+            max_combo,
             combo: None,
             acc: 1.0,
             n_misses: 0,
             passed_objects: None,
+            version: AlgorithmVersion::default(),
             n300: None,
             n100: None,
         }
     }
 
-    /// [`TaikoAttributeProvider`] is implemented by `f32`, [`StarResult`](crate::StarResult),
+    /// [`TaikoAttributeProvider`] is implemented by `Float`, [`StarResult`](crate::StarResult),
     /// and by [`PpResult`](crate::PpResult) meaning you can give the star rating,
     /// the result of a star calculation, or the result of a pp calculation.
     /// If you already calculated the stars for the current map-mod combination,
@@ -75,12 +88,87 @@ impl<'m> TaikoPP<'m> {
         self
     }
 
+    /// Fill the attributes from the given store or calculate and insert them if it has none.
+    ///
+    /// The checksum identifies the map, e.g. the MD5 hash of its .osu file.
+    /// Be sure to set the mods, clock rate, and version beforehand!
+    /// Attributes of partial plays or custom AR, OD, CS, or HP are not stored.
+    pub fn attributes_from_store(self, mut store: impl AttributeStore, checksum: &str) -> Self {
+        let key = AttributeKey::for_calculation(
+            checksum,
+            GameMode::TKO,
+            self.mods,
+            self.params,
+            self.passed_objects,
+            self.version,
+        );
+
+        match key {
+            Some(key) => {
+                let (map, mods, params) = (&self.map, self.mods, self.params);
+                let attributes =
+                    store.get_or_calculate(key, || stars_with_params(map, mods, None, params));
+
+                self.attributes(attributes)
+            }
+            None => self,
+        }
+    }
+
     /// Specify mods through their bit values.
     ///
     /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    ///
+    /// Mods without effect in this mode are ignored.
     #[inline]
     pub fn mods(mut self, mods: u32) -> Self {
-        self.mods = mods;
+        self.mods = mods.for_mode(GameMode::TKO);
+
+        self
+    }
+
+    /// Specify a custom clock rate that overrides the one of the mods, e.g. `1.2` for a 1.2x speed-up.
+    ///
+    /// Invalid rates are ignored or clamped, see [`DifficultyParams::clock_rate`](crate::DifficultyParams::clock_rate).
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: Float) -> Self {
+        self.params = self.params.clock_rate(clock_rate);
+
+        self
+    }
+
+    /// Specify an approach rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn ar(mut self, ar: Float) -> Self {
+        self.params = self.params.ar(ar);
+
+        self
+    }
+
+    /// Specify an overall difficulty that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn od(mut self, od: Float) -> Self {
+        self.params = self.params.od(od);
+
+        self
+    }
+
+    /// Specify a circle size that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn cs(mut self, cs: Float) -> Self {
+        self.params = self.params.cs(cs);
+
+        self
+    }
+
+    /// Specify a drain rate that overrides the map's value.
+    /// It will not be scaled by HR or EZ.
+    #[inline]
+    pub fn hp(mut self, hp: Float) -> Self {
+        self.params = self.params.hp(hp);
 
         self
     }
@@ -119,17 +207,18 @@ impl<'m> TaikoPP<'m> {
 
     /// Set the accuracy between 0.0 and 100.0.
     #[inline]
-    pub fn accuracy(mut self, acc: f32) -> Self {
+    pub fn accuracy(mut self, acc: Float) -> Self {
         self.set_accuracy(acc);
         self
     }
 
     #[inline]
     /// Set acc value
-    /// 
-    /// If it is used to calculate the PP of multiple different ACCs, 
+    ///
+    /// If it is used to calculate the PP of multiple different ACCs,
     /// it should be called from high to low according to the ACC value, otherwise it is invalid.
-    /// 
+    /// Use [`TaikoPP::calculate_accuracies`] to calculate multiple accuracies in any order.
+    ///
     /// Examples:
     /// ```
     /// // valid
@@ -149,7 +238,7 @@ impl<'m> TaikoPP<'m> {
     ///     c.set_accuracy(95.0);
     ///     c.calculate().await
     /// };
-    /// 
+    ///
     /// // invalid
     /// let acc_95 = {
     ///     c.set_accuracy(95.0);
@@ -168,8 +257,8 @@ impl<'m> TaikoPP<'m> {
     ///     c.calculate().await
     /// };
     /// ```
-    /// 
-    pub fn set_accuracy(&mut self, acc: f32) {
+    ///
+    pub fn set_accuracy(&mut self, acc: Float) {
         self.acc = acc / 100.0;
         self.n300.take();
         self.n100.take();
@@ -183,11 +272,22 @@ impl<'m> TaikoPP<'m> {
         self
     }
 
+    /// Specify the version of the difficulty and pp algorithm.
+    ///
+    /// Versions without an implementation for this mode fall back
+    /// to the newest older one, see [`AlgorithmVersion::resolve`].
+    #[inline]
+    pub fn version(mut self, version: AlgorithmVersion) -> Self {
+        self.version = version;
+
+        self
+    }
+
     /// Returns an object which contains the pp and stars.
     pub fn calculate(&mut self) -> PpResult {
-        let stars = self
-            .stars
-            .unwrap_or_else(|| stars(self.map, self.mods, self.passed_objects).stars());
+        let stars = self.stars.unwrap_or_else(|| {
+            stars_with_params(&self.map, self.mods, self.passed_objects, self.params).stars()
+        });
 
         if self.n300.or(self.n100).is_some() {
             let total = self.map.n_circles as usize;
@@ -206,7 +306,7 @@ impl<'m> TaikoPP<'m> {
                 (None, None) => unreachable!(),
             };
 
-            self.acc = (2 * n300 + n100) as f32 / (2 * (n300 + n100 + misses)) as f32;
+            self.acc = (2 * n300 + n100) as Float / (2 * (n300 + n100 + misses)) as Float;
         }
 
         let mut multiplier = 1.1;
@@ -228,26 +328,118 @@ impl<'m> TaikoPP<'m> {
             mode: 1,
             mods: self.mods,
             pp,
-            raw: PpRaw::new(None, None, Some(strain_value), Some(acc_value), pp),
+            raw: PpRaw::new(None, None, Some(strain_value), Some(acc_value), None, pp),
             attributes: StarResult::Taiko(DifficultyAttributes { stars }),
+            version: self.version.resolve(GameMode::TKO),
+            grade: self.grade(),
         }
     }
 
+    /// Same as `calculate` but returns an error if the map has no hit objects
+    /// or can not be converted to osu!taiko, see [`Beatmap::check_calculable`],
+    /// or if the difficulty parameters are invalid, see [`DifficultyParams::validate`](crate::DifficultyParams::validate).
+    #[inline]
+    pub fn try_calculate(&mut self) -> CalcResult<PpResult> {
+        self.map.check_calculable(GameMode::TKO)?;
+        self.params.validate()?;
+
+        Ok(self.calculate())
+    }
+
     #[inline]
     pub async fn calculate_async(&mut self) -> PpResult {
         self.calculate()
     }
 
-    fn compute_strain_value(&self, stars: f32) -> f32 {
+    /// Calculate the pp for each of the given accuracies between `0` and `100`.
+    ///
+    /// The difficulty attributes are calculated only once and the hit results
+    /// are generated from the previously given input for every accuracy
+    /// so, unlike [`set_accuracy`](TaikoPP::set_accuracy), the order does not matter.
+    pub fn calculate_accuracies(&mut self, accs: &[Float]) -> Vec<PpResult> {
+        accs.iter()
+            .map(|&acc| self.calculate_accuracy(acc))
+            .collect()
+    }
+
+    /// The minimum accuracy between `0` and `100` that is required to reach the given pp
+    /// w.r.t. all previously specified values such as mods, combo, or misses.
+    ///
+    /// Returns `None` if the pp can not be reached, not even with 100% accuracy.
+    /// The difficulty attributes are calculated only once.
+    pub fn accuracy_for_pp(&mut self, pp: Float) -> Option<Float> {
+        crate::solver::min_accuracy_for_pp(pp, |acc| self.calculate_accuracy(acc).pp)
+    }
+
+    /// Calculate the pp of the play and of the same play with a full combo.
+    ///
+    /// For the full combo, misses become 300s and the combo is the map's max combo.
+    /// The stars are calculated only once.
+    pub fn calculate_if_fc(&mut self) -> IfFcResult {
+        let current = self.calculate();
+        self.stars.get_or_insert(current.stars());
+
+        // Calculate on a copy so that none of the hit results are overwritten
+        let mut fc = self.clone();
+        let (n300, n100) = fc.n300_n100();
+
+        fc.n300 = Some(n300.saturating_add(fc.n_misses));
+        fc.n100 = Some(n100);
+        fc.combo = Some(fc.max_combo);
+        fc.n_misses = 0;
+
+        let if_fc = fc.calculate();
+
+        IfFcResult { current, if_fc }
+    }
+
+    /// The 300s and 100s w.r.t. the accuracy which is always set after calculating.
+    fn n300_n100(&self) -> (usize, usize) {
+        let total = self.map.n_circles as usize;
+        let n_hits = total.saturating_sub(self.n_misses);
+        let points = (2.0 * self.acc * total as Float).round() as usize;
+        let n300 = points.saturating_sub(n_hits).min(n_hits);
+
+        (n300, n_hits - n300)
+    }
+
+    fn grade(&self) -> Grade {
+        let (n300, n100) = self.n300_n100();
+
+        let hits = HitResults {
+            n300,
+            n100,
+            n_misses: self.n_misses,
+            ..Default::default()
+        };
+
+        Grade::new(GameMode::TKO, self.mods, &hits)
+    }
+
+    fn calculate_accuracy(&mut self, acc: Float) -> PpResult {
+        let input = (self.n300, self.n100, self.acc);
+
+        self.set_accuracy(acc);
+        let result = self.calculate();
+        self.stars.get_or_insert(result.stars());
+
+        self.n300 = input.0;
+        self.n100 = input.1;
+        self.acc = input.2;
+
+        result
+    }
+
+    fn compute_strain_value(&self, stars: Float) -> Float {
         let exp_base = 5.0 * (stars / 0.0075).max(1.0) - 4.0;
         let mut strain = exp_base * exp_base / 100_000.0;
 
         // Longer maps are worth more
-        let len_bonus = 1.0 + 0.1 * (self.max_combo as f32 / 1500.0).min(1.0);
+        let len_bonus = 1.0 + 0.1 * (self.max_combo as Float / 1500.0).min(1.0);
         strain *= len_bonus;
 
         // Penalize misses exponentially
-        strain *= 0.985_f32.powi(self.n_misses as i32);
+        strain *= (0.985 as Float).powi(self.n_misses as i32);
 
         // HD bonus
         if self.mods.hd() {
@@ -264,54 +456,56 @@ impl<'m> TaikoPP<'m> {
     }
 
     #[inline]
-    fn compute_accuracy_value(&self) -> f32 {
-        let mut od = self.map.od;
-
-        if self.mods.hr() {
-            od *= 1.4;
-        } else if self.mods.ez() {
-            od *= 0.5;
-        }
-
-        let hit_window = difficulty_range_od(od).floor() / self.mods.speed();
+    fn compute_accuracy_value(&self) -> Float {
+        let od = self.params.od.unwrap_or_else(|| {
+            if self.mods.hr() {
+                self.map.od * 1.4
+            } else if self.mods.ez() {
+                self.map.od * 0.5
+            } else {
+                self.map.od
+            }
+        });
+
+        let hit_window = difficulty_range_od(od).floor() / self.params.clock_rate_with(self.mods);
 
         (150.0 / hit_window).powf(1.1)
             * self.acc.powi(15)
             * 22.0
-            * (self.max_combo as f32 / 1500.0).powf(0.3).min(1.15)
+            * (self.max_combo as Float / 1500.0).powf(0.3).min(1.15)
     }
 }
 
-const HITWINDOW_MIN: f32 = 50.0;
-const HITWINDOW_AVG: f32 = 35.0;
-const HITWINDOW_MAX: f32 = 20.0;
+const HITWINDOW_MIN: Float = 50.0;
+const HITWINDOW_AVG: Float = 35.0;
+const HITWINDOW_MAX: Float = 20.0;
 
 #[inline]
-fn difficulty_range_od(od: f32) -> f32 {
+fn difficulty_range_od(od: Float) -> Float {
     crate::difficulty_range(od, HITWINDOW_MAX, HITWINDOW_AVG, HITWINDOW_MIN)
 }
 
 pub trait TaikoAttributeProvider {
-    fn attributes(self) -> Option<f32>;
+    fn attributes(self) -> Option<Float>;
 }
 
-impl TaikoAttributeProvider for f32 {
+impl TaikoAttributeProvider for Float {
     #[inline]
-    fn attributes(self) -> Option<f32> {
+    fn attributes(self) -> Option<Float> {
         Some(self)
     }
 }
 
 impl TaikoAttributeProvider for DifficultyAttributes {
     #[inline]
-    fn attributes(self) -> Option<f32> {
+    fn attributes(self) -> Option<Float> {
         Some(self.stars)
     }
 }
 
 impl TaikoAttributeProvider for StarResult {
     #[inline]
-    fn attributes(self) -> Option<f32> {
+    fn attributes(self) -> Option<Float> {
         #[allow(irrefutable_let_patterns)]
         if let StarResult::Taiko(attributes) = self {
             Some(attributes.stars)
@@ -323,7 +517,7 @@ impl TaikoAttributeProvider for StarResult {
 
 impl TaikoAttributeProvider for PpResult {
     #[inline]
-    fn attributes(self) -> Option<f32> {
+    fn attributes(self) -> Option<Float> {
         self.attributes.attributes()
     }
 }
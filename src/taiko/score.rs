@@ -0,0 +1,210 @@
+use crate::{
+    score::{self, ScoreVersion, SCORE_V2_MAX},
+    Beatmap, Float, GameMode, Mods,
+};
+
+use std::borrow::Cow;
+
+const GREAT_VALUE: Float = 300.0;
+const GOOD_VALUE: Float = 150.0;
+
+/// Score calculator for osu!taiko maps.
+///
+/// Only the hits on circles are considered, drumrolls and swells don't give any points.
+/// Since the order of hits is unknown, the combo bonus of plays other than
+/// full combos is estimated from the combo and the amount of misses.
+///
+/// osu!standard maps are converted first, see [`Beatmap::to_taiko`].
+#[derive(Clone, Debug)]
+pub struct TaikoScore<'m> {
+    map: Cow<'m, Beatmap>,
+    mods: u32,
+    version: ScoreVersion,
+    combo: Option<usize>,
+    n300: Option<usize>,
+    n100: Option<usize>,
+    n_misses: usize,
+    passed_objects: Option<usize>,
+}
+
+impl<'m> TaikoScore<'m> {
+    #[inline]
+    pub fn new(map: &'m Beatmap) -> Self {
+        Self {
+            map: map.to_taiko(),
+            mods: 0,
+            version: ScoreVersion::default(),
+            combo: None,
+            n300: None,
+            n100: None,
+            n_misses: 0,
+            passed_objects: None,
+        }
+    }
+
+    /// [`Mods`](crate::Mods) interface.
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods.for_mode(GameMode::TKO);
+
+        self
+    }
+
+    /// Specify the scoring system, defaults to [`ScoreVersion::V1`].
+    #[inline]
+    pub fn version(mut self, version: ScoreVersion) -> Self {
+        self.version = version;
+
+        self
+    }
+
+    /// Specify the max combo of the play.
+    #[inline]
+    pub fn combo(mut self, combo: usize) -> Self {
+        self.combo.replace(combo);
+
+        self
+    }
+
+    /// Specify the amount of 300s of a play.
+    #[inline]
+    pub fn n300(mut self, n300: usize) -> Self {
+        self.n300.replace(n300);
+
+        self
+    }
+
+    /// Specify the amount of 100s of a play.
+    #[inline]
+    pub fn n100(mut self, n100: usize) -> Self {
+        self.n100.replace(n100);
+
+        self
+    }
+
+    /// Specify the amount of misses of a play.
+    #[inline]
+    pub fn misses(mut self, n_misses: usize) -> Self {
+        self.n_misses = n_misses;
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects.replace(passed_objects);
+
+        self
+    }
+
+    /// Calculate the score of a full combo with only 300s.
+    pub fn max_score(&self) -> u64 {
+        let score = match self.version {
+            ScoreVersion::V1 => {
+                let max_combo = self.max_combo();
+
+                GREAT_VALUE * max_combo as Float + self.max_combo_bonus(max_combo)
+            }
+            ScoreVersion::V2 => {
+                SCORE_V2_MAX * self.mods.score_multiplier(GameMode::TKO, self.version)
+            }
+        };
+
+        score as u64
+    }
+
+    /// Calculate the score of the specified play.
+    ///
+    /// Unspecified 300s fill up the remaining circles and an unspecified combo
+    /// is considered to be the max combo minus the misses.
+    pub fn calculate(&self) -> u64 {
+        let max_combo = self.max_combo();
+
+        let n_misses = self.n_misses.min(max_combo);
+        let n100 = self.n100.unwrap_or(0).min(max_combo - n_misses);
+        let remaining = max_combo - n_misses - n100;
+        let n300 = self.n300.map_or(remaining, |n300| n300.min(remaining));
+
+        let n_hits = n300 + n100;
+        let hit_points = GREAT_VALUE * n300 as Float + GOOD_VALUE * n100 as Float;
+
+        let combo = self
+            .combo
+            .unwrap_or_else(|| max_combo.saturating_sub(n_misses));
+
+        let chains = score::combo_chains(combo, max_combo, n_misses);
+        let combo_ratio = score::combo_bonus_ratio(&chains, max_combo, combo_bonus_factor);
+
+        // The combo bonus of a hit scales with its value
+        let value_ratio = if n_hits == 0 {
+            0.0
+        } else {
+            hit_points / (GREAT_VALUE * n_hits as Float)
+        };
+
+        let score = match self.version {
+            ScoreVersion::V1 => {
+                hit_points + self.max_combo_bonus(max_combo) * combo_ratio * value_ratio
+            }
+            ScoreVersion::V2 => {
+                let n_judged = n_hits + n_misses;
+
+                let acc = if n_judged == 0 {
+                    0.0
+                } else {
+                    hit_points / (GREAT_VALUE * n_judged as Float)
+                };
+
+                let progress = n_judged as Float / self.map.n_circles.max(1) as Float;
+                let combo_ratio_v2 = score::score_v2_combo_ratio(&chains, max_combo);
+                let multiplier = self.mods.score_multiplier(GameMode::TKO, self.version);
+
+                score::score_v2(combo_ratio_v2 * value_ratio, acc, progress) * multiplier
+            }
+        };
+
+        score as u64
+    }
+
+    /// The amount of circles within the passed objects.
+    fn max_combo(&self) -> usize {
+        let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+
+        self.map
+            .hit_objects
+            .iter()
+            .take(n_objects)
+            .filter(|h| h.is_circle())
+            .count()
+    }
+
+    /// ScoreV1 combo bonus of a full combo with only 300s.
+    fn max_combo_bonus(&self, max_combo: usize) -> Float {
+        let difficulty_multiplier = score::difficulty_multiplier(&self.map);
+        let mod_multiplier = self.mods.score_multiplier(GameMode::TKO, ScoreVersion::V1);
+        let bonus_multiplier = GREAT_VALUE / 10.0 * difficulty_multiplier * mod_multiplier;
+
+        (0..max_combo)
+            .map(|combo| (combo_bonus_factor(combo) * bonus_multiplier).floor())
+            .sum()
+    }
+}
+
+/// The combo bonus increases every 10 combo until a combo of 100.
+#[inline]
+fn combo_bonus_factor(combo: usize) -> Float {
+    (combo / 10).min(10) as Float
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn taiko_score_combo_bonus() {
+        assert_eq!(combo_bonus_factor(9), 0.0);
+        assert_eq!(combo_bonus_factor(25), 2.0);
+        assert_eq!(combo_bonus_factor(1000), 10.0);
+    }
+}
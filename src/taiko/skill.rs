@@ -1,26 +1,27 @@
 use super::{DifficultyObject, SkillKind};
+use crate::Float;
 
 use std::cmp::Ordering;
 
-const DECAY_WEIGHT: f32 = 0.9;
+const DECAY_WEIGHT: Float = 0.9;
 
-const COLOR_SKILL_MULTIPLIER: f32 = 1.0;
-const COLOR_STRAIN_DECAY_BASE: f32 = 0.4;
+const COLOR_SKILL_MULTIPLIER: Float = 1.0;
+const COLOR_STRAIN_DECAY_BASE: Float = 0.4;
 
-const RHYTHM_SKILL_MULTIPLIER: f32 = 10.0;
-const RHYTHM_STRAIN_DECAY_BASE: f32 = 0.0;
+const RHYTHM_SKILL_MULTIPLIER: Float = 10.0;
+const RHYTHM_STRAIN_DECAY_BASE: Float = 0.0;
 
-const STAMINA_SKILL_MULTIPLIER: f32 = 1.0;
-const STAMINA_STRAIN_DECAY_BASE: f32 = 0.4;
+const STAMINA_SKILL_MULTIPLIER: Float = 1.0;
+const STAMINA_STRAIN_DECAY_BASE: Float = 0.4;
 
 pub(crate) struct Skill {
-    pub current_strain: f32,
-    current_section_peak: f32,
+    pub current_strain: Float,
+    current_section_peak: Float,
 
     kind: SkillKind,
-    pub(crate) strain_peaks: Vec<f32>,
+    pub(crate) strain_peaks: Vec<Float>,
 
-    prev_time: Option<f32>,
+    prev_time: Option<Float>,
 }
 
 impl Skill {
@@ -37,13 +38,23 @@ impl Skill {
         }
     }
 
+    /// Reset to the state of a new skill while keeping the allocations.
+    #[inline]
+    pub(crate) fn reset(&mut self) {
+        self.current_strain = 1.0;
+        self.current_section_peak = 1.0;
+        self.kind.reset();
+        self.strain_peaks.clear();
+        self.prev_time = None;
+    }
+
     #[inline]
     pub(crate) fn save_current_peak(&mut self) {
         self.strain_peaks.push(self.current_section_peak);
     }
 
     #[inline]
-    pub(crate) fn start_new_section_from(&mut self, time: f32) {
+    pub(crate) fn start_new_section_from(&mut self, time: Float) {
         self.current_section_peak = self.peak_strain(time - self.prev_time.unwrap());
     }
 
@@ -57,7 +68,7 @@ impl Skill {
     }
 
     #[inline]
-    pub(crate) fn difficulty_value(&self, buf: &mut [f32]) -> f32 {
+    pub(crate) fn difficulty_value(&self, buf: &mut [Float]) -> Float {
         let mut difficulty = 0.0;
         let mut weight = 1.0;
 
@@ -73,7 +84,7 @@ impl Skill {
     }
 
     #[inline]
-    fn skill_multiplier(&self) -> f32 {
+    fn skill_multiplier(&self) -> Float {
         match self.kind {
             SkillKind::Color { .. } => COLOR_SKILL_MULTIPLIER,
             SkillKind::Rhythm { .. } => RHYTHM_SKILL_MULTIPLIER,
@@ -82,7 +93,7 @@ impl Skill {
     }
 
     #[inline]
-    fn strain_decay_base(&self) -> f32 {
+    fn strain_decay_base(&self) -> Float {
         match self.kind {
             SkillKind::Color { .. } => COLOR_STRAIN_DECAY_BASE,
             SkillKind::Rhythm { .. } => RHYTHM_STRAIN_DECAY_BASE,
@@ -91,12 +102,12 @@ impl Skill {
     }
 
     #[inline]
-    fn peak_strain(&self, delta_time: f32) -> f32 {
+    fn peak_strain(&self, delta_time: Float) -> Float {
         self.current_strain * self.strain_decay(delta_time)
     }
 
     #[inline]
-    fn strain_decay(&self, ms: f32) -> f32 {
+    fn strain_decay(&self, ms: Float) -> Float {
         self.strain_decay_base().powf(ms / 1000.0)
     }
 }
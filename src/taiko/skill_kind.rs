@@ -1,8 +1,9 @@
 use super::{DifficultyObject, HitObjectRhythm, LimitedQueue, Rim};
+use crate::Float;
 
 use std::ops::Index;
 
-const RHYTHM_STRAIN_DECAY: f32 = 0.96;
+const RHYTHM_STRAIN_DECAY: Float = 0.96;
 const MOST_RECENT_PATTERNS_TO_COMPARE: usize = 2;
 
 const MONO_HISTORY_MAX_LEN: usize = 5;
@@ -18,12 +19,12 @@ pub(crate) enum SkillKind {
     Rhythm {
         rhythm_history: LimitedQueue<(usize, HitObjectRhythm)>, // (idx, rhythm)
         notes_since_rhythm_change: usize,
-        current_strain: f32,
+        current_strain: Float,
     },
     Stamina {
-        note_pair_duration_history: LimitedQueue<f32>,
+        note_pair_duration_history: LimitedQueue<Float>,
         hand: u8,
-        off_hand_object_duration: f32,
+        off_hand_object_duration: Float,
     },
 }
 
@@ -51,11 +52,43 @@ impl SkillKind {
         Self::Stamina {
             note_pair_duration_history: LimitedQueue::new(STAMINA_HISTORY_MAX_LEN),
             hand: right_hand as u8,
-            off_hand_object_duration: f32::MAX,
+            off_hand_object_duration: Float::MAX,
         }
     }
 
-    pub(crate) fn strain_value_of(&mut self, current: &DifficultyObject, cheese: &[bool]) -> f32 {
+    /// Reset to the initial state while keeping the allocations.
+    pub(crate) fn reset(&mut self) {
+        match self {
+            Self::Color {
+                mono_history,
+                prev_is_rim,
+                current_mono_len,
+            } => {
+                mono_history.clear();
+                *prev_is_rim = None;
+                *current_mono_len = 0;
+            }
+            Self::Rhythm {
+                rhythm_history,
+                notes_since_rhythm_change,
+                current_strain,
+            } => {
+                rhythm_history.clear();
+                *notes_since_rhythm_change = 0;
+                *current_strain = 0.0;
+            }
+            Self::Stamina {
+                note_pair_duration_history,
+                off_hand_object_duration,
+                ..
+            } => {
+                note_pair_duration_history.clear();
+                *off_hand_object_duration = Float::MAX;
+            }
+        }
+    }
+
+    pub(crate) fn strain_value_of(&mut self, current: &DifficultyObject, cheese: &[bool]) -> Float {
         match self {
             Self::Color {
                 mono_history,
@@ -153,7 +186,7 @@ impl SkillKind {
                 *current_strain *= RHYTHM_STRAIN_DECAY;
                 *notes_since_rhythm_change += 1;
 
-                if current.rhythm.difficulty.abs() <= f32::EPSILON {
+                if current.rhythm.difficulty.abs() <= Float::EPSILON {
                     return 0.0;
                 }
 
@@ -246,15 +279,15 @@ impl SkillKind {
 }
 
 #[inline]
-fn pattern_len_penalty(pattern_len: usize) -> f32 {
-    let short_pattern_penalty = (0.15 * pattern_len as f32).min(1.0);
-    let long_pattern_penalty = (2.5 - 0.15 * pattern_len as f32).max(0.0).min(1.0);
+fn pattern_len_penalty(pattern_len: usize) -> Float {
+    let short_pattern_penalty = (0.15 * pattern_len as Float).min(1.0);
+    let long_pattern_penalty = (2.5 - 0.15 * pattern_len as Float).clamp(0.0, 1.0);
 
     short_pattern_penalty.min(long_pattern_penalty)
 }
 
 #[inline]
-fn cheese_penalty(note_pair_duration: f32) -> f32 {
+fn cheese_penalty(note_pair_duration: Float) -> Float {
     if note_pair_duration > 125.0 {
         1.0
     } else if note_pair_duration < 100.0 {
@@ -265,7 +298,7 @@ fn cheese_penalty(note_pair_duration: f32) -> f32 {
 }
 
 #[inline]
-fn speed_bonus(note_pair_duration: f32) -> f32 {
+fn speed_bonus(note_pair_duration: Float) -> Float {
     if note_pair_duration > 200.0 {
         return 0.0;
     }
@@ -277,6 +310,6 @@ fn speed_bonus(note_pair_duration: f32) -> f32 {
 }
 
 #[inline]
-fn repetition_penalty(notes_since: usize) -> f32 {
-    (0.032 * notes_since as f32).min(1.0)
+fn repetition_penalty(notes_since: usize) -> Float {
+    (0.032 * notes_since as Float).min(1.0)
 }
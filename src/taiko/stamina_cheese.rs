@@ -1,44 +1,39 @@
-use super::{LimitedQueue, Rim};
+use super::Rim;
 use crate::{parse::HitObject, Beatmap};
 
 const ROLL_MIN_REPETITIONS: usize = 12;
 const TL_MIN_REPETITIONS: isize = 16;
 
 pub(crate) trait StaminaCheeseDetector {
-    fn find_cheese(&self) -> Vec<bool>;
+    fn find_cheese(&self, cheese: &mut Vec<bool>);
     fn find_rolls(&self, pattern_len: usize, cheese: &mut [bool]);
     fn find_tl_tap(&self, parity: usize, is_rin: bool, cheese: &mut [bool]);
 }
 
 impl StaminaCheeseDetector for Beatmap {
-    fn find_cheese(&self) -> Vec<bool> {
-        let mut cheese = vec![false; self.hit_objects.len()];
+    fn find_cheese(&self, cheese: &mut Vec<bool>) {
+        cheese.clear();
+        cheese.resize(self.hit_objects.len(), false);
 
-        self.find_rolls(3, &mut cheese);
-        self.find_rolls(4, &mut cheese);
+        self.find_rolls(3, cheese);
+        self.find_rolls(4, cheese);
 
-        self.find_tl_tap(0, true, &mut cheese);
-        self.find_tl_tap(1, true, &mut cheese);
-        self.find_tl_tap(0, false, &mut cheese);
-        self.find_tl_tap(1, false, &mut cheese);
-
-        cheese
+        self.find_tl_tap(0, true, cheese);
+        self.find_tl_tap(1, true, cheese);
+        self.find_tl_tap(0, false, cheese);
+        self.find_tl_tap(1, false, cheese);
     }
 
     fn find_rolls(&self, pattern_len: usize, cheese: &mut [bool]) {
-        let mut history = LimitedQueue::new(2 * pattern_len);
+        let history_len = 2 * pattern_len;
 
         let mut index_before_last_repeat = -1;
         let mut last_mark_end = 0;
 
-        for (i, h) in self.hit_objects.iter().enumerate() {
-            history.push(h);
-
-            if !history.full() {
-                continue;
-            }
-
-            let contains = contains_pattern_repeat(&history, pattern_len);
+        for i in history_len - 1..self.hit_objects.len() {
+            // The latest objects up to the current one
+            let history = &self.hit_objects[i + 1 - history_len..=i];
+            let contains = contains_pattern_repeat(history, pattern_len);
 
             if !contains {
                 index_before_last_repeat = (i + 1 - history.len()) as isize;
@@ -94,8 +89,8 @@ fn mark_as_cheese(start: usize, end: usize, cheese: &mut [bool]) {
 }
 
 #[inline]
-fn contains_pattern_repeat(history: &LimitedQueue<&HitObject>, pattern_len: usize) -> bool {
-    for (&curr, &to_compare) in history.iter().zip(history.iter().skip(pattern_len)) {
+fn contains_pattern_repeat(history: &[HitObject], pattern_len: usize) -> bool {
+    for (curr, to_compare) in history.iter().zip(history.iter().skip(pattern_len)) {
         if curr.is_rim() != to_compare.is_rim() {
             return false;
         }
@@ -0,0 +1,230 @@
+//! Synthetic maps for unit tests that don't rely on map files being present.
+
+use crate::{Beatmap, GameMode};
+
+use std::fmt::Write;
+
+/// Parse the given content of a `.osu` file.
+pub(crate) fn parse_str(content: &str) -> Beatmap {
+    #[cfg(not(any(feature = "async_std", feature = "async_tokio")))]
+    let map = Beatmap::parse(content.as_bytes());
+
+    #[cfg(any(feature = "async_std", feature = "async_tokio"))]
+    let map = Beatmap::parse_sync(content.as_bytes());
+
+    map.expect("invalid test map")
+}
+
+/// A map of the given mode with a few hundred objects of every kind.
+pub(crate) fn map(mode: GameMode) -> Beatmap {
+    parse_str(&osu_file(mode))
+}
+
+/// Content of a `.osu` file of the given mode.
+///
+/// The objects follow a fixed pattern so that calculations are deterministic
+/// but contain jumps, streams, all slider path types, repeats, and spinners.
+pub(crate) fn osu_file(mode: GameMode) -> String {
+    let mut content = String::with_capacity(16_384);
+
+    let _ = write!(
+        content,
+        "osu file format v14\n\n\
+        [General]\n\
+        StackLeniency: 0.7\n\
+        Mode: {}\n\n\
+        [Difficulty]\n\
+        HPDrainRate:5\n\
+        CircleSize:{}\n\
+        OverallDifficulty:8\n\
+        ApproachRate:9\n\
+        SliderMultiplier:1.4\n\
+        SliderTickRate:1\n\n\
+        [TimingPoints]\n\
+        0,300,4,2,0,100,1,0\n\
+        15000,-75,4,2,0,100,0,0\n\
+        30000,250,4,2,0,100,1,0\n\n\
+        [HitObjects]\n",
+        mode as u8,
+        if mode == GameMode::MNA { 7 } else { 4 },
+    );
+
+    let mut time = 1000;
+
+    for i in 0..240_u32 {
+        if mode == GameMode::MNA {
+            let x = (i * 3 % 7) * 512 / 7 + 36;
+
+            if i % 5 == 0 {
+                let _ = writeln!(content, "{},192,{},128,0,{}:0:0:0:0:", x, time, time + 300);
+            } else {
+                let _ = writeln!(content, "{},192,{},1,0,0:0:0:0:", x, time);
+            }
+
+            time += if i % 4 == 3 { 75 } else { 150 };
+
+            continue;
+        }
+
+        let x = 32 + (i * 97) % 320;
+        let y = 32 + (i * 61) % 272;
+        let sound = [0, 2, 0, 8, 4, 0, 10][i as usize % 7];
+
+        if i == 120 {
+            let _ = writeln!(content, "256,192,{},12,0,{}", time, time + 1500);
+            time += 1800;
+
+            continue;
+        }
+
+        match i % 10 {
+            6 => {
+                let _ = writeln!(
+                    content,
+                    "{x},{y},{t},2,{s},B|{}:{y}|{}:{}|{}:{},1,160",
+                    x + 80,
+                    x + 80,
+                    y + 80,
+                    x + 160,
+                    y + 80,
+                    x = x,
+                    y = y,
+                    t = time,
+                    s = sound,
+                );
+                time += 600;
+            }
+            7 => {
+                let _ = writeln!(
+                    content,
+                    "{x},{y},{t},2,{s},P|{}:{}|{}:{y},2,120",
+                    x + 50,
+                    y + 50,
+                    x + 100,
+                    x = x,
+                    y = y,
+                    t = time,
+                    s = sound,
+                );
+                time += 600;
+            }
+            // Zero-length last segment which requires extrapolation
+            8 if i % 20 == 8 => {
+                let _ = writeln!(
+                    content,
+                    "{x},{y},{t},2,{s},L|{}:{y}|{}:{y}|{}:{y},1,150",
+                    x + 100,
+                    x + 100,
+                    x + 100,
+                    x = x,
+                    y = y,
+                    t = time,
+                    s = sound,
+                );
+                time += 600;
+            }
+            8 => {
+                let _ = writeln!(
+                    content,
+                    "{x},{y},{t},2,{s},L|{}:{y},1,100",
+                    x + 100,
+                    x = x,
+                    y = y,
+                    t = time,
+                    s = sound,
+                );
+                time += 600;
+            }
+            9 => {
+                let _ = writeln!(
+                    content,
+                    "{x},{y},{t},2,{s},C|{}:{}|{}:{y}|{}:{},1,120",
+                    x + 40,
+                    y + 40,
+                    x + 80,
+                    x + 120,
+                    y + 40,
+                    x = x,
+                    y = y,
+                    t = time,
+                    s = sound,
+                );
+                time += 600;
+            }
+            _ => {
+                let _ = writeln!(content, "{},{},{},1,{},0:0:0:0:", x, y, time, sound);
+                time += if i % 3 == 2 { 75 } else { 150 };
+            }
+        }
+    }
+
+    content
+}
+
+/// A short osu!standard map with a stack of circles whose strains were recorded
+/// with the original implementation, before the calculations were refactored.
+#[cfg(all(
+    feature = "osu",
+    any(feature = "all_included", feature = "no_leniency")
+))]
+pub(crate) const SHORT_STD_MAP: &str = "osu file format v14
+
+[General]
+StackLeniency: 0.7
+Mode: 0
+
+[Difficulty]
+HPDrainRate:5
+CircleSize:4
+OverallDifficulty:8
+ApproachRate:9
+SliderMultiplier:1.4
+SliderTickRate:1
+
+[TimingPoints]
+0,300,4,2,0,100,1,0
+
+[HitObjects]
+100,100,1000,1,0,0:0:0:0:
+300,100,1300,1,0,0:0:0:0:
+300,300,1600,1,0,0:0:0:0:
+100,300,1900,1,0,0:0:0:0:
+256,192,2200,1,0,0:0:0:0:
+256,192,2350,1,0,0:0:0:0:
+256,192,2500,1,0,0:0:0:0:
+400,50,2800,2,0,L|480:50,1,70
+120,250,3400,1,0,0:0:0:0:
+200,250,3500,1,0,0:0:0:0:
+280,250,3600,1,0,0:0:0:0:
+360,250,3700,1,0,0:0:0:0:
+440,250,3800,1,0,0:0:0:0:
+60,60,4300,1,0,0:0:0:0:
+450,330,4600,1,0,0:0:0:0:
+60,330,4900,1,0,0:0:0:0:
+450,60,5200,1,0,0:0:0:0:
+256,192,5500,1,0,0:0:0:0:
+";
+
+/// Asserts that the values are equal up to a relative error of `1e-5`.
+#[cfg(all(
+    feature = "osu",
+    any(feature = "all_included", feature = "no_leniency")
+))]
+pub(crate) fn assert_close(actual: &[crate::Float], expected: &[crate::Float]) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(
+            (a - e).abs() <= e.abs() * 1e-5,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+}
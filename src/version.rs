@@ -0,0 +1,389 @@
+use crate::GameMode;
+
+use std::fmt;
+
+/// Version of a mode's difficulty and pp algorithm, named after the year of the changes.
+///
+/// Requesting a version for a mode that has no implementation of that exact version
+/// will use the mode's newest implementation that is not newer than the requested one,
+/// i.e. the rules that were in effect at that time.
+/// The version that was actually used is stored in [`PpResult`](crate::PpResult).
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlgorithmVersion {
+    /// The difficulty & pp changes from 21.07.27.
+    ///
+    /// Unlike the original implementation, osu!standard maps are converted
+    /// for osu!taiko and osu!mania calculations in every version, as the game does,
+    /// so the results of converted maps differ from it.
+    V2021,
+    /// The osu!mania pp rework from October 2022 which uses the accuracy
+    /// of the judgements instead of the score, and the osu!standard
+    /// flashlight skill from January 2022 which replaces the aim bonus of FL.
+    V2022,
+    /// The osu!standard changes up to March 2024 including separate slider aim,
+    /// rhythm complexity for speed, and difficult strain counts.
+    /// Requires the `all_included` feature.
+    ///
+    /// The `relax_nerf` and `ppysb_edition` features only apply to [`AlgorithmVersion::V2021`].
+    V2024,
+}
+
+impl AlgorithmVersion {
+    /// All versions that have an implementation for the given mode, oldest first.
+    pub fn available(mode: GameMode) -> &'static [Self] {
+        match mode {
+            #[cfg(feature = "all_included")]
+            GameMode::STD => &[Self::V2021, Self::V2022, Self::V2024],
+            #[cfg(not(feature = "all_included"))]
+            GameMode::STD => &[Self::V2021, Self::V2022],
+            GameMode::MNA => &[Self::V2021, Self::V2022],
+            _ => &[Self::V2021],
+        }
+    }
+
+    /// The newest version that has an implementation for the given mode.
+    #[inline]
+    pub fn latest(mode: GameMode) -> Self {
+        *Self::available(mode).last().unwrap()
+    }
+
+    /// The version whose implementation will be used when requesting `self` for the given mode.
+    #[inline]
+    pub fn resolve(self, mode: GameMode) -> Self {
+        Self::available(mode)
+            .iter()
+            .rev()
+            .find(|&&version| version <= self)
+            .copied()
+            .unwrap_or(Self::V2021)
+    }
+}
+
+impl Default for AlgorithmVersion {
+    #[inline]
+    fn default() -> Self {
+        Self::V2021
+    }
+}
+
+impl fmt::Display for AlgorithmVersion {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V2021 => f.write_str("2021"),
+            Self::V2022 => f.write_str("2022"),
+            Self::V2024 => f.write_str("2024"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, BeatmapExt, Float, PpResult, Strains};
+
+    #[test]
+    fn resolve_falls_back_to_older_version() {
+        assert_eq!(
+            AlgorithmVersion::V2024.resolve(GameMode::TKO),
+            AlgorithmVersion::V2021
+        );
+        assert_eq!(
+            AlgorithmVersion::V2021.resolve(GameMode::STD),
+            AlgorithmVersion::V2021
+        );
+        assert_eq!(
+            AlgorithmVersion::V2024.resolve(GameMode::MNA),
+            AlgorithmVersion::V2022
+        );
+        assert_eq!(
+            AlgorithmVersion::latest(GameMode::CTB),
+            AlgorithmVersion::V2021
+        );
+    }
+
+    /// Mods, stars, pp, amount of strains, and sum of strains.
+    type Expected = (u32, f32, f32, usize, f32);
+
+    /// Values of the 21.07.27 implementation for the maps of [`test_util::map`]
+    /// with 2 misses and 97% accuracy, or a score of 900,000 for osu!mania.
+    ///
+    /// The map has no stacks so `no_leniency` matches `all_included`.
+    /// Its strains of DT and HT used to ignore the clock rate though.
+    #[cfg(all(
+        feature = "osu",
+        not(any(
+            feature = "ppysb_edition",
+            feature = "relax_nerf",
+            feature = "score_v2_buff"
+        )),
+        not(feature = "no_sliders_no_leniency")
+    ))]
+    const OSU: [Expected; 9] = [
+        (0, 6.2439656, 201.30228, 193, 31974.809),
+        (8, 6.2439656, 225.00343, 193, 31974.809),
+        (16, 6.6809072, 264.25458, 193, 35469.91),
+        (64, 8.307272, 515.89716, 129, 51739.617),
+        (256, 5.144499, 108.694626, 256, 23944.264),
+        (1024, 6.2439656, 256.60895, 193, 31974.809),
+        (1032, 6.2439656, 286.94806, 193, 31974.809),
+        (24, 6.6809072, 285.39493, 193, 35469.91),
+        (2, 5.6932173, 146.35628, 193, 27930.414),
+    ];
+
+    #[cfg(all(
+        feature = "osu",
+        not(any(
+            feature = "ppysb_edition",
+            feature = "relax_nerf",
+            feature = "score_v2_buff"
+        )),
+        feature = "no_sliders_no_leniency"
+    ))]
+    const OSU: [Expected; 9] = [
+        (0, 6.243741, 201.28294, 193, 31880.488),
+        (8, 6.243741, 224.98177, 193, 31880.488),
+        (16, 6.680701, 264.23315, 193, 35365.516),
+        (64, 8.306199, 515.7438, 129, 51593.293),
+        (256, 5.1444545, 108.69202, 256, 23876.48),
+        (1024, 6.243741, 256.58182, 193, 31880.488),
+        (1032, 6.243741, 286.91766, 193, 31880.488),
+        (24, 6.680701, 285.37183, 193, 35365.516),
+        (2, 5.693157, 146.35225, 193, 27920.145),
+    ];
+
+    #[cfg(feature = "taiko")]
+    const TAIKO: [Expected; 9] = [
+        (0, 2.5017424, 76.346725, 192, 636.24097),
+        (8, 2.5017424, 84.704025, 192, 636.24097),
+        (16, 2.5017424, 112.012924, 192, 636.24097),
+        (64, 2.8489437, 112.35632, 128, 626.92645),
+        (256, 2.2723656, 58.16453, 255, 621.6947),
+        (1024, 2.5017424, 77.92539, 192, 636.24097),
+        (1032, 2.5017424, 86.48681, 192, 636.24097),
+        (24, 2.5017424, 123.909775, 192, 636.24097),
+        (2, 2.5017424, 59.377396, 192, 636.24097),
+    ];
+
+    #[cfg(feature = "fruits")]
+    const FRUITS: [Expected; 9] = [
+        (0, 4.7776833, 187.75053, 103, 3703.0125),
+        (8, 4.7776833, 211.21935, 103, 3703.0125),
+        (16, 5.0563526, 231.34131, 103, 4133.8623),
+        (64, 6.528467, 409.17303, 69, 5747.2104),
+        (256, 3.8274207, 121.44674, 137, 2724.4421),
+        (1024, 4.7776833, 252.31757, 103, 3703.0125),
+        (1032, 4.7776833, 283.85724, 103, 3703.0125),
+        (24, 5.0563526, 242.90836, 103, 4133.8623),
+        (2, 4.279449, 163.78282, 103, 2792.2776),
+    ];
+
+    #[cfg(feature = "mania")]
+    const MANIA: [Expected; 9] = [
+        (0, 1.9070005, 21.999113, 80, 805.7999),
+        (8, 1.9070005, 21.999113, 80, 805.7999),
+        (16, 1.9070005, 21.999113, 80, 805.7999),
+        (64, 2.581666, 45.124447, 54, 738.57776),
+        (256, 1.5722787, 146.89001, 106, 863.516),
+        (1024, 1.9070005, 21.999113, 80, 805.7999),
+        (1032, 1.9070005, 21.999113, 80, 805.7999),
+        (24, 1.9070005, 21.999113, 80, 805.7999),
+        (2, 1.9070005, 52.129627, 80, 805.7999),
+    ];
+
+    /// Values for the osu!standard map of [`test_util::map`] converted to osu!taiko.
+    ///
+    /// The 21.07.27 implementation did not convert maps so these values differ from it.
+    #[cfg(all(feature = "osu", feature = "taiko"))]
+    const TAIKO_CONVERT: [Expected; 9] = [
+        (0, 3.4813707, 117.88877, 192, 1628.9604),
+        (8, 3.4813707, 131.13351, 192, 1628.9604),
+        (16, 3.4813707, 164.29372, 192, 1628.9604),
+        (64, 4.5055995, 190.20427, 128, 1672.946),
+        (256, 2.9602063, 85.5833, 256, 1623.662),
+        (1024, 3.4813707, 121.87142, 192, 1628.9604),
+        (1032, 3.4813707, 135.62993, 192, 1628.9604),
+        (24, 3.4813707, 182.13167, 192, 1628.9604),
+        (2, 3.4813707, 95.91952, 192, 1628.9604),
+    ];
+
+    /// Same as [`TAIKO_CONVERT`] but converted to osu!mania with a score of 900,000.
+    #[cfg(all(feature = "osu", feature = "mania"))]
+    const MANIA_CONVERT: [Expected; 9] = [
+        (0, 2.2950542, 34.678463, 193, 1729.1699),
+        (8, 2.2950542, 34.678463, 193, 1729.1699),
+        (16, 2.2950542, 34.678463, 193, 1729.1699),
+        (64, 2.8836179, 59.24911, 129, 1590.9594),
+        (256, 1.988307, 262.83017, 256, 1821.0394),
+        (1024, 2.2950542, 34.678463, 193, 1729.1699),
+        (1032, 2.2950542, 34.678463, 193, 1729.1699),
+        (24, 2.2950542, 34.678463, 193, 1729.1699),
+        (2, 2.2950542, 82.17492, 193, 1729.1699),
+    ];
+
+    fn assert_v2021(
+        mode: GameMode,
+        expected: &[Expected],
+        calculate: impl Fn(u32) -> PpResult,
+        strains: impl Fn(u32) -> Strains,
+    ) {
+        for &(mods, stars, pp, n_strains, strain_sum) in expected {
+            let result = calculate(mods);
+            let strains = strains(mods);
+            let sum: Float = strains.strains.iter().sum();
+
+            let close = |actual: Float, expected: f32| {
+                (actual - expected as Float).abs() <= 1e-4 * (expected as Float).max(1.0)
+            };
+
+            assert_eq!(result.version, AlgorithmVersion::V2021);
+            assert!(
+                close(result.stars(), stars),
+                "{:?} {}: stars {}",
+                mode,
+                mods,
+                result.stars()
+            );
+            assert!(
+                close(result.pp(), pp),
+                "{:?} {}: pp {}",
+                mode,
+                mods,
+                result.pp()
+            );
+            assert_eq!(strains.strains.len(), n_strains, "{:?} {}", mode, mods);
+            assert!(
+                close(sum, strain_sum),
+                "{:?} {}: strains {}",
+                mode,
+                mods,
+                sum
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "osu",
+        not(any(
+            feature = "ppysb_edition",
+            feature = "relax_nerf",
+            feature = "score_v2_buff"
+        ))
+    ))]
+    fn osu_v2021_reproduces_baseline() {
+        let map = test_util::map(GameMode::STD);
+
+        assert_v2021(
+            GameMode::STD,
+            &OSU,
+            |mods| {
+                crate::OsuPP::new(&map)
+                    .mods(mods)
+                    .version(AlgorithmVersion::V2021)
+                    .misses(2)
+                    .accuracy(97.0)
+                    .calculate()
+            },
+            |mods| map.strains(mods),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "taiko")]
+    fn taiko_v2021_reproduces_baseline() {
+        let map = test_util::map(GameMode::TKO);
+
+        assert_v2021(
+            GameMode::TKO,
+            &TAIKO,
+            |mods| {
+                crate::TaikoPP::new(&map)
+                    .mods(mods)
+                    .version(AlgorithmVersion::V2021)
+                    .misses(2)
+                    .accuracy(97.0)
+                    .calculate()
+            },
+            |mods| map.strains(mods),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "fruits")]
+    fn fruits_v2021_reproduces_baseline() {
+        let map = test_util::map(GameMode::CTB);
+
+        assert_v2021(
+            GameMode::CTB,
+            &FRUITS,
+            |mods| {
+                crate::FruitsPP::new(&map)
+                    .mods(mods)
+                    .version(AlgorithmVersion::V2021)
+                    .misses(2)
+                    .accuracy(97.0)
+                    .calculate()
+            },
+            |mods| map.strains(mods),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "mania")]
+    fn mania_v2021_reproduces_baseline() {
+        let map = test_util::map(GameMode::MNA);
+
+        assert_v2021(
+            GameMode::MNA,
+            &MANIA,
+            |mods| {
+                crate::ManiaPP::new(&map)
+                    .mods(mods)
+                    .version(AlgorithmVersion::V2021)
+                    .score(900_000)
+                    .calculate()
+            },
+            |mods| map.strains(mods),
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "osu", feature = "taiko"))]
+    fn taiko_convert_v2021() {
+        let map = test_util::map(GameMode::STD);
+
+        assert_v2021(
+            GameMode::TKO,
+            &TAIKO_CONVERT,
+            |mods| {
+                crate::TaikoPP::new(&map)
+                    .mods(mods)
+                    .version(AlgorithmVersion::V2021)
+                    .misses(2)
+                    .accuracy(97.0)
+                    .calculate()
+            },
+            |mods| crate::taiko::strains(&map, mods),
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "osu", feature = "mania"))]
+    fn mania_convert_v2021() {
+        let map = test_util::map(GameMode::STD);
+
+        assert_v2021(
+            GameMode::MNA,
+            &MANIA_CONVERT,
+            |mods| {
+                crate::ManiaPP::new(&map)
+                    .mods(mods)
+                    .version(AlgorithmVersion::V2021)
+                    .score(900_000)
+                    .calculate()
+            },
+            |mods| crate::mania::strains(&map, mods),
+        );
+    }
+}
@@ -2,13 +2,13 @@
 
 extern crate peace_performance;
 
-use peace_performance::Beatmap;
+use peace_performance::{Beatmap, Float};
 
 struct MapResult {
     map_id: u32,
     mods: u32,
-    stars: f32,
-    pp: f32,
+    stars: Float,
+    pp: Float,
 }
 
 macro_rules! assert_result {
@@ -2,13 +2,13 @@
 
 extern crate peace_performance;
 
-use peace_performance::Beatmap;
+use peace_performance::{Beatmap, Float};
 
 struct MapResult {
     map_id: u32,
     mods: u32,
-    stars: f32,
-    pp: f32,
+    stars: Float,
+    pp: Float,
 }
 
 macro_rules! assert_result {
@@ -30,7 +30,7 @@ macro_rules! assert_result {
     };
 }
 
-fn margin() -> f32 {
+fn margin() -> Float {
     if cfg!(feature = "no_sliders_no_leniency") {
         0.0075
     } else if cfg!(feature = "no_leniency") {